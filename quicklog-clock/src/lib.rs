@@ -31,6 +31,7 @@
 use ::quanta::Instant;
 use chrono::{DateTime, OutOfRangeError, Utc};
 
+pub mod mock;
 pub mod quanta;
 
 pub trait Clock {
@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, OutOfRangeError, Utc};
+use quanta::{Instant, Mock};
+
+use crate::Clock;
+
+/// Clock which only moves forward when advanced manually, for deterministic
+/// timestamps in tests.
+///
+/// Clones share the same underlying time, so a clone can be kept to advance
+/// the clock after handing it over to the logger.
+///
+/// ```rust
+/// use std::time::Duration;
+/// use chrono::{TimeZone, Utc};
+/// use quicklog_clock::{Clock, mock::MockClock};
+///
+/// let start = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+/// let clock = MockClock::new(start);
+///
+/// clock.advance(Duration::from_nanos(1500));
+/// let time = clock.compute_system_time_from_instant(clock.get_instant());
+/// assert_eq!(time.unwrap(), start + chrono::Duration::nanoseconds(1500));
+/// ```
+#[derive(Clone)]
+pub struct MockClock {
+    clock: quanta::Clock,
+    mock: Arc<Mock>,
+    start_time: DateTime<Utc>,
+    start_instant: Instant,
+}
+
+impl MockClock {
+    /// Creates a clock which reads `start_time` until advanced
+    pub fn new(start_time: DateTime<Utc>) -> MockClock {
+        let (clock, mock) = quanta::Clock::mock();
+        let start_instant = clock.now();
+        MockClock {
+            clock,
+            mock,
+            start_time,
+            start_instant,
+        }
+    }
+
+    /// Moves the time of this clock, and all of its clones, forward by `duration`
+    pub fn advance(&self, duration: std::time::Duration) {
+        self.mock.increment(duration);
+    }
+}
+
+impl Clock for MockClock {
+    fn get_instant(&self) -> Instant {
+        self.clock.now()
+    }
+
+    fn compute_system_time_from_instant(
+        &self,
+        instant: Instant,
+    ) -> Result<DateTime<Utc>, OutOfRangeError> {
+        let elapsed_time = instant.duration_since(self.start_instant);
+        Duration::from_std(elapsed_time).map(|duration| self.start_time + duration)
+    }
+}
@@ -0,0 +1,297 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    file_hooks::{FileHooks, FileSummary},
+    Flush,
+};
+
+/// When [`BufferedFileFlusher`] calls `fsync` after writing its buffer to disk
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FsyncPolicy {
+    /// Never fsync, leaving it to the OS
+    Never,
+    /// Fsync every time the buffer is written
+    Always,
+    /// Fsync when the buffer is written, at most once per interval
+    Interval(Duration),
+}
+
+/// Flushes into a file, accumulating lines in memory and only writing them to
+/// disk once the buffer reaches its capacity or the flush interval elapses.
+///
+/// This avoids a syscall per log line. Note that the flush interval is only
+/// checked whenever a line is flushed. Any buffered lines are written when the
+/// flusher is dropped, or explicitly through [`BufferedFileFlusher::flush_buffer`].
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use quicklog_flush::buffered_file_flusher::{BufferedFileFlusher, FsyncPolicy};
+///
+/// let flusher = BufferedFileFlusher::new("logs/quicklog.log")
+///     .buffer_capacity(1024 * 1024)
+///     .flush_interval(Duration::from_millis(100))
+///     .fsync(FsyncPolicy::Interval(Duration::from_secs(1)));
+/// ```
+pub struct BufferedFileFlusher {
+    path: PathBuf,
+    file: Option<File>,
+    buffer: Vec<u8>,
+    capacity: usize,
+    flush_interval: Option<Duration>,
+    fsync: FsyncPolicy,
+    last_flush: Instant,
+    last_sync: Instant,
+    hooks: FileHooks,
+    /// Whether the header was buffered, and the footer still has to be
+    started: bool,
+}
+
+impl BufferedFileFlusher {
+    /// Default buffer capacity of 1MB
+    pub const DEFAULT_CAPACITY: usize = 1024 * 1024;
+
+    /// Flushes into file with specified path. Ensure that the directory exists
+    /// for the destination log file, otherwise, an error would be thrown
+    pub fn new(path: impl Into<PathBuf>) -> BufferedFileFlusher {
+        let now = Instant::now();
+        BufferedFileFlusher {
+            path: path.into(),
+            file: None,
+            buffer: Vec::with_capacity(Self::DEFAULT_CAPACITY),
+            capacity: Self::DEFAULT_CAPACITY,
+            flush_interval: None,
+            fsync: FsyncPolicy::Never,
+            last_flush: now,
+            last_sync: now,
+            hooks: FileHooks::default(),
+            started: false,
+        }
+    }
+
+    /// Writes to disk once at least `bytes` are buffered
+    pub fn buffer_capacity(mut self, bytes: usize) -> Self {
+        self.capacity = bytes;
+        self.buffer.reserve(bytes.saturating_sub(self.buffer.len()));
+        self
+    }
+
+    /// Writes to disk if `interval` has elapsed since the last write
+    pub fn flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = Some(interval);
+        self
+    }
+
+    /// Sets the [`FsyncPolicy`]
+    pub fn fsync(mut self, policy: FsyncPolicy) -> Self {
+        self.fsync = policy;
+        self
+    }
+
+    /// Writes the output of `header` before the first log line, see
+    /// [`FileHooks`]
+    pub fn header(mut self, header: impl FnMut(&Path) -> String + Send + 'static) -> Self {
+        self.hooks.set_header(header);
+        self
+    }
+
+    /// Writes the output of `footer` after the last log line, see
+    /// [`FileHooks`]
+    pub fn footer(
+        mut self,
+        footer: impl FnMut(&FileSummary<'_>) -> String + Send + 'static,
+    ) -> Self {
+        self.hooks.set_footer(footer);
+        self
+    }
+
+    /// Writes all buffered lines to disk
+    pub fn flush_buffer(&mut self) {
+        if let Err(err) = self.try_flush_buffer() {
+            panic!("Unable to write to file: {}", err)
+        }
+    }
+
+    /// Same as [`BufferedFileFlusher::flush_buffer`], but returns any error
+    /// encountered while writing or syncing instead of panicking, in which
+    /// case the buffered lines are discarded
+    pub fn try_flush_buffer(&mut self) -> io::Result<()> {
+        let now = Instant::now();
+        self.last_flush = now;
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        let file = match &mut self.file {
+            Some(file) => file,
+            file @ None => {
+                let opened = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.path);
+                match opened {
+                    Ok(opened) => file.insert(opened),
+                    Err(err) => {
+                        self.buffer.clear();
+                        return Err(err);
+                    }
+                }
+            }
+        };
+        let written = file.write_all(&self.buffer);
+        self.buffer.clear();
+        written?;
+
+        let sync = match self.fsync {
+            FsyncPolicy::Never => false,
+            FsyncPolicy::Always => true,
+            FsyncPolicy::Interval(interval) => now.duration_since(self.last_sync) >= interval,
+        };
+        if sync {
+            file.sync_data()?;
+            self.last_sync = now;
+        }
+
+        Ok(())
+    }
+}
+
+impl Flush for BufferedFileFlusher {
+    fn flush_one(&mut self, display: String) {
+        self.flush_str(&display)
+    }
+
+    fn flush_str(&mut self, display: &str) {
+        if let Err(err) = self.try_flush_one(display) {
+            panic!("Unable to write to file: {}", err)
+        }
+    }
+
+    fn try_flush_one(&mut self, display: &str) -> io::Result<()> {
+        if !self.started {
+            if let Some(header) = self.hooks.header(&self.path) {
+                self.buffer.extend_from_slice(header.as_bytes());
+            }
+            self.started = true;
+        }
+        self.buffer.extend_from_slice(display.as_bytes());
+        self.hooks.count(display.len());
+
+        let interval_elapsed = self
+            .flush_interval
+            .map_or(false, |interval| self.last_flush.elapsed() >= interval);
+        if self.buffer.len() >= self.capacity || interval_elapsed {
+            self.try_flush_buffer()?;
+        }
+
+        Ok(())
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        if std::mem::take(&mut self.started) {
+            if let Some(footer) = self.hooks.footer(&self.path) {
+                self.buffer.extend_from_slice(footer.as_bytes());
+            }
+        }
+
+        self.try_flush_buffer()
+    }
+}
+
+impl Drop for BufferedFileFlusher {
+    fn drop(&mut self) {
+        _ = self.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, time::Duration};
+
+    use super::BufferedFileFlusher;
+    use crate::Flush;
+
+    #[test]
+    fn write_on_capacity_and_drop() {
+        let path =
+            std::env::temp_dir().join(format!("quicklog-buffered-{}.log", std::process::id()));
+        _ = fs::remove_file(&path);
+
+        let mut flusher = BufferedFileFlusher::new(&path).buffer_capacity(10);
+        flusher.flush_one("12345\n".to_string());
+        assert!(!path.exists());
+
+        flusher.flush_one("67890\n".to_string());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "12345\n67890\n");
+
+        flusher.flush_one("abc\n".to_string());
+        drop(flusher);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "12345\n67890\nabc\n");
+
+        _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn write_on_interval() {
+        let path = std::env::temp_dir().join(format!(
+            "quicklog-buffered-interval-{}.log",
+            std::process::id()
+        ));
+        _ = fs::remove_file(&path);
+
+        let mut flusher = BufferedFileFlusher::new(&path).flush_interval(Duration::ZERO);
+        flusher.flush_one("hello\n".to_string());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "hello\n");
+
+        _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn header_and_footer() {
+        let path = std::env::temp_dir().join(format!(
+            "quicklog-buffered-hooks-{}.log",
+            std::process::id()
+        ));
+        _ = fs::remove_file(&path);
+
+        let mut flusher = BufferedFileFlusher::new(&path)
+            .header(|_| "# schema: v1\n".to_string())
+            .footer(|summary| format!("# {} lines\n", summary.lines));
+        flusher.flush_str("hello\n");
+        flusher.flush_str("world\n");
+        flusher.close().unwrap();
+        // closing again without logging anything writes nothing
+        flusher.close().unwrap();
+        flusher.flush_str("again\n");
+        drop(flusher);
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "# schema: v1\nhello\nworld\n# 2 lines\n# schema: v1\nagain\n# 1 lines\n"
+        );
+
+        _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn return_write_errors() {
+        let path = std::env::temp_dir()
+            .join(format!("quicklog-buffered-missing-{}", std::process::id()))
+            .join("quicklog.log");
+
+        let mut flusher = BufferedFileFlusher::new(path).buffer_capacity(10);
+        assert!(flusher.try_flush_one("12345\n").is_ok());
+        let err = flusher.try_flush_one("67890\n").unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+        // The lines which failed to be written are discarded
+        assert!(flusher.buffer.is_empty());
+
+        // Dropping with lines which cannot be written does not panic
+        assert!(flusher.try_flush_one("abc\n").is_ok());
+        drop(flusher);
+    }
+}
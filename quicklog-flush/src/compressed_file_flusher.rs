@@ -0,0 +1,179 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use flate2::{write::GzEncoder, Compression};
+
+use crate::{
+    file_hooks::{FileHooks, FileSummary},
+    Flush,
+};
+
+/// Flushes into a gzip-compressed file, streaming each line through the
+/// compressor.
+///
+/// The compressor buffers its output, so lines only reach the file in
+/// compressed blocks, and the gzip trailer is written when the flusher is
+/// dropped or [`CompressedFileFlusher::finish`] is called. A process which
+/// exits without either leaves a truncated file, whose lines written so far
+/// can still be read by most decompressors, e.g. `zcat`.
+///
+/// Appending to an existing file starts a new gzip member after the previous
+/// ones, which decompressors read as a single concatenated stream.
+///
+/// ```no_run
+/// use quicklog_flush::compressed_file_flusher::CompressedFileFlusher;
+///
+/// let flusher = CompressedFileFlusher::new("logs/app.log.gz").level(6);
+/// ```
+pub struct CompressedFileFlusher {
+    path: PathBuf,
+    level: u32,
+    encoder: Option<GzEncoder<File>>,
+    hooks: FileHooks,
+}
+
+impl CompressedFileFlusher {
+    /// Default compression level, favouring speed over ratio
+    pub const DEFAULT_LEVEL: u32 = 1;
+
+    /// Flushes into file with specified path. Ensure that the directory exists
+    /// for the destination log file, otherwise, an error would be thrown
+    pub fn new(path: impl Into<PathBuf>) -> CompressedFileFlusher {
+        CompressedFileFlusher {
+            path: path.into(),
+            level: Self::DEFAULT_LEVEL,
+            encoder: None,
+            hooks: FileHooks::default(),
+        }
+    }
+
+    /// Sets the compression level, from 0 for none to 9 for the best ratio
+    pub fn level(mut self, level: u32) -> Self {
+        self.level = level.min(9);
+        self
+    }
+
+    /// Writes the output of `header` at the start of each gzip member, see
+    /// [`FileHooks`]
+    pub fn header(mut self, header: impl FnMut(&Path) -> String + Send + 'static) -> Self {
+        self.hooks.set_header(header);
+        self
+    }
+
+    /// Writes the output of `footer` at the end of each gzip member, see
+    /// [`FileHooks`]
+    pub fn footer(
+        mut self,
+        footer: impl FnMut(&FileSummary<'_>) -> String + Send + 'static,
+    ) -> Self {
+        self.hooks.set_footer(footer);
+        self
+    }
+
+    /// Writes any buffered lines along with the footer and the gzip trailer,
+    /// completing the file. The next line starts a new gzip member.
+    pub fn finish(&mut self) -> io::Result<()> {
+        let Some(mut encoder) = self.encoder.take() else {
+            return Ok(());
+        };
+
+        if let Some(footer) = self.hooks.footer(&self.path) {
+            encoder.write_all(footer.as_bytes())?;
+        }
+        encoder.finish().map(|_| ())
+    }
+}
+
+impl Flush for CompressedFileFlusher {
+    fn flush_one(&mut self, display: String) {
+        self.flush_str(&display)
+    }
+
+    fn flush_str(&mut self, display: &str) {
+        if let Err(err) = self.try_flush_one(display) {
+            panic!("Unable to write to file: {}", err)
+        }
+    }
+
+    fn try_flush_one(&mut self, display: &str) -> io::Result<()> {
+        let encoder = match &mut self.encoder {
+            Some(encoder) => encoder,
+            encoder @ None => {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.path)?;
+                let mut opened = GzEncoder::new(file, Compression::new(self.level));
+                if let Some(header) = self.hooks.header(&self.path) {
+                    opened.write_all(header.as_bytes())?;
+                }
+                encoder.insert(opened)
+            }
+        };
+        encoder.write_all(display.as_bytes())?;
+        self.hooks.count(display.len());
+
+        Ok(())
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        self.finish()
+    }
+}
+
+impl Drop for CompressedFileFlusher {
+    fn drop(&mut self) {
+        _ = self.finish();
+    }
+}
+
+/// Compresses the file at `path` into `<path>.gz`, removing the original
+/// once compressed
+pub(crate) fn compress_file(path: &Path) -> io::Result<()> {
+    let mut compressed_path = path.as_os_str().to_owned();
+    compressed_path.push(".gz");
+
+    let mut input = File::open(path)?;
+    let mut encoder = GzEncoder::new(File::create(compressed_path)?, Compression::default());
+    io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?;
+
+    fs::remove_file(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io::Read};
+
+    use flate2::read::MultiGzDecoder;
+
+    use super::CompressedFileFlusher;
+    use crate::Flush;
+
+    #[test]
+    fn compress_and_append() {
+        let path =
+            std::env::temp_dir().join(format!("quicklog-compressed-{}.log.gz", std::process::id()));
+        _ = fs::remove_file(&path);
+
+        let mut flusher = CompressedFileFlusher::new(&path);
+        flusher.flush_one("hello\n".to_string());
+        flusher.flush_str("world\n");
+        drop(flusher);
+
+        let mut flusher = CompressedFileFlusher::new(&path).level(9);
+        flusher.flush_str("again\n");
+        flusher.finish().unwrap();
+
+        let mut decompressed = String::new();
+        MultiGzDecoder::new(fs::File::open(&path).unwrap())
+            .read_to_string(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, "hello\nworld\nagain\n");
+
+        _ = fs::remove_file(&path);
+    }
+}
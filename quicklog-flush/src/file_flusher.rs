@@ -1,32 +1,99 @@
 use std::{
-    fs::OpenOptions,
-    io::{LineWriter, Write},
+    fs::{File, OpenOptions},
+    io::{self, LineWriter, Write},
+    path::Path,
 };
 
-use crate::Flush;
+use crate::{
+    file_hooks::{FileHooks, FileSummary},
+    Flush,
+};
 
 /// Flushes into a file
-pub struct FileFlusher(&'static str);
+pub struct FileFlusher {
+    path: &'static str,
+    hooks: FileHooks,
+    /// Whether the header was written, and the footer still has to be
+    started: bool,
+}
 
 impl FileFlusher {
     /// Flushes into file with specified path. Ensure that the directory exists for the destination log file,
     /// otherwise, an error would be thrown
     pub fn new(path: &'static str) -> FileFlusher {
-        FileFlusher(path)
+        FileFlusher {
+            path,
+            hooks: FileHooks::default(),
+            started: false,
+        }
+    }
+
+    /// Writes the output of `header` before the first log line, see
+    /// [`FileHooks`]
+    pub fn header(mut self, header: impl FnMut(&Path) -> String + Send + 'static) -> Self {
+        self.hooks.set_header(header);
+        self
+    }
+
+    /// Writes the output of `footer` after the last log line, see
+    /// [`FileHooks`]
+    pub fn footer(
+        mut self,
+        footer: impl FnMut(&FileSummary<'_>) -> String + Send + 'static,
+    ) -> Self {
+        self.hooks.set_footer(footer);
+        self
+    }
+
+    fn open(&self) -> io::Result<LineWriter<File>> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path)?;
+        Ok(LineWriter::new(file))
     }
 }
 
 impl Flush for FileFlusher {
     fn flush_one(&mut self, display: String) {
-        match OpenOptions::new().create(true).append(true).open(self.0) {
-            Ok(file) => {
-                let mut writer = LineWriter::new(file);
-                match writer.write_all(display.as_bytes()) {
-                    Ok(_) => (),
-                    Err(_) => panic!("Unable to write to file"),
-                };
+        self.flush_str(&display)
+    }
+
+    fn flush_str(&mut self, display: &str) {
+        if let Err(err) = self.try_flush_one(display) {
+            panic!("Unable to write to file: {}", err)
+        }
+    }
+
+    fn try_flush_one(&mut self, display: &str) -> io::Result<()> {
+        let mut file = self.open()?;
+        if !self.started {
+            if let Some(header) = self.hooks.header(Path::new(self.path)) {
+                file.write_all(header.as_bytes())?;
             }
-            Err(_) => panic!("Unable to open file"),
+            self.started = true;
+        }
+
+        file.write_all(display.as_bytes())?;
+        self.hooks.count(display.len());
+
+        Ok(())
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        if !std::mem::take(&mut self.started) {
+            return Ok(());
+        }
+
+        match self.hooks.footer(Path::new(self.path)) {
+            Some(footer) => self.open()?.write_all(footer.as_bytes()),
+            None => Ok(()),
         }
     }
 }
+
+impl Drop for FileFlusher {
+    fn drop(&mut self) {
+        _ = self.close();
+    }
+}
@@ -0,0 +1,97 @@
+use std::path::Path;
+
+/// Lines written to a file through [`FileHooks`], along with the path of the
+/// file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FileSummary<'a> {
+    /// Path of the file
+    pub path: &'a Path,
+    /// Number of log lines written since the header
+    pub lines: u64,
+    /// Number of bytes of log lines written since the header, excluding the
+    /// header itself
+    pub bytes: u64,
+}
+
+type Header = Box<dyn FnMut(&Path) -> String + Send>;
+type Footer = Box<dyn FnMut(&FileSummary<'_>) -> String + Send>;
+
+/// Header and footer written by file flushers around the log lines of each
+/// file, e.g. describing the schema of the lines, or summarizing them along
+/// with the number of records dropped through `quicklog::dropped_count`.
+///
+/// The header is written when a flusher starts writing to a file, and the
+/// footer once it stops, i.e. when the file is rotated, when the logger is
+/// shut down through [`Flush::close`](crate::Flush::close), or when the
+/// flusher is dropped. Appending to an existing file writes another header
+/// after its previous contents.
+///
+/// Both are written as is, so they should end with a newline.
+#[derive(Default)]
+pub struct FileHooks {
+    header: Option<Header>,
+    footer: Option<Footer>,
+    lines: u64,
+    bytes: u64,
+}
+
+impl FileHooks {
+    pub(crate) fn set_header(&mut self, header: impl FnMut(&Path) -> String + Send + 'static) {
+        self.header = Some(Box::new(header));
+    }
+
+    pub(crate) fn set_footer(
+        &mut self,
+        footer: impl FnMut(&FileSummary<'_>) -> String + Send + 'static,
+    ) {
+        self.footer = Some(Box::new(footer));
+    }
+
+    /// Header of the file at `path`, which was just opened
+    pub(crate) fn header(&mut self, path: &Path) -> Option<String> {
+        self.lines = 0;
+        self.bytes = 0;
+        self.header.as_mut().map(|header| header(path))
+    }
+
+    /// Counts a log line of `len` bytes written since the header
+    pub(crate) fn count(&mut self, len: usize) {
+        self.lines += 1;
+        self.bytes += len as u64;
+    }
+
+    /// Footer of the file at `path`, which is about to be closed
+    pub(crate) fn footer(&mut self, path: &Path) -> Option<String> {
+        let summary = FileSummary {
+            path,
+            lines: self.lines,
+            bytes: self.bytes,
+        };
+        self.footer.as_mut().map(|footer| footer(&summary))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::FileHooks;
+
+    #[test]
+    fn count_lines_since_header() {
+        let mut hooks = FileHooks::default();
+        assert!(hooks.header(Path::new("app.log")).is_none());
+        assert!(hooks.footer(Path::new("app.log")).is_none());
+
+        hooks.set_header(|path| format!("# {}\n", path.display()));
+        hooks.set_footer(|summary| format!("# {} lines {} bytes\n", summary.lines, summary.bytes));
+        hooks.count(4);
+        assert_eq!(hooks.header(Path::new("app.log")).unwrap(), "# app.log\n");
+        hooks.count(6);
+        hooks.count(3);
+        assert_eq!(
+            hooks.footer(Path::new("app.log")).unwrap(),
+            "# 2 lines 9 bytes\n"
+        );
+    }
+}
@@ -26,12 +26,32 @@
 //! }
 //! ```
 
+/// Flushes to a file through an in-memory buffer
+pub mod buffered_file_flusher;
+/// Flushes to a gzip-compressed file
+#[cfg(feature = "gzip")]
+pub mod compressed_file_flusher;
 /// Flushes to a file
 pub mod file_flusher;
+/// Headers and footers written around the log lines of each file
+pub mod file_hooks;
+/// Flushes to memory-mapped files
+#[cfg(feature = "mmap")]
+pub mod mmap_flusher;
 /// No-op Flush, does nothing
 pub mod noop_flusher;
-/// Flushes to stdout through `print!` macro
+/// Flushes to a file rotated by time and/or size
+pub mod rolling_file_flusher;
+/// Flushes to stderr through `eprint!` macro
+pub mod stderr_flusher;
+/// Flushes to stdout through `print!` macro, or while holding its lock
 pub mod stdout_flusher;
+/// Flushes to a syslog collector over UDP or TCP
+pub mod syslog_flusher;
+/// Flushes to a TCP connection
+pub mod tcp_flusher;
+/// Flushes to any `std::io::Write`
+pub mod writer_flusher;
 
 /// Simple trait that allows an underlying implementation of Flush to
 /// perform some type of IO operation, i.e. writing to file, writing to
@@ -40,4 +60,46 @@ pub trait Flush {
     /// Handles a string from another thread, and potentially performs I/O
     /// operations such as writing to a file or to stdout
     fn flush_one(&mut self, display: String);
+
+    /// Same as [`Flush::flush_one`], but borrowing the string, which allows
+    /// the caller to reuse its buffer across log lines.
+    ///
+    /// Defaults to copying into a `String` and calling `flush_one`, which
+    /// should be overridden by implementations which do not need ownership.
+    fn flush_str(&mut self, display: &str) {
+        self.flush_one(display.to_string())
+    }
+
+    /// Same as [`Flush::flush_str`], but returns any I/O error encountered
+    /// while writing the log line, e.g. when the disk is full or the pipe
+    /// was closed, instead of panicking or ignoring it. This allows the
+    /// caller to detect a dead sink and fail over to another one.
+    ///
+    /// Defaults to calling `flush_str`, which never fails, so it should be
+    /// overridden by implementations which perform I/O.
+    fn try_flush_one(&mut self, display: &str) -> std::io::Result<()> {
+        self.flush_str(display);
+        Ok(())
+    }
+
+    /// Same as [`Flush::try_flush_one`], additionally passed the name of the
+    /// level the log line was logged at, e.g. `"ERROR"`, so that it can be
+    /// carried over into the output, as by
+    /// [`SyslogFlusher`](crate::syslog_flusher::SyslogFlusher).
+    ///
+    /// Defaults to calling `try_flush_one`, ignoring the level.
+    fn try_flush_leveled(&mut self, level: &str, display: &str) -> std::io::Result<()> {
+        _ = level;
+        self.try_flush_one(display)
+    }
+
+    /// Completes the output once no more log lines are flushed for now, e.g.
+    /// writing any buffered lines and the footer of the current file, see
+    /// [`FileHooks`](crate::file_hooks::FileHooks). Flushing another line
+    /// afterwards starts the output again.
+    ///
+    /// Defaults to doing nothing.
+    fn close(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
 }
@@ -0,0 +1,245 @@
+use std::{
+    fs::{File, OpenOptions},
+    io,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use memmap2::MmapMut;
+
+use crate::Flush;
+
+/// File currently mapped into memory, along with the number of bytes written
+struct Mapping {
+    file: File,
+    map: MmapMut,
+    len: usize,
+}
+
+/// Flushes into a pre-allocated memory-mapped file, copying each line into
+/// the mapping without a syscall per line.
+///
+/// Files are allocated with [`MmapFlusher::file_size`] up front. Once a line
+/// does not fit into the remaining space, the file is truncated to the bytes
+/// written and a new file is started, with an index inserted before the
+/// extension, e.g. `app.log`, then `app.1.log`. Existing files are never
+/// overwritten, so a restarted process continues at the next free index.
+///
+/// Dirty pages are left to the OS to write back, unless a
+/// [`MmapFlusher::sync_interval`] is set, in which case they are scheduled
+/// through `msync` at most once per interval, checked whenever a line is
+/// flushed. Lines longer than the file size are dropped and counted by
+/// [`MmapFlusher::errors`].
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use quicklog_flush::mmap_flusher::MmapFlusher;
+///
+/// let flusher = MmapFlusher::new("logs", "app.log")
+///     .file_size(256 * 1024 * 1024)
+///     .sync_interval(Duration::from_secs(1));
+/// ```
+pub struct MmapFlusher {
+    directory: PathBuf,
+    stem: String,
+    extension: Option<String>,
+    file_size: usize,
+    sync_interval: Option<Duration>,
+    mapping: Option<Mapping>,
+    index: usize,
+    last_sync: Instant,
+    errors: usize,
+}
+
+impl MmapFlusher {
+    /// Default file size of 64MB
+    pub const DEFAULT_FILE_SIZE: usize = 64 * 1024 * 1024;
+
+    /// Flushes into files named after `file_name` in `directory`, which
+    /// should already exist. Files are only created on the first log line.
+    pub fn new(directory: impl Into<PathBuf>, file_name: &str) -> MmapFlusher {
+        let (stem, extension) = match file_name.rsplit_once('.') {
+            Some((stem, extension)) if !stem.is_empty() => {
+                (stem.to_string(), Some(extension.to_string()))
+            }
+            _ => (file_name.to_string(), None),
+        };
+
+        MmapFlusher {
+            directory: directory.into(),
+            stem,
+            extension,
+            file_size: Self::DEFAULT_FILE_SIZE,
+            sync_interval: None,
+            mapping: None,
+            index: 0,
+            last_sync: Instant::now(),
+            errors: 0,
+        }
+    }
+
+    /// Size each file is allocated with, and so the maximum size of each file
+    pub fn file_size(mut self, bytes: usize) -> Self {
+        self.file_size = bytes;
+        self
+    }
+
+    /// Schedules dirty pages to be written to disk at most once per
+    /// `interval`
+    pub fn sync_interval(mut self, interval: Duration) -> Self {
+        self.sync_interval = Some(interval);
+        self
+    }
+
+    /// Path of the file currently written to
+    pub fn current_path(&self) -> PathBuf {
+        let mut name = self.stem.clone();
+        if self.index > 0 {
+            name.push_str(&format!(".{}", self.index));
+        }
+        if let Some(extension) = &self.extension {
+            name.push('.');
+            name.push_str(extension);
+        }
+
+        self.directory.join(name)
+    }
+
+    /// Number of log lines which could not be written
+    pub fn errors(&self) -> usize {
+        self.errors
+    }
+
+    /// Synchronously writes the lines flushed so far to disk
+    pub fn sync(&mut self) -> io::Result<()> {
+        self.last_sync = Instant::now();
+        match &self.mapping {
+            Some(mapping) => mapping.map.flush_range(0, mapping.len),
+            None => Ok(()),
+        }
+    }
+
+    /// Maps the next file which does not exist yet
+    fn open(&mut self) -> io::Result<Mapping> {
+        while self.current_path().exists() {
+            self.index += 1;
+        }
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(self.current_path())?;
+        file.set_len(self.file_size as u64)?;
+        // SAFETY: the file was just created by this flusher, which is the only
+        // one writing to it while mapped
+        let map = unsafe { MmapMut::map_mut(&file)? };
+
+        Ok(Mapping { file, map, len: 0 })
+    }
+
+    /// Unmaps the current file, truncating it to the bytes written
+    fn close(&mut self) -> io::Result<()> {
+        let Some(Mapping { file, map, len }) = self.mapping.take() else {
+            return Ok(());
+        };
+
+        map.flush_range(0, len)?;
+        drop(map);
+        file.set_len(len as u64)
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        if bytes.len() > self.file_size {
+            return Err(io::ErrorKind::InvalidInput.into());
+        }
+
+        let full = self
+            .mapping
+            .as_ref()
+            .map_or(false, |mapping| mapping.len + bytes.len() > self.file_size);
+        if full {
+            self.close()?;
+            self.index += 1;
+        }
+
+        let mapping = match &mut self.mapping {
+            Some(mapping) => mapping,
+            None => {
+                let mapping = self.open()?;
+                self.mapping.insert(mapping)
+            }
+        };
+        mapping.map[mapping.len..mapping.len + bytes.len()].copy_from_slice(bytes);
+        mapping.len += bytes.len();
+
+        if let Some(interval) = self.sync_interval {
+            if self.last_sync.elapsed() >= interval {
+                self.last_sync = Instant::now();
+                mapping.map.flush_async()?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Flush for MmapFlusher {
+    fn flush_one(&mut self, display: String) {
+        self.flush_str(&display)
+    }
+
+    fn flush_str(&mut self, display: &str) {
+        _ = self.try_flush_one(display);
+    }
+
+    fn try_flush_one(&mut self, display: &str) -> io::Result<()> {
+        let written = self.write(display.as_bytes());
+        if written.is_err() {
+            self.errors += 1;
+        }
+
+        written
+    }
+}
+
+impl Drop for MmapFlusher {
+    fn drop(&mut self) {
+        _ = self.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::MmapFlusher;
+    use crate::Flush;
+
+    #[test]
+    fn roll_over_when_full() {
+        let dir = std::env::temp_dir().join(format!("quicklog_mmap_{}", std::process::id()));
+        _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut flusher = MmapFlusher::new(&dir, "app.log").file_size(12);
+        flusher.flush_str("hello\n");
+        flusher.flush_str("world\n");
+        flusher.flush_str("again\n");
+        flusher.flush_str("this line is too long\n");
+        assert_eq!(flusher.current_path(), dir.join("app.1.log"));
+        assert_eq!(flusher.errors(), 1);
+        drop(flusher);
+
+        // Continues after the existing files
+        let mut flusher = MmapFlusher::new(&dir, "app.log").file_size(12);
+        flusher.flush_str("restart\n");
+        drop(flusher);
+
+        let read = |name| fs::read_to_string(dir.join(name)).unwrap();
+        assert_eq!(read("app.log"), "hello\nworld\n");
+        assert_eq!(read("app.1.log"), "again\n");
+        assert_eq!(read("app.2.log"), "restart\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -16,4 +16,6 @@ impl Default for NoopFlusher {
 
 impl Flush for NoopFlusher {
     fn flush_one(&mut self, _display: String) {}
+
+    fn flush_str(&mut self, _display: &str) {}
 }
@@ -0,0 +1,455 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    file_hooks::{FileHooks, FileSummary},
+    Flush,
+};
+
+/// How often [`RollingFileFlusher`] starts a new file, based on UTC time
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rotation {
+    /// Never rotate based on time
+    Never,
+    /// New file every hour, e.g. `app.2024-05-01-13.log`
+    Hourly,
+    /// New file every day, e.g. `app.2024-05-01.log`
+    Daily,
+}
+
+impl Rotation {
+    fn period(&self, now: DateTime<Utc>) -> Option<String> {
+        match self {
+            Self::Never => None,
+            Self::Hourly => Some(now.format("%Y-%m-%d-%H").to_string()),
+            Self::Daily => Some(now.format("%Y-%m-%d").to_string()),
+        }
+    }
+}
+
+/// Flushes into a file which is rotated on a time schedule and/or once it
+/// exceeds a maximum size.
+///
+/// Files are named after the file name passed in, with the current period
+/// inserted before the extension, e.g. `app.log` is written to
+/// `app.2024-05-01.log` with [`Rotation::Daily`]. Files rotated because of
+/// their size get an additional index, e.g. `app.2024-05-01.1.log`.
+///
+/// With the `gzip` feature, `RollingFileFlusher::compress_rotated`
+/// compresses each file once a new one is started, e.g. into
+/// `app.2024-05-01.log.gz`.
+///
+/// Each file can start with a header and end with a footer, see
+/// [`FileHooks`].
+///
+/// ```no_run
+/// use quicklog_flush::rolling_file_flusher::{RollingFileFlusher, Rotation};
+///
+/// let flusher = RollingFileFlusher::new("logs", "app.log")
+///     .rotation(Rotation::Daily)
+///     .max_file_size(64 * 1024 * 1024)
+///     .max_files(7);
+/// ```
+pub struct RollingFileFlusher {
+    directory: PathBuf,
+    stem: String,
+    extension: Option<String>,
+    rotation: Rotation,
+    max_file_size: Option<u64>,
+    max_files: Option<usize>,
+    #[cfg(feature = "gzip")]
+    compress_rotated: bool,
+    file: Option<File>,
+    period: Option<String>,
+    index: usize,
+    size: u64,
+    hooks: FileHooks,
+}
+
+impl RollingFileFlusher {
+    /// Flushes into files named after `file_name` in `directory`, which
+    /// should already exist. Defaults to [`Rotation::Daily`], with no size
+    /// limit and no limit on the number of retained files.
+    pub fn new(directory: impl Into<PathBuf>, file_name: &str) -> RollingFileFlusher {
+        let (stem, extension) = match file_name.rsplit_once('.') {
+            Some((stem, extension)) if !stem.is_empty() => {
+                (stem.to_string(), Some(extension.to_string()))
+            }
+            _ => (file_name.to_string(), None),
+        };
+
+        RollingFileFlusher {
+            directory: directory.into(),
+            stem,
+            extension,
+            rotation: Rotation::Daily,
+            max_file_size: None,
+            max_files: None,
+            #[cfg(feature = "gzip")]
+            compress_rotated: false,
+            file: None,
+            period: None,
+            index: 0,
+            size: 0,
+            hooks: FileHooks::default(),
+        }
+    }
+
+    /// Sets the time-based [`Rotation`]
+    pub fn rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Starts a new file once writing to the current one would exceed
+    /// `max_bytes`
+    pub fn max_file_size(mut self, max_bytes: u64) -> Self {
+        self.max_file_size = Some(max_bytes);
+        self
+    }
+
+    /// Deletes the oldest log files once there are more than `max_files`
+    pub fn max_files(mut self, max_files: usize) -> Self {
+        self.max_files = Some(max_files);
+        self
+    }
+
+    /// Compresses each file into `<file>.gz` once a new file is started,
+    /// removing the uncompressed file. Disabled by default.
+    ///
+    /// Files are compressed on the flushing thread when rotating, which
+    /// delays the next log line for the duration.
+    #[cfg(feature = "gzip")]
+    pub fn compress_rotated(mut self, compress: bool) -> Self {
+        self.compress_rotated = compress;
+        self
+    }
+
+    /// Writes the output of `header` at the start of each file, see
+    /// [`FileHooks`]
+    pub fn header(mut self, header: impl FnMut(&Path) -> String + Send + 'static) -> Self {
+        self.hooks.set_header(header);
+        self
+    }
+
+    /// Writes the output of `footer` at the end of each file, once a new one
+    /// is started, see [`FileHooks`]
+    pub fn footer(
+        mut self,
+        footer: impl FnMut(&FileSummary<'_>) -> String + Send + 'static,
+    ) -> Self {
+        self.hooks.set_footer(footer);
+        self
+    }
+
+    /// Path of the file currently written to
+    pub fn current_path(&self) -> PathBuf {
+        self.path_for(self.period.as_deref(), self.index)
+    }
+
+    fn path_for(&self, period: Option<&str>, index: usize) -> PathBuf {
+        let mut name = self.stem.clone();
+        if let Some(period) = period {
+            name.push('.');
+            name.push_str(period);
+        }
+        if index > 0 {
+            name.push_str(&format!(".{}", index));
+        }
+        if let Some(extension) = &self.extension {
+            name.push('.');
+            name.push_str(extension);
+        }
+
+        self.directory.join(name)
+    }
+
+    /// If `path` is one of the files written by this flusher, returns its
+    /// period and index
+    fn parse_log_file(&self, path: &Path) -> Option<(String, usize)> {
+        let name = path.file_name()?.to_str()?;
+        let name = name.strip_suffix(".gz").unwrap_or(name);
+        let mut rest = name.strip_prefix(self.stem.as_str())?;
+        if let Some(extension) = &self.extension {
+            rest = rest.strip_suffix(extension.as_str())?.strip_suffix('.')?;
+        }
+        if rest.is_empty() {
+            return Some((String::new(), 0));
+        }
+
+        let mut segments = rest.strip_prefix('.')?.split('.');
+        let first = segments.next()?;
+        match (first.parse::<usize>(), segments.next()) {
+            (Ok(index), None) => Some((String::new(), index)),
+            (Err(_), None) => Some((first.to_string(), 0)),
+            (_, Some(index)) => Some((first.to_string(), index.parse().ok()?)),
+        }
+    }
+
+    /// Whether the file at `path` exists, or was compressed into `<path>.gz`
+    fn exists(path: &Path) -> bool {
+        let mut compressed_path = path.as_os_str().to_owned();
+        compressed_path.push(".gz");
+        path.exists() || Path::new(&compressed_path).exists()
+    }
+
+    fn open(&mut self, period: Option<String>, index: usize) -> io::Result<()> {
+        #[cfg(feature = "gzip")]
+        let previous = self.file.is_some().then(|| self.current_path());
+        self.write_footer()?;
+        let path = self.path_for(period.as_deref(), index);
+        let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+
+        self.size = file.metadata().map(|m| m.len()).unwrap_or(0);
+        if let Some(header) = self.hooks.header(&path) {
+            file.write_all(header.as_bytes())?;
+            self.size += header.len() as u64;
+        }
+        self.file = Some(file);
+        self.period = period;
+        self.index = index;
+
+        #[cfg(feature = "gzip")]
+        if let Some(previous) = previous.filter(|_| self.compress_rotated) {
+            _ = crate::compressed_file_flusher::compress_file(&previous);
+        }
+
+        self.remove_old_files();
+
+        Ok(())
+    }
+
+    /// Writes the footer to the current file, if any, and closes it
+    fn write_footer(&mut self) -> io::Result<()> {
+        let Some(mut file) = self.file.take() else {
+            return Ok(());
+        };
+
+        match self.hooks.footer(&self.current_path()) {
+            Some(footer) => file.write_all(footer.as_bytes()),
+            None => Ok(()),
+        }
+    }
+
+    fn remove_old_files(&self) {
+        let Some(max_files) = self.max_files else {
+            return;
+        };
+        let Ok(entries) = fs::read_dir(&self.directory) else {
+            return;
+        };
+
+        let mut files: Vec<_> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter_map(|path| {
+                let (period, index) = self.parse_log_file(&path)?;
+                let modified = path.metadata().and_then(|m| m.modified()).ok()?;
+                Some(((modified, period, index), path))
+            })
+            .collect();
+        if files.len() <= max_files {
+            return;
+        }
+
+        let current = self.current_path();
+        files.sort();
+        let excess = files.len() - max_files;
+        for (_, path) in files
+            .into_iter()
+            .filter(|(_, path)| *path != current)
+            .take(excess)
+        {
+            _ = fs::remove_file(path);
+        }
+    }
+}
+
+impl Flush for RollingFileFlusher {
+    fn flush_one(&mut self, display: String) {
+        self.flush_str(&display)
+    }
+
+    fn flush_str(&mut self, display: &str) {
+        if let Err(err) = self.try_flush_one(display) {
+            panic!("Unable to write to file: {}", err)
+        }
+    }
+
+    fn try_flush_one(&mut self, display: &str) -> io::Result<()> {
+        let period = self.rotation.period(Utc::now());
+        let len = display.len() as u64;
+
+        if self.file.is_none() || period != self.period {
+            // Continue after any files rotated due to size in this period
+            let mut index = 0;
+            while self.max_file_size.is_some()
+                && Self::exists(&self.path_for(period.as_deref(), index + 1))
+            {
+                index += 1;
+            }
+            self.open(period, index)?;
+        } else if matches!(self.max_file_size, Some(max) if self.size > 0 && self.size + len > max)
+        {
+            self.open(period, self.index + 1)?;
+        }
+
+        // Unwrap: file is always opened above
+        let file = self.file.as_mut().unwrap();
+        file.write_all(display.as_bytes())?;
+        self.size += len;
+        self.hooks.count(display.len());
+
+        Ok(())
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        self.write_footer()
+    }
+}
+
+impl Drop for RollingFileFlusher {
+    fn drop(&mut self) {
+        _ = self.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use chrono::{TimeZone, Utc};
+
+    use super::{RollingFileFlusher, Rotation};
+    use crate::Flush;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("quicklog-rolling-{}-{}", name, std::process::id()));
+        _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn rotation_periods() {
+        let time = Utc.with_ymd_and_hms(2024, 5, 1, 13, 0, 0).unwrap();
+        assert_eq!(Rotation::Never.period(time), None);
+        assert_eq!(Rotation::Hourly.period(time).unwrap(), "2024-05-01-13");
+        assert_eq!(Rotation::Daily.period(time).unwrap(), "2024-05-01");
+
+        let flusher = RollingFileFlusher::new("logs", "app.log");
+        assert_eq!(
+            flusher.path_for(Some("2024-05-01"), 0),
+            PathBuf::from("logs/app.2024-05-01.log")
+        );
+        assert_eq!(
+            flusher.path_for(Some("2024-05-01"), 2),
+            PathBuf::from("logs/app.2024-05-01.2.log")
+        );
+        assert_eq!(flusher.path_for(None, 0), PathBuf::from("logs/app.log"));
+
+        let parse = |name: &str| flusher.parse_log_file(&PathBuf::from(name));
+        assert_eq!(parse("app.log"), Some((String::new(), 0)));
+        assert_eq!(parse("app.3.log"), Some((String::new(), 3)));
+        assert_eq!(
+            parse("app.2024-05-01.log"),
+            Some(("2024-05-01".to_string(), 0))
+        );
+        assert_eq!(
+            parse("app.2024-05-01.2.log"),
+            Some(("2024-05-01".to_string(), 2))
+        );
+        assert_eq!(parse("application.log"), None);
+        assert_eq!(parse("app.txt"), None);
+    }
+
+    #[test]
+    fn rotate_on_size_and_retain() {
+        let dir = test_dir("size");
+        let mut flusher = RollingFileFlusher::new(&dir, "app.log")
+            .rotation(Rotation::Never)
+            .max_file_size(10)
+            .max_files(2);
+
+        for line in ["12345\n", "12345\n", "abcde\n", "fghij\n"] {
+            flusher.flush_one(line.to_string());
+        }
+
+        assert_eq!(
+            fs::read_to_string(dir.join("app.2.log")).unwrap(),
+            "abcde\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("app.3.log")).unwrap(),
+            "fghij\n"
+        );
+        assert!(!dir.join("app.log").exists());
+        assert!(!dir.join("app.1.log").exists());
+
+        _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn header_and_footer_per_file() {
+        let dir = test_dir("hooks");
+        let mut flusher = RollingFileFlusher::new(&dir, "app.log")
+            .rotation(Rotation::Never)
+            .max_file_size(25)
+            .header(|path| format!("# {}\n", path.file_name().unwrap().to_str().unwrap()))
+            .footer(|summary| format!("# {} lines\n", summary.lines));
+
+        for line in ["12345\n", "abcde\n", "fghij\n"] {
+            flusher.flush_one(line.to_string());
+        }
+        drop(flusher);
+
+        assert_eq!(
+            fs::read_to_string(dir.join("app.log")).unwrap(),
+            "# app.log\n12345\nabcde\n# 2 lines\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("app.1.log")).unwrap(),
+            "# app.1.log\nfghij\n# 1 lines\n"
+        );
+
+        _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn compress_rotated() {
+        use std::io::Read;
+
+        let dir = test_dir("gzip");
+        let mut flusher = RollingFileFlusher::new(&dir, "app.log")
+            .rotation(Rotation::Never)
+            .max_file_size(10)
+            .max_files(2)
+            .compress_rotated(true);
+
+        for line in ["12345\n", "abcde\n", "fghij\n"] {
+            flusher.flush_one(line.to_string());
+        }
+
+        let mut decompressed = String::new();
+        flate2::read::GzDecoder::new(fs::File::open(dir.join("app.1.log.gz")).unwrap())
+            .read_to_string(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, "abcde\n");
+        assert!(!dir.join("app.1.log").exists());
+        // the oldest compressed file is removed by the retention limit
+        assert!(!dir.join("app.log.gz").exists());
+        assert_eq!(
+            fs::read_to_string(dir.join("app.2.log")).unwrap(),
+            "fghij\n"
+        );
+
+        _ = fs::remove_dir_all(&dir);
+    }
+}
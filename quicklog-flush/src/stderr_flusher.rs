@@ -0,0 +1,32 @@
+use std::io::{self, Write};
+
+use crate::Flush;
+
+/// Flushes into stderr
+pub struct StderrFlusher;
+
+impl StderrFlusher {
+    pub fn new() -> StderrFlusher {
+        StderrFlusher {}
+    }
+}
+
+impl Default for StderrFlusher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Flush for StderrFlusher {
+    fn flush_one(&mut self, display: String) {
+        self.flush_str(&display)
+    }
+
+    fn flush_str(&mut self, display: &str) {
+        eprint!("{}", display);
+    }
+
+    fn try_flush_one(&mut self, display: &str) -> io::Result<()> {
+        io::stderr().write_all(display.as_bytes())
+    }
+}
@@ -1,3 +1,8 @@
+use std::{
+    io::{self, StdoutLock, Write},
+    time::{Duration, Instant},
+};
+
 use crate::Flush;
 
 /// Flushes into stdout
@@ -17,6 +22,153 @@ impl Default for StdoutFlusher {
 
 impl Flush for StdoutFlusher {
     fn flush_one(&mut self, display: String) {
+        self.flush_str(&display)
+    }
+
+    fn flush_str(&mut self, display: &str) {
         print!("{}", display);
     }
+
+    fn try_flush_one(&mut self, display: &str) -> io::Result<()> {
+        io::stdout().write_all(display.as_bytes())
+    }
+}
+
+/// Flushes into stdout while holding its lock, accumulating lines in memory
+/// and only writing them once the buffer reaches its capacity or the flush
+/// interval elapses.
+///
+/// Unlike [`StdoutFlusher`], which locks stdout for every line through
+/// `print!`, the lock is acquired on the first write and held until the
+/// flusher is dropped, so writing to stdout from elsewhere blocks in the
+/// meantime. As the lock belongs to the thread which acquired it, the flusher
+/// should always be used from the same thread.
+///
+/// Note that the flush interval is only checked whenever a line is flushed.
+/// Any buffered lines are written when the flusher is dropped, or explicitly
+/// through [`LockedStdoutFlusher::flush_buffer`].
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use quicklog_flush::stdout_flusher::LockedStdoutFlusher;
+///
+/// let flusher = LockedStdoutFlusher::new()
+///     .buffer_capacity(64 * 1024)
+///     .flush_interval(Duration::from_millis(100));
+/// ```
+pub struct LockedStdoutFlusher {
+    stdout: Option<StdoutLock<'static>>,
+    buffer: Vec<u8>,
+    capacity: usize,
+    flush_interval: Option<Duration>,
+    last_flush: Instant,
+}
+
+impl LockedStdoutFlusher {
+    /// Default buffer capacity of 8KB
+    pub const DEFAULT_CAPACITY: usize = 8 * 1024;
+
+    pub fn new() -> LockedStdoutFlusher {
+        LockedStdoutFlusher {
+            stdout: None,
+            buffer: Vec::with_capacity(Self::DEFAULT_CAPACITY),
+            capacity: Self::DEFAULT_CAPACITY,
+            flush_interval: None,
+            last_flush: Instant::now(),
+        }
+    }
+
+    /// Writes to stdout once at least `bytes` are buffered
+    pub fn buffer_capacity(mut self, bytes: usize) -> Self {
+        self.capacity = bytes;
+        self.buffer.reserve(bytes.saturating_sub(self.buffer.len()));
+        self
+    }
+
+    /// Writes to stdout if `interval` has elapsed since the last write
+    pub fn flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = Some(interval);
+        self
+    }
+
+    /// Writes all buffered lines to stdout
+    pub fn flush_buffer(&mut self) {
+        // Same as `print!`, except that a closed stdout is ignored rather
+        // than panicking
+        _ = self.try_flush_buffer();
+    }
+
+    /// Same as [`LockedStdoutFlusher::flush_buffer`], but returns any error
+    /// encountered while writing, in which case the buffered lines are
+    /// discarded
+    pub fn try_flush_buffer(&mut self) -> io::Result<()> {
+        self.last_flush = Instant::now();
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        let stdout = self.stdout.get_or_insert_with(|| io::stdout().lock());
+        let written = stdout.write_all(&self.buffer).and_then(|_| stdout.flush());
+        self.buffer.clear();
+
+        written
+    }
+}
+
+impl Default for LockedStdoutFlusher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Flush for LockedStdoutFlusher {
+    fn flush_one(&mut self, display: String) {
+        self.flush_str(&display)
+    }
+
+    fn flush_str(&mut self, display: &str) {
+        _ = self.try_flush_one(display);
+    }
+
+    fn try_flush_one(&mut self, display: &str) -> io::Result<()> {
+        self.buffer.extend_from_slice(display.as_bytes());
+
+        let interval_elapsed = self
+            .flush_interval
+            .map_or(false, |interval| self.last_flush.elapsed() >= interval);
+        if self.buffer.len() >= self.capacity || interval_elapsed {
+            self.try_flush_buffer()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for LockedStdoutFlusher {
+    fn drop(&mut self) {
+        self.flush_buffer();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::LockedStdoutFlusher;
+    use crate::Flush;
+
+    #[test]
+    fn buffer_until_capacity_or_interval() {
+        let mut flusher = LockedStdoutFlusher::new().buffer_capacity(10);
+        flusher.flush_one("12345\n".to_string());
+        assert_eq!(flusher.buffer, b"12345\n");
+
+        flusher.flush_one("67890\n".to_string());
+        assert!(flusher.buffer.is_empty());
+        assert!(flusher.stdout.is_some());
+
+        let mut flusher = LockedStdoutFlusher::new().flush_interval(Duration::ZERO);
+        flusher.flush_str("hello\n");
+        assert!(flusher.buffer.is_empty());
+    }
 }
@@ -0,0 +1,327 @@
+use std::{io, net::UdpSocket, time::Duration};
+
+use chrono::{SecondsFormat, Utc};
+
+use crate::{tcp_flusher::Connection, Flush};
+
+/// Syslog facility, see RFC 5424 section 6.2.1
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Facility {
+    Kern = 0,
+    User = 1,
+    Mail = 2,
+    Daemon = 3,
+    Auth = 4,
+    Syslog = 5,
+    Lpr = 6,
+    News = 7,
+    Uucp = 8,
+    Cron = 9,
+    AuthPriv = 10,
+    Ftp = 11,
+    Local0 = 16,
+    Local1 = 17,
+    Local2 = 18,
+    Local3 = 19,
+    Local4 = 20,
+    Local5 = 21,
+    Local6 = 22,
+    Local7 = 23,
+}
+
+/// Syslog severity, see RFC 5424 section 6.2.1
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Emergency = 0,
+    Alert = 1,
+    Critical = 2,
+    Error = 3,
+    Warning = 4,
+    Notice = 5,
+    Informational = 6,
+    Debug = 7,
+}
+
+impl Severity {
+    /// Maps the name of a quicklog level onto a severity: `TRACE` and `DEBUG`
+    /// onto [`Severity::Debug`], `WARN` onto [`Severity::Warning`], `ERROR`
+    /// onto [`Severity::Error`] and `FATAL` onto [`Severity::Critical`].
+    /// Any other level, e.g. `INFO`, `EVENT` or a custom level, is
+    /// [`Severity::Informational`].
+    pub fn from_level(level: &str) -> Severity {
+        match level {
+            "TRACE" | "DEBUG" => Severity::Debug,
+            "WARN" => Severity::Warning,
+            "ERROR" => Severity::Error,
+            "FATAL" => Severity::Critical,
+            _ => Severity::Informational,
+        }
+    }
+}
+
+enum Transport {
+    Udp {
+        addr: String,
+        socket: Option<UdpSocket>,
+        dropped: usize,
+    },
+    Tcp(Connection),
+}
+
+/// Flushes into a syslog collector as RFC 5424 messages, over UDP or TCP.
+///
+/// The severity of each message is mapped from the level of its log line
+/// through [`Severity::from_level`], unless overridden through
+/// [`SyslogFlusher::severity`].
+///
+/// Over TCP, messages are framed with octet counting (RFC 6587), and are
+/// dropped while the connection is down, in the same way as
+/// [`TcpFlusher`](crate::tcp_flusher::TcpFlusher).
+///
+/// ```no_run
+/// use quicklog_flush::syslog_flusher::{Facility, SyslogFlusher};
+///
+/// let flusher = SyslogFlusher::udp("127.0.0.1:514")
+///     .facility(Facility::Local0)
+///     .app_name("my-app");
+/// ```
+pub struct SyslogFlusher {
+    transport: Transport,
+    facility: Facility,
+    /// Overrides the severity mapped from the level of each log line
+    severity: Option<Severity>,
+    hostname: String,
+    app_name: String,
+    proc_id: u32,
+}
+
+impl SyslogFlusher {
+    fn new(transport: Transport) -> SyslogFlusher {
+        let app_name = std::env::current_exe()
+            .ok()
+            .and_then(|path| Some(path.file_name()?.to_string_lossy().into_owned()))
+            .unwrap_or_else(|| "-".to_string());
+
+        SyslogFlusher {
+            transport,
+            facility: Facility::User,
+            severity: None,
+            hostname: "-".to_string(),
+            app_name,
+            proc_id: std::process::id(),
+        }
+    }
+
+    /// Sends messages as UDP datagrams to `addr`, e.g. `"127.0.0.1:514"`
+    pub fn udp(addr: impl Into<String>) -> SyslogFlusher {
+        Self::new(Transport::Udp {
+            addr: addr.into(),
+            socket: None,
+            dropped: 0,
+        })
+    }
+
+    /// Sends messages over a TCP connection to `addr`, e.g. `"127.0.0.1:601"`
+    pub fn tcp(addr: impl Into<String>) -> SyslogFlusher {
+        Self::new(Transport::Tcp(Connection::new(addr.into())))
+    }
+
+    /// Facility of every message, defaults to [`Facility::User`]
+    pub fn facility(mut self, facility: Facility) -> Self {
+        self.facility = facility;
+        self
+    }
+
+    /// Severity of every message, overriding the one mapped from the level
+    /// of each log line. Messages flushed without a level, e.g. through
+    /// [`Flush::flush_one`], default to [`Severity::Informational`].
+    pub fn severity(mut self, severity: Severity) -> Self {
+        self.severity = Some(severity);
+        self
+    }
+
+    /// Hostname of every message, defaults to the nil value `-`, which
+    /// collectors usually replace with the sender's address
+    pub fn hostname(mut self, hostname: impl Into<String>) -> Self {
+        self.hostname = hostname.into();
+        self
+    }
+
+    /// Application name of every message, defaults to the executable name
+    pub fn app_name(mut self, app_name: impl Into<String>) -> Self {
+        self.app_name = app_name.into();
+        self
+    }
+
+    /// Timeout when connecting over TCP, defaults to 1s
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        if let Transport::Tcp(connection) = &mut self.transport {
+            connection.set_connect_timeout(timeout);
+        }
+        self
+    }
+
+    /// Timeout of each write over TCP, defaults to 1s, see
+    /// [`TcpFlusher::write_timeout`](crate::tcp_flusher::TcpFlusher::write_timeout)
+    pub fn write_timeout(mut self, timeout: Duration) -> Self {
+        if let Transport::Tcp(connection) = &mut self.transport {
+            connection.set_write_timeout(timeout);
+        }
+        self
+    }
+
+    /// Backoff between failed connection attempts over TCP, see
+    /// [`TcpFlusher::backoff`](crate::tcp_flusher::TcpFlusher::backoff)
+    pub fn backoff(mut self, min: Duration, max: Duration) -> Self {
+        if let Transport::Tcp(connection) = &mut self.transport {
+            connection.set_backoff(min, max);
+        }
+        self
+    }
+
+    /// Number of messages dropped as they could not be sent
+    pub fn dropped(&self) -> usize {
+        match &self.transport {
+            Transport::Udp { dropped, .. } => *dropped,
+            Transport::Tcp(connection) => connection.dropped(),
+        }
+    }
+
+    fn format(&self, severity: Severity, display: &str) -> String {
+        format!(
+            "<{}>1 {} {} {} {} - - {}",
+            self.facility as u8 * 8 + severity as u8,
+            Utc::now().to_rfc3339_opts(SecondsFormat::Micros, true),
+            self.hostname,
+            self.app_name,
+            self.proc_id,
+            display.trim_end_matches('\n'),
+        )
+    }
+
+    fn send(&mut self, severity: Severity, display: &str) -> io::Result<()> {
+        let message = self.format(severity, display);
+        match &mut self.transport {
+            Transport::Udp {
+                addr,
+                socket,
+                dropped,
+            } => {
+                let socket = match socket {
+                    Some(socket) => socket,
+                    socket @ None => match UdpSocket::bind("0.0.0.0:0") {
+                        Ok(bound) => socket.insert(bound),
+                        Err(err) => {
+                            *dropped += 1;
+                            return Err(err);
+                        }
+                    },
+                };
+                if let Err(err) = socket.send_to(message.as_bytes(), addr.as_str()) {
+                    *dropped += 1;
+                    return Err(err);
+                }
+
+                Ok(())
+            }
+            Transport::Tcp(connection) => {
+                connection.write(format!("{} {}", message.len(), message).as_bytes())
+            }
+        }
+    }
+}
+
+impl Flush for SyslogFlusher {
+    fn flush_one(&mut self, display: String) {
+        self.flush_str(&display)
+    }
+
+    fn flush_str(&mut self, display: &str) {
+        _ = self.try_flush_one(display);
+    }
+
+    fn try_flush_one(&mut self, display: &str) -> io::Result<()> {
+        self.send(self.severity.unwrap_or(Severity::Informational), display)
+    }
+
+    fn try_flush_leveled(&mut self, level: &str, display: &str) -> io::Result<()> {
+        let severity = self.severity.unwrap_or_else(|| Severity::from_level(level));
+        self.send(severity, display)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Read,
+        net::{TcpListener, UdpSocket},
+    };
+
+    use super::{Facility, Severity, SyslogFlusher};
+    use crate::Flush;
+
+    #[test]
+    fn udp_message() {
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut flusher = SyslogFlusher::udp(receiver.local_addr().unwrap().to_string())
+            .facility(Facility::Local0)
+            .severity(Severity::Warning)
+            .hostname("host")
+            .app_name("app");
+
+        flusher.flush_one("hello world\n".to_string());
+
+        let mut buf = [0; 1024];
+        let len = receiver.recv(&mut buf).unwrap();
+        let message = std::str::from_utf8(&buf[..len]).unwrap();
+        assert!(message.starts_with("<132>1 "), "{}", message);
+        assert!(
+            message.ends_with(&format!(" host app {} - - hello world", std::process::id())),
+            "{}",
+            message
+        );
+        assert_eq!(flusher.dropped(), 0);
+    }
+
+    #[test]
+    fn severity_from_level() {
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = receiver.local_addr().unwrap().to_string();
+        let mut buf = [0; 1024];
+        let mut pri = |flusher: &mut SyslogFlusher, level: &str| {
+            flusher.try_flush_leveled(level, "hello\n").unwrap();
+            let len = receiver.recv(&mut buf).unwrap();
+            let message = std::str::from_utf8(&buf[..len]).unwrap();
+            message[..message.find('>').unwrap() + 1].to_string()
+        };
+
+        // facility user is 1, so PRI is 8 + severity
+        let mut flusher = SyslogFlusher::udp(addr.clone());
+        assert_eq!(pri(&mut flusher, "DEBUG"), "<15>");
+        assert_eq!(pri(&mut flusher, "INFO"), "<14>");
+        assert_eq!(pri(&mut flusher, "WARN"), "<12>");
+        assert_eq!(pri(&mut flusher, "ERROR"), "<11>");
+        assert_eq!(pri(&mut flusher, "FATAL"), "<10>");
+
+        let mut flusher = SyslogFlusher::udp(addr).severity(Severity::Notice);
+        assert_eq!(pri(&mut flusher, "ERROR"), "<13>");
+    }
+
+    #[test]
+    fn tcp_octet_counting() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut flusher = SyslogFlusher::tcp(listener.local_addr().unwrap().to_string());
+
+        flusher.flush_one("hello\n".to_string());
+        drop(flusher);
+
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut received = String::new();
+        stream.read_to_string(&mut received).unwrap();
+
+        let (len, message) = received.split_once(' ').unwrap();
+        assert_eq!(len.parse::<usize>().unwrap(), message.len());
+        assert!(message.starts_with("<14>1 "));
+        assert!(message.ends_with(" - - hello"));
+    }
+}
@@ -0,0 +1,256 @@
+use std::{
+    io::{self, Write},
+    net::{TcpStream, ToSocketAddrs},
+    time::{Duration, Instant},
+};
+
+use crate::Flush;
+
+/// TCP connection which is re-established on failure, backing off
+/// exponentially between failed attempts, including writes which failed or
+/// timed out
+pub(crate) struct Connection {
+    addr: String,
+    stream: Option<TcpStream>,
+    connect_timeout: Duration,
+    write_timeout: Duration,
+    min_backoff: Duration,
+    max_backoff: Duration,
+    backoff: Duration,
+    next_attempt: Option<Instant>,
+    dropped: usize,
+}
+
+impl Connection {
+    pub(crate) fn new(addr: String) -> Self {
+        let min_backoff = Duration::from_millis(100);
+        Self {
+            addr,
+            stream: None,
+            connect_timeout: Duration::from_secs(1),
+            write_timeout: Duration::from_secs(1),
+            min_backoff,
+            max_backoff: Duration::from_secs(30),
+            backoff: min_backoff,
+            next_attempt: None,
+            dropped: 0,
+        }
+    }
+
+    pub(crate) fn set_connect_timeout(&mut self, timeout: Duration) {
+        self.connect_timeout = timeout;
+    }
+
+    pub(crate) fn set_write_timeout(&mut self, timeout: Duration) {
+        self.write_timeout = timeout;
+        if let Some(stream) = &self.stream {
+            _ = stream.set_write_timeout(Some(timeout));
+        }
+    }
+
+    pub(crate) fn set_backoff(&mut self, min: Duration, max: Duration) {
+        self.min_backoff = min;
+        self.max_backoff = max;
+        self.backoff = min;
+    }
+
+    pub(crate) fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Writes `bytes`, or drops them if the connection is down, returning
+    /// an error of kind [`io::ErrorKind::NotConnected`] while waiting to
+    /// reconnect
+    pub(crate) fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        if self.stream.is_none() && !self.try_connect() {
+            self.dropped += 1;
+            return Err(io::ErrorKind::NotConnected.into());
+        }
+
+        if let Some(stream) = &mut self.stream {
+            if let Err(err) = stream.write_all(bytes) {
+                // Reconnect once the backoff has elapsed, as the collector
+                // may be stalled rather than gone
+                self.stream = None;
+                self.dropped += 1;
+                self.back_off(Instant::now());
+                return Err(err);
+            }
+        }
+
+        self.backoff = self.min_backoff;
+        Ok(())
+    }
+
+    fn try_connect(&mut self) -> bool {
+        let now = Instant::now();
+        if self.next_attempt.map_or(false, |next| now < next) {
+            return false;
+        }
+
+        let stream = self.addr.to_socket_addrs().ok().and_then(|mut addrs| {
+            addrs.find_map(|addr| TcpStream::connect_timeout(&addr, self.connect_timeout).ok())
+        });
+        match stream {
+            Some(stream) => {
+                _ = stream.set_nodelay(true);
+                _ = stream.set_write_timeout(Some(self.write_timeout));
+                self.stream = Some(stream);
+                self.next_attempt = None;
+                true
+            }
+            None => {
+                self.back_off(now);
+                false
+            }
+        }
+    }
+
+    /// Waits for the current backoff before the next connection attempt,
+    /// doubling it for the attempt after. The backoff is only reset once a
+    /// write succeeds.
+    fn back_off(&mut self, now: Instant) {
+        self.next_attempt = Some(now + self.backoff);
+        self.backoff = (self.backoff * 2).min(self.max_backoff);
+    }
+}
+
+/// Flushes into a TCP connection, e.g. to a central log collector.
+///
+/// Connects lazily on the first log line. If the connection goes down, or a
+/// write does not complete within the write timeout, e.g. as the collector
+/// stopped reading, log lines are dropped rather than buffered, while
+/// reconnecting with exponential backoff. The number of dropped lines is available through
+/// [`TcpFlusher::dropped`].
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use quicklog_flush::tcp_flusher::TcpFlusher;
+///
+/// let flusher = TcpFlusher::new("collector:5170")
+///     .backoff(Duration::from_millis(50), Duration::from_secs(10));
+/// ```
+pub struct TcpFlusher {
+    connection: Connection,
+}
+
+impl TcpFlusher {
+    /// Flushes into a connection to `addr`, e.g. `"127.0.0.1:5170"`
+    pub fn new(addr: impl Into<String>) -> TcpFlusher {
+        TcpFlusher {
+            connection: Connection::new(addr.into()),
+        }
+    }
+
+    /// Timeout when connecting, defaults to 1s
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connection.set_connect_timeout(timeout);
+        self
+    }
+
+    /// Timeout of each write, after which the log line is dropped and the
+    /// connection re-established, defaults to 1s
+    pub fn write_timeout(mut self, timeout: Duration) -> Self {
+        self.connection.set_write_timeout(timeout);
+        self
+    }
+
+    /// Backoff between failed connection attempts, doubling from `min` up to
+    /// `max` while the connection stays down. Defaults to 100ms and 30s.
+    pub fn backoff(mut self, min: Duration, max: Duration) -> Self {
+        self.connection.set_backoff(min, max);
+        self
+    }
+
+    /// Number of log lines dropped as the connection was down
+    pub fn dropped(&self) -> usize {
+        self.connection.dropped()
+    }
+}
+
+impl Flush for TcpFlusher {
+    fn flush_one(&mut self, display: String) {
+        self.flush_str(&display)
+    }
+
+    fn flush_str(&mut self, display: &str) {
+        _ = self.try_flush_one(display);
+    }
+
+    fn try_flush_one(&mut self, display: &str) -> io::Result<()> {
+        self.connection.write(display.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader},
+        net::TcpListener,
+        time::Duration,
+    };
+
+    use super::TcpFlusher;
+    use crate::Flush;
+
+    #[test]
+    fn write_lines() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut flusher = TcpFlusher::new(listener.local_addr().unwrap().to_string());
+
+        flusher.flush_one("hello\n".to_string());
+        flusher.flush_one("world\n".to_string());
+
+        let (stream, _) = listener.accept().unwrap();
+        let lines: Vec<_> = BufReader::new(stream)
+            .lines()
+            .take(2)
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(lines, vec!["hello", "world"]);
+        assert_eq!(flusher.dropped(), 0);
+    }
+
+    #[test]
+    fn drop_while_disconnected() {
+        // Nothing listens on the port once the listener is dropped
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let mut flusher = TcpFlusher::new(addr.to_string())
+            .backoff(Duration::from_secs(60), Duration::from_secs(60));
+
+        flusher.flush_one("lost\n".to_string());
+        flusher.flush_one("also lost\n".to_string());
+        assert_eq!(flusher.dropped(), 2);
+
+        let err = flusher.try_flush_one("still lost\n").unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotConnected);
+        assert_eq!(flusher.dropped(), 3);
+    }
+
+    #[test]
+    fn back_off_after_write_timeout() {
+        // The collector accepts the connection but never reads from it
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut flusher = TcpFlusher::new(listener.local_addr().unwrap().to_string())
+            .write_timeout(Duration::from_millis(10))
+            .backoff(Duration::from_secs(60), Duration::from_secs(60));
+
+        let line = "x".repeat(1 << 20);
+        let err = (0..1000)
+            .find_map(|_| flusher.try_flush_one(&line).err())
+            .expect("write never timed out");
+        assert!(matches!(
+            err.kind(),
+            std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+        ));
+
+        // No reconnection is attempted until the backoff has elapsed
+        let err = flusher.try_flush_one("hello\n").unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotConnected);
+        assert_eq!(flusher.dropped(), 2);
+        drop(listener);
+    }
+}
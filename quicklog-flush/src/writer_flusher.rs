@@ -0,0 +1,97 @@
+use std::io::{self, Write};
+
+use crate::Flush;
+
+/// Flushes into any [`std::io::Write`], e.g. a socket, a pipe, a memory-mapped
+/// region wrapped in a [`std::io::Cursor`] or an in-memory `Vec<u8>`.
+///
+/// Log lines passed through [`Flush::flush_str`] are written as bytes
+/// directly, without an intermediate `String` per line. Every line is written
+/// through `write_all`, so wrap unbuffered writers in a
+/// [`std::io::BufWriter`] to avoid a syscall per line. Lines which fail to be
+/// written are counted by [`WriterFlusher::errors`].
+///
+/// ```
+/// use std::io::BufWriter;
+/// use quicklog_flush::{writer_flusher::WriterFlusher, Flush};
+///
+/// let mut flusher = WriterFlusher::new(Vec::new());
+/// flusher.flush_str("hello world\n");
+/// assert_eq!(flusher.get_ref(), b"hello world\n");
+///
+/// let stderr = WriterFlusher::new(BufWriter::new(std::io::stderr()));
+/// ```
+pub struct WriterFlusher<W: Write> {
+    writer: W,
+    errors: usize,
+}
+
+impl<W: Write> WriterFlusher<W> {
+    /// Flushes into `writer`
+    pub fn new(writer: W) -> WriterFlusher<W> {
+        WriterFlusher { writer, errors: 0 }
+    }
+
+    /// Returns a reference to the underlying writer
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Returns a mutable reference to the underlying writer, e.g. to flush it
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Returns the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Number of log lines which failed to be written
+    pub fn errors(&self) -> usize {
+        self.errors
+    }
+}
+
+impl<W: Write> Flush for WriterFlusher<W> {
+    fn flush_one(&mut self, display: String) {
+        self.flush_str(&display)
+    }
+
+    fn flush_str(&mut self, display: &str) {
+        _ = self.try_flush_one(display);
+    }
+
+    fn try_flush_one(&mut self, display: &str) -> io::Result<()> {
+        let written = self.writer.write_all(display.as_bytes());
+        if written.is_err() {
+            self.errors += 1;
+        }
+
+        written
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::WriterFlusher;
+    use crate::Flush;
+
+    #[test]
+    fn write_and_count_errors() {
+        let mut flusher = WriterFlusher::new(Cursor::new([0; 8]));
+        flusher.flush_one("hello\n".to_string());
+        assert_eq!(flusher.errors(), 0);
+
+        // Does not fit into the remaining 2 bytes
+        flusher.flush_str("world\n");
+        assert_eq!(flusher.errors(), 1);
+        let err = flusher.try_flush_one("world\n").unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::WriteZero);
+        assert_eq!(flusher.errors(), 2);
+
+        assert_eq!(&flusher.into_inner().get_ref()[..6], b"hello\n");
+    }
+}
@@ -15,14 +15,15 @@ pub(crate) type DotDelimitedIdent = Punctuated<Ident, Token![.]>;
 /// e.g. `my.name = ?debug_struct`, `%display_struct`
 pub(crate) type PrefixedFields = Punctuated<NamedField<PrefixedArg>, Token![,]>;
 
-/// Comma-separated sequence of `Expr`-based named fields
-/// e.g. `my.name = debug_struct`, `display_struct`
-/// Similar to `PrefixedFields`, but doesn't allow for prefixes for the
-/// main field argument
-pub(crate) type ExprFields = Punctuated<NamedField<Expr>, Token![,]>;
+/// Comma-separated sequence of `FmtArg`-based named fields
+/// e.g. `my.name = debug_struct`, `^serialize_struct`
+/// Similar to `PrefixedFields`, but `?` and `%` eagerly format the main
+/// field argument
+pub(crate) type ExprFields = Punctuated<NamedField<FmtArg>, Token![,]>;
 
 /// Formatting argument with an optional prefix
-/// e.g. `?debug_struct`, `%display_struct`, `^serialize_struct`, `some_struct`
+/// e.g. `?debug_struct`, `%display_struct`, `^serialize_struct`, `!err`,
+/// `some_struct`
 #[derive(Clone)]
 pub(crate) enum PrefixedArg {
     /// `?debug_struct`
@@ -31,6 +32,8 @@ pub(crate) enum PrefixedArg {
     Display(Expr),
     /// `^serialize_struct`
     Serialize(Expr),
+    /// `!err`
+    Error(Expr),
     /// `some_struct`
     Normal(Expr),
 }
@@ -39,7 +42,11 @@ impl PrefixedArg {
     /// The captured expression for this argument
     pub(crate) fn expr(&self) -> &Expr {
         match self {
-            Self::Debug(i) | Self::Display(i) | Self::Serialize(i) | Self::Normal(i) => i,
+            Self::Debug(i)
+            | Self::Display(i)
+            | Self::Serialize(i)
+            | Self::Error(i)
+            | Self::Normal(i) => i,
         }
     }
 }
@@ -64,6 +71,10 @@ impl Parse for PrefixedArg {
             input.parse::<Token![^]>()?;
 
             Ok(PrefixedArg::Serialize(input.parse()?))
+        } else if input.peek(Token![!]) {
+            input.parse::<Token![!]>()?;
+
+            Ok(PrefixedArg::Error(input.parse()?))
         } else {
             Ok(PrefixedArg::Normal(input.parse()?))
         }
@@ -74,7 +85,72 @@ impl FormatArg for PrefixedArg {
     fn formatter(&self) -> &'static str {
         match self {
             Self::Debug(_) => "{:?}",
-            Self::Display(_) | Self::Serialize(_) | Self::Normal(_) => "{}",
+            Self::Display(_) | Self::Serialize(_) | Self::Error(_) | Self::Normal(_) => "{}",
+        }
+    }
+
+    fn default_name(&self) -> Option<&'static str> {
+        match self {
+            Self::Error(_) => Some("error"),
+            _ => None,
+        }
+    }
+}
+
+/// Argument substituted into the format string, with an optional prefix
+/// e.g. `?debug_struct`, `%display_struct`, `^serialize_struct`, `some_struct`
+#[derive(Clone)]
+pub(crate) enum FmtArg {
+    /// `?debug_struct`, formatted when logging
+    Debug(Expr),
+    /// `%display_struct`, formatted when logging
+    Display(Expr),
+    /// `^serialize_struct`
+    Serialize(Expr),
+    /// `some_struct`
+    Normal(Expr),
+}
+
+impl FmtArg {
+    /// The captured expression for this argument
+    pub(crate) fn expr(&self) -> &Expr {
+        match self {
+            Self::Debug(i) | Self::Display(i) | Self::Serialize(i) | Self::Normal(i) => i,
+        }
+    }
+}
+
+impl ToTokens for FmtArg {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        self.expr().to_tokens(tokens);
+    }
+}
+
+impl Parse for FmtArg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Token![?]) {
+            input.parse::<Token![?]>()?;
+
+            Ok(FmtArg::Debug(input.parse()?))
+        } else if input.peek(Token![%]) {
+            input.parse::<Token![%]>()?;
+
+            Ok(FmtArg::Display(input.parse()?))
+        } else if input.peek(Token![^]) {
+            input.parse::<Token![^]>()?;
+
+            Ok(FmtArg::Serialize(input.parse()?))
+        } else {
+            let arg = input.parse()?;
+            if input.peek(Token![:]) {
+                return Err(input.error(
+                    "unexpected `:` after argument, formatting options go into the \
+                     format string, e.g. `{:?}`, or prefix the argument with `?` to \
+                     format it with `Debug`",
+                ));
+            }
+
+            Ok(FmtArg::Normal(arg))
         }
     }
 }
@@ -93,14 +169,52 @@ impl<T: Parse + FormatArg + ToTokens> NamedField<T> {
     /// Helper method for describing how to form this `NamedField` as part
     /// of a format string
     pub(crate) fn formatter(&self) -> String {
-        let name = if let Some(n) = &self.name {
+        self.name() + "=" + self.arg.formatter()
+    }
+
+    /// Name of this field, which is the default name of the argument or
+    /// otherwise the argument itself if not assigned
+    pub(crate) fn name(&self) -> String {
+        if let Some(n) = &self.name {
             n.into_token_stream().to_string()
+        } else if let Some(name) = self.arg.default_name() {
+            name.to_string()
         } else {
-            (&self.arg).into_token_stream().to_string()
-        };
+            expr_name(&(&self.arg).into_token_stream().to_string())
+        }
+    }
+}
 
-        name + "=" + self.arg.formatter()
+/// Name of a field passed without one, which is its expression without
+/// leading references, or the spaces between tokens which are not needed to
+/// separate them, e.g. `order.side()` for `&order.side()` and `v[0]` rather
+/// than `v [0]`
+fn expr_name(expr: &str) -> String {
+    let expr = expr.trim_start_matches(|c: char| c == '&' || c.is_whitespace());
+    let expr = expr.strip_prefix("mut ").unwrap_or(expr);
+    let is_ident_char = |c: char| c.is_alphanumeric() || c == '_';
+
+    let mut name = String::with_capacity(expr.len());
+    let mut in_str = false;
+    let mut space = false;
+    let mut prev = '\0';
+    for c in expr.chars() {
+        if in_str || !c.is_whitespace() {
+            if space && name.ends_with(is_ident_char) && is_ident_char(c) {
+                name.push(' ');
+            }
+            if c == '"' && prev != '\\' {
+                in_str = !in_str;
+            }
+            name.push(c);
+            space = false;
+        } else {
+            space = true;
+        }
+        prev = c;
     }
+
+    name
 }
 
 impl<T: Parse + ToTokens> Parse for NamedField<T> {
@@ -193,7 +307,14 @@ impl Parse for Args {
                 break;
             }
 
-            prefixed_fields.push_value(input.parse()?);
+            let field = input.parse().map_err(|e| {
+                syn::Error::new(
+                    e.span(),
+                    "expected a field such as `name = value`, `?debug`, `%display`, \
+                     `^serialize` or `!error`, or the format string",
+                )
+            })?;
+            prefixed_fields.push_value(field);
             if let Some(comma) = input.parse::<Option<Token![,]>>()? {
                 prefixed_fields.push_punct(comma);
             } else {
@@ -204,7 +325,14 @@ impl Parse for Args {
         if let Ok(format_string) = input.parse::<LitStr>() {
             // Start parsing formatting args, if any
             let formatting_args = if !input.is_empty() {
-                input.parse::<Token![,]>()?;
+                let comma = input.parse::<Token![,]>()?;
+                if input.is_empty() {
+                    return Err(syn::Error::new(
+                        comma.span,
+                        "unexpected trailing comma after the format string, remove it or \
+                         pass a format argument after it",
+                    ));
+                }
 
                 Punctuated::parse_separated_nonempty(input)?
             } else {
@@ -227,6 +355,115 @@ impl Parse for Args {
     }
 }
 
+/// Arguments to a `span!` call, consisting of the span name followed by
+/// prefixed fields, e.g. `span!("order", id = order.id, ?side)`
+pub(crate) struct SpanArgs {
+    /// `"order"`
+    pub(crate) name: LitStr,
+    /// `id = order.id, ?side`
+    pub(crate) fields: PrefixedFields,
+}
+
+impl Parse for SpanArgs {
+    fn parse(input: ParseStream) -> parse::Result<Self> {
+        let name = input.parse()?;
+        let fields = if input.is_empty() {
+            PrefixedFields::new()
+        } else {
+            input.parse::<Token![,]>()?;
+            Punctuated::parse_terminated(input)?
+        };
+
+        Ok(Self { name, fields })
+    }
+}
+
+/// Arguments to a `log!` call, consisting of a level expression followed by
+/// the same arguments as the other logging macros, e.g.
+/// `log!(level, ?side, "order {}", id)`
+pub(crate) struct LogArgs {
+    /// `level`
+    pub(crate) level: Expr,
+    /// `?side, "order {}", id`
+    pub(crate) args: Args,
+}
+
+impl Parse for LogArgs {
+    fn parse(input: ParseStream) -> parse::Result<Self> {
+        let level = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let args = input.parse()?;
+
+        Ok(Self { level, args })
+    }
+}
+
+/// Arguments to a `*_to!` call, consisting of the logger to record into
+/// followed by the same arguments as the other logging macros, e.g.
+/// `info_to!(&mut audit, "order {}", id)`
+pub(crate) struct ToArgs {
+    /// `&mut audit`
+    pub(crate) logger: Expr,
+    /// `"order {}", id`
+    pub(crate) args: Args,
+}
+
+impl Parse for ToArgs {
+    fn parse(input: ParseStream) -> parse::Result<Self> {
+        let logger = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let args = input.parse()?;
+
+        Ok(Self { logger, args })
+    }
+}
+
+/// Arguments to a `*_throttle!` call, consisting of the minimum interval
+/// between log lines followed by the same arguments as the other logging
+/// macros, e.g. `info_throttle!(Duration::from_secs(1), "order {}", id)`
+pub(crate) struct ThrottleArgs {
+    /// `Duration::from_secs(1)`
+    pub(crate) interval: Expr,
+    /// `"order {}", id`
+    pub(crate) args: Args,
+}
+
+impl Parse for ThrottleArgs {
+    fn parse(input: ParseStream) -> parse::Result<Self> {
+        let interval = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let args = input.parse()?;
+
+        Ok(Self { interval, args })
+    }
+}
+
+/// Arguments to a `*_sampled!` call, consisting of the sampling rate followed
+/// by the same arguments as the other logging macros, e.g.
+/// `info_sampled!(100, "tick {}", price)` or
+/// `info_sampled!(rate = 100, "tick {}", price)`
+pub(crate) struct SampleArgs {
+    /// `100`
+    pub(crate) rate: Expr,
+    /// `"tick {}", price`
+    pub(crate) args: Args,
+}
+
+impl Parse for SampleArgs {
+    fn parse(input: ParseStream) -> parse::Result<Self> {
+        let fork = input.fork();
+        if fork.parse::<Ident>().is_ok_and(|ident| ident == "rate") && fork.peek(Token![=]) {
+            input.parse::<Ident>()?;
+            input.parse::<Token![=]>()?;
+        }
+        let rate = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let args = input.parse()?;
+
+        Ok(Self { rate, args })
+    }
+}
+
 /// Replaces all expression arguments with a new set of expressions.
 /// e.g. for the expression field `a = &my_struct` and the new expression `x`,
 /// the field gets transformed to `a = &my_struct` -> `a = x`
@@ -238,6 +475,6 @@ pub(crate) fn replace_fields_expr(
         .iter_mut()
         .zip(to_replace)
         .for_each(|(field, replacement)| {
-            field.arg = replacement;
+            field.arg = FmtArg::Normal(replacement);
         });
 }
@@ -0,0 +1,30 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse::Parser, punctuated::Punctuated};
+
+use crate::{
+    args::{PrefixedArg, PrefixedFields},
+    format_arg::FormatArg,
+};
+
+/// Generates a call to `register`, e.g. `quicklog::context::set_global`,
+/// with all fields eagerly formatted into `(name, value)` pairs
+pub(crate) fn expand_context(register: TokenStream2, input: TokenStream) -> TokenStream {
+    let fields: PrefixedFields = match Punctuated::parse_terminated.parse(input) {
+        Ok(fields) => fields,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    let names = fields.iter().map(|field| field.name());
+    let fmt_strs = fields.iter().map(|field| field.arg.formatter());
+    let fmt_args = fields.iter().map(|field| match &field.arg {
+        PrefixedArg::Serialize(a) => quote! { quicklog::make_store!(#a) },
+        arg => quote! { #arg },
+    });
+
+    quote! {
+        #register(vec![#((#names, format!(#fmt_strs, #fmt_args))),*])
+    }
+    .into()
+}
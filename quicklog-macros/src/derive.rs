@@ -1,7 +1,90 @@
 use proc_macro::TokenStream;
 use proc_macro2::{Ident, TokenStream as TokenStream2};
-use quote::quote;
-use syn::{parse_macro_input, Data, DataStruct, DeriveInput, Type};
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, parse_quote, punctuated::Punctuated, Attribute, Data, DataEnum, DataStruct,
+    DeriveInput, Field, Fields, Generics, Index, LitStr, Member, Path, Token, Type, Variant,
+    WherePredicate,
+};
+
+/// Printed in place of fields marked `#[quicklog(redact)]`
+const REDACTED: &str = "***";
+
+#[derive(Default)]
+struct FieldAttrs {
+    skip: bool,
+    redact: bool,
+    rename: Option<String>,
+    with: Option<Path>,
+}
+
+impl FieldAttrs {
+    fn parse(field: &Field) -> syn::Result<Self> {
+        let mut attrs = Self::default();
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("quicklog")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    attrs.skip = true;
+                } else if meta.path.is_ident("redact") {
+                    attrs.redact = true;
+                } else if meta.path.is_ident("rename") {
+                    attrs.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("with") {
+                    attrs.with = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+                } else {
+                    return Err(meta.error("unsupported quicklog attribute"));
+                }
+
+                Ok(())
+            })?;
+        }
+
+        Ok(attrs)
+    }
+}
+
+/// Formatting trait through which a type is encoded as a string, instead of
+/// field by field
+#[derive(Clone, Copy)]
+enum Formatted {
+    Display,
+    Debug,
+}
+
+#[derive(Default)]
+struct ContainerAttrs {
+    bound: Option<Punctuated<WherePredicate, Token![,]>>,
+    formatted: Option<Formatted>,
+}
+
+impl ContainerAttrs {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut container_attrs = Self::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("quicklog")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("bound") {
+                    let bound = meta.value()?.parse::<LitStr>()?;
+                    container_attrs.bound = Some(bound.parse_with(Punctuated::parse_terminated)?);
+                } else if meta.path.is_ident("display") || meta.path.is_ident("debug") {
+                    if container_attrs.formatted.is_some() {
+                        return Err(meta.error("only one of `display` and `debug` can be set"));
+                    }
+                    container_attrs.formatted = Some(if meta.path.is_ident("display") {
+                        Formatted::Display
+                    } else {
+                        Formatted::Debug
+                    });
+                } else {
+                    return Err(meta.error("unsupported quicklog attribute"));
+                }
+
+                Ok(())
+            })?;
+        }
+
+        Ok(container_attrs)
+    }
+}
 
 /// Generates a `quicklog` `Serialize` implementation for a user-defined struct.
 ///
@@ -52,104 +135,269 @@ use syn::{parse_macro_input, Data, DataStruct, DeriveInput, Type};
 ///     }
 /// }
 /// ```
+///
+/// Tuple structs are supported in the same way, with any number of fields,
+/// which are printed in order and keyed by their index in the typed decoding.
+///
+/// Fields can be customized through `#[quicklog(...)]` attributes:
+/// - `#[quicklog(skip)]`: the field is neither encoded nor printed.
+/// - `#[quicklog(redact)]`: the field is not encoded, and is printed as
+/// `***`, so that sensitive values such as account numbers never reach the
+/// serialization buffer or the log output.
+/// - `#[quicklog(rename = "name")]`: the field is printed as `name=value`,
+/// rather than just its value.
+/// - `#[quicklog(with = "path::to::module")]`: the field is encoded with the
+/// `encode`, `decode` and `buffer_size_required` functions in the given
+/// module, instead of its own `Serialize` implementation. These mirror the
+/// `Serialize` methods, taking the field by reference where `Serialize`
+/// takes `&self`.
+///
+/// Every type parameter of the struct is bounded by `Serialize` in the
+/// generated impl, e.g. `impl<T: Serialize> Serialize for Wrapper<T>`. These
+/// bounds can be replaced through an attribute on the struct:
+/// - `#[quicklog(bound = "T: Serialize + Clone")]`: the given predicates are
+/// used instead, or none at all with `bound = ""`, such as when a type
+/// parameter only appears in skipped fields.
+///
+/// Types whose fields cannot be encoded one by one can instead be formatted
+/// eagerly through `Display` or `Debug` when logged, see `derive_formatted`:
+/// - `#[quicklog(display)]` or `#[quicklog(debug)]`: the value is formatted
+/// into the buffer as a string, and field attributes are ignored.
+///
+/// Enums without fields are encoded as their discriminant and printed as the
+/// name of the variant, see `derive_unit_enum`:
+/// - `#[quicklog(rename = "name")]`: the variant is printed as `name`
+/// instead.
 pub(crate) fn derive(input: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(input as DeriveInput);
+    let mut input = parse_macro_input!(input as DeriveInput);
     let struct_name = &input.ident;
+
+    let container_attrs = match ContainerAttrs::parse(&input.attrs) {
+        Ok(attrs) => attrs,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let bound_trait: Path = match container_attrs.formatted {
+        Some(Formatted::Display) => parse_quote! { std::fmt::Display },
+        Some(Formatted::Debug) => parse_quote! { std::fmt::Debug },
+        None => parse_quote! { quicklog::serialize::Serialize },
+    };
+    let bounds: Vec<WherePredicate> = match container_attrs.bound {
+        Some(bound) => bound.into_iter().collect(),
+        None => input
+            .generics
+            .type_params()
+            .map(|param| {
+                let ident = &param.ident;
+                parse_quote! { #ident: #bound_trait }
+            })
+            .collect(),
+    };
+    input.generics.make_where_clause().predicates.extend(bounds);
+
+    if let Some(formatted) = container_attrs.formatted {
+        return derive_formatted(struct_name, &input.generics, formatted).into();
+    }
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
 
-    let Data::Struct(DataStruct { fields, .. }) = input.data else {
-        todo!("Deriving Serialize only supported for structs currently")
+    let fields = match input.data {
+        Data::Struct(DataStruct { fields, .. }) => fields,
+        Data::Enum(data) => {
+            return derive_unit_enum(struct_name, &input.generics, &input.attrs, &data)
+                .unwrap_or_else(|e| e.to_compile_error())
+                .into()
+        }
+        Data::Union(_) => {
+            return syn::Error::new_spanned(
+                &input.ident,
+                "Deriving Serialize is not supported for unions",
+            )
+            .to_compile_error()
+            .into()
+        }
     };
 
     if fields.is_empty() {
         return quote! {}.into();
     }
 
-    let field_names: Vec<_> = fields
+    let mut serialized_fields = Vec::new();
+    // Fields are accessed by name, or by index for tuple structs
+    let mut members = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        match FieldAttrs::parse(field) {
+            Ok(attrs) if attrs.skip => {}
+            Ok(attrs) => {
+                serialized_fields.push((field, attrs));
+                members.push(match &field.ident {
+                    Some(ident) => Member::Named(ident.clone()),
+                    None => Member::Unnamed(Index::from(i)),
+                });
+            }
+            Err(e) => return e.to_compile_error().into(),
+        }
+    }
+
+    // Fields of tuple structs are decoded into `field_<index>`
+    let field_names: Vec<_> = members
         .iter()
-        .filter_map(|field| field.ident.as_ref())
+        .map(|member| match member {
+            Member::Named(ident) => ident.clone(),
+            Member::Unnamed(index) => format_ident!("field_{}", index.index),
+        })
+        .collect();
+
+    let encode_calls: Vec<_> = serialized_fields
+        .iter()
+        .zip(&members)
+        .map(|((_, attrs), name)| match &attrs.with {
+            _ if attrs.redact => quote! { ((), chunk_rest) },
+            Some(with) => quote! { #with::encode(&self.#name, chunk_rest) },
+            None => quote! { quicklog::serialize::Encode::encode(&self.#name, chunk_rest) },
+        })
         .collect();
 
+    let size_calls: Vec<_> = serialized_fields
+        .iter()
+        .zip(&members)
+        .map(|((_, attrs), name)| match &attrs.with {
+            _ if attrs.redact => quote! { 0 },
+            Some(with) => quote! { #with::buffer_size_required(&self.#name) },
+            None => quote! { quicklog::serialize::Encode::buffer_size_required(&self.#name) },
+        })
+        .collect();
+    let buffer_size_required = if size_calls.is_empty() {
+        quote! { 0 }
+    } else {
+        quote! { #(#size_calls)+* }
+    };
+
     // If we have > 1 field, then we split once at the top-level to get the
     // single chunk that has enough capacity to encode all the fields.
     // From there, each field will just encode into this single chunk.
     //
-    // Otherwise, if we only have 1 field, we can simply let the single field
-    // directly read off the main `write_buf` chunk and return the remainder
-    // unread.
+    // Otherwise, if we only have 1 field which is neither renamed nor
+    // redacted, we can simply let the single field directly read off the main
+    // `write_buf` chunk and return the remainder unread.
+    let single_field = encode_calls.len() == 1
+        && serialized_fields[0].1.rename.is_none()
+        && !serialized_fields[0].1.redact;
     let (initial_chunk_split, chunk_encode_and_store): (TokenStream2, TokenStream2) =
-        if field_names.len() > 1 {
+        if !single_field {
             // Split off just large enough chunk to be kept in final Store
             let initial_split = quote! {
-                let (chunk, rest) = write_buf.split_at_mut(self.buffer_size_required());
+                let size = quicklog::serialize::Encode::buffer_size_required(self);
+                let (chunk, rest) = write_buf.split_at_mut(size);
             };
 
             // Sequentially encode
-            let encode: Vec<_> = field_names
-                .iter()
-                .enumerate()
-                .map(|(idx, name)| {
-                    if idx == 0 {
-                        quote! {
-                            let (_, chunk_rest) = self.#name.encode(chunk);
-                        }
-                    } else {
-                        quote! {
-                            let (_, chunk_rest) = self.#name.encode(chunk_rest);
-                        }
-                    }
-                })
-                .collect();
-
             let encode_and_store = quote! {
-                #(#encode)*
+                let chunk_rest = &mut *chunk;
+                #(let (_, chunk_rest) = #encode_calls;)*
 
                 assert!(chunk_rest.is_empty());
-                (quicklog::serialize::Store::new(Self::decode, chunk), rest)
+                (
+                    quicklog::serialize::Store::new(<Self as quicklog::serialize::Decode>::decode, chunk)
+                        .with_decode_value(<Self as quicklog::serialize::Decode>::decode_value),
+                    rest,
+                )
             };
 
             (initial_split, encode_and_store)
         } else {
             let initial_split = quote! {
-                let chunk = write_buf;
+                let chunk_rest = write_buf;
             };
 
-            // Only one field, so can directly encode in main chunk
-            let field_name = &field_names[0];
+            // Only one field, so can directly encode in main chunk. The
+            // struct is laid out exactly as its field, so only the typed
+            // decoding needs to be swapped out.
+            let encode_call = &encode_calls[0];
             let encode_and_store = quote! {
-                self.#field_name.encode(chunk)
+                let (store, rest) = #encode_call;
+                (store.with_decode_value(<Self as quicklog::serialize::Decode>::decode_value), rest)
             };
 
             (initial_split, encode_and_store)
         };
 
     // Combine decode implementations from all field types
-    let field_tys: Vec<_> = fields
+    let field_tys: Vec<_> = serialized_fields
         .iter()
-        .map(|field| {
-            // Unwrap: safe since we checked that this macro is only for structs
-            // which always have named fields
-            let field_name = field.ident.as_ref().unwrap();
+        .zip(&field_names)
+        .map(|((field, attrs), decoded_ident)| {
+            if attrs.redact {
+                return quote! {
+                    let #decoded_ident = #REDACTED;
+                };
+            }
+            if let Some(with) = &attrs.with {
+                return quote! {
+                    let (#decoded_ident, read_buf) = #with::decode(read_buf);
+                };
+            }
+
             let mut field_ty = field.ty.clone();
             if let Type::Reference(ty_ref) = &mut field_ty {
                 _ = ty_ref.lifetime.take();
                 _ = ty_ref.mutability.take();
             }
-            let decoded_ident = Ident::new(format!("{}", field_name).as_str(), field_name.span());
 
             quote! {
-                let (#decoded_ident, read_buf) = <#field_ty as quicklog::serialize::Serialize>::decode(read_buf);
+                let (#decoded_ident, read_buf) = <#field_ty as quicklog::serialize::Decode>::decode(read_buf);
             }
         })
         .collect();
 
-    // Assuming that each field in the output should just be separated by a space
-    // TODO: proper field naming?
-    let mut decode_fmt_str = String::new();
-    for _ in 0..fields.len() {
-        decode_fmt_str.push_str("{} ");
-    }
-    let decode_fmt_str = decode_fmt_str.trim_end();
+    // Typed decoding yields a map keyed by the (renamed) field names. Fields
+    // encoded through `with` only provide a formatted `String`.
+    let value_entries: Vec<_> = serialized_fields
+        .iter()
+        .zip(&members)
+        .map(|((field, attrs), member)| {
+            let key = attrs.rename.clone().unwrap_or_else(|| match member {
+                Member::Named(ident) => ident.to_string(),
+                Member::Unnamed(index) => index.index.to_string(),
+            });
+
+            let decode_value = if attrs.redact {
+                quote! {
+                    let value = quicklog::serialize::Value::Str(#REDACTED.to_string());
+                }
+            } else if let Some(with) = &attrs.with {
+                quote! {
+                    let (s, read_buf) = #with::decode(read_buf);
+                    let value = quicklog::serialize::Value::Str(s);
+                }
+            } else {
+                let mut field_ty = field.ty.clone();
+                if let Type::Reference(ty_ref) = &mut field_ty {
+                    _ = ty_ref.lifetime.take();
+                    _ = ty_ref.mutability.take();
+                }
+
+                quote! {
+                    let (value, read_buf) = <#field_ty as quicklog::serialize::Decode>::decode_value(read_buf);
+                }
+            };
+
+            quote! {
+                #decode_value
+                entries.push((quicklog::serialize::Value::Str(#key.to_string()), value));
+            }
+        })
+        .collect();
+    let num_entries = value_entries.len();
+
+    // Each field in the output is separated by a space, with renamed fields
+    // printed as `name=value`
+    let decode_fmt_str = serialized_fields
+        .iter()
+        .map(|(_, attrs)| match &attrs.rename {
+            Some(rename) => format!("{}={{}}", rename.replace('{', "{{").replace('}', "}}")),
+            None => "{}".to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
 
     quote! {
         impl #impl_generics quicklog::serialize::Serialize for #struct_name #ty_generics #where_clause {
@@ -167,10 +415,259 @@ pub(crate) fn derive(input: TokenStream) -> TokenStream {
                 (format!(#decode_fmt_str, #(#field_names),*), read_buf)
             }
 
+            fn decode_value(read_buf: &[u8]) -> (quicklog::serialize::Value, &[u8]) {
+                let mut entries = Vec::with_capacity(#num_entries);
+                #(#value_entries)*
+
+                (quicklog::serialize::Value::Map(entries), read_buf)
+            }
+
             fn buffer_size_required(&self) -> usize {
-                #(self.#field_names.buffer_size_required())+*
+                #buffer_size_required
             }
         }
     }
     .into()
 }
+
+/// Generates a `Serialize` implementation for a type marked
+/// `#[quicklog(display)]` or `#[quicklog(debug)]`, which formats the value
+/// when encoding it and is decoded in the same way as a `&str`.
+///
+/// This supports any type implementing the formatting trait, such as enums
+/// with fields or types holding values which do not implement `Serialize`,
+/// so that they can be passed where a `Serialize` bound is required.
+///
+/// For instance:
+/// ```ignore
+/// #[derive(Serialize)]
+/// #[quicklog(display)]
+/// enum Side {
+///     Bid(u64),
+///     Ask(u64),
+/// }
+///
+/// // Generated code
+/// impl quicklog::serialize::Serialize for Side {
+///     fn encode<'buf>(
+///         &self,
+///         write_buf: &'buf mut [u8],
+///     ) -> (quicklog::serialize::Store<'buf>, &'buf mut [u8]) {
+///         quicklog::serialize::encode_display(self, write_buf)
+///     }
+///     fn decode(read_buf: &[u8]) -> (String, &[u8]) {
+///         <&str as quicklog::serialize::Serialize>::decode(read_buf)
+///     }
+///     fn buffer_size_required(&self) -> usize {
+///         quicklog::serialize::formatted_size(format_args!("{}", self))
+///     }
+/// }
+/// ```
+fn derive_formatted(name: &Ident, generics: &Generics, formatted: Formatted) -> TokenStream2 {
+    let (encode_fn, fmt_str) = match formatted {
+        Formatted::Display => (quote! { encode_display }, "{}"),
+        Formatted::Debug => (quote! { encode_debug }, "{:?}"),
+    };
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        impl #impl_generics quicklog::serialize::Serialize for #name #ty_generics #where_clause {
+            fn encode<'buf>(&self, write_buf: &'buf mut [u8]) -> (quicklog::serialize::Store<'buf>, &'buf mut [u8]) {
+                quicklog::serialize::#encode_fn(self, write_buf)
+            }
+
+            fn decode(read_buf: &[u8]) -> (String, &[u8]) {
+                <&str as quicklog::serialize::Serialize>::decode(read_buf)
+            }
+
+            fn buffer_size_required(&self) -> usize {
+                quicklog::serialize::formatted_size(format_args!(#fmt_str, self))
+            }
+        }
+    }
+}
+
+/// Integer types accepted in `#[repr(...)]` on enums
+const REPR_INTS: [&str; 12] = [
+    "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
+];
+
+/// Generates a `Serialize` implementation for an enum without fields, which
+/// is encoded as a single integer and decoded into the name of its variant.
+///
+/// With a `#[repr(...)]` integer type, the variant's discriminant is encoded
+/// as that type, so explicit discriminants are kept as-is. Otherwise, the
+/// index of the variant is encoded as a `u8`, or a `u16` for enums with more
+/// than 256 variants.
+///
+/// For instance:
+/// ```ignore
+/// #[derive(Serialize)]
+/// #[repr(u16)]
+/// enum Status {
+///     Active = 100,
+///     Halted = 200,
+/// }
+///
+/// // Generated code
+/// impl quicklog::serialize::Serialize for Status {
+///     fn encode<'buf>(
+///         &self,
+///         write_buf: &'buf mut [u8],
+///     ) -> (quicklog::serialize::Store<'buf>, &'buf mut [u8]) {
+///         let discriminant: u16 = match self {
+///             Self::Active => Self::Active as u16,
+///             Self::Halted => Self::Halted as u16,
+///         };
+///         let (chunk, rest) = write_buf.split_at_mut(std::mem::size_of::<u16>());
+///         chunk.copy_from_slice(&discriminant.to_le_bytes());
+///         (
+///             quicklog::serialize::Store::new(Self::decode, chunk)
+///                 .with_decode_value(Self::decode_value),
+///             rest,
+///         )
+///     }
+///     fn decode(read_buf: &[u8]) -> (String, &[u8]) {
+///         let (chunk, rest) = read_buf.split_at(std::mem::size_of::<u16>());
+///         let discriminant = u16::from_le_bytes(chunk.try_into().unwrap());
+///         let name = match discriminant {
+///             d if d == Self::Active as u16 => "Active",
+///             d if d == Self::Halted as u16 => "Halted",
+///             d => unreachable!("unknown discriminant {} for Status", d),
+///         };
+///         (name.to_string(), rest)
+///     }
+///     // `decode_value` yields the name as a `Value::Str`
+///     fn buffer_size_required(&self) -> usize {
+///         std::mem::size_of::<u16>()
+///     }
+/// }
+/// ```
+fn derive_unit_enum(
+    enum_name: &Ident,
+    generics: &Generics,
+    attrs: &[Attribute],
+    data: &DataEnum,
+) -> syn::Result<TokenStream2> {
+    if let Some(variant) = data
+        .variants
+        .iter()
+        .find(|v| !matches!(v.fields, Fields::Unit))
+    {
+        return Err(syn::Error::new_spanned(
+            variant,
+            "Deriving Serialize only supported for enums without fields",
+        ));
+    }
+
+    let mut repr = None;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            if let Some(ident) = meta.path.get_ident() {
+                if REPR_INTS.contains(&ident.to_string().as_str()) {
+                    repr = Some(ident.clone());
+                }
+            }
+
+            Ok(())
+        })?;
+    }
+
+    let variants: Vec<_> = data.variants.iter().map(|v| &v.ident).collect();
+    let names = data
+        .variants
+        .iter()
+        .map(variant_name)
+        .collect::<syn::Result<Vec<_>>>()?;
+    let (repr, discriminants): (TokenStream2, Vec<TokenStream2>) = match repr {
+        Some(repr) => {
+            let discriminants = variants
+                .iter()
+                .map(|v| quote! { Self::#v as #repr })
+                .collect();
+            (quote! { #repr }, discriminants)
+        }
+        None => {
+            let repr = if variants.len() <= 256 {
+                quote! { u8 }
+            } else {
+                quote! { u16 }
+            };
+            let discriminants = (0..variants.len())
+                .map(|i| {
+                    let i = proc_macro2::Literal::usize_unsuffixed(i);
+                    quote! { #i }
+                })
+                .collect();
+            (repr, discriminants)
+        }
+    };
+
+    let unknown = format!("unknown discriminant {{}} for {}", enum_name);
+    let decode_name = quote! {
+        match discriminant {
+            #(d if d == #discriminants => #names,)*
+            d => unreachable!(#unknown, d),
+        }
+    };
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics quicklog::serialize::Serialize for #enum_name #ty_generics #where_clause {
+            fn encode<'buf>(&self, write_buf: &'buf mut [u8]) -> (quicklog::serialize::Store<'buf>, &'buf mut [u8]) {
+                let discriminant: #repr = match *self {
+                    #(Self::#variants => #discriminants,)*
+                };
+                let (chunk, rest) = write_buf.split_at_mut(std::mem::size_of::<#repr>());
+                chunk.copy_from_slice(&discriminant.to_le_bytes());
+
+                (
+                    quicklog::serialize::Store::new(<Self as quicklog::serialize::Decode>::decode, chunk)
+                        .with_decode_value(<Self as quicklog::serialize::Decode>::decode_value),
+                    rest,
+                )
+            }
+
+            fn decode(read_buf: &[u8]) -> (String, &[u8]) {
+                let (chunk, rest) = read_buf.split_at(std::mem::size_of::<#repr>());
+                let discriminant = <#repr>::from_le_bytes(chunk.try_into().unwrap());
+                let name: &str = #decode_name;
+
+                (name.to_string(), rest)
+            }
+
+            fn decode_value(read_buf: &[u8]) -> (quicklog::serialize::Value, &[u8]) {
+                let (name, rest) = Self::decode(read_buf);
+
+                (quicklog::serialize::Value::Str(name), rest)
+            }
+
+            fn buffer_size_required(&self) -> usize {
+                std::mem::size_of::<#repr>()
+            }
+        }
+    })
+}
+
+/// Name printed for `variant`, which can be set through
+/// `#[quicklog(rename = "name")]`
+fn variant_name(variant: &Variant) -> syn::Result<String> {
+    let mut name = variant.ident.to_string();
+    for attr in variant
+        .attrs
+        .iter()
+        .filter(|a| a.path().is_ident("quicklog"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                name = meta.value()?.parse::<LitStr>()?.value();
+            } else {
+                return Err(meta.error("unsupported quicklog attribute"));
+            }
+
+            Ok(())
+        })?;
+    }
+
+    Ok(name)
+}
@@ -1,32 +1,222 @@
 use proc_macro::TokenStream;
 use proc_macro2::{Span, TokenStream as TokenStream2};
-use quote::{quote, ToTokens};
-use syn::{parse_macro_input, parse_quote, Ident};
+use quote::{quote, quote_spanned};
+use syn::{parse_macro_input, parse_quote, spanned::Spanned, Expr, Ident};
 
-use crate::args::{replace_fields_expr, Args, PrefixedArg};
+use crate::args::{
+    replace_fields_expr, Args, ExprFields, FmtArg, LogArgs, PrefixedArg, SampleArgs, SpanArgs,
+    ThrottleArgs, ToArgs,
+};
+use crate::fmt_str::check_fmt_args;
 use crate::Level;
 
 /// Parses token stream into the different components of `Args` and
 /// generates required tokens from the inputs
 pub(crate) fn expand(level: Level, input: TokenStream) -> TokenStream {
-    expand_parsed(level, parse_macro_input!(input as Args)).into()
+    expand_parsed(
+        LogLevel::Static(level),
+        parse_macro_input!(input as Args),
+        None,
+        Emit::Log,
+        None,
+    )
+    .into()
+}
+
+/// Same as [`expand`], but evaluates to the `Result` of logging instead of
+/// ignoring errors
+pub(crate) fn expand_try(level: Level, input: TokenStream) -> TokenStream {
+    expand_parsed(
+        LogLevel::Static(level),
+        parse_macro_input!(input as Args),
+        None,
+        Emit::TryLog,
+        None,
+    )
+    .into()
+}
+
+/// Same as [`expand`], but at `quicklog::level::FATAL`, with the log line
+/// written straight to the flusher instead of being queued
+pub(crate) fn expand_fatal(input: TokenStream) -> TokenStream {
+    expand_parsed(
+        LogLevel::Dynamic(parse_quote! { quicklog::level::FATAL }),
+        parse_macro_input!(input as Args),
+        None,
+        Emit::Fatal,
+        None,
+    )
+    .into()
+}
+
+/// Same as [`expand`], but with the level passed in as the first argument,
+/// to be evaluated at runtime
+pub(crate) fn expand_dynamic(input: TokenStream) -> TokenStream {
+    let LogArgs { level, args } = parse_macro_input!(input as LogArgs);
+    expand_parsed(LogLevel::Dynamic(level), args, None, Emit::Log, None).into()
+}
+
+/// Logs an event at `quicklog::level::EVENT`, with the event name as the
+/// message followed by its fields. Fields without a prefix are serialized as
+/// if prefixed with `^`, so that formatters keep their typed values, e.g.
+/// nested objects for derived `Serialize` structs
+pub(crate) fn expand_event(input: TokenStream) -> TokenStream {
+    let SpanArgs { name, mut fields } = parse_macro_input!(input as SpanArgs);
+    for field in fields.iter_mut() {
+        if let PrefixedArg::Normal(expr) = &field.arg {
+            field.arg = PrefixedArg::Serialize(expr.clone());
+        }
+    }
+
+    let args = Args {
+        prefixed_fields: fields,
+        format_string: Some(name),
+        formatting_args: ExprFields::new(),
+    };
+    expand_parsed(
+        LogLevel::Dynamic(parse_quote! { quicklog::level::EVENT }),
+        args,
+        None,
+        Emit::Log,
+        None,
+    )
+    .into()
+}
+
+/// Same as [`expand`], but with the minimum interval between log lines from
+/// this callsite passed in as the first argument
+pub(crate) fn expand_throttled(level: Level, input: TokenStream) -> TokenStream {
+    let ThrottleArgs { interval, args } = parse_macro_input!(input as ThrottleArgs);
+    expand_parsed(
+        LogLevel::Static(level),
+        args,
+        Some(RateLimit::Throttle(interval)),
+        Emit::Log,
+        None,
+    )
+    .into()
+}
+
+/// Same as [`expand`], but with the sampling rate of log lines from this
+/// callsite passed in as the first argument
+pub(crate) fn expand_sampled(level: Level, input: TokenStream) -> TokenStream {
+    let SampleArgs { rate, args } = parse_macro_input!(input as SampleArgs);
+    expand_parsed(
+        LogLevel::Static(level),
+        args,
+        Some(RateLimit::Sample(rate)),
+        Emit::Log,
+        None,
+    )
+    .into()
+}
+
+/// Same as [`expand`], but records into the logger passed in as the first
+/// argument instead of the global logger
+pub(crate) fn expand_to(level: Level, input: TokenStream) -> TokenStream {
+    let ToArgs { logger, args } = parse_macro_input!(input as ToArgs);
+    expand_parsed(LogLevel::Static(level), args, None, Emit::Log, Some(logger)).into()
+}
+
+/// Level of a logging macro, either fixed by the macro or evaluated at runtime
+pub(crate) enum LogLevel {
+    Static(Level),
+    Dynamic(Expr),
+}
+
+/// How a log record is emitted once built
+pub(crate) enum Emit {
+    /// Queued, ignoring errors
+    Log,
+    /// Queued, evaluating to the `Result` of queueing
+    TryLog,
+    /// Written straight to the flusher, see `quicklog::fatal!`
+    Fatal,
+}
+
+/// Limit on the log lines recorded from a callsite
+pub(crate) enum RateLimit {
+    /// At most one log line per interval
+    Throttle(Expr),
+    /// One in every `rate` log lines
+    Sample(Expr),
 }
 
 /// Main function for expanding the components parsed from the macro call
-pub(crate) fn expand_parsed(level: Level, mut args: Args) -> TokenStream2 {
+///
+/// If `rate_limit` is passed, only the log lines it allows are recorded. With
+/// [`Emit::TryLog`], the expansion evaluates to a
+/// `Result<(), quicklog::QueueError>` instead of `()`. If `logger` is passed,
+/// the log line is recorded into it rather than into the global logger.
+pub(crate) fn expand_parsed(
+    level: LogLevel,
+    mut args: Args,
+    rate_limit: Option<RateLimit>,
+    emit: Emit,
+    logger: Option<Expr>,
+) -> TokenStream2 {
+    let captures = match &args.format_string {
+        Some(fmt_str) => match check_fmt_args(fmt_str, &mut args.formatting_args) {
+            Ok(captures) => captures,
+            Err(err) => return err.to_compile_error(),
+        },
+        None => Vec::new(),
+    };
+
     let args_traits_check: Vec<_> = args
         .prefixed_fields
         .iter()
         .filter_map(|arg| match &arg.arg {
-            PrefixedArg::Debug(a) => Some(quote! { debug_check(&#a); }),
-            PrefixedArg::Display(a) => Some(quote! { display_check(&#a); }),
-            PrefixedArg::Serialize(a) => Some(quote! { serialize_check(&#a); }),
-            PrefixedArg::Normal(_) => None,
+            PrefixedArg::Debug(a) => Some(quote_spanned! {a.span()=> debug_check(&#a); }),
+            PrefixedArg::Display(a) => Some(quote_spanned! {a.span()=> display_check(&#a); }),
+            PrefixedArg::Serialize(a) => Some(quote_spanned! {a.span()=> serialize_check(&#a); }),
+            PrefixedArg::Error(_) | PrefixedArg::Normal(_) => None,
         })
+        .chain(
+            args.formatting_args
+                .iter()
+                .filter_map(|arg| match &arg.arg {
+                    FmtArg::Serialize(a) => {
+                        Some(quote_spanned! {a.span()=> serialize_check(&#a); })
+                    }
+                    FmtArg::Debug(_) | FmtArg::Display(_) | FmtArg::Normal(_) => None,
+                }),
+        )
         .collect();
 
+    // Hygienic, so that the logger cannot shadow any arguments
+    let logger_ident = logger
+        .as_ref()
+        .map(|_| Ident::new("logger", Span::mixed_site()));
+    let logger_declaration = logger.map(|logger| {
+        quote! { let #logger_ident: &mut quicklog::Quicklog = #logger; }
+    });
+
     let (new_idents_declaration, fmt_arg_idents, prefixed_field_idents) =
-        convert_args_to_idents(&args);
+        convert_args_to_idents(&args, logger_ident.as_ref());
+
+    // Variables captured by the format string are also passed as typed
+    // values, e.g. so that JSON output has them as fields. Widths and
+    // precisions are not applied, while those formatted through another
+    // trait than `Display`, e.g. `{:?}` or `{:x}`, are passed as strings
+    // formatted through it
+    let capture_idents = &fmt_arg_idents[fmt_arg_idents.len() - captures.len()..];
+    let visit_captures = captures
+        .iter()
+        .zip(capture_idents)
+        .map(|(capture, ident)| {
+            let name = &capture.name;
+            let value = match capture.spec.chars().last() {
+                Some(c @ ('?' | 'x' | 'X' | 'o' | 'b' | 'e' | 'E')) => {
+                    let alternate = if capture.spec.contains('#') { "#" } else { "" };
+                    let fmt = format!("{{:{}{}}}", alternate, c);
+                    quote! { quicklog::serialize::Value::Str(format!(#fmt, #ident)) }
+                }
+                _ => quote! { (&quicklog::line::FieldRef(&#ident)).field_value() },
+            };
+            quote! { visit(#name, #value)?; }
+        })
+        .collect::<Vec<_>>();
 
     let mut fmt_args = args.formatting_args;
     replace_fields_expr(
@@ -49,41 +239,162 @@ pub(crate) fn expand_parsed(level: Level, mut args: Args) -> TokenStream2 {
         special_fmt_str.push(' ');
     }
     let special_fmt_str = special_fmt_str.trim_end();
+    let has_message = !fmt_str.is_empty();
+
+    let visit_fields =
+        args.prefixed_fields
+            .iter()
+            .zip(&prefixed_field_idents)
+            .map(|(field, ident)| {
+                let name = field.name();
+                match &field.arg {
+                    PrefixedArg::Debug(_) => {
+                        quote! { visit(#name, &format_args!("{:?}", #ident))?; }
+                    }
+                    PrefixedArg::Error(_) => {
+                        let chain_name = format!("{}.cause_chain", name);
+                        quote! {
+                            visit(#name, &#ident)?;
+                            if let Some(chain) = #ident.cause_chain() {
+                                visit(#chain_name, &chain)?;
+                            }
+                        }
+                    }
+                    _ => quote! { visit(#name, &#ident)?; },
+                }
+            });
+
+    // Typed values of fields passed without a prefix are picked through
+    // autoref specialization, see `quicklog::line::FieldRef`
+    let visit_values = args.prefixed_fields.iter().zip(&prefixed_field_idents).map(
+        |(field, ident)| {
+            let name = field.name();
+            let value = match &field.arg {
+                PrefixedArg::Debug(_) => {
+                    quote! { quicklog::serialize::Value::Str(format!("{:?}", #ident)) }
+                }
+                PrefixedArg::Display(_) => {
+                    quote! { quicklog::serialize::Value::Str(#ident.to_string()) }
+                }
+                PrefixedArg::Serialize(_) => quote! { #ident.as_value() },
+                // Nested under the name, as structured output cannot
+                // hold both a value and nested fields under one name
+                PrefixedArg::Error(_) => {
+                    let message_name = format!("{}.message", name);
+                    let chain_name = format!("{}.cause_chain", name);
+                    return quote! {
+                        visit(#message_name, quicklog::serialize::Value::Str(#ident.to_string()))?;
+                        if let Some(chain) = #ident.cause_chain() {
+                            visit(#chain_name, chain)?;
+                        }
+                    };
+                }
+                PrefixedArg::Normal(_) => {
+                    quote! { (&quicklog::line::FieldRef(&#ident)).field_value() }
+                }
+            };
+            quote! { visit(#name, #value)?; }
+        },
+    );
+
+    // Hygienic, so that the level cannot shadow any arguments
+    let level_ident = Ident::new("level", Span::mixed_site());
+    let (level, callsite_level) = match level {
+        LogLevel::Static(level) => (quote! { #level }, quote! { Some(#level) }),
+        LogLevel::Dynamic(level) => (quote! { #level }, quote! { None }),
+    };
+    let callsite_fmt_str = format!("{}{}", fmt_str, special_fmt_str);
+
+    let rate_limit_check = rate_limit.map(|rate_limit| match rate_limit {
+        RateLimit::Throttle(interval) => quote! {
+            && {
+                static THROTTLE: quicklog::throttle::Throttle = quicklog::throttle::Throttle::new();
+                THROTTLE.try_acquire(#interval)
+            }
+        },
+        RateLimit::Sample(rate) => quote! {
+            && {
+                static SAMPLER: quicklog::sample::Sampler = quicklog::sample::Sampler::new();
+                SAMPLER.sample(#rate)
+            }
+        },
+    });
+
+    let log_target = match &logger_ident {
+        Some(logger) => quote! { #logger },
+        None => quote! { quicklog::logger() },
+    };
+    let (log_fn, handle_result) = match emit {
+        Emit::Log => (quote! { log }, quote! { .unwrap_or(()) }),
+        Emit::TryLog => (quote! { try_log }, quote! {}),
+        Emit::Fatal => (quote! { log_fatal }, quote! { .unwrap_or(()) }),
+    };
 
     quote! {{
-        if quicklog::is_level_enabled!(#level) {
-            use quicklog::{Log, make_container, serialize::Serialize};
+        static CALLSITE: quicklog::callsite::Callsite = quicklog::callsite::Callsite::new(
+            #callsite_level,
+            module_path!(),
+            file!(),
+            line!(),
+            #callsite_fmt_str,
+        );
+        quicklog::inventory::submit!(quicklog::callsite::Registration::new(&CALLSITE));
+
+        const STATIC_MIN_PRIORITY: usize = quicklog::level::static_min_priority(module_path!());
+        let #level_ident: quicklog::level::Level = #level;
+        if #level_ident.priority() >= STATIC_MIN_PRIORITY && CALLSITE.is_enabled() && quicklog::is_level_enabled!(#level_ident) && quicklog::callsite::filter_enables(&CALLSITE) #rate_limit_check {
+            use quicklog::{Log, make_container, serialize::Encode};
 
-            const fn debug_check<T: ::std::fmt::Debug + Clone>(_: &T) {}
-            const fn display_check<T: ::std::fmt::Display + Clone>(_: &T) {}
-            const fn serialize_check<T: Serialize>(_: &T) {}
+            const fn debug_check<T: ::std::fmt::Debug>(_: &T) {}
+            const fn display_check<T: ::std::fmt::Display>(_: &T) {}
+            const fn serialize_check<T: Encode>(_: &T) {}
 
             #(#args_traits_check)*
 
+            #logger_declaration
             #new_idents_declaration
 
             let log_record = quicklog::LogRecord {
-                level: #level,
+                level: #level_ident,
                 module_path: module_path!(),
                 file: file!(),
                 line: line!(),
-                log_line: make_container!(quicklog::lazy_format::make_lazy_format!(|f| {
-                    write!(f, #fmt_str, #fmt_args)?;
-                    write!(f, #special_fmt_str, #(#prefixed_field_idents),*)
-                }))
+                span: quicklog::span::current(),
+                sequence: 0,
+                thread: None,
+                context: None,
+                log_line: make_container!(quicklog::line::LazyLine::new(
+                    #has_message,
+                    move |part: quicklog::line::LinePart<'_, '_>| match part {
+                        quicklog::line::LinePart::Message(f) => write!(f, #fmt_str, #fmt_args),
+                        quicklog::line::LinePart::Fields(visit) => {
+                            #(#visit_fields)*
+                            Ok(())
+                        }
+                        quicklog::line::LinePart::Values(visit) => {
+                            use quicklog::line::{DisplayField as _, TypedField as _};
+                            #(#visit_values)*
+                            #(#visit_captures)*
+                            Ok(())
+                        }
+                    }
+                ))
             };
 
-            quicklog::logger().log(log_record)
+            #log_target.#log_fn(log_record)
         } else {
             Ok(())
         }
-        .unwrap_or(())
+        #handle_result
     }}
 }
 
 /// Generates new identifier tokens and their declarations for every special
-/// and formatting argument
-fn convert_args_to_idents(args: &Args) -> (TokenStream2, Vec<Ident>, Vec<Ident>) {
+/// and formatting argument, serializing into `logger` if passed
+fn convert_args_to_idents(
+    args: &Args,
+    logger: Option<&Ident>,
+) -> (TokenStream2, Vec<Ident>, Vec<Ident>) {
     let mut args_to_own: Vec<TokenStream2> = Vec::new();
     let mut arg_count = 0;
 
@@ -94,17 +405,29 @@ fn convert_args_to_idents(args: &Args) -> (TokenStream2, Vec<Ident>, Vec<Ident>)
 
     let mut fmt_arg_idents = Vec::with_capacity(args.formatting_args.len());
     for fmt_arg in args.formatting_args.iter() {
-        args_to_own.push(fmt_arg.arg.to_token_stream());
+        match &fmt_arg.arg {
+            FmtArg::Debug(i) => args_to_own.push(quote! {
+                quicklog::Preformatted(format!("{:?}", #i))
+            }),
+            FmtArg::Display(i) => args_to_own.push(quote! {
+                quicklog::Preformatted(format!("{}", #i))
+            }),
+            FmtArg::Serialize(i) => args_to_own.push(make_store(i, logger)),
+            FmtArg::Normal(i) => args_to_own.push(owned(i, quote! { RequireClone })),
+        }
         fmt_arg_idents.push(new_ident());
     }
 
     let mut prefixed_field_idents = Vec::with_capacity(args.prefixed_fields.len());
     for field in args.prefixed_fields.iter() {
         match &field.arg {
-            PrefixedArg::Serialize(i) => args_to_own.push(quote! {
-                quicklog::make_store!(#i)
+            PrefixedArg::Serialize(i) => args_to_own.push(make_store(i, logger)),
+            PrefixedArg::Error(i) => args_to_own.push(quote! {
+                quicklog::line::ErrorChain::new(#i)
             }),
-            _ => args_to_own.push(field.arg.to_token_stream()),
+            PrefixedArg::Debug(i) => args_to_own.push(owned(i, quote! { EagerDebug })),
+            PrefixedArg::Display(i) => args_to_own.push(owned(i, quote! { EagerDisplay })),
+            PrefixedArg::Normal(i) => args_to_own.push(owned(i, quote! { RequireClone })),
         }
         prefixed_field_idents.push(new_ident());
     }
@@ -118,9 +441,33 @@ fn convert_args_to_idents(args: &Args) -> (TokenStream2, Vec<Ident>, Vec<Ident>)
 
     (
         quote! {
-            let (#(#new_idents),*) = (#( (#args_to_own).to_owned() ),*);
+            let (#(#new_idents),*) = (#(#args_to_own),*);
         },
         fmt_arg_idents,
         prefixed_field_idents,
     )
 }
+
+/// Takes an owned copy of `arg`, see `quicklog::line::Owned`, falling back to
+/// the `fallback` trait for types which are not `Clone`, e.g. `EagerDebug` to
+/// format `?` fields eagerly.
+///
+/// Spanned at the argument, so that arguments which cannot be owned are
+/// reported there rather than at the whole macro call.
+fn owned(arg: &Expr, fallback: TokenStream2) -> TokenStream2 {
+    quote_spanned! {arg.span()=> {
+        #[allow(unused_imports)]
+        use quicklog::line::{OwnedBorrow as _, OwnedClone as _, OwnedDeref as _, #fallback as _};
+        #[allow(clippy::needless_borrow)]
+        let owned = (&&&&quicklog::line::Owned(&(#arg))).owned();
+        owned
+    }}
+}
+
+/// Serializes `arg` into the byte buffer of `logger`, or of the global logger
+fn make_store(arg: &Expr, logger: Option<&Ident>) -> TokenStream2 {
+    match logger {
+        Some(logger) => quote! { quicklog::make_store!(@to #logger, #arg) },
+        None => quote! { quicklog::make_store!(#arg) },
+    }
+}
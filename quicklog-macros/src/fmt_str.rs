@@ -0,0 +1,240 @@
+use quote::ToTokens;
+use syn::{parse_quote_spanned, Ident, LitStr, Token};
+
+use crate::args::{ExprFields, FmtArg, NamedField};
+
+/// Argument referenced by a placeholder of a format string
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum ArgRef {
+    /// `{}`, or `.*` precision, the next positional argument
+    Next,
+    /// `{0}`, `{:1$}`
+    Index(usize),
+    /// `{name}`, `{:width$}`
+    Name(String),
+}
+
+/// Returns the contents of every placeholder of `fmt`, e.g. `name:>10` for
+/// `{name:>10}`, in order
+fn placeholders(fmt: &str) -> Result<Vec<String>, String> {
+    let mut placeholders = Vec::new();
+    let mut chars = fmt.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+            }
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => placeholder.push(c),
+                        None => return Err("unterminated `{` in format string".to_string()),
+                    }
+                }
+                placeholders.push(placeholder);
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+            }
+            '}' => return Err("unmatched `}` in format string".to_string()),
+            _ => {}
+        }
+    }
+
+    Ok(placeholders)
+}
+
+/// Splits a placeholder into its argument and format spec
+fn split_placeholder(placeholder: &str) -> (&str, &str) {
+    let (arg, spec) = placeholder.split_once(':').unwrap_or((placeholder, ""));
+    (arg.trim(), spec)
+}
+
+/// Returns every argument referenced by the placeholders of `fmt`, including
+/// widths and precisions taken from arguments, in order
+pub(crate) fn arg_refs(fmt: &str) -> Result<Vec<ArgRef>, String> {
+    let mut refs = Vec::new();
+    for placeholder in placeholders(fmt)? {
+        let (arg, spec) = split_placeholder(&placeholder);
+        // `.*` takes the precision from the next positional argument,
+        // before the argument being formatted
+        if spec.contains(".*") {
+            refs.push(ArgRef::Next);
+        }
+        refs.push(parse_arg(arg)?);
+        refs.extend(spec_arg_refs(spec));
+    }
+
+    Ok(refs)
+}
+
+fn parse_arg(arg: &str) -> Result<ArgRef, String> {
+    if arg.is_empty() {
+        Ok(ArgRef::Next)
+    } else if let Ok(idx) = arg.parse() {
+        Ok(ArgRef::Index(idx))
+    } else if is_ident(arg) {
+        Ok(ArgRef::Name(arg.to_string()))
+    } else {
+        Err(format!("invalid argument `{}` in format string", arg))
+    }
+}
+
+/// Arguments referenced as `width$` or `1$` in a format spec
+fn spec_arg_refs(spec: &str) -> impl Iterator<Item = ArgRef> + '_ {
+    spec.match_indices('$').filter_map(|(end, _)| {
+        let start = spec[..end]
+            .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .map_or(0, |idx| idx + 1);
+        parse_arg(&spec[start..end]).ok()
+    })
+}
+
+fn is_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .map_or(false, |c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Variable captured implicitly by a placeholder of the format string, e.g.
+/// `{qty}` or `{price:.2}`
+pub(crate) struct Capture {
+    /// `qty`
+    pub(crate) name: String,
+    /// `.2`, the format spec of the first placeholder referencing it
+    pub(crate) spec: String,
+}
+
+/// Cross-checks the placeholders of `fmt` against the formatting arguments.
+///
+/// Names which are not passed as named arguments are captured implicitly,
+/// and are appended to `args` as `name = name`, so that they are owned in
+/// the same way as any other argument, and returned in the same order.
+/// Unused arguments, and placeholders without a matching argument, are
+/// reported at the offending token.
+pub(crate) fn check_fmt_args(fmt: &LitStr, args: &mut ExprFields) -> syn::Result<Vec<Capture>> {
+    let fmt_value = fmt.value();
+    let refs = arg_refs(&fmt_value).map_err(|msg| syn::Error::new(fmt.span(), msg))?;
+
+    let positional = args.iter().take_while(|arg| arg.name.is_none()).count();
+    if let Some(arg) = args.iter().skip(positional).find(|arg| arg.name.is_none()) {
+        return Err(syn::Error::new_spanned(
+            arg,
+            "positional arguments cannot follow named arguments",
+        ));
+    }
+    if let Some(arg) = args
+        .iter()
+        .find(|arg| arg.name.as_ref().map_or(false, |n| n.len() > 1))
+    {
+        return Err(syn::Error::new_spanned(
+            arg.name.as_ref(),
+            "named format arguments must be a single identifier",
+        ));
+    }
+    let named: Vec<String> = args
+        .iter()
+        .filter_map(|arg| arg.name.as_ref())
+        .map(|name| name.to_token_stream().to_string())
+        .collect();
+
+    let mut used = vec![false; args.len()];
+    let mut next = 0;
+    let mut captures = Vec::new();
+    for arg_ref in refs {
+        let idx = match arg_ref {
+            ArgRef::Next => {
+                next += 1;
+                next - 1
+            }
+            ArgRef::Index(idx) => idx,
+            ArgRef::Name(name) => match named.iter().position(|n| *n == name) {
+                Some(idx) => positional + idx,
+                None => {
+                    if !captures.contains(&name) {
+                        captures.push(name);
+                    }
+                    continue;
+                }
+            },
+        };
+
+        match used.get_mut(idx) {
+            Some(used) => *used = true,
+            None => {
+                let msg = match args.len() {
+                    0 => format!(
+                        "format string references argument {}, but no arguments were given",
+                        idx
+                    ),
+                    1 => format!(
+                        "format string references argument {}, but there is 1 argument",
+                        idx
+                    ),
+                    len => format!(
+                        "format string references argument {}, but there are {} arguments",
+                        idx, len
+                    ),
+                };
+                return Err(syn::Error::new(fmt.span(), msg));
+            }
+        }
+    }
+
+    let mut errors = args
+        .iter()
+        .zip(&used)
+        .filter(|(_, used)| !**used)
+        .map(|(arg, _)| match &arg.name {
+            Some(name) => syn::Error::new_spanned(
+                name,
+                format!(
+                    "named argument `{}` is not used in the format string, pass it \
+                     before the format string to record it as a structured field",
+                    name.to_token_stream()
+                ),
+            ),
+            None => syn::Error::new_spanned(
+                arg,
+                "argument is not used in the format string, add a `{}` placeholder for it",
+            ),
+        });
+    if let Some(mut error) = errors.next() {
+        errors.for_each(|e| error.combine(e));
+        return Err(error);
+    }
+
+    // Already checked by `arg_refs`
+    let placeholders = placeholders(&fmt_value).unwrap_or_default();
+    let captures: Vec<Capture> = captures
+        .into_iter()
+        .map(|name| {
+            let spec = placeholders
+                .iter()
+                .map(|placeholder| split_placeholder(placeholder))
+                .find(|(arg, _)| *arg == name)
+                .map_or(String::new(), |(_, spec)| spec.to_string());
+            Capture { name, spec }
+        })
+        .collect();
+
+    for Capture { name, .. } in &captures {
+        // Spanned at the format string, so that a missing variable is
+        // reported there
+        let ident = Ident::new(name, fmt.span());
+        if !args.empty_or_trailing() {
+            args.push_punct(<Token![,]>::default());
+        }
+        args.push(NamedField {
+            name: Some(parse_quote_spanned!(fmt.span()=> #ident)),
+            assign: Some(<Token![=]>::default()),
+            arg: FmtArg::Normal(parse_quote_spanned!(fmt.span()=> #ident)),
+        });
+    }
+
+    Ok(captures)
+}
@@ -6,6 +6,12 @@ pub(crate) trait FormatArg {
     /// Describes how to apply this object as part of a format string.
     /// e.g. `{:?}`, `{}`, `custom.name={}`
     fn formatter(&self) -> &'static str;
+
+    /// Name of the field if not assigned a name, instead of the argument
+    /// itself
+    fn default_name(&self) -> Option<&'static str> {
+        None
+    }
 }
 
 impl FormatArg for Expr {
@@ -1,14 +1,23 @@
 use proc_macro::TokenStream;
+use quote::quote;
 
 mod args;
+mod context;
 mod derive;
 mod expand;
+mod fmt_str;
 mod format_arg;
 mod quicklog;
+mod span;
 
+use context::expand_context;
 use derive::derive;
-use expand::expand;
+use expand::{
+    expand, expand_dynamic, expand_event, expand_fatal, expand_sampled, expand_throttled,
+    expand_to, expand_try,
+};
 use quicklog::Level;
+use span::expand_span;
 
 #[proc_macro]
 pub fn trace(input: TokenStream) -> TokenStream {
@@ -35,9 +44,236 @@ pub fn error(input: TokenStream) -> TokenStream {
     expand(Level::Error, input)
 }
 
+/// Same as `trace!`, but records at most one log line per interval from this
+/// callsite, e.g. `trace_throttle!(Duration::from_secs(1), "hello {}", name)`.
+#[proc_macro]
+pub fn trace_throttle(input: TokenStream) -> TokenStream {
+    expand_throttled(Level::Trace, input)
+}
+
+/// Same as `debug!`, but records at most one log line per interval from this
+/// callsite, e.g. `debug_throttle!(Duration::from_secs(1), "hello {}", name)`.
+#[proc_macro]
+pub fn debug_throttle(input: TokenStream) -> TokenStream {
+    expand_throttled(Level::Debug, input)
+}
+
+/// Same as `info!`, but records at most one log line per interval from this
+/// callsite, e.g. `info_throttle!(Duration::from_secs(1), "hello {}", name)`.
+#[proc_macro]
+pub fn info_throttle(input: TokenStream) -> TokenStream {
+    expand_throttled(Level::Info, input)
+}
+
+/// Same as `warn!`, but records at most one log line per interval from this
+/// callsite, e.g. `warn_throttle!(Duration::from_secs(1), "hello {}", name)`.
+#[proc_macro]
+pub fn warn_throttle(input: TokenStream) -> TokenStream {
+    expand_throttled(Level::Warn, input)
+}
+
+/// Same as `error!`, but records at most one log line per interval from this
+/// callsite, e.g. `error_throttle!(Duration::from_secs(1), "hello {}", name)`.
+#[proc_macro]
+pub fn error_throttle(input: TokenStream) -> TokenStream {
+    expand_throttled(Level::Error, input)
+}
+
+/// Same as `trace!`, but records only one in every `rate` log lines from this
+/// callsite, e.g. `trace_sampled!(rate = 100, "hello {}", name)`.
+#[proc_macro]
+pub fn trace_sampled(input: TokenStream) -> TokenStream {
+    expand_sampled(Level::Trace, input)
+}
+
+/// Same as `debug!`, but records only one in every `rate` log lines from this
+/// callsite, e.g. `debug_sampled!(rate = 100, "hello {}", name)`.
+#[proc_macro]
+pub fn debug_sampled(input: TokenStream) -> TokenStream {
+    expand_sampled(Level::Debug, input)
+}
+
+/// Same as `info!`, but records only one in every `rate` log lines from this
+/// callsite, e.g. `info_sampled!(rate = 100, "hello {}", name)`.
+#[proc_macro]
+pub fn info_sampled(input: TokenStream) -> TokenStream {
+    expand_sampled(Level::Info, input)
+}
+
+/// Same as `warn!`, but records only one in every `rate` log lines from this
+/// callsite, e.g. `warn_sampled!(rate = 100, "hello {}", name)`.
+#[proc_macro]
+pub fn warn_sampled(input: TokenStream) -> TokenStream {
+    expand_sampled(Level::Warn, input)
+}
+
+/// Same as `error!`, but records only one in every `rate` log lines from this
+/// callsite, e.g. `error_sampled!(rate = 100, "hello {}", name)`.
+#[proc_macro]
+pub fn error_sampled(input: TokenStream) -> TokenStream {
+    expand_sampled(Level::Error, input)
+}
+
+/// Same as `trace!`, but returns `Result<(), quicklog::QueueError>` instead of
+/// ignoring the error when the log line could not be queued, e.g.
+/// `try_trace!("hello {}", name)?`.
+#[proc_macro]
+pub fn try_trace(input: TokenStream) -> TokenStream {
+    expand_try(Level::Trace, input)
+}
+
+/// Same as `debug!`, but returns `Result<(), quicklog::QueueError>` instead of
+/// ignoring the error when the log line could not be queued, e.g.
+/// `try_debug!("hello {}", name)?`.
+#[proc_macro]
+pub fn try_debug(input: TokenStream) -> TokenStream {
+    expand_try(Level::Debug, input)
+}
+
+/// Same as `info!`, but returns `Result<(), quicklog::QueueError>` instead of
+/// ignoring the error when the log line could not be queued, e.g.
+/// `try_info!("hello {}", name)?`.
+#[proc_macro]
+pub fn try_info(input: TokenStream) -> TokenStream {
+    expand_try(Level::Info, input)
+}
+
+/// Same as `warn!`, but returns `Result<(), quicklog::QueueError>` instead of
+/// ignoring the error when the log line could not be queued, e.g.
+/// `try_warn!("hello {}", name)?`.
+#[proc_macro]
+pub fn try_warn(input: TokenStream) -> TokenStream {
+    expand_try(Level::Warn, input)
+}
+
+/// Same as `error!`, but returns `Result<(), quicklog::QueueError>` instead of
+/// ignoring the error when the log line could not be queued, e.g.
+/// `try_error!("hello {}", name)?`.
+#[proc_macro]
+pub fn try_error(input: TokenStream) -> TokenStream {
+    expand_try(Level::Error, input)
+}
+
+/// Same as `trace!`, but records into the `&mut quicklog::Quicklog` passed in
+/// as the first argument instead of the global logger, e.g.
+/// `trace_to!(&mut audit, "hello {}", name)`.
+#[proc_macro]
+pub fn trace_to(input: TokenStream) -> TokenStream {
+    expand_to(Level::Trace, input)
+}
+
+/// Same as `debug!`, but records into the `&mut quicklog::Quicklog` passed in
+/// as the first argument instead of the global logger, e.g.
+/// `debug_to!(&mut audit, "hello {}", name)`.
+#[proc_macro]
+pub fn debug_to(input: TokenStream) -> TokenStream {
+    expand_to(Level::Debug, input)
+}
+
+/// Same as `info!`, but records into the `&mut quicklog::Quicklog` passed in
+/// as the first argument instead of the global logger, e.g.
+/// `info_to!(&mut audit, "hello {}", name)`.
+#[proc_macro]
+pub fn info_to(input: TokenStream) -> TokenStream {
+    expand_to(Level::Info, input)
+}
+
+/// Same as `warn!`, but records into the `&mut quicklog::Quicklog` passed in
+/// as the first argument instead of the global logger, e.g.
+/// `warn_to!(&mut audit, "hello {}", name)`.
+#[proc_macro]
+pub fn warn_to(input: TokenStream) -> TokenStream {
+    expand_to(Level::Warn, input)
+}
+
+/// Same as `error!`, but records into the `&mut quicklog::Quicklog` passed in
+/// as the first argument instead of the global logger, e.g.
+/// `error_to!(&mut audit, "hello {}", name)`.
+#[proc_macro]
+pub fn error_to(input: TokenStream) -> TokenStream {
+    expand_to(Level::Error, input)
+}
+
+/// Logs at a level determined at runtime, passed in as the first argument,
+/// e.g. `log!(level, "hello {}", name)`.
+///
+/// The remaining arguments are the same as the other logging macros.
+#[proc_macro]
+pub fn log(input: TokenStream) -> TokenStream {
+    expand_dynamic(input)
+}
+
+/// Logs at `quicklog::level::FATAL`, writing the log line straight to the
+/// flusher, see `quicklog::fatal!`.
+#[doc(hidden)]
+#[proc_macro]
+pub fn log_fatal(input: TokenStream) -> TokenStream {
+    expand_fatal(input)
+}
+
+/// Logs a structured event at `quicklog::level::EVENT`, with its name
+/// followed by its fields, e.g. `event!("order_filled", order, qty = 10)`.
+///
+/// Fields use the same syntax as the logging macros, but are serialized as
+/// if prefixed with `^` unless prefixed otherwise, so their types have to
+/// implement `Serialize`. Formatters producing structured output then write
+/// their typed values, e.g. nested objects for derived `Serialize` structs.
+#[proc_macro]
+pub fn event(input: TokenStream) -> TokenStream {
+    expand_event(input)
+}
+
+/// Creates a `quicklog::span::Span` with a name and fields, which are
+/// attached to every log line recorded while the span is entered.
+///
+/// Fields use the same syntax as the logging macros, e.g.
+/// `span!("order", id = order.id, ?side)`.
+#[proc_macro]
+pub fn span(input: TokenStream) -> TokenStream {
+    expand_span(input)
+}
+
+/// Registers fields appended to every log line afterwards, on all threads,
+/// e.g. `with_context!(region = "us-east", strategy = "mm1")`.
+#[proc_macro]
+pub fn with_context(input: TokenStream) -> TokenStream {
+    expand_context(quote! { quicklog::context::set_global }, input)
+}
+
+/// Registers fields appended to every log line afterwards, on the calling
+/// thread only, e.g. `with_thread_context!(worker = id)`.
+#[proc_macro]
+pub fn with_thread_context(input: TokenStream) -> TokenStream {
+    expand_context(quote! { quicklog::context::set_thread }, input)
+}
+
+/// Appends fields to the log lines of the calling thread until the returned
+/// guard is dropped, e.g. `let _guard = scoped_context!(venue = "XNAS");`.
+#[proc_macro]
+pub fn scoped_context(input: TokenStream) -> TokenStream {
+    expand_context(quote! { quicklog::context::scoped }, input)
+}
+
 /// Derive macro for generating `quicklog` `Serialize`
 /// implementations.
-#[proc_macro_derive(Serialize)]
+///
+/// Supports the `#[quicklog(skip)]`, `#[quicklog(redact)]`,
+/// `#[quicklog(rename = "name")]` and `#[quicklog(with = "module")]` field
+/// attributes, where redacted fields are printed as `***` without being
+/// encoded. Type parameters are
+/// bounded by `Serialize`, unless replaced through the
+/// `#[quicklog(bound = "...")]` struct attribute. Tuple structs are
+/// supported with any number of fields.
+///
+/// Enums without fields are also supported, and are encoded as their
+/// `#[repr]` discriminant, or otherwise as the index of the variant in a
+/// single byte. Variants are printed by name, which can be replaced through
+/// `#[quicklog(rename = "name")]`.
+///
+/// With the `#[quicklog(display)]` or `#[quicklog(debug)]` attribute, any
+/// type is instead formatted eagerly through `Display` or `Debug` when
+/// logged, with type parameters bounded by that trait.
+#[proc_macro_derive(Serialize, attributes(quicklog))]
 pub fn derive_serialize(input: TokenStream) -> TokenStream {
     derive(input)
 }
@@ -0,0 +1,25 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::parse_macro_input;
+
+use crate::args::SpanArgs;
+
+/// Generates a `quicklog::span::Span`, eagerly formatting all fields into
+/// `key=value` pairs separated by spaces. Fields prefixed with `^` are
+/// formatted directly like the others, as serializing them would only be
+/// decoded again straight away.
+pub(crate) fn expand_span(input: TokenStream) -> TokenStream {
+    let SpanArgs { name, fields } = parse_macro_input!(input as SpanArgs);
+
+    let fmt_str = fields
+        .iter()
+        .map(|field| field.formatter())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let fmt_args = fields.iter().map(|field| &field.arg);
+
+    quote! {
+        quicklog::span::Span::new(#name, format!(#fmt_str, #(#fmt_args),*))
+    }
+    .into()
+}
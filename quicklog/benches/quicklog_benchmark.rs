@@ -1,10 +1,7 @@
 use criterion::{black_box, criterion_group, criterion_main, Bencher, Criterion};
 use delog::render::DefaultRenderer;
 use quanta::Instant;
-use quicklog::{
-    serialize::{Serialize, Store},
-    with_flush,
-};
+use quicklog::with_flush;
 use quicklog_flush::noop_flusher::NoopFlusher;
 
 macro_rules! loop_with_cleanup {
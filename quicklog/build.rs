@@ -25,6 +25,30 @@ fn parse_value_from_config_with_default<T: FromStr>(
     }
 }
 
+/// Checks that every directive in `QUICKLOG_MIN_LEVEL` is either a level, or
+/// `module::path=level`
+fn validate_min_level(directives: &str) -> Result<(), String> {
+    const LEVELS: [&str; 11] = [
+        "trace", "trc", "debug", "dbg", "info", "inf", "warn", "wrn", "error", "err", "off",
+    ];
+
+    for directive in directives.split(',').map(str::trim) {
+        let level = match directive.split_once('=') {
+            Some((module, level)) if !module.trim().is_empty() => level,
+            Some(_) => return Err(format!("missing module path in directive '{}'", directive)),
+            None => directive,
+        };
+        if !directive.is_empty() && !LEVELS.contains(&level.trim().to_lowercase().as_str()) {
+            return Err(format!(
+                "unknown level '{}' in directive '{}'",
+                level, directive
+            ));
+        }
+    }
+
+    Ok(())
+}
+
 fn main() {
     println!("cargo:rerun-if-env-changed=QUICKLOG_MAX_SERIALIZE_BUFFER_CAPACITY");
     let max_buffer_capacity = match parse_value_from_config_with_default(
@@ -50,6 +74,19 @@ fn main() {
         }
     };
 
+    println!("cargo:rerun-if-env-changed=QUICKLOG_MIN_LEVEL");
+    let min_level = env::var("QUICKLOG_MIN_LEVEL").unwrap_or_default();
+    let min_level = match validate_min_level(&min_level) {
+        Ok(()) => min_level,
+        Err(err) => {
+            println!(
+                "cargo:warning=env var 'QUICKLOG_MIN_LEVEL' is invalid, {}. All levels are compiled in.",
+                err
+            );
+            String::new()
+        }
+    };
+
     // Generate the Rust source code
     let rust_code = format!(
         "// This file was generated by `build.rs`, do not modify this file manually!
@@ -59,8 +96,11 @@ pub const MAX_LOGGER_CAPACITY: usize = {};
 
 /// Sets max capacity of byte buffer used for serialization with `^` prefix in logging, can be set through `QUICKLOG_MAX_SERIALIZE_BUFFER_CAPACITY`.
 pub const MAX_SERIALIZE_BUFFER_CAPACITY: usize = {};
+
+/// Directives for the minimum level of log lines compiled in, e.g. `info,hot_path=off`, can be set through `QUICKLOG_MIN_LEVEL`.
+pub const MIN_LEVEL: &str = {:?};
 ",
-        max_logger_capacity, max_buffer_capacity
+        max_logger_capacity, max_buffer_capacity, min_level
     );
 
     // Write the code to a file
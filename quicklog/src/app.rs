@@ -0,0 +1,251 @@
+//! Host and application metadata captured once when the logger is initialized.
+//!
+//! This is made available through [`app_info`], so that every formatter can
+//! attribute records to the process that produced them without each callsite
+//! having to log the same details.
+//!
+//! The binary itself can be identified by a [`BuildInfo`](crate::app::BuildInfo) stamp passed to
+//! [`Config::stamp`](crate::Config::stamp), which is logged as the first
+//! record at init, so that every log file names the build which produced it:
+//!
+//! ```
+//! use quicklog::{build_info, config, init};
+//!
+//! // output: "build info name=app version=1.2.3 git_hash=4f2c1e0 hostname=host pid=42 start_time=2023-11-14T22:13:20Z"
+//! init!(config().stamp(build_info!()));
+//! ```
+
+use std::{
+    fmt::{Display, Formatter},
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::{
+    level::Level,
+    line::{FieldVisitor, LogLine, ValueVisitor},
+    serialize::Value,
+    LogRecord,
+};
+
+/// Set to a leaked [`AppInfo`] at every initialization, see [`set_app_info`]
+static APP_INFO: AtomicPtr<AppInfo> = AtomicPtr::new(std::ptr::null_mut());
+
+/// Describes the process producing log records.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AppInfo {
+    hostname: String,
+    pid: u32,
+    executable: String,
+    version: Option<&'static str>,
+}
+
+impl AppInfo {
+    /// Captures the hostname, process id and executable name of the current
+    /// process, along with an application-supplied version string.
+    pub fn capture(version: Option<&'static str>) -> Self {
+        Self {
+            hostname: hostname(),
+            pid: std::process::id(),
+            executable: executable(),
+            version,
+        }
+    }
+
+    /// Name of the host, or an empty string if it could not be determined
+    pub fn hostname(&self) -> &str {
+        &self.hostname
+    }
+
+    /// Id of the current process
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// File name of the current executable
+    pub fn executable(&self) -> &str {
+        &self.executable
+    }
+
+    /// Application version passed to [`init!`](crate::init)
+    pub fn version(&self) -> Option<&'static str> {
+        self.version
+    }
+}
+
+/// Identifies the binary producing log records, usually created through
+/// [`build_info!`](crate::build_info) in the application crate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BuildInfo {
+    name: &'static str,
+    version: &'static str,
+    git_hash: Option<&'static str>,
+}
+
+impl BuildInfo {
+    /// Describes version `version` of the crate `name`, built from the git
+    /// commit `git_hash` if known
+    pub const fn new(
+        name: &'static str,
+        version: &'static str,
+        git_hash: Option<&'static str>,
+    ) -> Self {
+        Self {
+            name,
+            version,
+            git_hash,
+        }
+    }
+
+    /// Name of the crate
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Version of the crate
+    pub fn version(&self) -> &'static str {
+        self.version
+    }
+
+    /// Hash of the git commit the binary was built from
+    pub fn git_hash(&self) -> Option<&'static str> {
+        self.git_hash
+    }
+}
+
+/// Log line of the stamp logged at init, see [`BuildInfo`]
+struct Stamp {
+    build: BuildInfo,
+    hostname: String,
+    pid: u32,
+    start_time: String,
+}
+
+impl Display for Stamp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.fmt_message(f)?;
+        self.visit_fields(&mut |name, value| write!(f, " {}={}", name, value))
+    }
+}
+
+impl LogLine for Stamp {
+    fn fmt_message(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("build info")
+    }
+
+    fn visit_fields(&self, visit: &mut FieldVisitor<'_>) -> std::fmt::Result {
+        visit("name", &self.build.name)?;
+        visit("version", &self.build.version)?;
+        if let Some(git_hash) = self.build.git_hash {
+            visit("git_hash", &git_hash)?;
+        }
+        visit("hostname", &self.hostname)?;
+        visit("pid", &self.pid)?;
+        visit("start_time", &self.start_time)
+    }
+
+    fn visit_values(&self, visit: &mut ValueVisitor<'_>) -> std::fmt::Result {
+        self.visit_fields(&mut |name, value| match name {
+            "pid" => visit(name, Value::UInt(u64::from(self.pid))),
+            _ => visit(name, Value::Str(value.to_string())),
+        })
+    }
+}
+
+/// Record stamping the log with `build`, along with the host and process
+/// it runs in and the time the logger was started at
+pub(crate) fn stamp_record(build: BuildInfo, start_time: DateTime<Utc>) -> LogRecord {
+    LogRecord {
+        level: Level::Info,
+        module_path: module_path!(),
+        file: file!(),
+        line: line!(),
+        span: None,
+        sequence: 0,
+        thread: None,
+        context: None,
+        log_line: Box::new(Stamp {
+            build,
+            hostname: hostname(),
+            pid: std::process::id(),
+            start_time: start_time.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        }),
+    }
+}
+
+/// Returns the [`AppInfo`] captured by the last [`init!`](crate::init), or
+/// `None` if the logger has not been initialized yet, or was reset through
+/// [`shutdown()`](crate::shutdown()).
+pub fn app_info() -> Option<&'static AppInfo> {
+    // SAFETY: only ever null or leaked by `set_app_info`, so never freed
+    unsafe { APP_INFO.load(Ordering::Acquire).as_ref() }
+}
+
+/// Replaces the info captured by the previous initialization, if any. That
+/// one is leaked rather than freed, as formatters may still refer to it, which
+/// is fine as the logger is only initialized a handful of times.
+pub(crate) fn set_app_info(info: AppInfo) {
+    APP_INFO.store(Box::into_raw(Box::new(info)), Ordering::Release);
+}
+
+/// Forgets the info captured at init, see [`shutdown()`](crate::shutdown())
+pub(crate) fn clear_app_info() {
+    APP_INFO.store(std::ptr::null_mut(), Ordering::Release);
+}
+
+fn hostname() -> String {
+    ["/proc/sys/kernel/hostname", "/etc/hostname"]
+        .iter()
+        .find_map(|path| std::fs::read_to_string(path).ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .unwrap_or_default()
+}
+
+fn executable() -> String {
+    std::env::current_exe()
+        .ok()
+        .and_then(|path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        })
+        .or_else(|| std::env::args().next())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::{AppInfo, BuildInfo};
+
+    #[test]
+    fn capture_process_info() {
+        let info = AppInfo::capture(Some("1.2.3"));
+
+        assert_eq!(info.pid(), std::process::id());
+        assert_eq!(info.version(), Some("1.2.3"));
+        assert!(!info.executable().is_empty());
+    }
+
+    #[test]
+    fn stamp_build_info() {
+        let start_time = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let build = BuildInfo::new("app", "1.2.3", Some("4f2c1e0"));
+        let record = super::stamp_record(build, start_time);
+
+        let expected = format!(
+            "build info name=app version=1.2.3 git_hash=4f2c1e0 hostname={} pid={} start_time=2023-11-14T22:13:20Z",
+            super::hostname(),
+            std::process::id()
+        );
+        assert_eq!(record.log_line.to_string(), expected);
+
+        let build = BuildInfo::new("app", "1.2.3", None);
+        let record = super::stamp_record(build, start_time);
+        assert!(!record.log_line.to_string().contains("git_hash"));
+    }
+}
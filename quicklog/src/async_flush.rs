@@ -0,0 +1,158 @@
+//! Flushing from a tokio runtime, instead of dedicating an OS thread to it.
+//!
+//! Requires the `tokio` feature. [`spawn_flush_task`](crate::async_flush::spawn_flush_task)
+//! flushes through the configured [`Flush`](quicklog_flush::Flush), while
+//! [`spawn_async_flush_task`](crate::async_flush::spawn_async_flush_task) writes
+//! log lines to an [`AsyncFlush`](crate::async_flush::AsyncFlush), such as a
+//! `tokio::fs::File` or `tokio::net::TcpStream`:
+//!
+//! ```ignore
+//! use std::time::Duration;
+//! use quicklog::{info, init, spawn_async_flush_task};
+//!
+//! init!();
+//! let file = tokio::fs::File::create("logs/quicklog.log").await?;
+//! let flush_task = spawn_async_flush_task(Duration::from_millis(10), file);
+//!
+//! info!("flushed from a tokio task");
+//! ```
+//!
+//! Both tasks drain the queue every interval, yielding to other tasks in
+//! between, and complete once the logger is shut down through
+//! [`Quicklog::shutdown`](crate::Quicklog::shutdown).
+
+use std::{future::Future, pin::Pin, time::Duration};
+
+use tokio::{io::AsyncWrite, io::AsyncWriteExt, task::JoinHandle};
+
+use crate::{logger, FlushError, Log};
+
+/// Asynchronous counterpart of [`Flush`](quicklog_flush::Flush)
+///
+/// Implemented for every [`AsyncWrite`], where write errors are ignored in the
+/// same way as the synchronous flushers.
+pub trait AsyncFlush: Send {
+    /// Writes a single formatted log line
+    fn flush_one<'a>(
+        &'a mut self,
+        display: String,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>>;
+}
+
+impl<W: AsyncWrite + Unpin + Send> AsyncFlush for W {
+    fn flush_one<'a>(
+        &'a mut self,
+        display: String,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>> {
+        Box::pin(async move {
+            if self.write_all(display.as_bytes()).await.is_ok() {
+                _ = self.flush().await;
+            }
+        })
+    }
+}
+
+/// Spawns a task on the current tokio runtime, which flushes all queued log
+/// records through the configured flusher every `interval`
+pub fn spawn_flush_task(interval: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            loop {
+                match logger().flush_one() {
+                    Ok(())
+                    | Err(FlushError::InvalidTime | FlushError::Corrupted | FlushError::Io(_)) => {
+                        continue
+                    }
+                    Err(FlushError::Empty) => break,
+                    Err(FlushError::NotInitialized) => return,
+                }
+            }
+
+            tokio::time::sleep(interval).await;
+        }
+    })
+}
+
+/// Spawns a task on the current tokio runtime, which writes all queued log
+/// records to `flusher` every `interval`
+pub fn spawn_async_flush_task(
+    interval: Duration,
+    mut flusher: impl AsyncFlush + 'static,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            loop {
+                let next = logger().format_one();
+                match next {
+                    Ok(log_line) => flusher.flush_one(log_line).await,
+                    Err(FlushError::InvalidTime | FlushError::Corrupted | FlushError::Io(_)) => {
+                        continue
+                    }
+                    Err(FlushError::Empty) => break,
+                    Err(FlushError::NotInitialized) => return,
+                }
+            }
+
+            tokio::time::sleep(interval).await;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        future::Future,
+        pin::Pin,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use super::{spawn_async_flush_task, AsyncFlush};
+    use crate::{level::Level, logger, Log, LogRecord};
+
+    struct SharedVecFlusher(Arc<Mutex<Vec<String>>>);
+
+    impl AsyncFlush for SharedVecFlusher {
+        fn flush_one<'a>(
+            &'a mut self,
+            display: String,
+        ) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>> {
+            Box::pin(async move { self.0.lock().unwrap().push(display) })
+        }
+    }
+
+    #[test]
+    fn flushes_until_shutdown() {
+        let _lock = crate::LOGGER_TEST_LOCK.lock().unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        crate::init!();
+        _ = logger().log(LogRecord {
+            level: Level::Info,
+            module_path: module_path!(),
+            file: file!(),
+            line: line!(),
+            span: None,
+            sequence: 0,
+            thread: None,
+            context: None,
+            log_line: Box::new("hello from tokio"),
+        });
+
+        runtime.block_on(async {
+            let task =
+                spawn_async_flush_task(Duration::from_millis(1), SharedVecFlusher(lines.clone()));
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            logger().shutdown();
+            task.await.unwrap();
+        });
+
+        let lines = lines.lock().unwrap();
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].contains("hello from tokio"));
+    }
+}
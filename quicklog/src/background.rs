@@ -0,0 +1,117 @@
+//! Flushing on a dedicated thread managed by quicklog.
+//!
+//! Enabled through [`Config::background_flush`](crate::Config::background_flush),
+//! in which case [`init!`](crate::init) returns a [`BackgroundFlush`] handle:
+//!
+//! ```
+//! # use std::time::Duration;
+//! # use quicklog::{config, info, init};
+//! let _flush_handle = init!(config().background_flush(Duration::from_millis(10)));
+//!
+//! info!("flushed on the background thread");
+//!
+//! // remaining log lines are flushed when `_flush_handle` is dropped
+//! ```
+//!
+//! [`numa::spawn_flush_thread`](crate::numa::spawn_flush_thread) spawns the
+//! same thread pinned to a CPU core.
+
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use crate::{logger, numa, FlushError, Log};
+
+/// Handle to the background flush thread.
+///
+/// Dropping the handle stops the thread after flushing all remaining log
+/// records.
+#[must_use = "the background flush thread is stopped when the handle is dropped"]
+pub struct BackgroundFlush {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl BackgroundFlush {
+    /// Spawns a thread which flushes all queued log records every `interval`
+    pub(crate) fn spawn(interval: Duration) -> Self {
+        Self::spawn_pinned(interval, None).expect("Unable to spawn background flush thread")
+    }
+
+    /// Same as [`BackgroundFlush::spawn`], additionally pinning the thread to
+    /// the CPU core `core_id` if set, before it flushes anything
+    pub(crate) fn spawn_pinned(interval: Duration, core_id: Option<usize>) -> io::Result<Self> {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let (pinned_tx, pinned_rx) = mpsc::channel();
+        let handle = thread::Builder::new()
+            .name("quicklog-flush".to_string())
+            .spawn(move || {
+                let pinned = core_id.map_or(Ok(()), numa::pin_current_thread);
+                let failed = pinned.is_err();
+                _ = pinned_tx.send(pinned);
+                if failed {
+                    return;
+                }
+
+                while !thread_stop.load(Ordering::Acquire) {
+                    if flush_until_empty().is_err() {
+                        // Logger was shut down
+                        return;
+                    }
+                    thread::sleep(interval);
+                }
+
+                _ = flush_until_empty();
+            })?;
+
+        let pinned = pinned_rx.recv().unwrap_or(Ok(()));
+        if let Err(err) = pinned {
+            _ = handle.join();
+            return Err(err);
+        }
+
+        Ok(Self {
+            stop,
+            handle: Some(handle),
+        })
+    }
+
+    /// Stops the background thread, after flushing all remaining log records
+    pub fn stop(mut self) {
+        self.stop_and_join();
+    }
+
+    fn stop_and_join(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(handle) = self.handle.take() {
+            _ = handle.join();
+        }
+    }
+}
+
+impl Drop for BackgroundFlush {
+    fn drop(&mut self) {
+        self.stop_and_join();
+    }
+}
+
+/// Flushes until the queue is empty, returning an error only if the logger
+/// is not initialized
+fn flush_until_empty() -> Result<(), FlushError> {
+    loop {
+        match logger().flush_one() {
+            Ok(()) | Err(FlushError::InvalidTime | FlushError::Corrupted | FlushError::Io(_)) => {
+                continue
+            }
+            Err(FlushError::Empty) => return Ok(()),
+            Err(err) => return Err(err),
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! Log records built across several statements, through the
+//! [`info_builder!`](crate::info_builder) family of macros.
+//!
+//! A [`RecordBuilder`](crate::builder::RecordBuilder) collects structured fields and a message, e.g. when
+//! they are computed in different branches, and queues them as a single log
+//! record when [`finish`](crate::builder::RecordBuilder::finish) is called:
+//!
+//! ```
+//! use quicklog::{info_builder, init};
+//!
+//! # init!();
+//! # let (filled, qty) = (true, 5);
+//! let mut rec = info_builder!();
+//! rec.field("qty", qty);
+//! if filled {
+//!     rec.msg("order filled");
+//! } else {
+//!     rec.field("reason", "no liquidity").msg("order rejected");
+//! }
+//! rec.finish();
+//! ```
+//!
+//! Nothing is queued until `finish` is called, and a builder which is
+//! dropped without calling it discards its fields. If the builder is
+//! disabled when it is created, by its level or by a
+//! [callsite](crate::callsite) filter, its fields are not formatted at all.
+//!
+//! Unlike the logging macros, fields are formatted when they are added, as
+//! the builder cannot know their types ahead of time. [`value`] adds a typed
+//! [`Value`] instead, e.g. so that structured output writes it as a number.
+//!
+//! [`value`]: crate::builder::RecordBuilder::value
+
+use std::{
+    borrow::Cow,
+    fmt::{Display, Formatter, Result},
+};
+
+use crate::{
+    level::Level,
+    line::{FieldVisitor, LogLine, ValueVisitor},
+    logger,
+    serialize::Value,
+    span, Log, LogRecord,
+};
+
+/// Log record built incrementally, created through the
+/// [`info_builder!`](crate::info_builder) family of macros, see
+/// [`builder`](crate::builder)
+#[must_use = "nothing is logged until `finish` is called"]
+pub struct RecordBuilder {
+    level: Level,
+    module_path: &'static str,
+    file: &'static str,
+    line: u32,
+    enabled: bool,
+    built: BuiltLine,
+}
+
+impl RecordBuilder {
+    /// Creates a builder which queues its record only if `enabled`, as
+    /// computed by the [`info_builder!`](crate::info_builder) family of macros
+    /// for the level and callsite of the builder
+    pub fn new(
+        level: Level,
+        module_path: &'static str,
+        file: &'static str,
+        line: u32,
+        enabled: bool,
+    ) -> Self {
+        Self {
+            level,
+            module_path,
+            file,
+            line,
+            enabled,
+            built: BuiltLine::default(),
+        }
+    }
+
+    /// Whether the record will be queued by [`finish`](RecordBuilder::finish),
+    /// i.e. whether its level and callsite were enabled when the builder was
+    /// created
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Adds a structured field, formatted through `Display`
+    pub fn field(&mut self, name: &'static str, value: impl Display) -> &mut Self {
+        if self.enabled {
+            self.built
+                .fields
+                .push((name, Value::Str(value.to_string())));
+        }
+        self
+    }
+
+    /// Adds a structured field with a typed [`Value`]
+    pub fn value(&mut self, name: &'static str, value: Value) -> &mut Self {
+        if self.enabled {
+            self.built.fields.push((name, value));
+        }
+        self
+    }
+
+    /// Sets the message, replacing any previous one
+    pub fn msg(&mut self, message: impl Into<Cow<'static, str>>) -> &mut Self {
+        if self.enabled {
+            self.built.message = message.into();
+        }
+        self
+    }
+
+    /// Queues the record built so far, with the message and fields in the
+    /// order they were added
+    pub fn finish(self) {
+        if !self.enabled {
+            return;
+        }
+
+        let log_record = LogRecord {
+            level: self.level,
+            module_path: self.module_path,
+            file: self.file,
+            line: self.line,
+            span: span::current(),
+            sequence: 0,
+            thread: None,
+            context: None,
+            log_line: Box::new(self.built),
+        };
+
+        _ = logger().log(log_record);
+    }
+}
+
+/// [`LogLine`] queued by [`RecordBuilder::finish`] and
+/// [`log_dynamic`](crate::log_dynamic)
+#[derive(Default)]
+pub(crate) struct BuiltLine {
+    message: Cow<'static, str>,
+    fields: Vec<(&'static str, Value)>,
+}
+
+impl BuiltLine {
+    pub(crate) fn new(
+        message: impl Into<Cow<'static, str>>,
+        fields: Vec<(&'static str, Value)>,
+    ) -> Self {
+        Self {
+            message: message.into(),
+            fields,
+        }
+    }
+}
+
+impl Display for BuiltLine {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.write_str(&self.message)?;
+
+        let mut separate = !self.message.is_empty();
+        for (name, value) in &self.fields {
+            if separate {
+                f.write_str(" ")?;
+            }
+            separate = true;
+            write!(f, "{}={}", name, value)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl LogLine for BuiltLine {
+    fn fmt_message(&self, f: &mut Formatter<'_>) -> Result {
+        f.write_str(&self.message)
+    }
+
+    fn visit_fields(&self, visit: &mut FieldVisitor<'_>) -> Result {
+        self.fields
+            .iter()
+            .try_for_each(|(name, value)| visit(name, value))
+    }
+
+    fn visit_values(&self, visit: &mut ValueVisitor<'_>) -> Result {
+        self.fields
+            .iter()
+            .try_for_each(|(name, value)| visit(name, value.clone()))
+    }
+}
@@ -0,0 +1,227 @@
+//! Registry of every callsite of the logging macros in the application.
+//!
+//! Each macro invocation declares a static [`Callsite`](crate::callsite::Callsite) describing its level,
+//! module path, file, line and format string, which is registered before
+//! `main` runs. The registry can then be enumerated through
+//! [`callsites()`](crate::callsite::callsites),
+//! e.g. to list every log line a binary can emit:
+//!
+//! ```
+//! use quicklog::{callsite::callsites, info};
+//!
+//! # fn handle_order() {
+//! info!("order accepted");
+//! # }
+//! for callsite in callsites() {
+//!     println!(
+//!         "{}:{} {:?} {}",
+//!         callsite.file(),
+//!         callsite.line(),
+//!         callsite.level(),
+//!         callsite.format_string()
+//!     );
+//! }
+//! ```
+//!
+//! Callsites can also be disabled at runtime, e.g. to silence a single noisy
+//! log line without recompiling:
+//!
+//! ```
+//! # use quicklog::callsite::callsites;
+//! for callsite in callsites().filter(|c| c.module_path().ends_with("::market_data")) {
+//!     callsite.set_enabled(false);
+//! }
+//! ```
+//!
+//! Arbitrary rules can be applied through a filter instead, which is called
+//! with the callsite of every log line whose level is enabled, set through
+//! [`Config::filter`](crate::Config::filter) or
+//! [`set_filter`](crate::callsite::set_filter):
+//!
+//! ```
+//! use quicklog::{config, init};
+//!
+//! init!(config().filter(|callsite| !callsite.file().ends_with("market_data.rs")));
+//! ```
+//!
+//! Without a filter, the check is a single atomic load.
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    RwLock,
+};
+
+use crate::level::Level;
+
+/// Static information about a single logging macro invocation
+#[derive(Debug)]
+pub struct Callsite {
+    level: Option<Level>,
+    module_path: &'static str,
+    file: &'static str,
+    line: u32,
+    format_string: &'static str,
+    enabled: AtomicBool,
+}
+
+impl Callsite {
+    /// Internal API, used in the logging macros
+    #[doc(hidden)]
+    pub const fn new(
+        level: Option<Level>,
+        module_path: &'static str,
+        file: &'static str,
+        line: u32,
+        format_string: &'static str,
+    ) -> Self {
+        Self {
+            level,
+            module_path,
+            file,
+            line,
+            format_string,
+            enabled: AtomicBool::new(true),
+        }
+    }
+
+    /// Whether log lines from this callsite are recorded, checked before
+    /// the level
+    #[inline(always)]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Enables or disables recording log lines from this callsite
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Unique identifier of the callsite within this process
+    pub fn id(&'static self) -> CallsiteId {
+        CallsiteId(self as *const Self as usize)
+    }
+
+    /// Level of the callsite, or `None` if it is determined at runtime
+    /// through [`log!`](crate::log)
+    pub fn level(&self) -> Option<Level> {
+        self.level
+    }
+
+    /// Module path of the callsite
+    pub fn module_path(&self) -> &'static str {
+        self.module_path
+    }
+
+    /// File of the callsite
+    pub fn file(&self) -> &'static str {
+        self.file
+    }
+
+    /// Line of the callsite
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    /// Format string of the callsite, including any prefixed fields,
+    /// e.g. `"order {} side={:?}"`
+    pub fn format_string(&self) -> &'static str {
+        self.format_string
+    }
+}
+
+/// Identifies a [`Callsite`], see [`Callsite::id`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CallsiteId(usize);
+
+/// Internal API, submitted to the registry by the logging macros
+#[doc(hidden)]
+pub struct Registration(&'static Callsite);
+
+impl Registration {
+    pub const fn new(callsite: &'static Callsite) -> Self {
+        Self(callsite)
+    }
+}
+
+inventory::collect!(Registration);
+
+/// Returns every registered callsite, in no particular order
+pub fn callsites() -> impl Iterator<Item = &'static Callsite> {
+    inventory::iter::<Registration>.into_iter().map(|r| r.0)
+}
+
+/// Predicate deciding whether log lines are recorded from a [`Callsite`], see
+/// [`set_filter`]
+pub type CallsiteFilter = Box<dyn Fn(&Callsite) -> bool + Send + Sync>;
+
+static FILTER: RwLock<Option<CallsiteFilter>> = RwLock::new(None);
+static HAS_FILTER: AtomicBool = AtomicBool::new(false);
+
+/// Records log lines only from callsites for which `filter` returns `true`,
+/// replacing any previous filter.
+///
+/// The filter is checked after the level of the log line, so that it is only
+/// called for log lines which would otherwise be recorded. It is shared by
+/// all loggers, like the maximum level, and does not apply to metrics or
+/// records built through the `*_builder!` and `*_raw!` macros, which have no
+/// callsite.
+pub fn set_filter(filter: impl Fn(&Callsite) -> bool + Send + Sync + 'static) {
+    let mut current = FILTER.write().unwrap_or_else(|e| e.into_inner());
+    *current = Some(Box::new(filter));
+    HAS_FILTER.store(true, Ordering::Release);
+}
+
+/// Removes the filter set through [`set_filter`], recording log lines from
+/// every enabled callsite again
+pub fn clear_filter() {
+    let mut current = FILTER.write().unwrap_or_else(|e| e.into_inner());
+    HAS_FILTER.store(false, Ordering::Release);
+    *current = None;
+}
+
+/// Internal API, used in the logging macros
+///
+/// Whether the filter set through [`set_filter`], if any, records log lines
+/// from `callsite`
+#[doc(hidden)]
+#[inline(always)]
+pub fn filter_enables(callsite: &Callsite) -> bool {
+    !HAS_FILTER.load(Ordering::Acquire) || call_filter(callsite)
+}
+
+#[cold]
+fn call_filter(callsite: &Callsite) -> bool {
+    FILTER
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .map_or(true, |filter| filter(callsite))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{callsites, Callsite, Registration};
+    use crate::level::Level;
+
+    static CALLSITE: Callsite = Callsite::new(
+        Some(Level::Info),
+        module_path!(),
+        file!(),
+        line!(),
+        "hello {}",
+    );
+    inventory::submit!(Registration::new(&CALLSITE));
+
+    #[test]
+    fn registered() {
+        let registered = callsites()
+            .find(|c| c.id() == CALLSITE.id())
+            .expect("callsite not registered");
+        assert_eq!(registered.format_string(), "hello {}");
+        assert_eq!(registered.module_path(), "quicklog::callsite::tests");
+
+        assert!(registered.is_enabled());
+        registered.set_enabled(false);
+        assert!(!CALLSITE.is_enabled());
+    }
+}
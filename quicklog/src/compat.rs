@@ -0,0 +1,161 @@
+//! Adapters for recording events from other logging libraries into quicklog,
+//! allowing callsites to be migrated incrementally.
+//!
+//! Requires the `tracing` feature. [`QuicklogLayer`](crate::compat::QuicklogLayer) implements
+//! [`tracing_subscriber::Layer`], so it can be composed with any other layers
+//! in a subscriber:
+//!
+//! ```ignore
+//! use quicklog::compat::QuicklogLayer;
+//! use tracing_subscriber::prelude::*;
+//!
+//! quicklog::init!();
+//! tracing_subscriber::registry().with(QuicklogLayer::new()).init();
+//!
+//! // recorded into the quicklog queue, and flushed through `flush!`
+//! tracing::info!(order_id = 10, "order accepted");
+//! ```
+//!
+//! Unlike the quicklog macros, `tracing` only lends out its fields while the
+//! event is being recorded, so they are eagerly formatted on the callsite.
+
+use std::fmt::{Debug, Write};
+
+use tracing_core::{
+    field::{Field, Visit},
+    Event, Subscriber,
+};
+use tracing_subscriber::layer::{Context, Layer};
+
+use crate::{is_level_enabled, level::Level, logger, span, Log, LogRecord};
+
+/// A [`Layer`] which records `tracing` events into the quicklog queue.
+///
+/// The event's message is followed by its other fields as `key=value` pairs,
+/// and events below the quicklog max level are skipped.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct QuicklogLayer;
+
+impl QuicklogLayer {
+    /// Creates a new layer
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+fn to_level(level: &tracing_core::Level) -> Level {
+    match *level {
+        tracing_core::Level::TRACE => Level::Trace,
+        tracing_core::Level::DEBUG => Level::Debug,
+        tracing_core::Level::INFO => Level::Info,
+        tracing_core::Level::WARN => Level::Warn,
+        tracing_core::Level::ERROR => Level::Error,
+    }
+}
+
+#[derive(Default)]
+struct EventVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for EventVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == "message" {
+            _ = write!(self.message, "{:?}", value);
+        } else {
+            _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for QuicklogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let level = to_level(metadata.level());
+        if !is_level_enabled!(level) {
+            return;
+        }
+
+        let mut visitor = EventVisitor::default();
+        event.record(&mut visitor);
+        let mut log_line = visitor.message;
+        if log_line.is_empty() {
+            log_line.push_str(visitor.fields.trim_start());
+        } else {
+            log_line.push_str(&visitor.fields);
+        }
+
+        let log_record = LogRecord {
+            level,
+            module_path: metadata.module_path().unwrap_or_default(),
+            file: metadata.file().unwrap_or_default(),
+            line: metadata.line().unwrap_or_default(),
+            span: span::current(),
+            sequence: 0,
+            thread: None,
+            context: None,
+            log_line: Box::new(log_line),
+        };
+
+        _ = logger().log(log_record);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use quicklog_flush::Flush;
+    use tracing_subscriber::prelude::*;
+
+    use super::QuicklogLayer;
+    use crate::{logger, Log, LogRecord, PatternFormatter};
+
+    struct MessageFormatter;
+
+    impl PatternFormatter for MessageFormatter {
+        fn custom_format(
+            &mut self,
+            _: chrono::DateTime<chrono::Utc>,
+            log_record: LogRecord,
+        ) -> String {
+            format!(
+                "{} {}:{}",
+                log_record.level, log_record.module_path, log_record.log_line
+            )
+        }
+    }
+
+    struct SharedVecFlusher(Arc<Mutex<Vec<String>>>);
+
+    impl Flush for SharedVecFlusher {
+        fn flush_one(&mut self, display: String) {
+            self.0.lock().unwrap().push(display);
+        }
+    }
+
+    #[test]
+    fn records_tracing_events() {
+        let _lock = crate::LOGGER_TEST_LOCK.lock().unwrap();
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        crate::init!();
+        logger().use_flush(Box::new(SharedVecFlusher(lines.clone())));
+        logger().use_formatter(Box::new(MessageFormatter));
+
+        let subscriber = tracing_subscriber::registry().with(QuicklogLayer::new());
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(a = 1, b = "two", "hello {}", "world");
+            tracing::warn!(only = true);
+        });
+        while logger().flush_one().is_ok() {}
+
+        assert_eq!(
+            *lines.lock().unwrap(),
+            vec![
+                "INFO quicklog::compat::tests:hello world a=1 b=\"two\"".to_string(),
+                "WARN quicklog::compat::tests:only=true".to_string(),
+            ]
+        );
+    }
+}
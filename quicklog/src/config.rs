@@ -0,0 +1,651 @@
+//! Configuration applied when initializing [`Quicklog`](crate::Quicklog).
+//!
+//! A [`Config`] is built up through [`config()`] and passed into
+//! [`init!`](crate::init):
+//!
+//! ```
+//! use quicklog::{config, init, OverflowPolicy};
+//!
+//! init!(config().overflow_policy(OverflowPolicy::FlushInPlace));
+//! ```
+//!
+//! A [`Config`] can also be read from environment variables through
+//! [`Config::from_env`], so that deployments can tune the logger without code
+//! changes.
+//!
+//! After init, the formatter, flushers and level filter can still be changed
+//! through [`update_config`].
+
+use std::{
+    os::raw::c_int,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, Once,
+    },
+    time::Duration,
+};
+
+use quicklog_clock::Clock;
+use quicklog_flush::{
+    file_flusher::FileFlusher, noop_flusher::NoopFlusher, rolling_file_flusher::RollingFileFlusher,
+    stderr_flusher::StderrFlusher, stdout_flusher::StdoutFlusher, tcp_flusher::TcpFlusher, Flush,
+};
+
+use crate::{
+    app::BuildInfo,
+    callsite::{self, Callsite, CallsiteFilter},
+    formatter::{formatter, TimestampFormat},
+    level::{self, LevelFilter},
+    logger, FlushError, LogRecord, PatternFormatter, Quicklog,
+};
+
+/// Callback notified of log records which could not be flushed, see
+/// [`Config::on_flush_error`]
+pub type FlushErrorHook = Box<dyn FnMut(FlushError, &LogRecord) + Send>;
+
+/// Behaviour when logging onto a full queue.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Drops the log record, keeping callsite latency low
+    #[default]
+    Drop,
+    /// Spins, yielding to other threads, until the queue has space again.
+    ///
+    /// This relies on flushing being done on a separate thread, otherwise
+    /// logging would block forever on a full queue.
+    Block,
+    /// Flushes a log record on the logging thread to free up space.
+    ///
+    /// Records are never dequeued from two threads at once, so while another
+    /// thread is flushing, e.g. the background flush thread, logging waits
+    /// for it to free up space instead, as with [`OverflowPolicy::Block`].
+    /// Records are dropped if the consumer side of the queue was handed over
+    /// through [`init_split!`](crate::init_split).
+    FlushInPlace,
+}
+
+/// Configuration for [`Quicklog`](crate::Quicklog), created through [`config()`].
+#[derive(Default)]
+pub struct Config {
+    pub(crate) version: Option<&'static str>,
+    pub(crate) stamp: Option<BuildInfo>,
+    pub(crate) overflow_policy: OverflowPolicy,
+    pub(crate) capacity: Option<usize>,
+    pub(crate) grow_to: Option<usize>,
+    pub(crate) max_record_size: Option<usize>,
+    pub(crate) serialize_buffer_capacity: Option<usize>,
+    pub(crate) report_dropped: bool,
+    pub(crate) background_flush: Option<Duration>,
+    pub(crate) flush_on_panic: bool,
+    pub(crate) flusher: Option<Box<dyn Flush + Send>>,
+    pub(crate) clock: Option<Box<dyn Clock + Send + Sync>>,
+    pub(crate) formatter: Option<Box<dyn PatternFormatter + Send>>,
+    pub(crate) max_level: Option<LevelFilter>,
+    pub(crate) filter: Option<CallsiteFilter>,
+    pub(crate) checksum: Option<bool>,
+    pub(crate) on_flush_error: Option<FlushErrorHook>,
+    pub(crate) routes: Vec<(LevelFilter, Box<dyn Flush + Send>)>,
+}
+
+impl Config {
+    /// Creates a [`Config`] from the following environment variables, where
+    /// unset variables keep their defaults:
+    ///
+    /// - `QUICKLOG_OUTPUT`: `stdout`, `stderr`, `none`, `file:<path>` or
+    /// `tcp:<addr>`
+    /// - `QUICKLOG_FORMAT`: `text`, the format of [`QuickLogFormatter`](crate::QuickLogFormatter),
+    /// `logfmt` for [`LogfmtFormatter`](crate::formatter::LogfmtFormatter), or `json` for
+    /// [`JsonFormatter`](crate::formatter::JsonFormatter)
+    /// - `QUICKLOG_TIME_FORMAT`: `rfc3339`, `rfc3339-nanos`, `epoch-secs`,
+    /// `epoch-millis`, `epoch-micros`, `epoch-nanos`, or otherwise a
+    /// `strftime`-like format string such as `%+`
+    /// - `QUICKLOG_LEVEL`: the maximum level, e.g. `info` or `INF`
+    /// - `QUICKLOG_OVERFLOW`: `drop`, `block` or `flush-in-place`, see
+    /// [`OverflowPolicy`]
+    /// - `QUICKLOG_CAPACITY`: the number of records the logging queue holds,
+    /// optionally suffixed with `K`, `M` or `G` for multiples of 1024, e.g.
+    /// `8M`, see [`Config::capacity`]
+    /// - `QUICKLOG_GROW_TO`: the number of records the logging queue may
+    /// grow to, see [`Config::grow_to`]
+    ///
+    /// ```
+    /// use quicklog::{init, Config};
+    ///
+    /// init!(Config::from_env().expect("invalid quicklog configuration"));
+    /// ```
+    pub fn from_env() -> Result<Self, EnvConfigError> {
+        Self::from_vars(|var| std::env::var(var).ok())
+    }
+
+    fn from_vars(var: impl Fn(&'static str) -> Option<String>) -> Result<Self, EnvConfigError> {
+        let invalid = |var: &'static str, value: &str| EnvConfigError {
+            var,
+            value: value.to_string(),
+        };
+        let mut config = Config::default();
+
+        if let Some(output) = var("QUICKLOG_OUTPUT") {
+            config = match output.split_once(':') {
+                None if output == "stdout" => config.flusher(StdoutFlusher),
+                None if output == "stderr" => config.flusher(StderrFlusher),
+                None if output == "none" => config.flusher(NoopFlusher),
+                Some(("file", path)) if !path.is_empty() => {
+                    // Flushers only take `&'static str` paths, and the
+                    // config is read once per process
+                    config.flusher(FileFlusher::new(Box::leak(Box::<str>::from(path))))
+                }
+                Some(("tcp", addr)) if !addr.is_empty() => config.flusher(TcpFlusher::new(addr)),
+                _ => return Err(invalid("QUICKLOG_OUTPUT", &output)),
+            };
+        }
+
+        let time_format =
+            var("QUICKLOG_TIME_FORMAT").map(|time_format| match time_format.as_str() {
+                "rfc3339" => TimestampFormat::Rfc3339,
+                "rfc3339-nanos" => TimestampFormat::Rfc3339Nanos,
+                "epoch-secs" => TimestampFormat::EpochSeconds,
+                "epoch-millis" => TimestampFormat::EpochMillis,
+                "epoch-micros" => TimestampFormat::EpochMicros,
+                "epoch-nanos" => TimestampFormat::EpochNanos,
+                _ => TimestampFormat::Custom(time_format),
+            });
+        match var("QUICKLOG_FORMAT").as_deref() {
+            None | Some("text") => {
+                if let Some(time_format) = time_format {
+                    config = config.formatter(formatter().with_time_format(time_format));
+                }
+            }
+            Some("logfmt") => {
+                config = config.formatter(
+                    formatter()
+                        .logfmt()
+                        .with_time_format(time_format.unwrap_or_default())
+                        .build(),
+                );
+            }
+            Some("json") => {
+                config = config.formatter(
+                    formatter()
+                        .json()
+                        .with_time_format(time_format.unwrap_or_default())
+                        .build(),
+                );
+            }
+            Some(format) => return Err(invalid("QUICKLOG_FORMAT", format)),
+        }
+
+        if let Some(filter) = var("QUICKLOG_LEVEL") {
+            config = config.max_level(
+                filter
+                    .parse()
+                    .map_err(|_| invalid("QUICKLOG_LEVEL", &filter))?,
+            );
+        }
+
+        if let Some(policy) = var("QUICKLOG_OVERFLOW") {
+            config = config.overflow_policy(match policy.as_str() {
+                "drop" => OverflowPolicy::Drop,
+                "block" => OverflowPolicy::Block,
+                "flush-in-place" => OverflowPolicy::FlushInPlace,
+                _ => return Err(invalid("QUICKLOG_OVERFLOW", &policy)),
+            });
+        }
+
+        if let Some(capacity) = var("QUICKLOG_CAPACITY") {
+            config = config.capacity(
+                parse_capacity(&capacity).ok_or_else(|| invalid("QUICKLOG_CAPACITY", &capacity))?,
+            );
+        }
+
+        if let Some(max_capacity) = var("QUICKLOG_GROW_TO") {
+            config = config.grow_to(
+                max_capacity
+                    .parse()
+                    .map_err(|_| invalid("QUICKLOG_GROW_TO", &max_capacity))?,
+            );
+        }
+
+        Ok(config)
+    }
+
+    /// Sets the application version recorded in [`AppInfo`](crate::app::AppInfo)
+    pub fn version(mut self, version: &'static str) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// Logs `build` as the first record once the logger is initialized, along
+    /// with the hostname, process id and start time, so that every log file
+    /// identifies the binary which produced it. `build` is usually created
+    /// through [`build_info!`](crate::build_info), see [`app`](crate::app).
+    pub fn stamp(mut self, build: BuildInfo) -> Self {
+        self.stamp = Some(build);
+        self
+    }
+
+    /// Sets the [`OverflowPolicy`] applied when the logging queue is full
+    pub fn overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow_policy = policy;
+        self
+    }
+
+    /// Sets the number of records the logging queue holds at runtime.
+    ///
+    /// The ring buffer of the queue is sized at build time through
+    /// `QUICKLOG_MAX_LOGGER_CAPACITY`, so records past it spill over into
+    /// room allocated upfront when initializing, as with
+    /// [`Config::grow_to`], which grows further from there if set higher.
+    /// Has no effect if `records` does not exceed the capacity of the ring
+    /// buffer.
+    pub fn capacity(mut self, records: usize) -> Self {
+        self.capacity = Some(records);
+        self
+    }
+
+    /// Lets the logging queue grow when full, up to `max_capacity` records,
+    /// before the [`OverflowPolicy`] applies, see [`grow`](crate::grow).
+    /// Has no effect if `max_capacity` does not exceed the capacity set
+    /// through `QUICKLOG_MAX_LOGGER_CAPACITY`.
+    pub fn grow_to(mut self, max_capacity: usize) -> Self {
+        self.grow_to = Some(max_capacity);
+        self
+    }
+
+    /// Limits the number of bytes each argument logged with `^` may take up
+    /// in the serialization buffer, truncating larger values rather than
+    /// overrunning the buffer.
+    ///
+    /// Strings keep their leading characters and collections their leading
+    /// elements which fit, followed by `...`, while other types are replaced
+    /// by `...` entirely, see
+    /// [`Serialize::encode_truncated`](crate::serialize::Serialize::encode_truncated).
+    /// The limit is clamped between
+    /// [`MIN_TRUNCATED_SIZE`](crate::serialize::MIN_TRUNCATED_SIZE) and
+    /// the capacity of the serialization buffer, which is also the default,
+    /// see [`Config::serialize_buffer_capacity`].
+    pub fn max_record_size(mut self, bytes: usize) -> Self {
+        self.max_record_size = Some(bytes);
+        self
+    }
+
+    /// Sets the capacity in bytes of the buffer which arguments logged with
+    /// `^` are serialized into, overriding
+    /// `QUICKLOG_MAX_SERIALIZE_BUFFER_CAPACITY` at runtime.
+    ///
+    /// The buffer is reused from the start once full, so the capacity should
+    /// cover the arguments of all log records which may be queued at once.
+    /// Only applied on the first initialization, as queued records may still
+    /// refer to the previous buffer otherwise.
+    pub fn serialize_buffer_capacity(mut self, bytes: usize) -> Self {
+        self.serialize_buffer_capacity = Some(bytes);
+        self
+    }
+
+    /// Records a CRC-32 checksum of each argument logged with `^`, which is
+    /// checked again before the record is flushed. Disabled by default.
+    ///
+    /// A record with an argument which was overwritten in the serialization
+    /// buffer in the meantime, e.g. by a [`Serialize`](crate::serialize::Serialize)
+    /// implementation writing past its chunk, or by the buffer wrapping around
+    /// while the record was queued, is then skipped and reported as
+    /// [`FlushError::Corrupted`](crate::FlushError::Corrupted) rather than
+    /// written out as garbage. Shared by all loggers, like the maximum level.
+    pub fn checksum(mut self, enabled: bool) -> Self {
+        self.checksum = Some(enabled);
+        self
+    }
+
+    /// Emits a synthetic warning, e.g. "5 messages dropped", on the next
+    /// successful flush after log records have been dropped
+    pub fn report_dropped(mut self, report: bool) -> Self {
+        self.report_dropped = report;
+        self
+    }
+
+    /// Spawns a thread which flushes all queued log records every `interval`.
+    ///
+    /// [`init!`](crate::init) then returns a
+    /// [`BackgroundFlush`](crate::background::BackgroundFlush) handle, which
+    /// stops the thread after flushing remaining log records when dropped.
+    pub fn background_flush(mut self, interval: Duration) -> Self {
+        self.background_flush = Some(interval);
+        self
+    }
+
+    /// Flushes all queued log records when the process panics or exits, so
+    /// that the last log lines before a crash are not lost in the queue.
+    ///
+    /// This installs a panic hook, which flushes before calling the
+    /// previously installed hook, and an `atexit` handler, which also closes
+    /// the flushers through [`Flush::close`].
+    ///
+    /// Both flush on the calling thread once any other thread flushing, e.g.
+    /// the background flush thread, has finished its current batch, so that
+    /// records are never dequeued from two threads at once. Nothing is
+    /// flushed if the panic was raised while flushing on the same thread,
+    /// e.g. by the flusher, if the other thread is still flushing after half
+    /// a second, e.g. as its flusher is stuck, or if the consumer side of the
+    /// queue was handed over through [`init_split!`](crate::init_split).
+    pub fn flush_on_panic(mut self, flush: bool) -> Self {
+        self.flush_on_panic = flush;
+        self
+    }
+
+    /// Sets the [`Flush`] implementation used, equivalent to calling
+    /// [`with_flush!`](crate::with_flush) after init
+    pub fn flusher(mut self, flusher: impl Flush + Send + 'static) -> Self {
+        self.flusher = Some(Box::new(flusher));
+        self
+    }
+
+    /// Sets the [`Clock`] used to timestamp log records, equivalent to calling
+    /// [`with_clock!`](crate::with_clock) after init.
+    ///
+    /// This allows timestamps to come from a calibrated TSC or hardware clock,
+    /// or to be made deterministic in tests with
+    /// [`MockClock`](quicklog_clock::mock::MockClock).
+    pub fn clock(mut self, clock: impl Clock + Send + Sync + 'static) -> Self {
+        self.clock = Some(Box::new(clock));
+        self
+    }
+
+    /// Sets the [`PatternFormatter`] used, equivalent to calling
+    /// [`with_formatter!`](crate::with_formatter) after init
+    pub fn formatter(mut self, formatter: impl PatternFormatter + Send + 'static) -> Self {
+        self.formatter = Some(Box::new(formatter));
+        self
+    }
+
+    /// Sets the maximum level of log lines recorded, equivalent to calling
+    /// [`set_max_level`](crate::level::set_max_level) after init
+    pub fn max_level(mut self, filter: LevelFilter) -> Self {
+        self.max_level = Some(filter);
+        self
+    }
+
+    /// Records log lines only from callsites for which `filter` returns
+    /// `true`, checked after their level, equivalent to calling
+    /// [`set_filter`](crate::callsite::set_filter) after init
+    pub fn filter(mut self, filter: impl Fn(&Callsite) -> bool + Send + Sync + 'static) -> Self {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    /// Calls `hook` with the error and the log record whenever a record is
+    /// skipped because it could not be flushed, e.g. to diagnose a
+    /// misbehaving [`Clock`](quicklog_clock::Clock).
+    ///
+    /// For a [`FlushError::Io`](crate::FlushError::Io) returned by the
+    /// flusher, the record holds the formatted log line without its span or
+    /// thread, so that the application can detect a dead sink and write the
+    /// line elsewhere.
+    pub fn on_flush_error(
+        mut self,
+        hook: impl FnMut(FlushError, &LogRecord) + Send + 'static,
+    ) -> Self {
+        self.on_flush_error = Some(Box::new(hook));
+        self
+    }
+
+    /// Flushes log lines with a level enabled by `filter` into `flusher`.
+    ///
+    /// Each log line is flushed into every route whose filter enables its
+    /// level, and into the flusher set through [`Config::flusher`] or
+    /// [`with_flush!`](crate::with_flush) if no route matches:
+    ///
+    /// ```
+    /// use quicklog::{config, init, level::LevelFilter};
+    /// use quicklog_flush::{
+    ///     file_flusher::FileFlusher, stderr_flusher::StderrFlusher,
+    /// };
+    ///
+    /// // errors are flushed into both stderr and the file
+    /// init!(config()
+    ///     .route(LevelFilter::Error, StderrFlusher)
+    ///     .route(LevelFilter::Info, FileFlusher::new("logs/app.log")));
+    /// ```
+    pub fn route(mut self, filter: LevelFilter, flusher: impl Flush + Send + 'static) -> Self {
+        self.routes.push((filter, Box::new(flusher)));
+        self
+    }
+
+    /// Flushes into files rotated by time and/or size, see [`RollingFileFlusher`]
+    pub fn rolling_file_flusher(self, flusher: RollingFileFlusher) -> Self {
+        self.flusher(flusher)
+    }
+}
+
+/// Error returned by [`Config::from_env`] when an environment variable has
+/// an invalid value
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EnvConfigError {
+    /// Name of the environment variable
+    pub var: &'static str,
+    /// Value which could not be parsed
+    pub value: String,
+}
+
+impl std::fmt::Display for EnvConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid value `{}` for {}", self.value, self.var)
+    }
+}
+
+impl std::error::Error for EnvConfigError {}
+
+/// Parses a number of records, optionally suffixed with `K`, `M` or `G` for
+/// multiples of 1024, e.g. `8M`
+fn parse_capacity(capacity: &str) -> Option<usize> {
+    let (digits, multiplier) = match capacity.char_indices().last()? {
+        (i, 'K' | 'k') => (&capacity[..i], 1 << 10),
+        (i, 'M' | 'm') => (&capacity[..i], 1 << 20),
+        (i, 'G' | 'g') => (&capacity[..i], 1 << 30),
+        _ => (capacity, 1),
+    };
+
+    digits.parse::<usize>().ok()?.checked_mul(multiplier)
+}
+
+/// Returns a default [`Config`] to be customized and passed into
+/// [`init!`](crate::init)
+pub fn config() -> Config {
+    Config::default()
+}
+
+type PendingUpdate = Box<dyn FnOnce(&mut ConfigUpdate) + Send>;
+
+static PENDING_UPDATES: Mutex<Vec<PendingUpdate>> = Mutex::new(Vec::new());
+static HAS_PENDING_UPDATES: AtomicBool = AtomicBool::new(false);
+
+/// Changes to the running [`Quicklog`], made through [`update_config`]
+pub struct ConfigUpdate<'a> {
+    logger: &'a mut Quicklog,
+}
+
+impl ConfigUpdate<'_> {
+    /// Replaces the [`PatternFormatter`] used
+    pub fn formatter(&mut self, formatter: impl PatternFormatter + Send + 'static) -> &mut Self {
+        self.logger.formatter = Box::new(formatter);
+        self
+    }
+
+    /// Replaces the [`Flush`] implementation used when no route matches,
+    /// see [`Config::flusher`]
+    pub fn flusher(&mut self, flusher: impl Flush + Send + 'static) -> &mut Self {
+        self.logger.flusher = Box::new(flusher);
+        self
+    }
+
+    /// Adds a route, see [`Config::route`]
+    pub fn route(
+        &mut self,
+        filter: LevelFilter,
+        flusher: impl Flush + Send + 'static,
+    ) -> &mut Self {
+        self.logger.routes.push((filter, Box::new(flusher)));
+        self
+    }
+
+    /// Removes all routes, so that every log line is flushed into the
+    /// flusher set through [`ConfigUpdate::flusher`]
+    pub fn clear_routes(&mut self) -> &mut Self {
+        self.logger.routes.clear();
+        self
+    }
+
+    /// Sets the maximum level of log lines recorded, see
+    /// [`set_max_level`](crate::level::set_max_level)
+    pub fn max_level(&mut self, filter: LevelFilter) -> &mut Self {
+        level::set_max_level(filter);
+        self
+    }
+
+    /// Replaces the filter of callsites, see
+    /// [`set_filter`](crate::callsite::set_filter)
+    pub fn filter(
+        &mut self,
+        filter: impl Fn(&Callsite) -> bool + Send + Sync + 'static,
+    ) -> &mut Self {
+        callsite::set_filter(filter);
+        self
+    }
+
+    /// Removes the filter of callsites, see
+    /// [`clear_filter`](crate::callsite::clear_filter)
+    pub fn clear_filter(&mut self) -> &mut Self {
+        callsite::clear_filter();
+        self
+    }
+}
+
+/// Changes the configuration of quicklog after [`init!`](crate::init), e.g. to
+/// redirect log lines into a new file without restarting.
+///
+/// As flushing may be done on another thread, `update` is not applied
+/// immediately. Instead, it is run on the flushing thread the next time log
+/// records are flushed, and so also applies to log records which were queued
+/// before calling `update_config`.
+///
+/// ```
+/// use quicklog::{formatter, init, update_config};
+/// use quicklog_flush::file_flusher::FileFlusher;
+///
+/// init!();
+/// update_config(|cfg| {
+///     cfg.formatter(formatter().with_time_epoch_nanos())
+///         .flusher(FileFlusher::new("logs/redirected.log"));
+/// });
+/// ```
+pub fn update_config(update: impl FnOnce(&mut ConfigUpdate) + Send + 'static) {
+    let mut pending = PENDING_UPDATES.lock().unwrap_or_else(|e| e.into_inner());
+    pending.push(Box::new(update));
+    HAS_PENDING_UPDATES.store(true, Ordering::Release);
+}
+
+/// Applies updates passed to [`update_config`] since the last call
+#[inline]
+pub(crate) fn apply_pending_updates(logger: &mut Quicklog) {
+    if !HAS_PENDING_UPDATES.load(Ordering::Acquire) {
+        return;
+    }
+
+    let updates = {
+        let mut pending = PENDING_UPDATES.lock().unwrap_or_else(|e| e.into_inner());
+        HAS_PENDING_UPDATES.store(false, Ordering::Release);
+        std::mem::take(&mut *pending)
+    };
+
+    let mut cfg = ConfigUpdate { logger };
+    for update in updates {
+        update(&mut cfg);
+    }
+}
+
+/// Installs the panic hook and `atexit` handler of
+/// [`Config::flush_on_panic`], at most once per process
+pub(crate) fn install_flush_on_exit() {
+    static INSTALLED: Once = Once::new();
+
+    extern "C" {
+        fn atexit(callback: extern "C" fn()) -> c_int;
+    }
+
+    extern "C" fn flush_at_exit() {
+        logger().flush_remaining(true);
+    }
+
+    INSTALLED.call_once(|| {
+        let previous_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            logger().flush_remaining(false);
+            previous_hook(info);
+        }));
+
+        // Safety: `flush_at_exit` only accesses the static logger, which is
+        // never dropped
+        unsafe { atexit(flush_at_exit) };
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{Config, EnvConfigError, OverflowPolicy};
+    use crate::level::LevelFilter;
+
+    fn from_vars(vars: &[(&str, &str)]) -> Result<Config, EnvConfigError> {
+        let vars: HashMap<_, _> = vars.iter().copied().collect();
+        Config::from_vars(|var| vars.get(var).map(|value| value.to_string()))
+    }
+
+    #[test]
+    fn config_from_env() {
+        let config = from_vars(&[]).unwrap();
+        assert!(config.flusher.is_none() && config.formatter.is_none());
+
+        let config = from_vars(&[
+            ("QUICKLOG_OUTPUT", "file:logs/env.log"),
+            ("QUICKLOG_TIME_FORMAT", "%+"),
+            ("QUICKLOG_LEVEL", "warn"),
+            ("QUICKLOG_OVERFLOW", "flush-in-place"),
+            ("QUICKLOG_CAPACITY", "8M"),
+            ("QUICKLOG_GROW_TO", "4000000"),
+        ])
+        .unwrap();
+        assert!(config.flusher.is_some() && config.formatter.is_some());
+        assert_eq!(config.max_level, Some(LevelFilter::Warn));
+        assert_eq!(config.overflow_policy, OverflowPolicy::FlushInPlace);
+        assert_eq!(config.capacity, Some(8 << 20));
+        assert_eq!(config.grow_to, Some(4_000_000));
+
+        for (capacity, records) in [("1000", 1000), ("4k", 4096), ("2G", 2 << 30)] {
+            let config = from_vars(&[("QUICKLOG_CAPACITY", capacity)]).unwrap();
+            assert_eq!(config.capacity, Some(records));
+        }
+
+        for format in ["logfmt", "json"] {
+            let config = from_vars(&[("QUICKLOG_FORMAT", format)]).unwrap();
+            assert!(config.formatter.is_some());
+        }
+
+        for (var, value) in [
+            ("QUICKLOG_OUTPUT", "file:"),
+            ("QUICKLOG_OUTPUT", "kafka"),
+            ("QUICKLOG_FORMAT", "xml"),
+            ("QUICKLOG_LEVEL", "verbose"),
+            ("QUICKLOG_OVERFLOW", "wait"),
+            ("QUICKLOG_CAPACITY", "8MB"),
+            ("QUICKLOG_CAPACITY", "M"),
+            ("QUICKLOG_GROW_TO", "-1"),
+        ] {
+            assert_eq!(
+                from_vars(&[(var, value)]).err(),
+                Some(EnvConfigError {
+                    var,
+                    value: value.to_string()
+                })
+            );
+        }
+    }
+}
@@ -5,3 +5,6 @@ pub const MAX_LOGGER_CAPACITY: usize = 1000000;
 
 /// Sets max capacity of byte buffer used for serialization with `^` prefix in logging, can be set through `QUICKLOG_MAX_SERIALIZE_BUFFER_CAPACITY`.
 pub const MAX_SERIALIZE_BUFFER_CAPACITY: usize = 1000000;
+
+/// Directives for the minimum level of log lines compiled in, e.g. `info,hot_path=off`, can be set through `QUICKLOG_MIN_LEVEL`.
+pub const MIN_LEVEL: &str = "";
@@ -0,0 +1,314 @@
+//! Default fields appended to every log line, without repeating them at
+//! each callsite.
+//!
+//! [`with_context!`](crate::with_context) registers fields for all threads,
+//! e.g. the region or strategy a process runs, while
+//! [`with_thread_context!`](crate::with_thread_context) only registers them
+//! for the calling thread. [`scoped_context!`](crate::scoped_context) adds
+//! fields to the calling thread until the returned guard is dropped:
+//!
+//! ```
+//! use quicklog::{info, init, scoped_context, with_context};
+//!
+//! # init!();
+//! with_context!(region = "us-east", strategy = "mm1");
+//! {
+//!     let _guard = scoped_context!(venue = "XNAS");
+//!     // output: "order filled id=7 region=us-east strategy=mm1 venue=XNAS"
+//!     info!(id = 7, "order filled");
+//! }
+//! ```
+//!
+//! Fields take the same syntax as [`span!`](crate::span!), and are eagerly
+//! formatted once when registered. Each record only holds a pointer to the
+//! fields in effect when it was logged, attached by
+//! [`Log::log`](crate::Log::log) as [`LogRecord::context`](crate::LogRecord::context).
+//!
+//! The fields are written after those passed to the logging macro, global
+//! fields first and the innermost scope last, so that formatters keeping the
+//! last value of a name, such as [`JsonFormatter`](crate::formatter::JsonFormatter),
+//! keep the value of the innermost scope. Registering a global field with a name which
+//! was already registered replaces its value.
+
+use std::{
+    cell::RefCell,
+    fmt::{Display, Formatter, Result},
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+};
+
+use crate::{
+    line::{FieldVisitor, LogLine, ValueVisitor},
+    serialize::Value,
+};
+
+/// Names and formatted values of context fields
+pub type Fields = Vec<(&'static str, String)>;
+
+/// Global fields, replaced as a whole whenever they change
+static GLOBAL: RwLock<Option<Arc<Node>>> = RwLock::new(None);
+/// Incremented whenever the global fields change, so that threads only read
+/// them again once they did
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    static LOCAL: RefCell<Local> = RefCell::new(Local::default());
+}
+
+#[derive(Default)]
+struct Local {
+    /// Value of [`GENERATION`] when `global` was read
+    generation: u64,
+    global: Option<Arc<Node>>,
+    /// Fields of this thread, innermost first
+    scoped: Option<Arc<Node>>,
+}
+
+struct Node {
+    fields: Fields,
+    parent: Option<Arc<Node>>,
+}
+
+impl Node {
+    fn visit(&self, visit: &mut dyn FnMut(&'static str, &str) -> Result) -> Result {
+        if let Some(parent) = &self.parent {
+            parent.visit(visit)?;
+        }
+
+        self.fields
+            .iter()
+            .try_for_each(|(name, value)| visit(name, value))
+    }
+}
+
+/// Snapshot of the context fields in effect when a log line was recorded,
+/// only holding pointers to them
+#[derive(Clone)]
+pub struct Context {
+    global: Option<Arc<Node>>,
+    scoped: Option<Arc<Node>>,
+}
+
+impl Context {
+    /// Calls `visit` with the name and value of each field, global fields
+    /// first and the innermost scope last
+    pub fn visit(&self, visit: &mut dyn FnMut(&'static str, &str) -> Result) -> Result {
+        for node in [&self.global, &self.scoped].into_iter().flatten() {
+            node.visit(visit)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes the fields as `name=value` pairs separated by spaces
+impl Display for Context {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let mut leading = "";
+        self.visit(&mut |name, value| {
+            write!(f, "{}{}={}", leading, name, value)?;
+            leading = " ";
+            Ok(())
+        })
+    }
+}
+
+/// Registers `fields` for all threads, replacing the values of fields which
+/// were already registered. Prefer using [`with_context!`](crate::with_context),
+/// which also formats the fields.
+pub fn set_global(fields: Fields) {
+    let mut global = GLOBAL.write().unwrap_or_else(|e| e.into_inner());
+    let mut merged = global
+        .as_ref()
+        .map_or_else(Vec::new, |node| node.fields.clone());
+    for (name, value) in fields {
+        match merged.iter_mut().find(|(existing, _)| *existing == name) {
+            Some((_, existing)) => *existing = value,
+            None => merged.push((name, value)),
+        }
+    }
+
+    *global = Some(Arc::new(Node {
+        fields: merged,
+        parent: None,
+    }));
+    GENERATION.fetch_add(1, Ordering::Release);
+}
+
+/// Removes all fields registered through [`set_global`]
+pub fn clear_global() {
+    *GLOBAL.write().unwrap_or_else(|e| e.into_inner()) = None;
+    GENERATION.fetch_add(1, Ordering::Release);
+}
+
+/// Registers `fields` for the calling thread, after any fields registered
+/// so far. Prefer using [`with_thread_context!`](crate::with_thread_context),
+/// which also formats the fields.
+///
+/// Fields registered while a [`ContextGuard`] is alive are removed along
+/// with those of the guard.
+pub fn set_thread(fields: Fields) {
+    _ = push(fields);
+}
+
+/// Removes all fields registered for the calling thread, including those of
+/// any [`ContextGuard`] still alive
+pub fn clear_thread() {
+    _ = LOCAL.try_with(|local| local.borrow_mut().scoped = None);
+}
+
+/// Adds `fields` to the calling thread until the returned guard is dropped.
+/// Prefer using [`scoped_context!`](crate::scoped_context), which also
+/// formats the fields.
+pub fn scoped(fields: Fields) -> ContextGuard {
+    ContextGuard {
+        prev: push(fields),
+        _marker: PhantomData,
+    }
+}
+
+/// Pushes `fields` onto the fields of the calling thread, returning the
+/// previous ones
+fn push(fields: Fields) -> Option<Arc<Node>> {
+    LOCAL
+        .try_with(|local| {
+            let mut local = local.borrow_mut();
+            let prev = local.scoped.take();
+            local.scoped = Some(Arc::new(Node {
+                fields,
+                parent: prev.clone(),
+            }));
+            prev
+        })
+        .ok()
+        .flatten()
+}
+
+/// Guard returned by [`scoped`], removes its fields when dropped.
+///
+/// As the fields are tracked per thread, this guard cannot be sent to other
+/// threads.
+pub struct ContextGuard {
+    prev: Option<Arc<Node>>,
+    _marker: PhantomData<*const ()>,
+}
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        let prev = self.prev.take();
+        _ = LOCAL.try_with(|local| local.borrow_mut().scoped = prev);
+    }
+}
+
+/// Returns the context fields in effect on this thread, if any
+pub fn current() -> Option<Context> {
+    LOCAL
+        .try_with(|local| {
+            let mut local = local.borrow_mut();
+            let generation = GENERATION.load(Ordering::Acquire);
+            if local.generation != generation {
+                local.global = GLOBAL.read().unwrap_or_else(|e| e.into_inner()).clone();
+                local.generation = generation;
+            }
+
+            (local.global.is_some() || local.scoped.is_some()).then(|| Context {
+                global: local.global.clone(),
+                scoped: local.scoped.clone(),
+            })
+        })
+        .ok()
+        .flatten()
+}
+
+/// Log line whose structured fields are followed by the fields of a
+/// [`Context`], created through [`with_fields`]
+pub struct ContextLine<'a> {
+    line: &'a dyn LogLine,
+    context: Option<&'a Context>,
+}
+
+/// Appends the fields of `context`, usually [`LogRecord::context`](crate::LogRecord::context),
+/// to those of `line`, so that formatters write both in the same way
+pub fn with_fields<'a>(line: &'a dyn LogLine, context: Option<&'a Context>) -> ContextLine<'a> {
+    ContextLine { line, context }
+}
+
+impl Display for ContextLine<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{}", self.line)?;
+        match self.context {
+            Some(context) => write!(f, " {}", context),
+            None => Ok(()),
+        }
+    }
+}
+
+impl LogLine for ContextLine<'_> {
+    fn fmt_message(&self, f: &mut Formatter<'_>) -> Result {
+        self.line.fmt_message(f)
+    }
+
+    fn visit_fields(&self, visit: &mut FieldVisitor<'_>) -> Result {
+        self.line.visit_fields(visit)?;
+        match self.context {
+            Some(context) => context.visit(&mut |name, value| visit(name, &value)),
+            None => Ok(()),
+        }
+    }
+
+    fn visit_values(&self, visit: &mut ValueVisitor<'_>) -> Result {
+        self.line.visit_values(visit)?;
+        match self.context {
+            Some(context) => {
+                context.visit(&mut |name, value| visit(name, Value::Str(value.to_string())))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{clear_thread, current, scoped, set_thread, with_fields};
+
+    fn fields(context: &[(&'static str, &str)]) -> super::Fields {
+        context
+            .iter()
+            .map(|(name, value)| (*name, value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn thread_and_scoped_fields() {
+        // Global fields are left to the integration tests, as they would be
+        // attached to the records of every other test
+        assert!(current().is_none());
+
+        set_thread(fields(&[("worker", "1")]));
+        {
+            let _guard = scoped(fields(&[("venue", "XNAS"), ("side", "bid")]));
+            let _inner = scoped(fields(&[("side", "ask")]));
+            assert_eq!(
+                current().unwrap().to_string(),
+                "worker=1 venue=XNAS side=bid side=ask"
+            );
+        }
+        assert_eq!(current().unwrap().to_string(), "worker=1");
+        // Other threads do not see the fields of this one
+        assert!(std::thread::spawn(current).join().unwrap().is_none());
+
+        let line = "order filled".to_string();
+        let context = current();
+        assert_eq!(
+            with_fields(&line, context.as_ref()).to_string(),
+            "order filled worker=1"
+        );
+        assert_eq!(with_fields(&line, None).to_string(), "order filled");
+
+        clear_thread();
+        assert!(current().is_none());
+    }
+}
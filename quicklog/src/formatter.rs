@@ -0,0 +1,1217 @@
+//! The bundled [`PatternFormatter`]s, with a configurable timestamp format.
+//!
+//! A [`QuickLogFormatter`] is created through [`formatter()`] and passed
+//! into [`with_formatter!`](crate::with_formatter):
+//!
+//! ```
+//! use quicklog::{formatter, init, with_formatter};
+//!
+//! init!();
+//! // e.g. "[1700000000123456789]hello world"
+//! with_formatter!(formatter().with_time_epoch_nanos());
+//! ```
+//!
+//! [`QuickLogFormatter::with_ansi_auto`] colors lines by level when writing to
+//! a terminal, unless disabled through the `NO_COLOR` environment variable,
+//! with colors which can be changed through a
+//! [`Theme`](crate::formatter::Theme).
+//! [`QuickLogFormatter::with_fields_position`] and
+//! [`QuickLogFormatter::with_kv_separator`] change where and how the
+//! structured fields of each log line are written, e.g. as `a:1 b:2 message`,
+//! and [`QuickLogFormatter::with_quoted_values`] quotes values containing
+//! whitespace.
+//!
+//! [`QuickLogFormatter::logfmt`] switches to
+//! [`LogfmtFormatter`](crate::formatter::LogfmtFormatter), writing the
+//! structured fields of each log line as separate `key=value` pairs, and
+//! [`QuickLogFormatter::json`] to [`JsonFormatter`](crate::formatter::JsonFormatter):
+//!
+//! ```
+//! use quicklog::{formatter, init, with_formatter};
+//!
+//! init!();
+//! with_formatter!(formatter().logfmt().build());
+//! // or
+//! with_formatter!(formatter().json().build());
+//! ```
+
+use std::{
+    fmt::{Display, Write},
+    io::IsTerminal,
+};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::{
+    app_info, context,
+    level::Level,
+    line::LogLine,
+    serialize::{self, Value},
+    LogRecord, PatternFormatter,
+};
+
+/// How timestamps are written by [`QuickLogFormatter`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum TimestampFormat {
+    /// RFC3339 in UTC, with as many fractional digits as needed out of 0, 3,
+    /// 6 or 9, e.g. `2023-11-14T22:13:20.123Z`
+    #[default]
+    Rfc3339,
+    /// RFC3339 in UTC, always with 9 fractional digits, e.g.
+    /// `2023-11-14T22:13:20.123000000Z`
+    Rfc3339Nanos,
+    /// Seconds since the Unix epoch
+    EpochSeconds,
+    /// Milliseconds since the Unix epoch
+    EpochMillis,
+    /// Microseconds since the Unix epoch
+    EpochMicros,
+    /// Nanoseconds since the Unix epoch
+    EpochNanos,
+    /// `strftime`-like format string, see [`chrono::format::strftime`]
+    Custom(String),
+}
+
+impl TimestampFormat {
+    /// Writes `time` in this format into `w`
+    pub fn write_time(&self, time: &DateTime<Utc>, w: &mut impl Write) -> std::fmt::Result {
+        match self {
+            TimestampFormat::Rfc3339 => {
+                w.write_str(&time.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+            }
+            TimestampFormat::Rfc3339Nanos => {
+                w.write_str(&time.to_rfc3339_opts(SecondsFormat::Nanos, true))
+            }
+            TimestampFormat::EpochSeconds => write!(w, "{}", time.timestamp()),
+            TimestampFormat::EpochMillis => write!(w, "{}", time.timestamp_millis()),
+            TimestampFormat::EpochMicros => write!(w, "{}", time.timestamp_micros()),
+            TimestampFormat::EpochNanos => write!(
+                w,
+                "{}",
+                i128::from(time.timestamp()) * 1_000_000_000
+                    + i128::from(time.timestamp_subsec_nanos())
+            ),
+            TimestampFormat::Custom(fmt) => write!(w, "{}", time.format(fmt)),
+        }
+    }
+}
+
+/// Where [`QuickLogFormatter`] writes the structured fields of a log line,
+/// i.e. those passed to the logging macros before the format string
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FieldsPosition {
+    /// After the message, as `message a=1 b=2`
+    #[default]
+    Suffix,
+    /// Before the message, as `a=1 b=2 message`
+    Prefix,
+    /// Not written, leaving only the message
+    Omit,
+}
+
+/// Formats log lines as `[timestamp]span: message`, or `[timestamp]message`
+/// when no span is entered
+#[derive(Clone, Debug, Default)]
+pub struct QuickLogFormatter {
+    time_format: TimestampFormat,
+    pretty: bool,
+    float_precision: Option<usize>,
+    fields_position: FieldsPosition,
+    kv_separator: Option<&'static str>,
+    field_delimiter: Option<&'static str>,
+    message_delimiter: Option<&'static str>,
+    quote_values: bool,
+    theme: Theme,
+    hostname: bool,
+    pid: bool,
+    thread: bool,
+    ansi: bool,
+}
+
+impl QuickLogFormatter {
+    /// Creates a formatter writing timestamps as [`TimestampFormat::Rfc3339`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the [`TimestampFormat`] used
+    pub fn with_time_format(mut self, time_format: TimestampFormat) -> Self {
+        self.time_format = time_format;
+        self
+    }
+
+    /// Writes timestamps as [`TimestampFormat::Rfc3339`]
+    pub fn with_time_rfc3339(self) -> Self {
+        self.with_time_format(TimestampFormat::Rfc3339)
+    }
+
+    /// Writes timestamps as [`TimestampFormat::Rfc3339Nanos`]
+    pub fn with_time_nanos(self) -> Self {
+        self.with_time_format(TimestampFormat::Rfc3339Nanos)
+    }
+
+    /// Writes timestamps as [`TimestampFormat::EpochMicros`]
+    pub fn with_time_epoch_micros(self) -> Self {
+        self.with_time_format(TimestampFormat::EpochMicros)
+    }
+
+    /// Writes timestamps as [`TimestampFormat::EpochNanos`]
+    pub fn with_time_epoch_nanos(self) -> Self {
+        self.with_time_format(TimestampFormat::EpochNanos)
+    }
+
+    /// Pretty-prints derived structs and collections logged with `^` across
+    /// indented lines, see [`serialize::pretty`](crate::serialize::pretty).
+    /// Disabled by default, writing them compactly on one line
+    pub fn with_pretty(mut self, pretty: bool) -> Self {
+        self.pretty = pretty;
+        self
+    }
+
+    /// Writes floats logged with `^` with `precision` decimal places, see
+    /// [`serialize::float_precision`](crate::serialize::float_precision).
+    /// By default, floats are written in their shortest representation
+    pub fn with_float_precision(mut self, precision: usize) -> Self {
+        self.float_precision = Some(precision);
+        self
+    }
+
+    /// Sets where structured fields are written, see [`FieldsPosition`].
+    /// Defaults to [`FieldsPosition::Suffix`]
+    pub fn with_fields_position(mut self, fields_position: FieldsPosition) -> Self {
+        self.fields_position = fields_position;
+        self
+    }
+
+    /// Sets the separator written between the name and value of structured
+    /// fields, e.g. `":"` to write `a:1`. Defaults to `"="`
+    pub fn with_kv_separator(mut self, kv_separator: &'static str) -> Self {
+        self.kv_separator = Some(kv_separator);
+        self
+    }
+
+    /// Sets the delimiter written between structured fields, e.g. `", "` to
+    /// write `a=1, b=2`. Defaults to `" "`
+    pub fn with_field_delimiter(mut self, field_delimiter: &'static str) -> Self {
+        self.field_delimiter = Some(field_delimiter);
+        self
+    }
+
+    /// Sets the delimiter written between the message and the structured
+    /// fields, e.g. `" | "` to write `message | a=1`. Defaults to `" "`
+    pub fn with_message_delimiter(mut self, message_delimiter: &'static str) -> Self {
+        self.message_delimiter = Some(message_delimiter);
+        self
+    }
+
+    /// Quotes the values of structured fields which are empty or contain
+    /// whitespace or `"`, e.g. `reason="no liquidity"`, escaping `\`, `"` and
+    /// control characters, so that they can be split on whitespace. Disabled
+    /// by default
+    pub fn with_quoted_values(mut self, quote_values: bool) -> Self {
+        self.quote_values = quote_values;
+        self
+    }
+
+    /// Writes the hostname captured by [`init!`](crate::init) after the
+    /// timestamp, as `[timestamp][hostname]`. Disabled by default
+    pub fn with_hostname(mut self, hostname: bool) -> Self {
+        self.hostname = hostname;
+        self
+    }
+
+    /// Writes the process id captured by [`init!`](crate::init) after the
+    /// timestamp and hostname, as `[timestamp][pid]`. Disabled by default
+    pub fn with_pid(mut self, pid: bool) -> Self {
+        self.pid = pid;
+        self
+    }
+
+    /// Writes the name of the thread each record was logged from, or its id
+    /// if unnamed, after the timestamp, hostname and process id, as
+    /// `[timestamp][thread]`. Disabled by default
+    pub fn with_thread(mut self, thread: bool) -> Self {
+        self.thread = thread;
+        self
+    }
+
+    /// Colors each line with ANSI escape codes according to its level,
+    /// dimming trace and debug lines and writing warnings in yellow and
+    /// errors in red. Disabled by default
+    pub fn with_ansi(mut self, ansi: bool) -> Self {
+        self.ansi = ansi;
+        self
+    }
+
+    /// Sets the styles used when colors are enabled through
+    /// [`with_ansi`](QuickLogFormatter::with_ansi) or
+    /// [`with_ansi_auto`](QuickLogFormatter::with_ansi_auto), see [`Theme`]
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Same as [`with_ansi`](QuickLogFormatter::with_ansi), enabled only if
+    /// stdout is a terminal and the `NO_COLOR` environment variable is unset
+    /// or empty, see <https://no-color.org>
+    pub fn with_ansi_auto(self) -> Self {
+        let no_color = std::env::var_os("NO_COLOR").map_or(false, |v| !v.is_empty());
+        self.with_ansi(!no_color && std::io::stdout().is_terminal())
+    }
+}
+
+impl QuickLogFormatter {
+    /// Writes the message and structured fields of `line` according to
+    /// [`FieldsPosition`] and the configured delimiters
+    fn write_log_line(&self, buf: &mut String, line: &dyn LogLine) -> std::fmt::Result {
+        if self.fields_position == FieldsPosition::Suffix
+            && self.kv_separator.is_none()
+            && self.field_delimiter.is_none()
+            && self.message_delimiter.is_none()
+            && !self.quote_values
+        {
+            return write!(buf, "{}", line);
+        }
+
+        let kv_separator = self.kv_separator.unwrap_or("=");
+        let field_delimiter = self.field_delimiter.unwrap_or(" ");
+        let message_delimiter = self.message_delimiter.unwrap_or(" ");
+        // Reused for formatting values before quoting them
+        let mut value_buf = String::new();
+        // Writes the fields, with `leading` before the first one
+        let mut write_fields = |buf: &mut String, mut leading: &str| {
+            line.visit_fields(&mut |name, value| {
+                buf.push_str(leading);
+                leading = field_delimiter;
+                buf.push_str(name);
+                buf.push_str(kv_separator);
+                if !self.quote_values {
+                    return write!(buf, "{}", value);
+                }
+
+                value_buf.clear();
+                write!(value_buf, "{}", value)?;
+                if value_buf.is_empty() || value_buf.chars().any(|c| c.is_whitespace() || c == '"')
+                {
+                    write_quoted(buf, &value_buf);
+                } else {
+                    buf.push_str(&value_buf);
+                }
+                Ok(())
+            })
+        };
+
+        match self.fields_position {
+            FieldsPosition::Suffix => {
+                let start = buf.len();
+                write!(buf, "{}", line.message())?;
+                let leading = if buf.len() > start {
+                    message_delimiter
+                } else {
+                    ""
+                };
+                write_fields(buf, leading)
+            }
+            FieldsPosition::Prefix => {
+                let start = buf.len();
+                write_fields(buf, "")?;
+                if buf.len() == start {
+                    return write!(buf, "{}", line.message());
+                }
+                let fields_end = buf.len();
+                buf.push_str(message_delimiter);
+                let message_start = buf.len();
+                write!(buf, "{}", line.message())?;
+                if buf.len() == message_start {
+                    buf.truncate(fields_end);
+                }
+                Ok(())
+            }
+            FieldsPosition::Omit => write!(buf, "{}", line.message()),
+        }
+    }
+}
+
+/// ANSI styles used by [`QuickLogFormatter`] when colors are enabled through
+/// [`with_ansi`](QuickLogFormatter::with_ansi), set through
+/// [`with_theme`](QuickLogFormatter::with_theme).
+///
+/// Each style is the escape sequence starting it, e.g. `"\x1b[1;31m"` for
+/// bold red, or `None` to leave the text unstyled:
+///
+/// ```
+/// use quicklog::{formatter, formatter::Theme};
+///
+/// let theme = Theme {
+///     info: Some("\x1b[32m"),
+///     prefix: Some("\x1b[2m"),
+///     ..Theme::default()
+/// };
+/// let formatter = formatter().with_ansi_auto().with_theme(theme);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Theme {
+    /// Style of lines at [`Level::Trace`], dimmed by default
+    pub trace: Option<&'static str>,
+    /// Style of lines at [`Level::Debug`], dimmed by default
+    pub debug: Option<&'static str>,
+    /// Style of lines at [`Level::Info`], unstyled by default
+    pub info: Option<&'static str>,
+    /// Style of lines at [`Level::Warn`], yellow by default
+    pub warn: Option<&'static str>,
+    /// Style of lines at [`Level::Error`], red by default
+    pub error: Option<&'static str>,
+    /// Style of the bracketed timestamp, hostname, process id and thread
+    /// before the message, replacing the style of the line for them.
+    /// Unstyled by default
+    pub prefix: Option<&'static str>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            trace: Some("\x1b[2m"),
+            debug: Some("\x1b[2m"),
+            info: None,
+            warn: Some("\x1b[33m"),
+            error: Some("\x1b[31m"),
+            prefix: None,
+        }
+    }
+}
+
+impl Theme {
+    /// Style of lines at `level`, if any. Custom levels use their own
+    /// [`CustomLevel::color`](crate::level::CustomLevel::color)
+    pub fn level_style(&self, level: Level) -> Option<&'static str> {
+        match level {
+            Level::Trace => self.trace,
+            Level::Debug => self.debug,
+            Level::Info => self.info,
+            Level::Warn => self.warn,
+            Level::Error => self.error,
+            Level::Custom(level) => level.color,
+        }
+    }
+}
+
+const ANSI_RESET: &str = "\x1b[0m";
+
+impl PatternFormatter for QuickLogFormatter {
+    fn custom_format(&mut self, time: DateTime<Utc>, object: LogRecord) -> String {
+        let mut buf = String::new();
+        self.format_into(time, object, &mut buf);
+        buf
+    }
+
+    fn format_into(&mut self, time: DateTime<Utc>, object: LogRecord, buf: &mut String) {
+        let color = self.theme.level_style(object.level).filter(|_| self.ansi);
+        let prefix_style = self.theme.prefix.filter(|_| self.ansi);
+        if let Some(style) = prefix_style.or(color) {
+            buf.push_str(style);
+        }
+        // Writing into a `String` cannot fail
+        buf.push('[');
+        _ = self.time_format.write_time(&time, buf);
+        buf.push(']');
+        if let Some(info) = app_info().filter(|_| self.hostname || self.pid) {
+            if self.hostname {
+                _ = write!(buf, "[{}]", info.hostname());
+            }
+            if self.pid {
+                _ = write!(buf, "[{}]", info.pid());
+            }
+        }
+        if let Some(thread) = object.thread.as_ref().filter(|_| self.thread) {
+            _ = write!(buf, "[{}]", thread);
+        }
+        if prefix_style.is_some() {
+            buf.push_str(ANSI_RESET);
+            if let Some(color) = color {
+                buf.push_str(color);
+            }
+        }
+        let write_line = |buf: &mut String| {
+            if let Some(span) = &object.span {
+                write!(buf, "{}: ", span)?;
+            }
+            let line = context::with_fields(&*object.log_line, object.context.as_ref());
+            self.write_log_line(buf, &line)
+        };
+        let write_line = |buf: &mut String| match self.float_precision {
+            Some(precision) => serialize::float_precision(precision, || write_line(buf)),
+            None => write_line(buf),
+        };
+        _ = if self.pretty {
+            serialize::pretty(|| write_line(buf))
+        } else {
+            write_line(buf)
+        };
+        if color.is_some() {
+            buf.push_str(ANSI_RESET);
+        }
+        buf.push('\n');
+    }
+}
+
+impl QuickLogFormatter {
+    /// Switches to [logfmt](https://brandur.org/logfmt) output, keeping the
+    /// [`TimestampFormat`] set so far
+    pub fn logfmt(self) -> LogfmtBuilder {
+        LogfmtBuilder {
+            time_format: self.time_format,
+        }
+    }
+}
+
+/// Builder for a [`LogfmtFormatter`], created through
+/// [`QuickLogFormatter::logfmt`]
+#[derive(Clone, Debug, Default)]
+pub struct LogfmtBuilder {
+    time_format: TimestampFormat,
+}
+
+impl LogfmtBuilder {
+    /// Sets the [`TimestampFormat`] used
+    pub fn with_time_format(mut self, time_format: TimestampFormat) -> Self {
+        self.time_format = time_format;
+        self
+    }
+
+    /// Creates the [`LogfmtFormatter`]
+    pub fn build(self) -> LogfmtFormatter {
+        LogfmtFormatter {
+            time_format: self.time_format,
+            value: String::new(),
+        }
+    }
+}
+
+/// Formats log lines as [logfmt](https://brandur.org/logfmt), e.g.
+/// `ts=2023-11-14T22:13:20Z level=INF seq=0 tid=1 thread=main msg="order filled" id=1`
+///
+/// Structured fields passed to the logging macros are written as separate
+/// `key=value` pairs after the message, followed by `span=...` when a span is
+/// entered. Values are quoted when empty or when containing spaces, `=` or
+/// `"`, with `\`, `"` and control characters escaped. `seq` is the
+/// [`LogRecord::sequence`] of the record, which reveals lost records, and
+/// `tid` and `thread` the id and name of [`LogRecord::thread`], with `thread`
+/// omitted for unnamed threads.
+#[derive(Clone, Debug, Default)]
+pub struct LogfmtFormatter {
+    time_format: TimestampFormat,
+    /// Reused buffer for formatting values before quoting them
+    value: String,
+}
+
+impl LogfmtFormatter {
+    /// Writes ` key=value`, quoting the value if needed
+    fn write_pair(&mut self, buf: &mut String, key: &str, value: &dyn Display) {
+        self.value.clear();
+        _ = write!(self.value, "{}", value);
+        write_pair(buf, key, &self.value);
+    }
+}
+
+fn write_pair(buf: &mut String, key: &str, value: &str) {
+    buf.push(' ');
+    buf.push_str(key);
+    buf.push('=');
+
+    let needs_quotes = value.is_empty()
+        || value
+            .chars()
+            .any(|c| c == ' ' || c == '=' || c == '"' || c.is_control());
+    if needs_quotes {
+        write_quoted(buf, value);
+    } else {
+        buf.push_str(value);
+    }
+}
+
+/// Writes `value` within `"`, escaping `\`, `"` and control characters
+fn write_quoted(buf: &mut String, value: &str) {
+    buf.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => buf.push_str("\\\""),
+            '\\' => buf.push_str("\\\\"),
+            '\n' => buf.push_str("\\n"),
+            '\r' => buf.push_str("\\r"),
+            '\t' => buf.push_str("\\t"),
+            c if c.is_control() => _ = write!(buf, "\\u{{{:04x}}}", c as u32),
+            c => buf.push(c),
+        }
+    }
+    buf.push('"');
+}
+
+/// Short level names, matching those accepted by
+/// [`LevelFilter`](crate::level::LevelFilter)'s `FromStr`
+fn short_level(level: Level) -> &'static str {
+    match level {
+        Level::Trace => "TRC",
+        Level::Debug => "DBG",
+        Level::Info => "INF",
+        Level::Warn => "WRN",
+        Level::Error => "ERR",
+        Level::Custom(level) => level.name,
+    }
+}
+
+impl PatternFormatter for LogfmtFormatter {
+    fn custom_format(&mut self, time: DateTime<Utc>, object: LogRecord) -> String {
+        let mut buf = String::new();
+        self.format_into(time, object, &mut buf);
+        buf
+    }
+
+    fn format_into(&mut self, time: DateTime<Utc>, object: LogRecord, buf: &mut String) {
+        // Writing into a `String` cannot fail
+        buf.push_str("ts=");
+        _ = self.time_format.write_time(&time, buf);
+        write_pair(buf, "level", short_level(object.level));
+        _ = write!(buf, " seq={}", object.sequence);
+        if let Some(thread) = &object.thread {
+            _ = write!(buf, " tid={}", thread.id());
+            if let Some(name) = thread.name() {
+                write_pair(buf, "thread", name);
+            }
+        }
+        self.write_pair(buf, "msg", &object.log_line.message());
+        let line = context::with_fields(&*object.log_line, object.context.as_ref());
+        _ = line.visit_fields(&mut |name, value| {
+            self.write_pair(buf, name, value);
+            Ok(())
+        });
+        if let Some(span) = &object.span {
+            self.write_pair(buf, "span", span);
+        }
+        buf.push('\n');
+    }
+}
+
+impl QuickLogFormatter {
+    /// Switches to JSON output, keeping the [`TimestampFormat`] set so far
+    pub fn json(self) -> JsonBuilder {
+        JsonBuilder {
+            time_format: self.time_format,
+            ..JsonBuilder::default()
+        }
+    }
+}
+
+/// Builder for a [`JsonFormatter`], created through [`QuickLogFormatter::json`]
+#[derive(Clone, Debug)]
+pub struct JsonBuilder {
+    time_format: TimestampFormat,
+    app_info: bool,
+}
+
+impl Default for JsonBuilder {
+    fn default() -> Self {
+        Self {
+            time_format: TimestampFormat::default(),
+            app_info: true,
+        }
+    }
+}
+
+impl JsonBuilder {
+    /// Sets the [`TimestampFormat`] used
+    pub fn with_time_format(mut self, time_format: TimestampFormat) -> Self {
+        self.time_format = time_format;
+        self
+    }
+
+    /// Sets whether the [`AppInfo`](crate::app::AppInfo) captured at init is
+    /// written with every record, enabled by default
+    pub fn with_app_info(mut self, app_info: bool) -> Self {
+        self.app_info = app_info;
+        self
+    }
+
+    /// Creates the [`JsonFormatter`]
+    pub fn build(self) -> JsonFormatter {
+        JsonFormatter {
+            time_format: self.time_format,
+            app_info: self.app_info,
+            value: String::new(),
+        }
+    }
+}
+
+/// Formats log lines as one JSON object per line, e.g.
+///
+/// ```json
+/// {"timestamp":"2023-11-14T22:13:20Z","level":"INFO","seq":0,"thread_id":1,"thread_name":"main","target":"app::orders","file":"src/orders.rs","line":10,"hostname":"host","pid":42,"executable":"app","version":"1.2.3","message":"order filled","fields":{"order":{"price":1.5,"side":"buy"}}}
+/// ```
+///
+/// Structured fields passed to the logging macros are written under
+/// `"fields"`, with dotted names such as `order.price` nested into objects.
+/// Numbers and booleans passed without a prefix are written unquoted, as are
+/// the typed [`Value`]s of fields passed with `^`, while `?` and `%` fields
+/// are written as strings, see [`LogLine::visit_values`](crate::line::LogLine::visit_values).
+/// When the same name is logged more than once, the last value is kept. `"span"` is only written when a span is entered, and epoch
+/// [`TimestampFormat`]s are written as numbers. Gaps between `"seq"` values
+/// count the records lost in between, see [`LogRecord::sequence`].
+/// `"thread_name"` is omitted for unnamed threads, see [`LogRecord::thread`].
+/// `"hostname"`, `"pid"`, `"executable"` and `"version"` are taken from the
+/// [`AppInfo`](crate::app::AppInfo) captured at init, if any, with `"version"`
+/// omitted unless one was passed to [`init!`](crate::init), see
+/// [`JsonBuilder::with_app_info`].
+#[derive(Clone, Debug)]
+pub struct JsonFormatter {
+    time_format: TimestampFormat,
+    /// Whether the [`AppInfo`](crate::app::AppInfo) is written
+    app_info: bool,
+    /// Reused buffer for formatting values before escaping them
+    value: String,
+}
+
+impl Default for JsonFormatter {
+    fn default() -> Self {
+        JsonBuilder::default().build()
+    }
+}
+
+/// Structured fields, nested by the segments of dotted field names
+enum JsonNode {
+    Value(Value),
+    Object(Vec<(&'static str, JsonNode)>),
+}
+
+impl JsonNode {
+    fn insert(&mut self, mut path: std::str::Split<'static, char>, value: Value) {
+        let JsonNode::Object(entries) = self else {
+            // A value was logged under a prefix of this name, e.g. `a` before
+            // `a.b`, and is replaced by the nested object
+            *self = JsonNode::Object(Vec::new());
+            return self.insert(path, value);
+        };
+
+        let Some(key) = path.next() else {
+            return;
+        };
+        let idx = match entries.iter().position(|(k, _)| *k == key) {
+            Some(idx) => idx,
+            None => {
+                entries.push((key, JsonNode::Object(Vec::new())));
+                entries.len() - 1
+            }
+        };
+        let node = &mut entries[idx].1;
+        if path.clone().next().is_none() {
+            *node = JsonNode::Value(value);
+        } else {
+            node.insert(path, value);
+        }
+    }
+
+    fn write(&self, buf: &mut String) {
+        match self {
+            JsonNode::Value(value) => write_json_value(buf, value),
+            JsonNode::Object(entries) => {
+                buf.push('{');
+                for (i, (key, node)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        buf.push(',');
+                    }
+                    write_json_str(buf, key);
+                    buf.push(':');
+                    node.write(buf);
+                }
+                buf.push('}');
+            }
+        }
+    }
+}
+
+/// Writes `value` as JSON, with non-finite floats and bytes written as strings
+fn write_json_value(buf: &mut String, value: &Value) {
+    // Writing into a `String` cannot fail
+    match value {
+        Value::Int(x) => _ = write!(buf, "{}", x),
+        Value::UInt(x) => _ = write!(buf, "{}", x),
+        Value::Float(x) if x.is_finite() => _ = write!(buf, "{}", x),
+        Value::Bool(x) => _ = write!(buf, "{}", x),
+        Value::Str(s) => write_json_str(buf, s),
+        Value::Float(_) | Value::Bytes(_) => write_json_str(buf, &value.to_string()),
+        Value::List(values) => {
+            buf.push('[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    buf.push(',');
+                }
+                write_json_value(buf, value);
+            }
+            buf.push(']');
+        }
+        Value::Map(entries) => {
+            buf.push('{');
+            for (i, (k, v)) in entries.iter().enumerate() {
+                if i > 0 {
+                    buf.push(',');
+                }
+                match k {
+                    Value::Str(k) => write_json_str(buf, k),
+                    k => write_json_str(buf, &k.to_string()),
+                }
+                buf.push(':');
+                write_json_value(buf, v);
+            }
+            buf.push('}');
+        }
+    }
+}
+
+/// Writes `value` as a quoted JSON string
+fn write_json_str(buf: &mut String, value: &str) {
+    buf.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => buf.push_str("\\\""),
+            '\\' => buf.push_str("\\\\"),
+            '\n' => buf.push_str("\\n"),
+            '\r' => buf.push_str("\\r"),
+            '\t' => buf.push_str("\\t"),
+            '\u{8}' => buf.push_str("\\b"),
+            '\u{c}' => buf.push_str("\\f"),
+            c if c.is_control() => _ = write!(buf, "\\u{:04x}", c as u32),
+            c => buf.push(c),
+        }
+    }
+    buf.push('"');
+}
+
+impl JsonFormatter {
+    /// Writes `,"key":"value"`, escaping the formatted value
+    fn write_str_entry(&mut self, buf: &mut String, key: &str, value: &dyn Display) {
+        self.value.clear();
+        _ = write!(self.value, "{}", value);
+        buf.push(',');
+        write_json_str(buf, key);
+        buf.push(':');
+        write_json_str(buf, &self.value);
+    }
+}
+
+impl PatternFormatter for JsonFormatter {
+    fn custom_format(&mut self, time: DateTime<Utc>, object: LogRecord) -> String {
+        let mut buf = String::new();
+        self.format_into(time, object, &mut buf);
+        buf
+    }
+
+    fn format_into(&mut self, time: DateTime<Utc>, object: LogRecord, buf: &mut String) {
+        // Writing into a `String` cannot fail
+        buf.push_str("{\"timestamp\":");
+        self.value.clear();
+        _ = self.time_format.write_time(&time, &mut self.value);
+        match self.time_format {
+            TimestampFormat::EpochSeconds
+            | TimestampFormat::EpochMillis
+            | TimestampFormat::EpochMicros
+            | TimestampFormat::EpochNanos => buf.push_str(&self.value),
+            _ => write_json_str(buf, &self.value),
+        }
+        self.write_str_entry(buf, "level", &object.level);
+        _ = write!(buf, ",\"seq\":{}", object.sequence);
+        if let Some(thread) = &object.thread {
+            _ = write!(buf, ",\"thread_id\":{}", thread.id());
+            if let Some(name) = thread.name() {
+                self.write_str_entry(buf, "thread_name", &name);
+            }
+        }
+        self.write_str_entry(buf, "target", &object.module_path);
+        self.write_str_entry(buf, "file", &object.file);
+        _ = write!(buf, ",\"line\":{}", object.line);
+        if let Some(info) = app_info().filter(|_| self.app_info) {
+            self.write_str_entry(buf, "hostname", &info.hostname());
+            _ = write!(buf, ",\"pid\":{}", info.pid());
+            self.write_str_entry(buf, "executable", &info.executable());
+            if let Some(version) = info.version() {
+                self.write_str_entry(buf, "version", &version);
+            }
+        }
+        self.write_str_entry(buf, "message", &object.log_line.message());
+
+        let mut fields = JsonNode::Object(Vec::new());
+        let line = context::with_fields(&*object.log_line, object.context.as_ref());
+        _ = line.visit_values(&mut |name, value| {
+            fields.insert(name.split('.'), value);
+            Ok(())
+        });
+        if matches!(&fields, JsonNode::Object(entries) if !entries.is_empty()) {
+            buf.push_str(",\"fields\":");
+            fields.write(buf);
+        }
+
+        if let Some(span) = &object.span {
+            self.write_str_entry(buf, "span", span);
+        }
+        buf.push_str("}\n");
+    }
+}
+
+/// Returns a default [`QuickLogFormatter`] to be customized and passed into
+/// [`with_formatter!`](crate::with_formatter)
+pub fn formatter() -> QuickLogFormatter {
+    QuickLogFormatter::default()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::{QuickLogFormatter, TimestampFormat};
+    use crate::{
+        level::Level,
+        line::{LazyLine, LinePart},
+        serialize::Value,
+        LogRecord, PatternFormatter,
+    };
+
+    fn record(span: Option<crate::span::SpanContext>) -> LogRecord {
+        LogRecord {
+            level: Level::Info,
+            module_path: module_path!(),
+            file: file!(),
+            line: line!(),
+            span,
+            sequence: 0,
+            thread: None,
+            context: None,
+            log_line: Box::new("hello"),
+        }
+    }
+
+    #[test]
+    fn format_into_matches_custom_format() {
+        let time = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let mut formatter = QuickLogFormatter::new();
+        let span = crate::span::Span::new("order", "id=1".to_string());
+        let _entered = span.enter();
+
+        let mut buf = String::new();
+        for span in [None, crate::span::current()] {
+            buf.clear();
+            formatter.format_into(time, record(span.clone()), &mut buf);
+            assert_eq!(buf, formatter.custom_format(time, record(span)));
+        }
+    }
+
+    #[test]
+    fn app_info_prefix() {
+        crate::app::set_app_info(crate::app::AppInfo::capture(None));
+        let info = crate::app_info().unwrap();
+
+        let time = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let mut formatter = QuickLogFormatter::new()
+            .with_time_format(TimestampFormat::EpochSeconds)
+            .with_hostname(true)
+            .with_pid(true);
+        assert_eq!(
+            formatter.custom_format(time, record(None)),
+            format!("[1700000000][{}][{}]hello\n", info.hostname(), info.pid())
+        );
+    }
+
+    #[test]
+    fn ansi_colors() {
+        let time = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let mut formatter = QuickLogFormatter::new()
+            .with_time_format(TimestampFormat::EpochSeconds)
+            .with_ansi(true);
+        let mut error = record(None);
+        error.level = Level::Error;
+        assert_eq!(
+            formatter.custom_format(time, error),
+            "\x1b[31m[1700000000]hello\x1b[0m\n"
+        );
+        assert_eq!(
+            formatter.custom_format(time, record(None)),
+            "[1700000000]hello\n"
+        );
+    }
+
+    #[test]
+    fn ansi_theme() {
+        use super::Theme;
+
+        let time = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let mut formatter = QuickLogFormatter::new()
+            .with_time_format(TimestampFormat::EpochSeconds)
+            .with_ansi(true)
+            .with_theme(Theme {
+                info: Some("\x1b[32m"),
+                prefix: Some("\x1b[2m"),
+                ..Theme::default()
+            });
+        assert_eq!(
+            formatter.custom_format(time, record(None)),
+            "\x1b[2m[1700000000]\x1b[0m\x1b[32mhello\x1b[0m\n"
+        );
+        let mut warn = record(None);
+        warn.level = Level::Warn;
+        assert_eq!(
+            formatter.custom_format(time, warn),
+            "\x1b[2m[1700000000]\x1b[0m\x1b[33mhello\x1b[0m\n"
+        );
+        let mut audit = record(None);
+        audit.level = Level::Custom(&crate::level::CustomLevel {
+            name: "AUDIT",
+            priority: 5,
+            color: Some("\x1b[35m"),
+        });
+        assert_eq!(
+            formatter.custom_format(time, audit),
+            "\x1b[2m[1700000000]\x1b[0m\x1b[35mhello\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn pretty_serialize() {
+        use crate::serialize::Serialize;
+
+        let time = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let buf = Box::leak(Box::new([0; 64]));
+        let map: std::collections::BTreeMap<&str, u32> = [("a", 1), ("b", 2)].into_iter().collect();
+        let store = &*Box::leak(Box::new(map.encode(buf).0));
+        let formatted = |pretty| {
+            let mut formatter = QuickLogFormatter::new()
+                .with_time_format(TimestampFormat::EpochSeconds)
+                .with_pretty(pretty);
+            let mut record = record(None);
+            record.log_line = Box::new(LazyLine::new(
+                true,
+                move |part: LinePart<'_, '_>| match part {
+                    LinePart::Message(f) => write!(f, "values {}", store),
+                    LinePart::Fields(_) | LinePart::Values(_) => Ok(()),
+                },
+            ));
+            formatter.custom_format(time, record)
+        };
+
+        assert_eq!(formatted(false), "[1700000000]values {a: 1, b: 2}\n");
+        assert_eq!(
+            formatted(true),
+            "[1700000000]values {\n    a: 1,\n    b: 2,\n}\n"
+        );
+    }
+
+    #[test]
+    fn float_precision() {
+        use crate::serialize::Serialize;
+
+        let time = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let buf = Box::leak(Box::new([0; 16]));
+        let store = &*Box::leak(Box::new(101.25f64.encode(buf).0));
+        let mut formatter = QuickLogFormatter::new()
+            .with_time_format(TimestampFormat::EpochSeconds)
+            .with_float_precision(4);
+        let mut record = record(None);
+        record.log_line = Box::new(LazyLine::new(
+            true,
+            move |part: LinePart<'_, '_>| match part {
+                LinePart::Message(f) => write!(f, "price {}", store),
+                LinePart::Fields(_) | LinePart::Values(_) => Ok(()),
+            },
+        ));
+
+        assert_eq!(
+            formatter.custom_format(time, record),
+            "[1700000000]price 101.2500\n"
+        );
+    }
+
+    #[test]
+    fn fields_position() {
+        use super::FieldsPosition;
+
+        let time = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let formatted = |formatter: QuickLogFormatter, message: &'static str| {
+            let mut formatter = formatter.with_time_format(TimestampFormat::EpochSeconds);
+            let mut record = record(None);
+            record.log_line = Box::new(LazyLine::new(
+                true,
+                move |part: LinePart<'_, '_>| match part {
+                    LinePart::Message(f) => f.write_str(message),
+                    LinePart::Fields(visit) => {
+                        visit("id", &1)?;
+                        visit("side", &"buy")
+                    }
+                    LinePart::Values(_) => Ok(()),
+                },
+            ));
+            formatter.custom_format(time, record)
+        };
+
+        assert_eq!(
+            formatted(QuickLogFormatter::new(), "filled"),
+            "[1700000000]filled id=1 side=buy\n"
+        );
+        assert_eq!(
+            formatted(QuickLogFormatter::new().with_kv_separator(":"), "filled"),
+            "[1700000000]filled id:1 side:buy\n"
+        );
+        let prefix = QuickLogFormatter::new().with_fields_position(FieldsPosition::Prefix);
+        assert_eq!(
+            formatted(prefix.clone(), "filled"),
+            "[1700000000]id=1 side=buy filled\n"
+        );
+        assert_eq!(formatted(prefix, ""), "[1700000000]id=1 side=buy\n");
+        assert_eq!(
+            formatted(
+                QuickLogFormatter::new().with_fields_position(FieldsPosition::Omit),
+                "filled"
+            ),
+            "[1700000000]filled\n"
+        );
+    }
+
+    #[test]
+    fn field_delimiters_and_quoting() {
+        let time = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let formatted = |formatter: QuickLogFormatter| {
+            let mut formatter = formatter.with_time_format(TimestampFormat::EpochSeconds);
+            let mut record = record(None);
+            record.log_line = Box::new(LazyLine::new(true, |part: LinePart<'_, '_>| match part {
+                LinePart::Message(f) => f.write_str("rejected"),
+                LinePart::Fields(visit) => {
+                    visit("id", &1)?;
+                    visit("reason", &"no liquidity")?;
+                    visit("venue", &"")
+                }
+                LinePart::Values(_) => Ok(()),
+            }));
+            formatter.custom_format(time, record)
+        };
+
+        assert_eq!(
+            formatted(QuickLogFormatter::new().with_quoted_values(true)),
+            "[1700000000]rejected id=1 reason=\"no liquidity\" venue=\"\"\n"
+        );
+        assert_eq!(
+            formatted(
+                QuickLogFormatter::new()
+                    .with_message_delimiter(" | ")
+                    .with_field_delimiter(", ")
+            ),
+            "[1700000000]rejected | id=1, reason=no liquidity, venue=\n"
+        );
+    }
+
+    #[test]
+    fn logfmt_quoting() {
+        let time = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let mut formatter = QuickLogFormatter::new()
+            .logfmt()
+            .with_time_format(TimestampFormat::EpochSeconds)
+            .build();
+        let mut record = record(None);
+        record.level = Level::Error;
+        record.log_line = Box::new(LazyLine::new(true, |part: LinePart<'_, '_>| match part {
+            LinePart::Message(f) => f.write_str("multi\nline\\"),
+            LinePart::Fields(visit) => {
+                visit("plain", &1.5)?;
+                visit("eq", &"a=b")?;
+                visit("ctrl", &"\u{1}")
+            }
+            LinePart::Values(_) => Ok(()),
+        }));
+
+        assert_eq!(
+            formatter.custom_format(time, record),
+            "ts=1700000000 level=ERR seq=0 msg=\"multi\\nline\\\\\" plain=1.5 eq=\"a=b\" ctrl=\"\\u{0001}\"\n"
+        );
+    }
+
+    #[test]
+    fn json_escaping_and_nesting() {
+        let time = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let mut formatter = QuickLogFormatter::new()
+            .json()
+            .with_time_format(TimestampFormat::EpochMillis)
+            .with_app_info(false)
+            .build();
+        let mut object = record(None);
+        object.file = "src/main.rs";
+        object.line = 7;
+        object.module_path = "app";
+        object.log_line = Box::new(LazyLine::new(true, |part: LinePart<'_, '_>| match part {
+            LinePart::Message(f) => f.write_str("say \"hi\"\n\\"),
+            LinePart::Fields(_) => Ok(()),
+            LinePart::Values(visit) => {
+                visit("order.price", Value::Float(1.5))?;
+                visit("id", Value::Int(1))?;
+                visit("order.side", Value::Str("buy".to_string()))?;
+                visit("id", Value::UInt(2))?;
+                visit("ctrl", Value::Str("\u{1}".to_string()))?;
+                visit("ok", Value::Bool(true))?;
+                visit("nan", Value::Float(f64::NAN))?;
+                visit(
+                    "list",
+                    Value::List(vec![
+                        Value::Int(-1),
+                        Value::Map(vec![(Value::UInt(1), Value::Bool(false))]),
+                    ]),
+                )
+            }
+        }));
+
+        assert_eq!(
+            formatter.custom_format(time, object),
+            concat!(
+                r#"{"timestamp":1700000000000,"level":"INFO","seq":0,"target":"app","file":"src/main.rs","line":7,"#,
+                r#""message":"say \"hi\"\n\\","fields":{"order":{"price":1.5,"side":"buy"},"id":2,"ctrl":"\u0001","ok":true,"nan":"NaN","list":[-1,{"1":false}]}}"#,
+                "\n"
+            )
+        );
+
+        let mut formatter = QuickLogFormatter::new().json().build();
+        let line = formatter.custom_format(time, record(None));
+        assert!(line.starts_with(r#"{"timestamp":"2023-11-14T22:13:20Z","#));
+        assert!(line.ends_with("\"message\":\"hello\"}\n"));
+    }
+
+    #[test]
+    fn timestamp_formats() {
+        let time = Utc.timestamp_opt(1_700_000_000, 1_500).unwrap();
+        let cases = [
+            (TimestampFormat::Rfc3339, "2023-11-14T22:13:20.000001500Z"),
+            (
+                TimestampFormat::Rfc3339Nanos,
+                "2023-11-14T22:13:20.000001500Z",
+            ),
+            (TimestampFormat::EpochSeconds, "1700000000"),
+            (TimestampFormat::EpochMillis, "1700000000000"),
+            (TimestampFormat::EpochMicros, "1700000000000001"),
+            (TimestampFormat::EpochNanos, "1700000000000001500"),
+            (
+                TimestampFormat::Custom("%H:%M:%S%.6f".to_string()),
+                "22:13:20.000001",
+            ),
+        ];
+        for (time_format, expected) in cases {
+            let mut formatter = QuickLogFormatter::new().with_time_format(time_format);
+            assert_eq!(
+                formatter.custom_format(time, record(None)),
+                format!("[{}]hello\n", expected)
+            );
+        }
+
+        // Matches the `Debug` representation previously used by default
+        let time = Utc.timestamp_opt(1_700_000_000, 123_000_000).unwrap();
+        let mut buf = String::new();
+        TimestampFormat::Rfc3339
+            .write_time(&time, &mut buf)
+            .unwrap();
+        assert_eq!(buf, format!("{:?}", time));
+        buf.clear();
+        TimestampFormat::Rfc3339Nanos
+            .write_time(&time, &mut buf)
+            .unwrap();
+        assert_eq!(buf, "2023-11-14T22:13:20.123000000Z");
+    }
+}
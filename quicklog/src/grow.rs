@@ -0,0 +1,219 @@
+//! Growth of the logging queue past its capacity, enabled through
+//! [`Config::grow_to`](crate::Config::grow_to).
+//!
+//! The logging queue is a ring buffer whose capacity is fixed at build time
+//! through `QUICKLOG_MAX_LOGGER_CAPACITY`. With a growth policy, records
+//! logged while it is full spill over into a second queue allocated on the
+//! heap, instead of being handled by the
+//! [`OverflowPolicy`](crate::OverflowPolicy), until the total capacity set
+//! through `grow_to` is reached:
+//!
+//! ```
+//! use quicklog::{config, init};
+//!
+//! // absorb bursts of up to 4 million records before dropping any
+//! init!(config().grow_to(4_000_000));
+//! ```
+//!
+//! The total capacity doubles each time the spilled records need more room,
+//! up to the maximum, and is kept afterwards so that later bursts do not
+//! allocate again. Each resize is counted in
+//! [`QueueStats::resizes`](crate::QueueStats::resizes).
+//!
+//! Once records have spilled over, every record logged afterwards spills
+//! over too until the flusher has drained them, so that records are still
+//! flushed in the order they were logged. Spilling over locks a mutex, so
+//! the queue should be sized for the usual load, with growth only absorbing
+//! bursts.
+
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex, MutexGuard,
+    },
+};
+
+use quanta::Instant;
+
+use crate::TimedLogRecord;
+
+/// Records logged past the capacity of the logging queue, shared by its
+/// producer and consumer
+#[derive(Default)]
+pub(crate) struct SpillQueue {
+    records: Mutex<VecDeque<TimedLogRecord>>,
+    /// Number of spilled records, read without locking so that the logging
+    /// and flushing hot paths only lock once records have spilled over
+    len: AtomicUsize,
+    /// Number of records which can be spilled without resizing
+    capacity: AtomicUsize,
+    resizes: AtomicUsize,
+}
+
+impl SpillQueue {
+    pub(crate) fn len(&self) -> usize {
+        self.len.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.capacity.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn resizes(&self) -> usize {
+        self.resizes.load(Ordering::Relaxed)
+    }
+
+    /// Spills `record` over a full logging queue holding `queue_capacity`
+    /// records, doubling the total capacity if needed, up to `max_capacity`.
+    /// Hands the record back if the total capacity is already at its maximum.
+    pub(crate) fn push(
+        &self,
+        record: TimedLogRecord,
+        queue_capacity: usize,
+        max_capacity: usize,
+    ) -> Result<(), TimedLogRecord> {
+        let max_spilled = max_capacity.saturating_sub(queue_capacity);
+        let mut records = self.lock();
+        let spilled = records.len();
+        if spilled >= max_spilled {
+            return Err(record);
+        }
+
+        let capacity = self.capacity();
+        if spilled == capacity {
+            let total = (queue_capacity + capacity).saturating_mul(2);
+            let capacity = total.min(max_capacity) - queue_capacity;
+            records.reserve_exact(capacity - spilled);
+            self.capacity.store(capacity, Ordering::Relaxed);
+            self.resizes.fetch_add(1, Ordering::Relaxed);
+        }
+
+        records.push_back(record);
+        self.len.store(records.len(), Ordering::Release);
+
+        Ok(())
+    }
+
+    /// Allocates room for `capacity` spilled records upfront, without
+    /// counting it as a resize
+    pub(crate) fn reserve(&self, capacity: usize) {
+        let mut records = self.lock();
+        if capacity > self.capacity() {
+            let spilled = records.len();
+            records.reserve_exact(capacity - spilled);
+            self.capacity.store(capacity, Ordering::Relaxed);
+        }
+    }
+
+    /// Instant the oldest spilled record was logged at, if any
+    pub(crate) fn peek_time(&self) -> Option<Instant> {
+        if self.is_empty() {
+            return None;
+        }
+
+        self.lock().front().map(|(time_logged, _)| *time_logged)
+    }
+
+    /// Takes the oldest spilled record, if any
+    pub(crate) fn pop(&self) -> Option<TimedLogRecord> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let mut records = self.lock();
+        let record = records.pop_front();
+        self.len.store(records.len(), Ordering::Release);
+
+        record
+    }
+
+    fn lock(&self) -> MutexGuard<'_, VecDeque<TimedLogRecord>> {
+        self.records.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quanta::Instant;
+
+    use super::SpillQueue;
+    use crate::{level::Level, LogRecord, TimedLogRecord};
+
+    fn record(message: &'static str) -> TimedLogRecord {
+        let record = LogRecord {
+            level: Level::Info,
+            module_path: module_path!(),
+            file: file!(),
+            line: line!(),
+            span: None,
+            sequence: 0,
+            thread: None,
+            context: None,
+            log_line: Box::new(message),
+        };
+        (Instant::now(), record)
+    }
+
+    fn message((_, record): TimedLogRecord) -> String {
+        record.log_line.to_string()
+    }
+
+    #[test]
+    fn spill_doubles_up_to_max_capacity() {
+        let spill = SpillQueue::default();
+        assert!(spill.is_empty());
+        assert!(spill.pop().is_none());
+
+        // queue of 4 records, growing to at most 10
+        spill.push(record("a"), 4, 10).ok().unwrap();
+        assert_eq!((spill.capacity(), spill.resizes()), (4, 1));
+        for message in ["b", "c", "d", "e"] {
+            spill.push(record(message), 4, 10).ok().unwrap();
+        }
+        assert_eq!((spill.capacity(), spill.resizes()), (6, 2));
+        spill.push(record("f"), 4, 10).ok().unwrap();
+        assert!(spill.push(record("g"), 4, 10).is_err());
+        assert_eq!(spill.len(), 6);
+
+        assert_eq!(message(spill.pop().unwrap()), "a");
+        assert_eq!(message(spill.pop().unwrap()), "b");
+        spill.push(record("g"), 4, 10).ok().unwrap();
+        assert_eq!((spill.capacity(), spill.resizes()), (6, 2));
+
+        let drained: Vec<_> = std::iter::from_fn(|| spill.pop()).map(message).collect();
+        assert_eq!(drained, ["c", "d", "e", "f", "g"]);
+        assert!(spill.is_empty());
+    }
+
+    #[test]
+    fn spill_into_reserved_capacity() {
+        let spill = SpillQueue::default();
+        spill.reserve(3);
+        assert_eq!((spill.capacity(), spill.resizes()), (3, 0));
+
+        // queue of 4 records, growing to at most 10
+        for message in ["a", "b", "c"] {
+            spill.push(record(message), 4, 10).ok().unwrap();
+        }
+        assert_eq!((spill.capacity(), spill.resizes()), (3, 0));
+        spill.push(record("d"), 4, 10).ok().unwrap();
+        assert_eq!((spill.capacity(), spill.resizes()), (6, 1));
+
+        // reserving less than the current capacity does nothing
+        spill.reserve(2);
+        assert_eq!(spill.capacity(), 6);
+    }
+
+    #[test]
+    fn spill_without_room_to_grow() {
+        let spill = SpillQueue::default();
+        assert!(spill.push(record("a"), 4, 4).is_err());
+        assert!(spill.push(record("a"), 4, 0).is_err());
+        assert_eq!((spill.len(), spill.capacity(), spill.resizes()), (0, 0, 0));
+    }
+}
@@ -0,0 +1,142 @@
+//! Interning of strings which are logged repeatedly, such as instrument names.
+//!
+//! [`intern()`](crate::intern()) assigns each distinct string a 4-byte id
+//! once, after which the returned [`InternedStr`](crate::InternedStr) is
+//! logged with `^` by copying only its id into the serialization buffer. The
+//! id is resolved back into the string through a table shared with the
+//! flushing side:
+//!
+//! ```
+//! use quicklog::{info, init, intern};
+//!
+//! # init!();
+//! let symbol = intern("AAPL");
+//! info!("order for {}", ^symbol);
+//! ```
+//!
+//! Interned strings are never freed, so only strings from a bounded set
+//! should be interned. The table can be retrieved through
+//! [`interned_strings`](crate::intern::interned_strings) to decode ids
+//! recorded elsewhere.
+
+use std::{collections::HashMap, fmt::Display, sync::Mutex};
+
+use crate::serialize::{Serialize, Store, Value};
+
+static TABLE: Mutex<Table> = Mutex::new(Table {
+    strings: Vec::new(),
+    ids: None,
+});
+
+struct Table {
+    /// Interned strings, indexed by id
+    strings: Vec<&'static str>,
+    ids: Option<HashMap<&'static str, u32>>,
+}
+
+/// Handle to a string interned through [`intern`], which is serialized as
+/// its 4-byte id
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct InternedStr(u32);
+
+impl InternedStr {
+    /// Id of the string, its index in [`interned_strings`]
+    pub fn id(&self) -> u32 {
+        self.0
+    }
+
+    /// Returns the interned string
+    pub fn as_str(&self) -> &'static str {
+        resolve(self.0).expect("interned strings are never removed")
+    }
+}
+
+impl Display for InternedStr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+/// Interns `s`, returning the same [`InternedStr`] for equal strings.
+///
+/// Interning a string for the first time allocates and takes a lock, so this
+/// should be done ahead of the hot path.
+pub fn intern(s: &str) -> InternedStr {
+    let mut table = TABLE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(&id) = table.ids.as_ref().and_then(|ids| ids.get(s)) {
+        return InternedStr(id);
+    }
+
+    let id = u32::try_from(table.strings.len()).expect("too many interned strings");
+    let s: &'static str = Box::leak(s.into());
+    table.strings.push(s);
+    table.ids.get_or_insert_with(HashMap::new).insert(s, id);
+
+    InternedStr(id)
+}
+
+/// Returns all strings interned so far, indexed by their id
+pub fn interned_strings() -> Vec<&'static str> {
+    TABLE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .strings
+        .clone()
+}
+
+fn resolve(id: u32) -> Option<&'static str> {
+    let table = TABLE.lock().unwrap_or_else(|e| e.into_inner());
+    table.strings.get(id as usize).copied()
+}
+
+/// Encoded as its 4-byte id, and formatted as the interned string
+impl Serialize for InternedStr {
+    fn encode<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+        let (chunk, rest) = write_buf.split_at_mut(4);
+        chunk.copy_from_slice(&self.0.to_le_bytes());
+
+        (
+            Store::new(Self::decode, chunk).with_decode_value(Self::decode_value),
+            rest,
+        )
+    }
+
+    fn decode(read_buf: &[u8]) -> (String, &[u8]) {
+        let (chunk, rest) = read_buf.split_at(4);
+        let id = u32::from_le_bytes(chunk.try_into().unwrap());
+
+        (resolve(id).unwrap_or_default().to_string(), rest)
+    }
+
+    fn decode_value(read_buf: &[u8]) -> (Value, &[u8]) {
+        let (s, rest) = Self::decode(read_buf);
+
+        (Value::Str(s), rest)
+    }
+
+    fn buffer_size_required(&self) -> usize {
+        4
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{intern, interned_strings};
+    use crate::serialize::Serialize;
+
+    #[test]
+    fn intern_and_serialize() {
+        let aapl = intern("AAPL");
+        let msft = intern("MSFT");
+        assert_eq!(intern(&String::from("AAPL")), aapl);
+        assert_ne!(aapl, msft);
+        assert_eq!(msft.as_str(), "MSFT");
+        assert_eq!(interned_strings()[aapl.id() as usize], "AAPL");
+
+        let mut buf = [0; 8];
+        let (store, rest) = aapl.encode(&mut buf);
+        assert_eq!(rest.len(), 4);
+        assert_eq!(store.to_string(), "AAPL");
+        assert_eq!(format!("{:>6}", aapl), "  AAPL");
+    }
+}
@@ -10,6 +10,16 @@
 //! * [`Warn`]
 //! * [`Error`]
 //!
+//! Built-in levels can be parsed from their names, e.g. `"warn".parse()`, and
+//! enumerated through [`Level::iter`](crate::level::Level::iter).
+//!
+//! Additional levels, e.g. for audit trails or metrics, can be defined
+//! through [`define_level!`](crate::define_level) as a
+//! [`CustomLevel`](crate::level::CustomLevel) with its own priority.
+//! [`FATAL`](crate::level::FATAL) and [`EVENT`](crate::level::EVENT) are
+//! predefined in this way for [`fatal!`](crate::fatal) and
+//! [`event!`](crate::event).
+//!
 //! ## LevelFilters
 //!
 //! Similarly, [`LevelFilter`] correspond to [`Level'], with the addition
@@ -17,7 +27,8 @@
 //!
 //! Logging levels will be skipped if a level is lower than current [`LevelFilter`],
 //! i.e. `Level::Debug` is skipped when LevelFilter is set to `Info`, but `Level::Info`
-//! logs will not be skipped.
+//! logs will not be skipped. [`enabled!`](crate::enabled) tells whether a level
+//! would be logged from the calling module.
 //!
 //! [`Trace`]: crate::level::Level::Trace
 //! [`Debug`]: crate::level::Level::Debug
@@ -27,31 +38,144 @@
 //! [`Level`]: crate::level::Level
 //! [`LevelFilter`]: crate::level::LevelFilter
 
-#[repr(usize)]
-#[derive(Clone, Copy, Eq, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub enum Level {
     /// Designates trace information, which is of very low priority
-    Trace = 0,
+    Trace,
     /// Designates debug information, which is of low priority
-    Debug = 1,
+    Debug,
     /// Designates useful information
-    Info = 2,
+    Info,
     /// Designates potentially hazardous situations
-    Warn = 3,
+    Warn,
     /// Designates serious errors
-    Error = 4,
+    Error,
+    /// Level defined through [`define_level!`](crate::define_level)
+    Custom(&'static CustomLevel),
 }
 
-impl std::fmt::Display for Level {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let level = match self {
+impl Level {
+    /// Priority compared against [`LevelFilter`]s, from 0 for
+    /// [`Level::Trace`] to 4 for [`Level::Error`]
+    pub const fn priority(&self) -> usize {
+        match self {
+            Self::Trace => 0,
+            Self::Debug => 1,
+            Self::Info => 2,
+            Self::Warn => 3,
+            Self::Error => 4,
+            Self::Custom(level) => level.priority,
+        }
+    }
+
+    /// Same as [`Level::priority`], which is the index of built-in levels in
+    /// [`Level::iter`] and can be converted back through `Level::try_from`
+    pub const fn as_usize(&self) -> usize {
+        self.priority()
+    }
+
+    /// Name of the level as written by `Display`, e.g. `"WARN"`, or the name
+    /// of a custom level
+    pub const fn name(&self) -> &'static str {
+        match self {
             Self::Trace => "TRACE",
             Self::Debug => "DEBUG",
             Self::Info => "INFO",
             Self::Warn => "WARN",
             Self::Error => "ERROR",
-        };
-        write!(f, "{}", level)
+            Self::Custom(level) => level.name,
+        }
+    }
+
+    /// Iterates over the built-in levels, from [`Level::Trace`] to
+    /// [`Level::Error`]
+    pub fn iter() -> impl Iterator<Item = Level> {
+        [
+            Self::Trace,
+            Self::Debug,
+            Self::Info,
+            Self::Warn,
+            Self::Error,
+        ]
+        .into_iter()
+    }
+}
+
+/// Parses the names written by `Display`, as well as their three-letter
+/// abbreviations, ignoring case, e.g. `warn` or `WRN`. Custom levels cannot
+/// be parsed
+impl std::str::FromStr for Level {
+    type Err = LogLevelParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "TRC" | "TRACE" => Ok(Self::Trace),
+            "DBG" | "DEBUG" => Ok(Self::Debug),
+            "INF" | "INFO" => Ok(Self::Info),
+            "WRN" | "WARN" => Ok(Self::Warn),
+            "ERR" | "ERROR" => Ok(Self::Error),
+            _ => Err(LogLevelParseError()),
+        }
+    }
+}
+
+/// Converts a [`Level::as_usize`] back into a built-in level
+impl TryFrom<usize> for Level {
+    type Error = LogLevelParseError;
+
+    fn try_from(value: usize) -> Result<Self, LogLevelParseError> {
+        Self::iter().nth(value).ok_or(LogLevelParseError())
+    }
+}
+
+/// Levels are ordered by [`Level::priority`], and custom levels with the same
+/// priority as another level are not comparable
+impl PartialOrd for Level {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        match self.priority().cmp(&other.priority()) {
+            std::cmp::Ordering::Equal if self != other => None,
+            ordering => Some(ordering),
+        }
+    }
+}
+
+/// Level of [`fatal!`](crate::fatal), above [`Level::Error`], so that it is
+/// only disabled by [`LevelFilter::Off`]. Written as `FATAL`, in bold red
+/// when colored.
+pub const FATAL: Level = Level::Custom(&CustomLevel {
+    name: "FATAL",
+    priority: 5,
+    color: Some("\x1b[1;31m"),
+});
+
+/// Level of [`event!`](crate::event), for structured records intended for
+/// machine processing, which is enabled by [`LevelFilter::Event`] and every
+/// filter below it. Written as `EVENT`.
+pub const EVENT: Level = Level::Custom(&CustomLevel {
+    name: "EVENT",
+    priority: 5,
+    color: None,
+});
+
+/// Level defined in addition to the built-in [`Level`]s, usually through
+/// [`define_level!`](crate::define_level)
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CustomLevel {
+    /// Name written by formatters, e.g. `AUDIT`
+    pub name: &'static str,
+    /// Priority compared against [`LevelFilter`]s, see [`Level::priority`].
+    ///
+    /// For instance, a priority of 5 is enabled by every filter up to
+    /// [`LevelFilter::Event`], and so is only disabled by [`LevelFilter::Off`].
+    pub priority: usize,
+    /// ANSI escape sequence for formatters which color their output, e.g.
+    /// `"\x1b[35m"` for magenta
+    pub color: Option<&'static str>,
+}
+
+impl std::fmt::Display for Level {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
     }
 }
 
@@ -115,22 +239,296 @@ impl std::str::FromStr for LevelFilter {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_uppercase().as_str() {
-            "TRC" => Ok(Self::Trace),
-            "DBG" => Ok(Self::Debug),
-            "INF" => Ok(Self::Info),
-            "WRN" => Ok(Self::Warn),
-            "ERR" => Ok(Self::Error),
+            "TRC" | "TRACE" => Ok(Self::Trace),
+            "DBG" | "DEBUG" => Ok(Self::Debug),
+            "INF" | "INFO" => Ok(Self::Info),
+            "WRN" | "WARN" => Ok(Self::Warn),
+            "ERR" | "ERROR" => Ok(Self::Error),
             "OFF" => Ok(Self::Off),
-            "EVT" => Ok(Self::Event),
+            "EVT" | "EVENT" => Ok(Self::Event),
             _ => Err(LogLevelParseError()),
         }
     }
 }
 
+/// Filter enabling `level` and every level above it. Custom levels with a
+/// priority above [`LevelFilter::Event`] convert to `Event`
+impl From<Level> for LevelFilter {
+    fn from(level: Level) -> Self {
+        match level.priority() {
+            0 => Self::Trace,
+            1 => Self::Debug,
+            2 => Self::Info,
+            3 => Self::Warn,
+            4 => Self::Error,
+            _ => Self::Event,
+        }
+    }
+}
+
+impl LevelFilter {
+    /// Returns `true` if log lines at `level` pass this filter
+    #[inline(always)]
+    pub fn enables(&self, level: Level) -> bool {
+        *self != Self::Off && level.priority() >= *self as usize
+    }
+}
+
+/// Defines a [`CustomLevel`] and a logging macro for it, e.g.
+///
+/// ```
+/// use quicklog::{define_level, init};
+///
+/// define_level!(pub AUDIT, audit, "AUDIT", 5);
+/// define_level!(METRIC, metric, "METRIC", 5, color = "\x1b[36m");
+///
+/// # fn main() {
+/// init!();
+/// audit!(user = "admin", "password changed");
+/// metric!(latency_us = 12, "order acked");
+/// # }
+/// ```
+///
+/// This declares a `const AUDIT: Level` with the given visibility, and an
+/// `audit!` macro which takes the same arguments as [`info!`](crate::info).
+/// As with any `macro_rules!` macro, `audit!` can be used after its
+/// definition, or from other crates when defined with `#[macro_export]`
+/// in front of `define_level!`.
+#[macro_export]
+macro_rules! define_level {
+    ($vis:vis $level:ident, $macro_name:ident, $name:literal, $priority:expr $(, color = $color:expr)? $(,)?) => {
+        $vis const $level: $crate::level::Level = $crate::level::Level::Custom(&$crate::level::CustomLevel {
+            name: $name,
+            priority: $priority,
+            color: $crate::define_level!(@color $($color)?),
+        });
+
+        $crate::define_level!(@macro ($) $macro_name, $name, $priority $(, $color)?);
+    };
+    (@macro ($d:tt) $macro_name:ident, $name:literal, $priority:expr $(, $color:expr)?) => {
+        macro_rules! $macro_name {
+            ($d($d args:tt)*) => {
+                $crate::log!(
+                    $crate::level::Level::Custom(&$crate::level::CustomLevel {
+                        name: $name,
+                        priority: $priority,
+                        color: $crate::define_level!(@color $($color)?),
+                    }),
+                    $d($d args)*
+                )
+            };
+        }
+    };
+    (@color $color:expr) => {
+        Some($color)
+    };
+    (@color) => {
+        None
+    };
+}
+
+/// Minimum [`Level::priority`] of log lines compiled in for `module_path`,
+/// according to the directives in [`MIN_LEVEL`](crate::constants::MIN_LEVEL).
+///
+/// Directives are separated by commas, and are either a level applying to
+/// all modules, or `module::path=level` applying to that module and its
+/// submodules, where the longest matching path wins. Levels are the same as
+/// for [`LevelFilter`], e.g. `info,my_app::hot_path=off` compiles out
+/// everything below `Info`, and every log line in `my_app::hot_path`.
+///
+/// The logging macros compare their level against this in a `const`, so log
+/// lines which are filtered out are removed by the compiler.
+pub const fn static_min_priority(module_path: &str) -> usize {
+    directive_min_priority(
+        crate::constants::MIN_LEVEL.as_bytes(),
+        module_path.as_bytes(),
+    )
+}
+
+/// Most verbose [`LevelFilter`] compiled in for any module, according to the
+/// directives in [`MIN_LEVEL`](crate::constants::MIN_LEVEL), e.g. `Debug` for
+/// `info,my_app::net=debug`.
+///
+/// Log lines below this are compiled out everywhere, so libraries wrapping
+/// quicklog can skip the work of preparing them:
+///
+/// ```
+/// use quicklog::{level::LevelFilter, STATIC_MAX_LEVEL};
+///
+/// if STATIC_MAX_LEVEL <= LevelFilter::Debug {
+///     // build expensive debug arguments
+/// }
+/// ```
+pub const STATIC_MAX_LEVEL: LevelFilter =
+    directive_max_filter(crate::constants::MIN_LEVEL.as_bytes());
+
+/// Most verbose filter out of the default and module `directives`
+const fn directive_max_filter(directives: &[u8]) -> LevelFilter {
+    // Everything is compiled in without a default directive
+    let mut default = 0;
+    let mut min_priority = usize::MAX;
+
+    let mut start = 0;
+    while start < directives.len() {
+        let mut end = start;
+        while end < directives.len() && directives[end] != b',' {
+            end += 1;
+        }
+        let mut eq = start;
+        while eq < end && directives[eq] != b'=' {
+            eq += 1;
+        }
+
+        if eq == end {
+            if let Some(priority) = parse_priority(directives, start, end) {
+                default = priority;
+            }
+        } else if let Some(priority) = parse_priority(directives, eq + 1, end) {
+            if priority < min_priority {
+                min_priority = priority;
+            }
+        }
+
+        start = end + 1;
+    }
+
+    if default < min_priority {
+        min_priority = default;
+    }
+    match min_priority {
+        0 => LevelFilter::Trace,
+        1 => LevelFilter::Debug,
+        2 => LevelFilter::Info,
+        3 => LevelFilter::Warn,
+        4 => LevelFilter::Error,
+        _ => LevelFilter::Off,
+    }
+}
+
+const fn directive_min_priority(directives: &[u8], module_path: &[u8]) -> usize {
+    let mut default = 0;
+    let mut module_priority = 0;
+    // Length of the longest module path matched so far
+    let mut matched_len = None;
+
+    let mut start = 0;
+    while start < directives.len() {
+        let mut end = start;
+        while end < directives.len() && directives[end] != b',' {
+            end += 1;
+        }
+        let mut eq = start;
+        while eq < end && directives[eq] != b'=' {
+            eq += 1;
+        }
+
+        if eq == end {
+            if let Some(priority) = parse_priority(directives, start, end) {
+                default = priority;
+            }
+        } else {
+            let (path_start, path_end) = trim(directives, start, eq);
+            let path_len = path_end - path_start;
+            let longer = match matched_len {
+                Some(len) => path_len > len,
+                None => true,
+            };
+            if longer && is_module_prefix(directives, path_start, path_end, module_path) {
+                if let Some(priority) = parse_priority(directives, eq + 1, end) {
+                    module_priority = priority;
+                    matched_len = Some(path_len);
+                }
+            }
+        }
+
+        start = end + 1;
+    }
+
+    match matched_len {
+        Some(_) => module_priority,
+        None => default,
+    }
+}
+
+/// Range of `bytes[start..end]` without surrounding whitespace
+const fn trim(bytes: &[u8], mut start: usize, mut end: usize) -> (usize, usize) {
+    while start < end && bytes[start].is_ascii_whitespace() {
+        start += 1;
+    }
+    while end > start && bytes[end - 1].is_ascii_whitespace() {
+        end -= 1;
+    }
+    (start, end)
+}
+
+/// Whether `module_path` is the module at `bytes[start..end]` or one of its
+/// submodules
+const fn is_module_prefix(bytes: &[u8], start: usize, end: usize, module_path: &[u8]) -> bool {
+    let len = end - start;
+    if module_path.len() < len {
+        return false;
+    }
+    let mut i = 0;
+    while i < len {
+        if bytes[start + i] != module_path[i] {
+            return false;
+        }
+        i += 1;
+    }
+    module_path.len() == len
+        || (module_path.len() > len + 1 && module_path[len] == b':' && module_path[len + 1] == b':')
+}
+
+/// Priority of the level named by `bytes[start..end]`, with `usize::MAX` for
+/// `off`
+const fn parse_priority(bytes: &[u8], start: usize, end: usize) -> Option<usize> {
+    const NAMES: [(&[u8], usize); 11] = [
+        (b"trace", 0),
+        (b"trc", 0),
+        (b"debug", 1),
+        (b"dbg", 1),
+        (b"info", 2),
+        (b"inf", 2),
+        (b"warn", 3),
+        (b"wrn", 3),
+        (b"error", 4),
+        (b"err", 4),
+        (b"off", usize::MAX),
+    ];
+
+    let (start, end) = trim(bytes, start, end);
+    let mut n = 0;
+    while n < NAMES.len() {
+        let (name, priority) = NAMES[n];
+        if name.len() == end - start {
+            let mut i = 0;
+            while i < name.len() && bytes[start + i].to_ascii_lowercase() == name[i] {
+                i += 1;
+            }
+            if i == name.len() {
+                return Some(priority);
+            }
+        }
+        n += 1;
+    }
+
+    None
+}
+
 static mut MAX_LOG_LEVEL_FILTER: LevelFilter = LevelFilter::Trace;
 
+/// Sets the [`LevelFilter`] checked at runtime.
+///
+/// In debug builds, a warning is printed to stderr if `level` enables levels
+/// below [`STATIC_MAX_LEVEL`], which are compiled out and so never logged.
 #[inline]
 pub fn set_max_level(level: LevelFilter) {
+    if cfg!(debug_assertions) && level < STATIC_MAX_LEVEL {
+        eprintln!(
+            "quicklog: max level set to {}, but levels below {} are compiled out through QUICKLOG_MIN_LEVEL",
+            level, STATIC_MAX_LEVEL
+        );
+    }
     unsafe {
         MAX_LOG_LEVEL_FILTER = level;
     }
@@ -176,14 +574,102 @@ mod tests {
         ];
         for (filter_idx, &filter) in filters.iter().enumerate() {
             for (level_idx, &level) in levels.iter().enumerate() {
-                let level_val = level as usize;
-                let filter_val = filter as usize;
-                if level_idx < filter_idx {
-                    assert!(level_val < filter_val);
-                } else {
-                    assert!(level_val >= filter_val);
-                }
+                assert_eq!(filter.enables(level), level_idx >= filter_idx);
             }
         }
     }
+
+    #[test]
+    fn min_level_directives() {
+        let cases = [
+            ("", "app", 0),
+            ("info", "app::net", 2),
+            ("info,app::hot=off", "app::hot", usize::MAX),
+            ("info,app::hot=off", "app::hot::inner", usize::MAX),
+            ("info,app::hot=off", "app::hotter", 2),
+            ("app::hot = off , WARN", "app", 3),
+            ("app=debug,app::net=err", "app::net::tcp", 4),
+            ("app::net=err,app=debug", "app::net::tcp", 4),
+            ("app=debug,app::net=err", "app::db", 1),
+            ("verbose,app=nope", "app", 0),
+        ];
+        for (directives, module_path, priority) in cases {
+            assert_eq!(
+                directive_min_priority(directives.as_bytes(), module_path.as_bytes()),
+                priority,
+                "{} in {}",
+                directives,
+                module_path
+            );
+        }
+    }
+
+    #[test]
+    fn max_filter_directives() {
+        let cases = [
+            ("", LevelFilter::Trace),
+            ("info", LevelFilter::Info),
+            ("app=warn", LevelFilter::Trace),
+            ("info,app::net=debug", LevelFilter::Debug),
+            ("warn,app::hot=off", LevelFilter::Warn),
+            ("off", LevelFilter::Off),
+            ("off,app=err", LevelFilter::Error),
+        ];
+        for (directives, filter) in cases {
+            assert_eq!(
+                directive_max_filter(directives.as_bytes()),
+                filter,
+                "{}",
+                directives
+            );
+        }
+        assert_eq!(STATIC_MAX_LEVEL, LevelFilter::Trace);
+    }
+
+    #[test]
+    fn compare_custom_levels() {
+        const AUDIT: Level = Level::Custom(&CustomLevel {
+            name: "AUDIT",
+            priority: 5,
+            color: None,
+        });
+        const NOTICE: Level = Level::Custom(&CustomLevel {
+            name: "NOTICE",
+            priority: 2,
+            color: None,
+        });
+
+        assert!(AUDIT > Level::Error);
+        assert!(NOTICE > Level::Debug && NOTICE < Level::Warn);
+        assert_eq!(NOTICE.partial_cmp(&Level::Info), None);
+        assert_eq!(AUDIT.to_string(), "AUDIT");
+
+        assert!(LevelFilter::Event.enables(AUDIT));
+        assert!(!LevelFilter::Off.enables(AUDIT));
+        assert!(!LevelFilter::Warn.enables(NOTICE));
+    }
+
+    #[test]
+    fn parse_and_iterate_levels() {
+        assert_eq!("warn".parse(), Ok(Level::Warn));
+        assert_eq!("ERR".parse(), Ok(Level::Error));
+        assert_eq!("off".parse::<Level>(), Err(LogLevelParseError()));
+
+        for (idx, level) in Level::iter().enumerate() {
+            assert_eq!(level.as_usize(), idx);
+            assert_eq!(Level::try_from(idx), Ok(level));
+            assert_eq!(level.to_string().parse(), Ok(level));
+            assert_eq!(LevelFilter::from(level) as usize, idx);
+            assert!(LevelFilter::from(level).enables(level));
+        }
+        assert_eq!(Level::try_from(5), Err(LogLevelParseError()));
+
+        const AUDIT: Level = Level::Custom(&CustomLevel {
+            name: "AUDIT",
+            priority: 7,
+            color: None,
+        });
+        assert_eq!(LevelFilter::from(AUDIT), LevelFilter::Event);
+        assert!(LevelFilter::from(AUDIT).enables(AUDIT));
+    }
 }
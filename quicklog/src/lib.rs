@@ -49,6 +49,18 @@
 //! }
 //! ```
 //!
+//! Instead of writing a flush loop, quicklog can also spawn and manage a flush
+//! thread itself through [`Config::background_flush`].
+//!
+//! `init!()` also captures the hostname, process id, executable name and
+//! application version once, which formatters can retrieve through
+//! [`app_info`] to attribute every log line to its source.
+//!
+//! Before exiting, [`Quicklog::shutdown`] can be called to flush any remaining
+//! log lines. Logging before `init!()` or after shutdown is silently dropped.
+//! [`shutdown()`] additionally resets the logger, so that it can be
+//! initialized again with a new configuration.
+//!
 //! # Macros
 //!
 //! #### Shorthand Macros
@@ -61,6 +73,39 @@
 //! * [`warn!`]
 //! * [`error!`]
 //!
+//! [`log!`] takes the level as its first argument instead, which is evaluated
+//! at runtime, e.g. `log!(level, "hello world")`.
+//!
+//! Each level also has a throttled variant, e.g. [`info_throttle!`], which
+//! records at most one log line per interval from its callsite, to avoid
+//! flooding the queue from hot loops. Similarly, sampled variants such as
+//! [`info_sampled!`] only record one in every `rate` log lines from their
+//! callsite, e.g. `info_sampled!(rate = 100, "tick {}", price)`.
+//!
+//! Variants such as [`info_to!`] take a logger created through
+//! [`Quicklog::new`] as their first argument, and record into it instead of
+//! the global logger.
+//!
+//! The logging macros ignore log lines which could not be queued. Fallible
+//! variants such as [`try_info!`] return a [`QueueError`] instead, so that
+//! callers can react, e.g. by flushing in place when the queue is full:
+//!
+//! ```
+//! # use quicklog::{flush_all, init, try_info, QueueError};
+//! # init!();
+//! if let Err(QueueError::Full) = try_info!("hello world") {
+//!     flush_all!();
+//! }
+//! ```
+//!
+//! [`counter!`] and [`gauge!`] record a named metric through the same queue,
+//! e.g. `counter!("orders_sent", 1)`, see [`metric`](crate::metric).
+//! [`info_builder!`] and the other `*_builder!` macros build a single record
+//! across several statements, see [`builder`](crate::builder), and
+//! [`log_dynamic`] logs a message which is only known at runtime.
+//! [`info_raw!`] and the other `*_raw!` macros log an already formatted
+//! string as is, see [`log_raw`].
+//!
 //! ## Setup Macros
 //!
 //! Quicklog allows a user specified [`Clock`] or [`Flush`] to be implemented by
@@ -71,6 +116,14 @@
 //! * [`with_flush!`]: Specify the Flusher Quicklog uses
 //! * [`with_flush_into_file`]: Specify path to flush log lines into
 //!
+//! [`init_split!`] initializes Quicklog like [`init!`], but returns a
+//! [`FlushHandle`] which flushes from whichever thread owns it, see
+//! [`split`](crate::split).
+//!
+//! [`reader::records`] reads the queued records along with the time they were
+//! logged at, without formatting or flushing them, e.g. to ship them to
+//! another process from a custom flush loop, see [`reader`](crate::reader).
+//!
 //! ## Macro prefix for partial serialization
 //!
 //! To speed things up, if you are logging a large struct, there could be some small things
@@ -100,6 +153,18 @@
 //! }
 //! ```
 //!
+//! The `^` prefix can also be used on arguments to the format string. Width,
+//! precision and alignment options are then applied to the decoded value,
+//! and integers can be written in hex, octal or binary, e.g. `{:#x}`.
+//!
+//! ```
+//! # use quicklog::{init, info};
+//! # init!();
+//! let price = 101.5;
+//! info!("price: {:>8} qty: {q:<4}|", ^price, q = ^10_usize);
+//! info!("order id: {:#x}", ^0xbeef_u64);
+//! ```
+//!
 //! ## Macro prefix for eager evaluation
 //!
 //! There are two prefixes you can use for variables, `%` and `?`. This works the same
@@ -119,6 +184,28 @@
 //! # }
 //! ```
 //!
+//! The same prefixes can be used on arguments to the format string, and can
+//! be freely mixed with `^` and plain arguments. Prefixed arguments are
+//! formatted when logging, while `^` arguments are only decoded when flushing.
+//!
+//! ```
+//! # use quicklog::{init, info};
+//! # init!();
+//! # let side = "Bid";
+//! # let price = 101.5;
+//! info!("{} {:>6} @ {}", ?side, ^price, 10);
+//! // output: "\"Bid\"  101.5 @ 10"
+//! ```
+//!
+//! A value which only implements `Display` can also be wrapped in
+//! [`Disp`](serialize::Disp) and passed with `^`, e.g.
+//! `info!("{}", ^Disp(&addr))`, which formats it straight into the
+//! serialization buffer instead of copying it into the log record.
+//!
+//! To defer an expensive `Debug` argument until flushing instead, wrap a
+//! closure formatting it in [`Lazy`], e.g.
+//! `info!("{}", Lazy::new(move |f| write!(f, "{:?}", book)))`.
+//!
 //! ## Structured fields
 //!
 //! Structured fields in log lines can be specified using `field_name = field_value`
@@ -136,16 +223,95 @@
 //! # }
 //! ```
 //!
+//! [`event!`] logs a structured event for machine processing, named by its
+//! first argument. Its fields are serialized as if prefixed with `^` unless
+//! prefixed otherwise, so that [`JsonFormatter`](formatter::JsonFormatter)
+//! writes derived [`Serialize`] structs as nested objects and collections as
+//! arrays:
+//!
+//! ```
+//! # use std::collections::BTreeSet;
+//! # use quicklog::{event, init, Serialize};
+//! #[derive(Serialize)]
+//! struct Fill {
+//!     price: f64,
+//!     qty: u64,
+//! }
+//!
+//! # init!();
+//! let legs: BTreeSet<u64> = [1, 2].into();
+//! event!("order_filled", fill = Fill { price: 1.5, qty: 10 }, legs);
+//! // output as JSON: "message":"order_filled","fields":{"fill":{"price":1.5,"qty":10},"legs":[1,2]}
+//! ```
+//!
+//! Errors can be passed with the `!` prefix, e.g. `info!(!err, "failed")`,
+//! which records them as an `error` field along with the messages of their
+//! sources, see [`ErrorChain`](line::ErrorChain).
+//!
+//! Fields passed without a name are named after their expression, e.g.
+//! `info!(%order.side, "filled")` records `order.side=...`. Variables captured
+//! by the format string, e.g. `info!("filled {qty}")`, are also written as
+//! fields by formatters producing structured output such as JSON.
+//!
+//! Arguments without a `^` prefix are copied into the log record, so their
+//! type has to be `Clone`. Borrowed values, such as `&str`, `&&str` or
+//! `Option<&str>`, are copied into owned ones, e.g. `Option<String>`. Fields
+//! prefixed with `?` or `%` whose type is not `Clone` are formatted when
+//! logging instead.
+//!
+//! ## Spans
+//!
+//! [`span!`] creates a named span with fields, which are attached to every log
+//! line recorded while the span is entered. See [`span`](mod@span) for more details.
+//!
+//! ```
+//! # use quicklog::{init, info, span};
+//! # fn main() {
+//! # init!();
+//! let span = span!("request", id = 42);
+//! let _entered = span.enter();
+//! info!("handling request");
+//! // output: "request{id=42}: handling request"
+//! # }
+//! ```
+//!
+//! ## Context
+//!
+//! [`with_context!`] registers fields which are appended to every log line
+//! afterwards, while [`with_thread_context!`] and [`scoped_context!`] only
+//! append them to the log lines of the calling thread. See
+//! [`context`](mod@context) for more details.
+//!
+//! ```
+//! # use quicklog::{init, info, with_context};
+//! # fn main() {
+//! # init!();
+//! with_context!(region = "us-east");
+//! info!("handling request");
+//! // output: "handling request region=us-east"
+//! # }
+//! ```
+//!
 //! # Environment variables
 //!
-//! There are two environment variables you can set:
+//! There are three environment variables you can set at compile time:
 //!
 //! 1. `QUICKLOG_MAX_LOGGER_CAPACITY`
 //!     - sets the size of the spsc ring buffer used for logging
+//!     - records can spill over past it at runtime through
+//!     [`Config::capacity`] or [`Config::grow_to`], e.g. through the
+//!     `QUICKLOG_CAPACITY` variable read by [`Config::from_env`]
 //! 2. `QUICKLOG_MAX_SERIALIZE_BUFFER_CAPACITY`
 //!     - sets the size of the byte buffer used for static serialization
 //!     - this can be increased when you run into issues out of memory in debug
 //!     when conducting load testing
+//!     - can be overridden at runtime through
+//!     [`Config::serialize_buffer_capacity`]
+//! 3. `QUICKLOG_MIN_LEVEL`
+//!     - sets the minimum level of log lines compiled in, either globally or
+//!     per module, e.g. `info,my_app::hot_path=off`
+//!     - log lines below it are compiled out of the callsite entirely,
+//!     whatever the level set at runtime, see [`level::static_min_priority`]
 //!
 //! # Components
 //!
@@ -180,7 +346,8 @@
 //! [`Flush`] is the trait that defines how the log messages would be flushed.
 //! These logs can be printed through using the pre-defined [`StdoutFlusher`] or
 //! saved to a file through the pre-defined [`FileFlusher`] to a specified
-//! location through the string passed in.
+//! location through the string passed in. [`LockedStdoutFlusher`] holds the
+//! stdout lock and buffers lines instead of locking stdout for every line.
 //!
 //! ### Example
 //!
@@ -199,38 +366,110 @@
 //!
 //! [`Serialize`]: serialize::Serialize
 //! [`StdoutFlusher`]: quicklog_flush::stdout_flusher::StdoutFlusher
+//! [`LockedStdoutFlusher`]: quicklog_flush::stdout_flusher::LockedStdoutFlusher
 //! [`FileFlusher`]: quicklog_flush::file_flusher::FileFlusher
 
+use background::BackgroundFlush;
+use grow::SpillQueue;
 use heapless::spsc::Queue;
-use level::Level;
-use once_cell::unsync::Lazy;
+use level::{Level, LevelFilter};
 use quanta::Instant;
 use serialize::buffer::ByteBuffer;
+use std::borrow::Cow;
 use std::cell::OnceCell;
 use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
 
 pub use std::{file, line, module_path};
 
 use chrono::{DateTime, Utc};
 use quicklog_clock::{quanta::QuantaClock, Clock};
-use quicklog_flush::{file_flusher::FileFlusher, Flush};
+use quicklog_flush::{file_flusher::FileFlusher, noop_flusher::NoopFlusher, Flush};
 
 /// re-export of crates, for use in macros
+#[doc(hidden)]
+pub use inventory;
 pub use lazy_format;
 pub use quicklog_flush;
 
+/// contains host and application metadata captured at init
+pub mod app;
+/// contains tasks for flushing from a tokio runtime
+#[cfg(feature = "tokio")]
+pub mod async_flush;
+/// contains the background flush thread
+pub mod background;
+/// contains log records built across several statements
+pub mod builder;
+/// contains the registry of logging macro callsites
+pub mod callsite;
+/// contains adapters for recording events from other logging libraries
+#[cfg(feature = "tracing")]
+pub mod compat;
+/// contains configuration applied at init
+pub mod config;
+/// contains default fields appended to every log line
+pub mod context;
+/// contains the bundled formatters and timestamp formats
+pub mod formatter;
+/// contains the growth of the logging queue past its capacity
+pub mod grow;
+/// contains interning of repeatedly logged strings
+pub mod intern;
 /// contains logging levels and filters
 pub mod level;
+/// contains access to the message and fields of log lines
+pub mod line;
 /// contains macros
 pub mod macros;
+/// contains the merging of records queued in several loggers
+pub mod merge;
+/// contains metrics recorded through the `counter!` and `gauge!` macros
+pub mod metric;
+/// contains NUMA placement of the logging queue and the flush thread
+pub mod numa;
+/// contains the reading of queued log records without flushing them
+pub mod reader;
+/// contains sampling for the `*_sampled!` macros
+pub mod sample;
 /// contains trait for serialization and pre-generated impl for common types and buffer
 pub mod serialize;
+/// contains spans, which attach context to log lines
+pub mod span;
+/// contains the handle flushing log lines from a thread chosen by the application
+pub mod split;
+/// contains in-memory capture of log lines for tests
+pub mod test_support;
+/// contains identification of the thread log records were logged from
+pub mod thread;
+/// contains rate limiting for the `*_throttle!` macros
+pub mod throttle;
 
 include!("constants.rs");
 /// `constants.rs` is generated from `build.rs`, should not be modified manually
 pub mod constants;
 
-pub use quicklog_macros::{debug, error, info, trace, warn, Serialize};
+pub use app::app_info;
+#[cfg(feature = "tokio")]
+pub use async_flush::{spawn_async_flush_task, spawn_flush_task, AsyncFlush};
+pub use callsite::callsites;
+pub use config::{
+    config, update_config, Config, ConfigUpdate, EnvConfigError, FlushErrorHook, OverflowPolicy,
+};
+pub use formatter::{formatter, QuickLogFormatter};
+pub use intern::{intern, InternedStr};
+pub use level::STATIC_MAX_LEVEL;
+pub use line::{Lazy, LogLine};
+#[doc(hidden)]
+pub use quicklog_macros::log_fatal;
+pub use quicklog_macros::{
+    debug, debug_sampled, debug_throttle, debug_to, error, error_sampled, error_throttle, error_to,
+    event, info, info_sampled, info_throttle, info_to, log, scoped_context, span, trace,
+    trace_sampled, trace_throttle, trace_to, try_debug, try_error, try_info, try_trace, try_warn,
+    warn, warn_sampled, warn_throttle, warn_to, with_context, with_thread_context, Serialize,
+};
+pub use split::FlushHandle;
 
 /// Internal API
 ///
@@ -238,9 +477,170 @@ pub use quicklog_macros::{debug, error, info, trace, warn, Serialize};
 #[doc(hidden)]
 pub type TimedLogRecord = (Instant, LogRecord);
 
+/// Internal API
+///
+/// Format string argument which was eagerly formatted through the `?` or `%`
+/// prefix. Both `{}` and `{:?}` print the formatted string as is, applying
+/// any width, precision and alignment options.
+#[doc(hidden)]
+#[derive(Clone)]
+pub struct Preformatted(pub String);
+
+impl Display for Preformatted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(&self.0)
+    }
+}
+
+impl std::fmt::Debug for Preformatted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(&self.0)
+    }
+}
+
 /// Logger initialized to Quicklog
 #[doc(hidden)]
-static mut LOGGER: Lazy<Quicklog> = Lazy::new(Quicklog::default);
+static mut LOGGER: once_cell::unsync::Lazy<Quicklog> =
+    once_cell::unsync::Lazy::new(Quicklog::default);
+
+/// Queue of log records waiting to be flushed
+type LogQueue = Queue<TimedLogRecord, MAX_LOGGER_CAPACITY>;
+
+/// Number of records [`LogQueue`] can hold, as one slot of the queue is
+/// always kept empty
+const QUEUE_CAPACITY: usize = MAX_LOGGER_CAPACITY - 1;
+
+/// Queue of the global logger, split at the first initialization
+static mut QUEUE: LogQueue = Queue::new();
+
+/// Set while the consumer side of [`QUEUE`] is owned by a [`FlushHandle`],
+/// so that the queue is not split again in the meantime, which would create
+/// a second consumer
+static QUEUE_HANDED_OVER: AtomicBool = AtomicBool::new(false);
+
+/// Queue allocated on the heap for a logger created through
+/// [`Quicklog::new`], freed when dropped
+struct OwnedQueue(std::ptr::NonNull<LogQueue>);
+
+// SAFETY: the queue is owned in the same way as by a `Box<LogQueue>`, which
+// is `Send` as the log records it holds are
+unsafe impl Send for OwnedQueue {}
+
+impl OwnedQueue {
+    fn new() -> Self {
+        let layout = std::alloc::Layout::new::<LogQueue>();
+        // Allocated zeroed rather than through `Box::new(Queue::new())`, as
+        // the queue may not fit on the stack. SAFETY: a zeroed queue has its
+        // head and tail at 0, which is the empty queue created by
+        // `Queue::new`, and its slots are `MaybeUninit`
+        let ptr = unsafe { std::alloc::alloc_zeroed(layout) } as *mut LogQueue;
+        match std::ptr::NonNull::new(ptr) {
+            Some(ptr) => Self(ptr),
+            None => std::alloc::handle_alloc_error(layout),
+        }
+    }
+
+    /// Splits the queue into its producer and consumer.
+    ///
+    /// SAFETY: both have to be dropped before the queue itself
+    unsafe fn split(&mut self) -> (Sender, Receiver) {
+        (*self.0.as_ptr()).split()
+    }
+}
+
+impl Drop for OwnedQueue {
+    fn drop(&mut self) {
+        // SAFETY: allocated with the layout of `LogQueue` in `OwnedQueue::new`
+        drop(unsafe { Box::from_raw(self.0.as_ptr()) });
+    }
+}
+
+/// Takes the oldest record off the queue, then off the records which spilled
+/// over once the queue is empty
+fn dequeue(receiver: &mut Receiver, spill: &SpillQueue) -> Option<TimedLogRecord> {
+    receiver.dequeue().or_else(|| spill.pop())
+}
+
+/// Instant the record which [`dequeue`] would take next was logged at
+fn peek_time(receiver: &Receiver, spill: &SpillQueue) -> Option<Instant> {
+    receiver
+        .peek()
+        .map(|(time_logged, _)| *time_logged)
+        .or_else(|| spill.peek_time())
+}
+
+/// Lock held by the thread taking records off the queue, so that records
+/// are never dequeued from two threads at once, e.g. when flushing in place
+/// while the background flush thread is flushing. Holds the
+/// [`ThreadInfo::id`](thread::ThreadInfo::id) of the thread holding it, or 0.
+#[derive(Default)]
+struct ConsumerLock(AtomicU64);
+
+/// Result of [`ConsumerLock::try_lock`]
+enum ConsumerClaim {
+    /// The lock was acquired, and is released when the guard is dropped
+    Acquired(ConsumerGuard),
+    /// The calling thread already holds the lock further up its stack, e.g.
+    /// when a flusher logs onto a full queue
+    Reentrant,
+    /// Another thread is dequeuing
+    Busy,
+}
+
+impl ConsumerLock {
+    fn try_lock(self: &Arc<Self>) -> ConsumerClaim {
+        let id = thread::ThreadInfo::current_id();
+        match self
+            .0
+            .compare_exchange(0, id, Ordering::Acquire, Ordering::Relaxed)
+        {
+            Ok(_) => ConsumerClaim::Acquired(ConsumerGuard(self.clone())),
+            Err(holder) if holder == id => ConsumerClaim::Reentrant,
+            Err(_) => ConsumerClaim::Busy,
+        }
+    }
+
+    /// Waits until no other thread is dequeuing. Returns `None` if the
+    /// calling thread already holds the lock.
+    fn lock(self: &Arc<Self>) -> Option<ConsumerGuard> {
+        loop {
+            match self.try_lock() {
+                ConsumerClaim::Acquired(guard) => return Some(guard),
+                ConsumerClaim::Reentrant => return None,
+                ConsumerClaim::Busy => std::thread::yield_now(),
+            }
+        }
+    }
+
+    /// Same as [`ConsumerLock::lock`], but gives up once another thread has
+    /// held the lock for [`CONSUMER_LOCK_TIMEOUT`], e.g. as its flusher is
+    /// stuck on a dead disk, returning [`ConsumerClaim::Busy`]
+    fn lock_with_timeout(self: &Arc<Self>) -> ConsumerClaim {
+        let deadline = std::time::Instant::now() + CONSUMER_LOCK_TIMEOUT;
+        loop {
+            match self.try_lock() {
+                ConsumerClaim::Busy if std::time::Instant::now() < deadline => {
+                    std::thread::yield_now()
+                }
+                claim => return claim,
+            }
+        }
+    }
+}
+
+/// How long [`fatal!`] and [`Config::flush_on_panic`] wait for another
+/// thread to finish dequeuing, before giving up on flushing the queue
+const CONSUMER_LOCK_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Releases the [`ConsumerLock`] when dropped, including when a flusher
+/// panics
+struct ConsumerGuard(Arc<ConsumerLock>);
+
+impl Drop for ConsumerGuard {
+    fn drop(&mut self) {
+        self.0 .0.store(0, Ordering::Release);
+    }
+}
 
 /// Producer side of queue
 pub type Sender = heapless::spsc::Producer<'static, TimedLogRecord, MAX_LOGGER_CAPACITY>;
@@ -251,6 +651,17 @@ pub type Receiver = heapless::spsc::Consumer<'static, TimedLogRecord, MAX_LOGGER
 /// Result from trying to pop from logging queue
 pub type RecvResult = Result<(), FlushError>;
 
+/// Returns the number of log records dropped so far because the logging
+/// queue was full
+pub fn dropped_count() -> usize {
+    logger().dropped_count()
+}
+
+/// Returns the current utilization of the logging queue, see [`QueueStats`]
+pub fn queue_stats() -> QueueStats {
+    logger().queue_stats()
+}
+
 /// Log is the base trait that Quicklog will implement.
 /// Flushing and formatting is deferred while logging.
 pub trait Log {
@@ -265,8 +676,135 @@ pub trait Log {
 pub enum FlushError {
     /// Queue is empty
     Empty,
+    /// Logger has not been initialized through [`init!`], or has already
+    /// been shut down through [`Quicklog::shutdown`]
+    NotInitialized,
+    /// The time the record was logged at could not be computed by the clock,
+    /// so the record was skipped
+    InvalidTime,
+    /// An argument logged with `^` no longer matched its checksum, e.g. as
+    /// it was overwritten in the serialization buffer, so the record was
+    /// skipped, see [`Config::checksum`]
+    Corrupted,
+    /// The flusher failed to write the log line, e.g. as the disk is full or
+    /// the pipe was closed, see [`Flush::try_flush_one`]. The record is not
+    /// flushed again.
+    Io(std::io::Error),
+}
+
+impl Clone for FlushError {
+    /// Clones an [`Io`](FlushError::Io) error into one of the same kind,
+    /// keeping the OS error code if there is one
+    fn clone(&self) -> Self {
+        match self {
+            Self::Empty => Self::Empty,
+            Self::NotInitialized => Self::NotInitialized,
+            Self::InvalidTime => Self::InvalidTime,
+            Self::Corrupted => Self::Corrupted,
+            Self::Io(err) => Self::Io(match err.raw_os_error() {
+                Some(code) => std::io::Error::from_raw_os_error(code),
+                None => std::io::Error::new(err.kind(), err.to_string()),
+            }),
+        }
+    }
+}
+
+/// [`Io`](FlushError::Io) errors are equal if they are of the same kind
+impl PartialEq for FlushError {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Io(err), Self::Io(other)) => err.kind() == other.kind(),
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+}
+
+impl Eq for FlushError {}
+
+impl std::fmt::Display for FlushError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => f.write_str("logging queue is empty"),
+            Self::NotInitialized => f.write_str("logger is not initialized"),
+            Self::InvalidTime => f.write_str("time of log record could not be computed"),
+            Self::Corrupted => f.write_str("log record is corrupted"),
+            Self::Io(err) => write!(f, "failed to flush log line: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for FlushError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Errors returned by the `try_*` logging macros, e.g. [`try_info!`], when a
+/// log record could not be queued
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueueError {
+    /// Queue is full, and the record was dropped according to
+    /// [`OverflowPolicy::Drop`]
+    Full,
+    /// Logger has not been initialized through [`init!`], or has already
+    /// been shut down through [`Quicklog::shutdown`]
+    NotInitialized,
+}
+
+impl std::fmt::Display for QueueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Full => f.write_str("logging queue is full"),
+            Self::NotInitialized => f.write_str("logger is not initialized"),
+        }
+    }
+}
+
+impl std::error::Error for QueueError {}
+
+/// Summary of a batch of log records flushed through [`Quicklog::flush_n`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FlushStats {
+    /// Number of records flushed
+    pub flushed: usize,
+    /// Number of bytes of formatted log lines flushed
+    pub bytes: usize,
+    /// Number of records which were skipped as they could not be flushed
+    pub errors: usize,
+}
+
+/// Utilization of the logging queue, returned by [`queue_stats`]
+///
+/// The queue holds log records rather than bytes, so all values are numbers
+/// of records. Totals are counted since the logger was created.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QueueStats {
+    /// Number of records currently waiting to be flushed
+    pub len: usize,
+    /// Highest number of records waiting to be flushed at any point
+    pub high_watermark: usize,
+    /// Maximum number of records the queue can currently hold, after which
+    /// records are handled according to the [`OverflowPolicy`], including
+    /// the room it has grown by, see [`Config::grow_to`]
+    pub capacity: usize,
+    /// Number of times the queue has grown, see [`Config::grow_to`]
+    pub resizes: usize,
+    /// Total number of records pushed onto the queue
+    pub enqueued: usize,
+    /// Total number of records taken off the queue to be flushed or formatted
+    pub dequeued: usize,
+    /// Total number of records dropped because the queue was full, same as
+    /// [`dropped_count`]
+    pub dropped: usize,
 }
 
+/// Serializes unit tests which use the global logger
+#[cfg(all(test, any(feature = "tracing", feature = "tokio")))]
+pub(crate) static LOGGER_TEST_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
 ///  ha**Internal API**
 ///
 /// Returns a mut reference to the globally static logger [`LOGGER`]
@@ -275,6 +813,117 @@ pub fn logger() -> &'static mut Quicklog {
     unsafe { &mut LOGGER }
 }
 
+/// Flushes all remaining log records through [`Quicklog::shutdown`], then
+/// drops the global logger along with its flusher and replaces it with a
+/// fresh, uninitialized one.
+///
+/// Unlike [`Quicklog::shutdown`], which keeps the configuration and counters
+/// of the logger, calling [`init!`] afterwards starts over as if the process
+/// had just started, e.g. to reconfigure the logger or between tests run in
+/// the same process. Any [`BackgroundFlush`] should be stopped beforehand.
+pub fn shutdown() {
+    let logger = logger();
+    logger.shutdown();
+    *logger = Quicklog::default();
+    app::clear_app_info();
+}
+
+/// Logs a message which is only known at runtime, e.g. read from
+/// configuration or external input, along with structured `fields`.
+///
+/// This is a slower fallback for the logging macros, which require a literal
+/// format string: the message is copied into the queued record, and `target`
+/// is [`intern()`]ed to be reported in place of the module path, so it should
+/// come from a bounded set of strings. The file and line are those of the
+/// caller.
+///
+/// ```
+/// use quicklog::{init, level::Level, log_dynamic, serialize::Value};
+///
+/// # init!();
+/// let template = String::from("alert triggered");
+/// _ = log_dynamic(Level::Warn, "alerts", template, vec![("threshold", Value::UInt(10))]);
+/// ```
+///
+/// Returns `Ok` without queueing anything if `level` is disabled.
+#[track_caller]
+pub fn log_dynamic(
+    level: Level,
+    target: &str,
+    message: impl Into<Cow<'static, str>>,
+    fields: Vec<(&'static str, serialize::Value)>,
+) -> Result<(), QueueError> {
+    if !is_level_enabled!(level) {
+        return Ok(());
+    }
+
+    let location = std::panic::Location::caller();
+    let log_record = LogRecord {
+        level,
+        module_path: intern(target).as_str(),
+        file: location.file(),
+        line: location.line(),
+        span: span::current(),
+        sequence: 0,
+        thread: None,
+        context: None,
+        log_line: Box::new(builder::BuiltLine::new(message, fields)),
+    };
+
+    logger().try_log(log_record)
+}
+
+/// Logs `message` as is, without going through a format string, through the
+/// [`info_raw!`] family of macros, e.g. for messages already formatted by
+/// another layer.
+///
+/// A `&'static str` is queued as a pointer and length, without copying it,
+/// and a `String` is moved into the record. The macros pass string literals
+/// as is, and copy other borrowed strings into a `String`:
+///
+/// ```
+/// use quicklog::{info_raw, init};
+///
+/// # init!();
+/// info_raw!("market open");
+/// let rendered = format!("{} orders pending", 3);
+/// info_raw!(&rendered);
+/// info_raw!(rendered);
+/// ```
+///
+/// Like the logging macros, the macros check the minimum level compiled in
+/// through `QUICKLOG_MIN_LEVEL` and the [callsite](callsite) filters, while
+/// calling this function directly only checks the maximum level set at
+/// runtime.
+pub fn log_raw(
+    level: Level,
+    message: impl Into<Cow<'static, str>>,
+    module_path: &'static str,
+    file: &'static str,
+    line: u32,
+) {
+    if !is_level_enabled!(level) {
+        return;
+    }
+
+    let log_record = LogRecord {
+        level,
+        module_path,
+        file,
+        line,
+        span: span::current(),
+        sequence: 0,
+        thread: None,
+        context: None,
+        log_line: match message.into() {
+            Cow::Borrowed(message) => Box::new(message),
+            Cow::Owned(message) => Box::new(message),
+        },
+    };
+
+    _ = logger().log(log_record);
+}
+
 pub struct LogRecord {
     /// Level
     pub level: Level,
@@ -284,63 +933,632 @@ pub struct LogRecord {
     pub file: &'static str,
     /// Line
     pub line: u32,
-    /// Log line captured by using LazyFormat which implements Display trait.
-    pub log_line: Box<dyn Display>,
+    /// Spans entered when the log line was recorded
+    pub span: Option<span::SpanContext>,
+    /// Sequence number assigned by [`Log::log`], increasing by one for every
+    /// record logged, including records dropped because the queue was full.
+    ///
+    /// A gap between the sequence numbers of two flushed log lines is the
+    /// exact number of records lost in between, whether dropped or skipped
+    /// while flushing. Records created outside of [`Log::log`] should leave
+    /// this as 0, and the warning flushed by [`Config::report_dropped`] reuses
+    /// the sequence number of the last record flushed.
+    pub sequence: u64,
+    /// Thread the record was logged from, attached by [`Log::log`]. Records
+    /// created outside of [`Log::log`] should leave this as `None`.
+    pub thread: Option<thread::ThreadInfo>,
+    /// Context fields in effect when the record was logged, attached by
+    /// [`Log::log`], see [`context`](mod@context). Records created outside of
+    /// [`Log::log`] should leave this as `None`.
+    pub context: Option<context::Context>,
+    /// Log line captured by the logging macros, which lazily formats the
+    /// message and structured fields.
+    pub log_line: Box<dyn LogLine + Send>,
 }
 
 pub trait PatternFormatter {
     fn custom_format(&mut self, time: DateTime<Utc>, log_record: LogRecord) -> String;
-}
-
-pub struct QuickLogFormatter;
-
-impl QuickLogFormatter {
-    fn new() -> Self {
-        Self {}
-    }
-}
 
-impl PatternFormatter for QuickLogFormatter {
-    fn custom_format(&mut self, time: DateTime<Utc>, object: LogRecord) -> String {
-        format!("[{:?}]{}\n", time, object.log_line)
+    /// Formats the log record by appending it to `buf`, which is reused
+    /// across log records when flushing.
+    ///
+    /// Defaults to appending the output of [`PatternFormatter::custom_format`],
+    /// override this to avoid allocating a `String` for every log record.
+    fn format_into(&mut self, time: DateTime<Utc>, log_record: LogRecord, buf: &mut String) {
+        buf.push_str(&self.custom_format(time, log_record))
     }
 }
 
 /// Quicklog implements the Log trait, to provide logging
 pub struct Quicklog {
-    flusher: Box<dyn Flush>,
-    clock: Box<dyn Clock>,
-    formatter: Box<dyn PatternFormatter>,
+    flusher: Box<dyn Flush + Send>,
+    /// Shared with the [`FlushHandle`] split off through
+    /// [`Quicklog::init_split`], if any
+    clock: Arc<dyn Clock + Send + Sync>,
+    formatter: Box<dyn PatternFormatter + Send>,
     sender: OnceCell<Sender>,
     receiver: OnceCell<Receiver>,
+    /// Queue of a logger created through [`Quicklog::new`], declared after
+    /// `sender` and `receiver` so that it is dropped after them
+    queue: Option<OwnedQueue>,
+    /// Records logged past the capacity of the queue, shared with the
+    /// [`FlushHandle`] split off through [`Quicklog::init_split`], if any
+    spill: Arc<SpillQueue>,
+    /// Held while dequeuing, see [`ConsumerLock`]
+    consumer: Arc<ConsumerLock>,
+    /// Total capacity the queue may grow to, see [`Config::grow_to`] and
+    /// [`Config::capacity`]
+    grow_to: Option<usize>,
     byte_buffer: ByteBuffer,
+    /// Maximum size of each serialized argument, see [`Config::max_record_size`]
+    max_record_size: usize,
+    overflow_policy: OverflowPolicy,
+    dropped: AtomicUsize,
+    enqueued: AtomicUsize,
+    dequeued: AtomicUsize,
+    high_watermark: AtomicUsize,
+    /// Sequence number of the next record logged
+    next_sequence: AtomicU64,
+    /// Sequence number of the last record flushed
+    last_sequence: u64,
+    report_dropped: bool,
+    reported_dropped: usize,
+    on_flush_error: Option<FlushErrorHook>,
+    routes: Vec<(LevelFilter, Box<dyn Flush + Send>)>,
+    /// Reused across log records to avoid allocating while flushing
+    line_buffer: String,
 }
 
 impl Quicklog {
     /// Sets which flusher to be used, used in [`with_flush!`]
     #[doc(hidden)]
-    pub fn use_flush(&mut self, flush: Box<dyn Flush>) {
+    pub fn use_flush(&mut self, flush: Box<dyn Flush + Send>) {
         self.flusher = flush
     }
 
-    pub fn use_formatter(&mut self, formatter: Box<dyn PatternFormatter>) {
+    pub fn use_formatter(&mut self, formatter: Box<dyn PatternFormatter + Send>) {
         self.formatter = formatter
     }
 
     /// Sets which clock to be used, used in [`with_clock!`]
     #[doc(hidden)]
-    pub fn use_clock(&mut self, clock: Box<dyn Clock>) {
-        self.clock = clock
+    pub fn use_clock(&mut self, clock: Box<dyn Clock + Send + Sync>) {
+        self.clock = clock.into()
     }
 
     /// Initializes channel inside of quicklog, can be called
     /// through [`init!`] macro
     pub fn init(&mut self) {
-        static mut QUEUE: Queue<TimedLogRecord, MAX_LOGGER_CAPACITY> = Queue::new();
-        let (sender, receiver): (Sender, Receiver) = unsafe { QUEUE.split() };
+        _ = self.init_with_config(Config::default());
+    }
+
+    /// Same as [`Quicklog::init`], additionally recording the application
+    /// `version` in the captured [`AppInfo`](app::AppInfo)
+    pub fn init_with_version(&mut self, version: Option<&'static str>) {
+        _ = self.init_with_config(Config {
+            version,
+            ..Config::default()
+        });
+    }
+
+    /// Initializes quicklog with the options set in `config`, can be called
+    /// through [`init!`] macro
+    ///
+    /// Initializing again after [`shutdown()`] while the [`FlushHandle`]
+    /// returned by [`Quicklog::init_split`] is still alive leaves the logger
+    /// uninitialized, as the handle still owns the consumer side of the
+    /// logging queue.
+    ///
+    /// Returns a handle to the background flush thread if one was configured
+    /// through [`Config::background_flush`].
+    pub fn init_with_config(&mut self, config: Config) -> Option<BackgroundFlush> {
+        app::set_app_info(app::AppInfo::capture(config.version));
+        let flush_on_panic = config.flush_on_panic;
+        let background_flush = config.background_flush;
+        let stamp = config.stamp;
+        self.configure(config);
+
+        // Only split the queue again once the previous consumer is gone, i.e.
+        // after shutdown, so that initializing again does not attach a second
+        // consumer while the first is still owned by a `FlushHandle`
+        if self.sender.get().is_none() && !QUEUE_HANDED_OVER.load(Ordering::Acquire) {
+            let (sender, receiver): (Sender, Receiver) = unsafe { QUEUE.split() };
+
+            self.sender.set(sender).ok();
+            self.receiver.set(receiver).ok();
+        }
+
+        if let Some(build) = stamp {
+            self.log_stamp(build);
+        }
+
+        if flush_on_panic {
+            config::install_flush_on_exit();
+        }
+
+        background_flush.map(BackgroundFlush::spawn)
+    }
+
+    /// Same as [`Quicklog::init_with_config`], but hands the consumer side
+    /// of the logging queue over to the returned [`FlushHandle`], along with
+    /// the flusher, formatter and routes, so that log lines are flushed from
+    /// whichever thread owns the handle, see [`split`](crate::split).
+    ///
+    /// [`Config::background_flush`] is ignored. Returns `None` if the
+    /// consumer side was already handed over to a handle which is still
+    /// alive.
+    pub fn init_split(&mut self, mut config: Config) -> Option<FlushHandle> {
+        config.background_flush = None;
+        _ = self.init_with_config(config);
+        let receiver = self.receiver.take()?;
+        QUEUE_HANDED_OVER.store(true, Ordering::Release);
+
+        let consumer = Quicklog {
+            flusher: std::mem::replace(&mut self.flusher, Box::new(NoopFlusher)),
+            clock: self.clock.clone(),
+            spill: self.spill.clone(),
+            formatter: std::mem::replace(&mut self.formatter, Box::new(QuickLogFormatter::new())),
+            routes: std::mem::take(&mut self.routes),
+            on_flush_error: self.on_flush_error.take(),
+            ..Quicklog::default()
+        };
+        consumer.receiver.set(receiver).ok();
+
+        Some(FlushHandle::new(consumer))
+    }
+
+    /// Creates a logger independent of the global one, with its own queue,
+    /// flusher and formatter, which log lines are recorded into through the
+    /// `*_to!` macros, e.g. [`info_to!`], and flushed by calling
+    /// [`Quicklog::flush_n`] on it. This allows several isolated pipelines in
+    /// one process, e.g. an audit log next to the application log.
+    ///
+    /// The maximum level set through [`Config::max_level`] is shared with the
+    /// global logger. [`Config::background_flush`] and
+    /// [`Config::flush_on_panic`] only apply to the global logger, and are
+    /// ignored here.
+    ///
+    /// ```
+    /// use quicklog::{config, info_to, Quicklog};
+    /// use quicklog_flush::noop_flusher::NoopFlusher;
+    ///
+    /// let mut audit = Quicklog::new(config().flusher(NoopFlusher));
+    /// info_to!(&mut audit, "order {} accepted", 42);
+    /// assert_eq!(audit.flush_n(usize::MAX).flushed, 1);
+    /// ```
+    pub fn new(config: Config) -> Quicklog {
+        let mut quicklog = Quicklog::default();
+        let stamp = config.stamp;
+        quicklog.configure(config);
+
+        let queue = quicklog.queue.insert(OwnedQueue::new());
+        // SAFETY: `queue` is dropped after `sender` and `receiver`
+        let (sender, receiver) = unsafe { queue.split() };
+        quicklog.sender.set(sender).ok();
+        quicklog.receiver.set(receiver).ok();
+
+        if let Some(build) = stamp {
+            quicklog.log_stamp(build);
+        }
+
+        quicklog
+    }
+
+    /// Logs the stamp set through [`Config::stamp`], timestamped by the clock
+    /// of this logger
+    fn log_stamp(&mut self, build: app::BuildInfo) {
+        let start_time = self
+            .clock
+            .compute_system_time_from_instant(self.clock.get_instant())
+            .unwrap_or_else(|_| chrono::Utc::now());
+        _ = self.log(app::stamp_record(build, start_time));
+    }
+
+    /// Applies the options of `config` which are specific to this logger
+    fn configure(&mut self, config: Config) {
+        self.overflow_policy = config.overflow_policy;
+        self.grow_to = config.grow_to;
+        if let Some(capacity) = config.capacity {
+            self.spill.reserve(capacity.saturating_sub(QUEUE_CAPACITY));
+            self.grow_to = self.grow_to.max(Some(capacity));
+        }
+        if let Some(capacity) = config.serialize_buffer_capacity {
+            if self.sender.get().is_none() {
+                let capacity = capacity.max(serialize::MIN_TRUNCATED_SIZE);
+                self.byte_buffer = ByteBuffer::with_capacity(capacity);
+                self.max_record_size = capacity;
+            }
+        }
+        if let Some(size) = config.max_record_size {
+            self.max_record_size =
+                size.clamp(serialize::MIN_TRUNCATED_SIZE, self.byte_buffer.capacity());
+        }
+        self.report_dropped = config.report_dropped;
+        if let Some(flusher) = config.flusher {
+            self.flusher = flusher;
+        }
+        if let Some(clock) = config.clock {
+            self.clock = clock.into();
+        }
+        if let Some(formatter) = config.formatter {
+            self.formatter = formatter;
+        }
+        if let Some(filter) = config.max_level {
+            level::set_max_level(filter);
+        }
+        if let Some(filter) = config.filter {
+            callsite::set_filter(filter);
+        }
+        if let Some(enabled) = config.checksum {
+            serialize::set_checksum(enabled);
+        }
+        self.on_flush_error = config.on_flush_error;
+        self.routes = config.routes;
+    }
+
+    /// Binds the memory of the logging queue to NUMA node `node`, moving the
+    /// parts of it which were already allocated on other nodes, see
+    /// [`numa`](crate::numa). Only supported on Linux.
+    ///
+    /// Memory is allocated as records are first queued, so this is best
+    /// called before logging anything.
+    pub fn bind_queue_to_node(&self, node: usize) -> std::io::Result<()> {
+        let queue = match &self.queue {
+            Some(queue) => queue.0.as_ptr() as *const LogQueue,
+            // SAFETY: only the address of the queue is taken
+            None => unsafe { std::ptr::addr_of!(QUEUE) },
+        };
+        numa::bind_memory(queue.cast(), std::mem::size_of::<LogQueue>(), node)
+    }
+
+    /// Flushes all remaining log records and detaches the logging queue,
+    /// then closes the flusher and routes through [`Flush::close`], e.g.
+    /// writing the footers of their files.
+    ///
+    /// Logging after shutdown, for instance from the `Drop` impl of some
+    /// other static, is silently dropped instead of panicking. Flushing after
+    /// shutdown returns [`FlushError::NotInitialized`].
+    pub fn shutdown(&mut self) {
+        // Detach the producer first, so that anything logged while draining
+        // (e.g. by the flusher itself) is dropped rather than looping forever
+        _ = self.sender.take();
+        // Records which fail to flush are skipped rather than ending the
+        // drain, as the rest would be discarded along with the receiver
+        self.flush_n(usize::MAX);
+        _ = self.receiver.take();
+        self.close_flushers();
+    }
+
+    /// Flushes every queued log record from the calling thread, e.g. when the
+    /// process panics, then closes the flushers if `close` is set.
+    ///
+    /// Waits for any other thread which is flushing to finish its batch
+    /// rather than dequeuing at the same time, and does nothing if the
+    /// calling thread is already flushing further up its stack, e.g. as the
+    /// flusher itself panicked, or if the other thread is still flushing
+    /// after [`CONSUMER_LOCK_TIMEOUT`].
+    pub(crate) fn flush_remaining(&mut self, close: bool) {
+        let ConsumerClaim::Acquired(_guard) = self.consumer.lock_with_timeout() else {
+            return;
+        };
+
+        self.flush_n(usize::MAX);
+        if close {
+            self.close_flushers();
+        }
+    }
+
+    /// Closes the flusher and all routes through [`Flush::close`]. Errors
+    /// are ignored, as no log lines are flushed afterwards to report them.
+    pub(crate) fn close_flushers(&mut self) {
+        _ = self.flusher.close();
+        for (_, route) in &mut self.routes {
+            _ = route.close();
+        }
+    }
+
+    /// Flushes up to `max` log records, stopping early once the queue is
+    /// empty. Records which fail to flush are skipped and counted in
+    /// [`FlushStats::errors`].
+    pub fn flush_n(&mut self, max: usize) -> FlushStats {
+        let _guard = self.consumer.lock();
+        config::apply_pending_updates(self);
+        let mut stats = FlushStats::default();
+        for _ in 0..max {
+            let Some((time_logged, record)) = self
+                .receiver
+                .get_mut()
+                .and_then(|receiver| dequeue(receiver, &self.spill))
+            else {
+                break;
+            };
+            self.dequeued.fetch_add(1, Ordering::Relaxed);
+
+            match self.flush_record(time_logged, record) {
+                Ok(bytes) => {
+                    stats.flushed += 1;
+                    stats.bytes += bytes;
+                }
+                Err(_) => stats.errors += 1,
+            }
+        }
+
+        if self.report_dropped && stats.flushed > 0 {
+            self.report_dropped();
+        }
+
+        stats
+    }
+
+    /// Dequeues and formats a single log record, without passing it to the
+    /// flusher. This allows log lines to be written out elsewhere, e.g. by
+    /// an async task.
+    pub fn format_one(&mut self) -> Result<String, FlushError> {
+        let _guard = self.consumer.lock();
+        config::apply_pending_updates(self);
+        let Some(receiver) = self.receiver.get_mut() else {
+            return Err(FlushError::NotInitialized);
+        };
 
-        self.sender.set(sender).ok();
-        self.receiver.set(receiver).ok();
+        match dequeue(receiver, &self.spill) {
+            Some((time_logged, record)) => {
+                self.dequeued.fetch_add(1, Ordering::Relaxed);
+                self.format_record(time_logged, record)
+            }
+            None => Err(FlushError::Empty),
+        }
+    }
+
+    /// Dequeues a single log record along with the time it was logged at,
+    /// without formatting or flushing it, see [`reader`]. Records whose time
+    /// cannot be computed or which were corrupted are dequeued and skipped,
+    /// returning the corresponding [`FlushError`].
+    pub fn read_one(&mut self) -> Result<reader::Record, FlushError> {
+        let _guard = self.consumer.lock();
+        config::apply_pending_updates(self);
+        let Some(receiver) = self.receiver.get_mut() else {
+            return Err(FlushError::NotInitialized);
+        };
+
+        match dequeue(receiver, &self.spill) {
+            Some((time_logged, record)) => {
+                self.dequeued.fetch_add(1, Ordering::Relaxed);
+                let timestamp = self.record_time(time_logged, &record)?;
+                self.verify_record(&record)?;
+                Ok(reader::Record::new(timestamp, record))
+            }
+            None => Err(FlushError::Empty),
+        }
+    }
+
+    /// Reads the queued log records, oldest first, until the queue is empty,
+    /// see [`reader`]
+    pub fn records(&mut self) -> reader::Records<'_> {
+        reader::Records::new(self)
+    }
+
+    /// Formats and flushes a single record, returning the length of the
+    /// flushed log line
+    fn flush_record(
+        &mut self,
+        time_logged: Instant,
+        record: LogRecord,
+    ) -> Result<usize, FlushError> {
+        let mut line_buffer = std::mem::take(&mut self.line_buffer);
+        let flushed = self.flush_record_into(time_logged, record, &mut line_buffer);
+        self.line_buffer = line_buffer;
+
+        flushed
+    }
+
+    /// Same as [`Quicklog::flush_record`], but formats the log line into
+    /// `line_buffer`
+    fn flush_record_into(
+        &mut self,
+        time_logged: Instant,
+        record: LogRecord,
+        line_buffer: &mut String,
+    ) -> Result<usize, FlushError> {
+        let LogRecord {
+            level,
+            module_path,
+            file,
+            line,
+            sequence,
+            ..
+        } = record;
+        let time = self.record_time(time_logged, &record)?;
+        self.verify_record(&record)?;
+
+        line_buffer.clear();
+        self.formatter.format_into(time, record, line_buffer);
+        let log_line = line_buffer.as_str();
+
+        let mut routes = self
+            .routes
+            .iter_mut()
+            .filter(|(filter, _)| filter.enables(level))
+            .map(|(_, flusher)| flusher)
+            .peekable();
+        let flushed = if routes.peek().is_none() {
+            self.flusher.try_flush_leveled(level.name(), log_line)
+        } else {
+            // Every route is written to, even once one of them failed
+            let mut flushed = Ok(());
+            for flusher in routes {
+                flushed = flushed.and(flusher.try_flush_leveled(level.name(), log_line));
+            }
+            flushed
+        };
+        self.last_sequence = sequence;
+
+        let Err(err) = flushed else {
+            return Ok(log_line.len());
+        };
+
+        // The record was consumed by the formatter, so the hook is passed
+        // one holding the formatted log line instead, e.g. to write it
+        // to another flusher
+        let err = FlushError::Io(err);
+        let flushed_record = LogRecord {
+            level,
+            module_path,
+            file,
+            line,
+            span: None,
+            sequence,
+            thread: None,
+            context: None,
+            log_line: Box::new(log_line.to_string()),
+        };
+        self.notify_flush_error(err.clone(), &flushed_record);
+
+        Err(err)
+    }
+
+    /// Formats a single record, without passing it to the flusher
+    fn format_record(
+        &mut self,
+        time_logged: Instant,
+        record: LogRecord,
+    ) -> Result<String, FlushError> {
+        let time = self.record_time(time_logged, &record)?;
+        self.verify_record(&record)?;
+
+        Ok(self.formatter.custom_format(time, record))
+    }
+
+    /// Computes the time a record was logged at, notifying the
+    /// [`FlushErrorHook`] if it could not be computed
+    fn record_time(
+        &mut self,
+        time_logged: Instant,
+        record: &LogRecord,
+    ) -> Result<DateTime<Utc>, FlushError> {
+        let Ok(time) = self.clock.compute_system_time_from_instant(time_logged) else {
+            self.notify_flush_error(FlushError::InvalidTime, record);
+            return Err(FlushError::InvalidTime);
+        };
+
+        Ok(time)
+    }
+
+    /// Checks that the arguments of `record` logged with `^` still match
+    /// their checksums, if enabled through [`Config::checksum`], notifying the
+    /// [`FlushErrorHook`] otherwise
+    fn verify_record(&mut self, record: &LogRecord) -> Result<(), FlushError> {
+        if !serialize::checksum_enabled() {
+            return Ok(());
+        }
+
+        let corrupted = serialize::detect_corruption(|| {
+            _ = std::io::Write::write_fmt(
+                &mut std::io::sink(),
+                format_args!("{}", record.log_line),
+            );
+        });
+        if corrupted {
+            self.notify_flush_error(FlushError::Corrupted, record);
+            return Err(FlushError::Corrupted);
+        }
+
+        Ok(())
+    }
+
+    fn notify_flush_error(&mut self, error: FlushError, record: &LogRecord) {
+        if let Some(hook) = &mut self.on_flush_error {
+            hook(error, record);
+        }
+    }
+
+    /// Logs a record of [`fatal!`]: the record is formatted on the calling
+    /// thread and written straight to the flusher, regardless of the queue
+    /// being full or the [`OverflowPolicy`], after flushing every queued log
+    /// record. The flushers are then closed.
+    ///
+    /// Waits for any other thread which is flushing to finish its batch, up
+    /// to [`CONSUMER_LOCK_TIMEOUT`], after which the record is written anyway
+    /// without flushing the queue. When the consumer side of the queue was
+    /// handed over through [`Quicklog::init_split`], the record is queued
+    /// instead, to be flushed by the [`FlushHandle`].
+    #[doc(hidden)]
+    pub fn log_fatal(&mut self, mut record: LogRecord) -> Result<(), FlushError> {
+        if self.receiver.get().is_none() {
+            return self.log(record).map_err(|_| FlushError::NotInitialized);
+        }
+
+        record.sequence = self.next_sequence.fetch_add(1, Ordering::Relaxed);
+        record.thread = Some(thread::ThreadInfo::current());
+        record.context = context::current();
+        let time_logged = self.clock.get_instant();
+
+        let claim = self.consumer.lock_with_timeout();
+        if let ConsumerClaim::Acquired(_) = &claim {
+            self.flush_n(usize::MAX);
+        }
+        // Formatted into its own buffer, as a thread which still holds the
+        // consumer lock may be using the shared one
+        let flushed = self.flush_record_into(time_logged, record, &mut String::new());
+        // The flusher is in use further up the stack if reentrant
+        if !matches!(claim, ConsumerClaim::Reentrant) {
+            self.close_flushers();
+        }
+
+        flushed.map(|_| ())
+    }
+
+    /// Same as [`Log::log`], but returns why the record could not be queued
+    /// instead of handing it back
+    pub fn try_log(&mut self, record: LogRecord) -> Result<(), QueueError> {
+        self.log(record).map_err(|_| match self.sender.get() {
+            Some(_) => QueueError::Full,
+            None => QueueError::NotInitialized,
+        })
+    }
+
+    /// Number of log records dropped so far because the logging queue was full
+    pub fn dropped_count(&self) -> usize {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Current utilization of the logging queue, see [`QueueStats`]
+    pub fn queue_stats(&self) -> QueueStats {
+        QueueStats {
+            len: self.receiver.get().map_or(0, |receiver| receiver.len()) + self.spill.len(),
+            high_watermark: self.high_watermark.load(Ordering::Relaxed),
+            capacity: QUEUE_CAPACITY + self.spill.capacity(),
+            resizes: self.spill.resizes(),
+            enqueued: self.enqueued.load(Ordering::Relaxed),
+            dequeued: self.dequeued.load(Ordering::Relaxed),
+            dropped: self.dropped_count(),
+        }
+    }
+
+    /// Flushes a warning with the number of records dropped since the last
+    /// report, if any
+    fn report_dropped(&mut self) {
+        let dropped = self.dropped_count();
+        if dropped == self.reported_dropped {
+            return;
+        }
+
+        let record = LogRecord {
+            level: Level::Warn,
+            module_path: module_path!(),
+            file: file!(),
+            line: line!(),
+            span: None,
+            sequence: self.last_sequence,
+            thread: None,
+            context: None,
+            log_line: Box::new(format!(
+                "{} messages dropped",
+                dropped - self.reported_dropped
+            )),
+        };
+        self.reported_dropped = dropped;
+
+        _ = self.flush_record(self.clock.get_instant(), record);
     }
 
     /// Internal API to get a chunk from buffer
@@ -356,49 +1574,141 @@ impl Quicklog {
     pub fn get_chunk_as_mut(&mut self, chunk_size: usize) -> &mut [u8] {
         self.byte_buffer.get_chunk_as_mut(chunk_size)
     }
+
+    /// Internal API
+    ///
+    /// Same as [`Quicklog::get_chunk_as_mut`], but for loggers created
+    /// through [`Quicklog::new`], which are not borrowed for `'static`. Used
+    /// by the `*_to!` macros.
+    ///
+    /// # Safety
+    ///
+    /// The chunk must only be referenced by a log record passed to this
+    /// logger, which does not outlive it.
+    #[doc(hidden)]
+    pub unsafe fn get_chunk_as_static_mut(&mut self, chunk_size: usize) -> &'static mut [u8] {
+        let chunk = self.byte_buffer.get_chunk_as_mut(chunk_size);
+        std::slice::from_raw_parts_mut(chunk.as_mut_ptr(), chunk.len())
+    }
+
+    /// Internal API
+    ///
+    /// Maximum number of bytes a single serialized argument may take up,
+    /// beyond which it is encoded through
+    /// [`Serialize::encode_truncated`](serialize::Serialize::encode_truncated)
+    #[doc(hidden)]
+    pub fn max_record_size(&self) -> usize {
+        self.max_record_size
+    }
 }
 
 impl Default for Quicklog {
     fn default() -> Self {
         Quicklog {
             flusher: Box::new(FileFlusher::new("logs/quicklog.log")),
-            clock: Box::new(QuantaClock::new()),
+            clock: Arc::new(QuantaClock::new()),
             formatter: Box::new(QuickLogFormatter::new()),
             sender: OnceCell::new(),
             receiver: OnceCell::new(),
+            queue: None,
+            spill: Arc::default(),
+            consumer: Arc::default(),
+            grow_to: None,
             byte_buffer: ByteBuffer::new(),
+            max_record_size: MAX_SERIALIZE_BUFFER_CAPACITY,
+            overflow_policy: OverflowPolicy::default(),
+            dropped: AtomicUsize::new(0),
+            enqueued: AtomicUsize::new(0),
+            dequeued: AtomicUsize::new(0),
+            high_watermark: AtomicUsize::new(0),
+            next_sequence: AtomicU64::new(0),
+            last_sequence: 0,
+            report_dropped: false,
+            reported_dropped: 0,
+            on_flush_error: None,
+            routes: Vec::new(),
+            line_buffer: String::new(),
         }
     }
 }
 
 impl Log for Quicklog {
-    fn log(&mut self, record: LogRecord) -> SendResult {
-        match
-            self.sender
-                .get_mut()
-                .expect("Sender is not initialized, `Quicklog::init()` needs to be called at the entry point of your application")
-                .enqueue((self.clock.get_instant(), record))
-        {
-            Ok(_) => Ok(()),
-            Err(err) => Err(err),
+    /// Logging before [`init!`] or after [`Quicklog::shutdown`] is a no-op,
+    /// handing the record back as an error.
+    ///
+    /// When the queue is full, and cannot grow any further through
+    /// [`Config::grow_to`], the configured [`OverflowPolicy`] decides
+    /// whether the record is dropped or logging waits for space.
+    fn log(&mut self, mut record: LogRecord) -> SendResult {
+        record.sequence = self.next_sequence.fetch_add(1, Ordering::Relaxed);
+        record.thread = Some(thread::ThreadInfo::current());
+        record.context = context::current();
+        let mut item = (self.clock.get_instant(), record);
+        loop {
+            let Some(sender) = self.sender.get_mut() else {
+                return Err(item);
+            };
+
+            // Records which spilled over are flushed first, so that
+            // records are flushed in the order they were logged
+            let enqueued = if self.spill.is_empty() {
+                sender.enqueue(item)
+            } else {
+                Err(item)
+            };
+            let enqueued = match (enqueued, self.grow_to) {
+                (Err(rejected), Some(max_capacity)) => {
+                    self.spill.push(rejected, QUEUE_CAPACITY, max_capacity)
+                }
+                (enqueued, _) => enqueued,
+            };
+
+            item = match enqueued {
+                Ok(()) => {
+                    self.enqueued.fetch_add(1, Ordering::Relaxed);
+                    self.high_watermark
+                        .fetch_max(sender.len() + self.spill.len(), Ordering::Relaxed);
+                    return Ok(());
+                }
+                Err(rejected) => rejected,
+            };
+
+            match self.overflow_policy {
+                OverflowPolicy::Drop => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    return Err(item);
+                }
+                OverflowPolicy::Block => std::thread::yield_now(),
+                OverflowPolicy::FlushInPlace => {
+                    // Records must not be dequeued from two threads at once,
+                    // and another thread dequeuing frees up space anyway
+                    if let ConsumerClaim::Busy = self.consumer.try_lock() {
+                        std::thread::yield_now();
+                        continue;
+                    }
+                    if self.flush_one().is_err() {
+                        self.dropped.fetch_add(1, Ordering::Relaxed);
+                        return Err(item);
+                    }
+                }
+            }
         }
     }
 
     fn flush_one(&mut self) -> RecvResult {
-        match
-            self.receiver
-                    .get_mut()
-                    .expect("RECEIVER is not initialized, `Quicklog::init()` needs to be called at the entry point of your application")
-                    .dequeue()
-        {
+        let _guard = self.consumer.lock();
+        config::apply_pending_updates(self);
+        let Some(receiver) = self.receiver.get_mut() else {
+            return Err(FlushError::NotInitialized);
+        };
+
+        match dequeue(receiver, &self.spill) {
             Some((time_logged, record)) => {
-                let log_line = self.formatter.custom_format(
-                    self.clock
-                        .compute_system_time_from_instant(time_logged)
-                        .expect("Unable to get time from instant"),
-                    record,
-                );
-                self.flusher.flush_one(log_line);
+                self.dequeued.fetch_add(1, Ordering::Relaxed);
+                self.flush_record(time_logged, record)?;
+                if self.report_dropped {
+                    self.report_dropped();
+                }
                 Ok(())
             }
             None => Err(FlushError::Empty),
@@ -0,0 +1,431 @@
+//! Access to the message and structured fields of a log line.
+//!
+//! The logging macros record the message from the format string separately
+//! from prefixed fields such as `a = ?b`. Formatting a [`LogLine`] through
+//! `Display` writes both, as `message a=b`, while formatters producing
+//! structured output can write them separately:
+//!
+//! ```
+//! # use std::fmt::Display;
+//! use quicklog::line::LogLine;
+//!
+//! fn field_names(line: &dyn LogLine) -> Vec<&'static str> {
+//!     let mut names = Vec::new();
+//!     _ = line.visit_fields(&mut |name, _value| {
+//!         names.push(name);
+//!         Ok(())
+//!     });
+//!     names
+//! }
+//! ```
+
+use std::{
+    error::Error,
+    fmt::{Display, Formatter, Result},
+    sync::Arc,
+};
+
+use crate::{serialize::Value, Preformatted};
+
+/// Callback receiving the name and value of each structured field of a
+/// [`LogLine`]
+pub type FieldVisitor<'a> = dyn FnMut(&'static str, &dyn Display) -> Result + 'a;
+
+/// Callback receiving the name and typed [`Value`] of each structured field
+/// of a [`LogLine`]
+pub type ValueVisitor<'a> = dyn FnMut(&'static str, Value) -> Result + 'a;
+
+/// Log line recorded by the logging macros, whose `Display` implementation
+/// writes the message followed by the structured fields as `name=value`
+pub trait LogLine: Display {
+    /// Writes the message from the format string, without structured fields
+    fn fmt_message(&self, f: &mut Formatter<'_>) -> Result;
+
+    /// Calls `visit` with the name and value of each structured field, in the
+    /// order they were passed to the logging macro
+    fn visit_fields(&self, visit: &mut FieldVisitor<'_>) -> Result;
+
+    /// Same as [`visit_fields`](LogLine::visit_fields), but passing each
+    /// value as a [`Value`], e.g. so that structured output can write
+    /// numbers and booleans unquoted.
+    ///
+    /// Defaults to formatting every value into a [`Value::Str`].
+    fn visit_values(&self, visit: &mut ValueVisitor<'_>) -> Result {
+        self.visit_fields(&mut |name, value| visit(name, Value::Str(value.to_string())))
+    }
+}
+
+impl dyn LogLine + '_ {
+    /// Displays only the message, see [`LogLine::fmt_message`]
+    pub fn message(&self) -> Message<'_> {
+        Message(self)
+    }
+}
+
+impl dyn LogLine + Send + '_ {
+    /// Displays only the message, see [`LogLine::fmt_message`]
+    pub fn message(&self) -> Message<'_> {
+        Message(self)
+    }
+}
+
+/// Displays only the message of a [`LogLine`], created through
+/// [`message`](trait.LogLine.html#method.message)
+pub struct Message<'a>(&'a dyn LogLine);
+
+impl Display for Message<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        self.0.fmt_message(f)
+    }
+}
+
+/// Log line without structured fields
+impl LogLine for String {
+    fn fmt_message(&self, f: &mut Formatter<'_>) -> Result {
+        f.write_str(self)
+    }
+
+    fn visit_fields(&self, _: &mut FieldVisitor<'_>) -> Result {
+        Ok(())
+    }
+}
+
+/// Log line without structured fields
+impl LogLine for &'static str {
+    fn fmt_message(&self, f: &mut Formatter<'_>) -> Result {
+        f.write_str(self)
+    }
+
+    fn visit_fields(&self, _: &mut FieldVisitor<'_>) -> Result {
+        Ok(())
+    }
+}
+
+/// Argument formatted through a closure, which is only called when the log
+/// line is formatted while flushing.
+///
+/// Arguments passed without a prefix are already formatted while flushing,
+/// while `?` and `%` arguments are formatted eagerly on the hot path. Wrapping
+/// an expensive `Debug` argument in [`Lazy`] defers its formatting instead,
+/// and skips it entirely if the log line is filtered out:
+///
+/// ```
+/// use quicklog::{info, init, Lazy};
+///
+/// # init!();
+/// let book = vec![(100, 5), (101, 3)];
+/// info!("book: {}", Lazy::new(move |f| write!(f, "{:?}", book)));
+/// ```
+///
+/// The closure is moved into the log record, so it has to own what it
+/// formats.
+#[derive(Clone)]
+pub struct Lazy<F>(F);
+
+impl<F: Fn(&mut Formatter<'_>) -> Result> Lazy<F> {
+    /// Wraps `f`, which writes the argument into the given [`Formatter`]
+    pub fn new(f: F) -> Self {
+        Self(f)
+    }
+}
+
+impl<F: Fn(&mut Formatter<'_>) -> Result> Display for Lazy<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        (self.0)(f)
+    }
+}
+
+impl<F: Fn(&mut Formatter<'_>) -> Result> std::fmt::Debug for Lazy<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        (self.0)(f)
+    }
+}
+
+/// Error captured through the `!` prefix, e.g. `info!(!err, "request failed")`,
+/// whose chain of sources is only walked once the log line is formatted.
+///
+/// The error is recorded as an `error` field, or under the assigned name as in
+/// `info!(cause = !err)`. If the error has a source, the messages of all
+/// sources are recorded as a list in an additional `error.cause_chain` field.
+/// As [`LogLine::visit_values`] cannot hold both a value and nested fields
+/// under `error`, it passes the message of the error as `error.message`
+/// instead, e.g. `"error":{"message":..,"cause_chain":[..]}` in JSON output:
+///
+/// ```
+/// use quicklog::{info, init};
+///
+/// # init!();
+/// let err = std::fs::read("missing.txt").unwrap_err();
+/// info!(!err, "could not load config");
+/// ```
+///
+/// Anything which converts into a `Box<dyn Error + Send + Sync>` can be
+/// captured, including owned errors and strings. The error is moved into the log record, so
+/// borrowed errors have to be converted first, e.g. through `e.to_string()`.
+#[derive(Clone, Debug)]
+pub struct ErrorChain(Arc<dyn Error + Send + Sync>);
+
+impl ErrorChain {
+    pub fn new(error: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
+        Self(error.into().into())
+    }
+
+    /// The captured error
+    pub fn error(&self) -> &(dyn Error + 'static) {
+        &*self.0
+    }
+
+    /// Messages of the sources of the error, starting from its direct
+    /// source, as a [`Value::List`], or `None` if the error has no source
+    pub fn cause_chain(&self) -> Option<Value> {
+        let mut source = self.0.source()?;
+        let mut chain = vec![Value::Str(source.to_string())];
+        while let Some(next) = source.source() {
+            chain.push(Value::Str(next.to_string()));
+            source = next;
+        }
+
+        Some(Value::List(chain))
+    }
+}
+
+/// Writes the message of the error, without its sources
+impl Display for ErrorChain {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+/// Internal API
+///
+/// Part of a [`LazyLine`] to be written
+#[doc(hidden)]
+pub enum LinePart<'a, 'b> {
+    Message(&'a mut Formatter<'b>),
+    Fields(&'a mut FieldVisitor<'b>),
+    Values(&'a mut ValueVisitor<'b>),
+}
+
+/// Internal API
+///
+/// [`LogLine`] generated by the logging macros, which lazily formats the
+/// arguments captured by `write_part`
+#[doc(hidden)]
+pub struct LazyLine<F> {
+    has_message: bool,
+    write_part: F,
+}
+
+impl<F: Fn(LinePart<'_, '_>) -> Result> LazyLine<F> {
+    pub fn new(has_message: bool, write_part: F) -> Self {
+        Self {
+            has_message,
+            write_part,
+        }
+    }
+}
+
+impl<F: Fn(LinePart<'_, '_>) -> Result> Display for LazyLine<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        self.fmt_message(f)?;
+
+        let mut separate = self.has_message;
+        self.visit_fields(&mut |name, value| {
+            if separate {
+                f.write_str(" ")?;
+            }
+            separate = true;
+            write!(f, "{}={}", name, value)
+        })
+    }
+}
+
+impl<F: Fn(LinePart<'_, '_>) -> Result> LogLine for LazyLine<F> {
+    fn fmt_message(&self, f: &mut Formatter<'_>) -> Result {
+        (self.write_part)(LinePart::Message(f))
+    }
+
+    fn visit_fields(&self, visit: &mut FieldVisitor<'_>) -> Result {
+        (self.write_part)(LinePart::Fields(visit))
+    }
+
+    fn visit_values(&self, visit: &mut ValueVisitor<'_>) -> Result {
+        (self.write_part)(LinePart::Values(visit))
+    }
+}
+
+/// Internal API
+///
+/// Wraps a structured field passed without a prefix, such that calling
+/// `(&FieldRef(&value)).field_value()` with both [`TypedField`] and
+/// [`DisplayField`] in scope picks the typed [`Value`] of primitives, falling
+/// back to [`Value::Str`] for any other `Display` type
+#[doc(hidden)]
+pub struct FieldRef<'a, T>(pub &'a T);
+
+/// Internal API
+#[doc(hidden)]
+pub trait TypedField {
+    fn field_value(&self) -> Value;
+}
+
+/// Internal API
+#[doc(hidden)]
+pub trait DisplayField {
+    fn field_value(&self) -> Value;
+}
+
+impl<T: Display> DisplayField for &FieldRef<'_, T> {
+    fn field_value(&self) -> Value {
+        Value::Str(self.0.to_string())
+    }
+}
+
+macro_rules! impl_typed_field {
+    ($variant:ident as $as:ty: $($ty:ty),*) => {
+        $(
+            impl TypedField for FieldRef<'_, $ty> {
+                fn field_value(&self) -> Value {
+                    Value::$variant(*self.0 as $as)
+                }
+            }
+        )*
+    };
+}
+
+impl_typed_field!(Int as i64: i8, i16, i32, i64, isize);
+impl_typed_field!(UInt as u64: u8, u16, u32, u64, usize);
+impl_typed_field!(Float as f64: f32, f64);
+
+impl TypedField for FieldRef<'_, bool> {
+    fn field_value(&self) -> Value {
+        Value::Bool(*self.0)
+    }
+}
+
+/// Internal API
+///
+/// Wraps an argument of the logging macros, such that calling
+/// `(&&&&Owned(&arg)).owned()` with [`OwnedBorrow`], [`OwnedDeref`] and
+/// [`OwnedClone`] in scope takes an owned copy of it, to be moved into the
+/// log record. `Option<&T>`, `&&T` and `&T` are converted through
+/// [`ToOwned`], e.g. `Option<&str>` into `Option<String>`, and any other type
+/// is cloned.
+///
+/// The last trait in scope decides what happens to other types:
+/// [`EagerDebug`] and [`EagerDisplay`], for fields prefixed with `?` and `%`,
+/// format them eagerly into a [`Preformatted`], while [`RequireClone`]
+/// reports that they are not `Clone` at the argument.
+#[doc(hidden)]
+pub struct Owned<'a, T>(pub &'a T);
+
+/// Internal API
+#[doc(hidden)]
+pub trait OwnedBorrow {
+    type Owned;
+
+    fn owned(&self) -> Self::Owned;
+}
+
+impl<T: ToOwned + ?Sized> OwnedBorrow for &&&Owned<'_, Option<&T>> {
+    type Owned = Option<T::Owned>;
+
+    fn owned(&self) -> Self::Owned {
+        self.0.map(ToOwned::to_owned)
+    }
+}
+
+impl<T: ToOwned + ?Sized> OwnedBorrow for &&&Owned<'_, &&T> {
+    type Owned = T::Owned;
+
+    fn owned(&self) -> Self::Owned {
+        (**self.0).to_owned()
+    }
+}
+
+/// Internal API
+#[doc(hidden)]
+pub trait OwnedDeref {
+    type Owned;
+
+    fn owned(&self) -> Self::Owned;
+}
+
+impl<T: ToOwned + ?Sized> OwnedDeref for &&Owned<'_, &T> {
+    type Owned = T::Owned;
+
+    fn owned(&self) -> Self::Owned {
+        (*self.0).to_owned()
+    }
+}
+
+/// Internal API
+#[doc(hidden)]
+pub trait OwnedClone {
+    type Owned;
+
+    fn owned(&self) -> Self::Owned;
+}
+
+impl<T: Clone> OwnedClone for &Owned<'_, T> {
+    type Owned = T;
+
+    fn owned(&self) -> Self::Owned {
+        self.0.clone()
+    }
+}
+
+// The fallbacks below are implemented for every type, with the bound on the
+// method instead, so that a missing bound is reported as such rather than as
+// a missing method
+
+/// Internal API
+#[doc(hidden)]
+pub trait RequireClone<T> {
+    fn owned(&self) -> T
+    where
+        T: Clone;
+}
+
+impl<T> RequireClone<T> for Owned<'_, T> {
+    fn owned(&self) -> T
+    where
+        T: Clone,
+    {
+        self.0.clone()
+    }
+}
+
+/// Internal API
+#[doc(hidden)]
+pub trait EagerDebug<T> {
+    fn owned(&self) -> Preformatted
+    where
+        T: std::fmt::Debug;
+}
+
+impl<T> EagerDebug<T> for Owned<'_, T> {
+    fn owned(&self) -> Preformatted
+    where
+        T: std::fmt::Debug,
+    {
+        Preformatted(format!("{:?}", self.0))
+    }
+}
+
+/// Internal API
+#[doc(hidden)]
+pub trait EagerDisplay<T> {
+    fn owned(&self) -> Preformatted
+    where
+        T: Display;
+}
+
+impl<T> EagerDisplay<T> for Owned<'_, T> {
+    fn owned(&self) -> Preformatted
+    where
+        T: Display,
+    {
+        Preformatted(self.0.to_string())
+    }
+}
@@ -27,14 +27,111 @@ macro_rules! with_flush_into_file {
     }};
 }
 
-/// Initializes Quicklog by calling [`Quicklog::init()`]
+/// Initializes Quicklog by calling [`Quicklog::init_with_version()`]
 /// Should only be called once in the application
 ///
-/// [`Quicklog::init()`]: crate::Quicklog::init
+/// The application version recorded in [`AppInfo`] defaults to the calling
+/// crate's `CARGO_PKG_VERSION`, and can be overridden with
+/// `init!(version = "1.2.3")`.
+///
+/// A [`Config`] can also be passed in, which calls
+/// [`Quicklog::init_with_config()`] instead, e.g.
+/// `init!(config().overflow_policy(OverflowPolicy::Block))`. This returns
+/// the [`BackgroundFlush`] handle if a background flush thread was configured.
+/// `init!(config().stamp(build_info!()))` also logs the build of the binary
+/// as the first record, see [`build_info!`](crate::build_info).
+///
+/// [`Quicklog::init_with_version()`]: crate::Quicklog::init_with_version
+/// [`Quicklog::init_with_config()`]: crate::Quicklog::init_with_config
+/// [`AppInfo`]: crate::app::AppInfo
+/// [`Config`]: crate::Config
+/// [`BackgroundFlush`]: crate::background::BackgroundFlush
 #[macro_export]
 macro_rules! init {
     () => {
-        $crate::logger().init();
+        $crate::logger().init_with_version(::std::option_env!("CARGO_PKG_VERSION"));
+    };
+    (version = $version:expr) => {
+        $crate::logger().init_with_version(::std::option::Option::Some($version));
+    };
+    ($config:expr) => {
+        $crate::logger().init_with_config($config)
+    };
+}
+
+/// Logs at [`FATAL`](crate::level::FATAL) on the calling thread: every queued
+/// log line is flushed, then the line is formatted and written straight to
+/// the flusher, and the flushers are closed through
+/// [`Flush::close`](quicklog_flush::Flush::close). Last-gasp errors thus
+/// reach the disk before the process aborts, without waiting for the thread
+/// which usually flushes, e.g. through
+/// [`Config::background_flush`](crate::Config::background_flush), and even
+/// when the logging queue is full, regardless of the
+/// [`OverflowPolicy`](crate::OverflowPolicy).
+///
+/// Takes the same arguments as [`info!`](crate::info):
+///
+/// ```no_run
+/// use quicklog::{fatal, init};
+///
+/// # init!();
+/// # let err = "disk full";
+/// fatal!(%err, "journal unwritable, aborting");
+/// std::process::abort();
+/// ```
+///
+/// Records are never dequeued by two threads at once: if another thread is
+/// flushing at the same time, the calling thread waits for it to finish its
+/// batch before flushing the rest. If that thread is still flushing after
+/// half a second, e.g. as its flusher is stuck, the line is written anyway,
+/// leaving the queue to that thread. Nothing is flushed if the consumer side
+/// of the logging queue was handed over through
+/// [`init_split!`](crate::init_split), in which case the line is queued for
+/// its [`FlushHandle`](crate::FlushHandle).
+#[macro_export]
+macro_rules! fatal {
+    ($($args:tt)*) => {
+        $crate::log_fatal!($($args)*)
+    };
+}
+
+/// Creates a [`BuildInfo`](crate::app::BuildInfo) describing the calling
+/// crate, to be logged at init through [`Config::stamp`](crate::Config::stamp).
+///
+/// The name and version are taken from `CARGO_PKG_NAME` and
+/// `CARGO_PKG_VERSION`, and the git hash from `QUICKLOG_GIT_HASH` if it was
+/// set at build time, e.g. from a build script:
+///
+/// ```ignore
+/// // build.rs
+/// let output = std::process::Command::new("git")
+///     .args(["rev-parse", "--short", "HEAD"])
+///     .output()
+///     .unwrap();
+/// let hash = String::from_utf8(output.stdout).unwrap();
+/// println!("cargo:rustc-env=QUICKLOG_GIT_HASH={}", hash.trim());
+/// ```
+#[macro_export]
+macro_rules! build_info {
+    () => {
+        $crate::app::BuildInfo::new(
+            ::std::env!("CARGO_PKG_NAME"),
+            ::std::env!("CARGO_PKG_VERSION"),
+            ::std::option_env!("QUICKLOG_GIT_HASH"),
+        )
+    };
+}
+
+/// Same as [`init!`] with a [`Config`](crate::Config), but returns a
+/// [`FlushHandle`](crate::FlushHandle) owning the consumer side of the
+/// logging queue, see [`split`](crate::split)
+#[macro_export]
+macro_rules! init_split {
+    () => {
+        $crate::logger().init_split($crate::config())
+    };
+    ($config:expr) => {
+        $crate::logger().init_split($config)
     };
 }
 
@@ -63,10 +160,72 @@ macro_rules! make_container {
 #[macro_export]
 macro_rules! is_level_enabled {
     ($level:expr) => {
-        $level as usize >= $crate::level::max_level() as usize
+        $crate::level::max_level().enables($level)
+    };
+}
+
+/// Whether log lines at `level` from the calling line would be recorded,
+/// applying the same checks as the logging macros: the minimum level compiled
+/// in through `QUICKLOG_MIN_LEVEL`, the maximum level set at runtime, then
+/// the [callsite](crate::callsite) of the calling line, which can be
+/// disabled or filtered out through [`Config::filter`](crate::Config::filter)
+/// like that of any logging macro. As `level` may only be known at runtime,
+/// the callsite is registered without a level.
+///
+/// This guards preparing arguments which are too expensive to compute for a
+/// log line which is then discarded, and cannot be deferred through `^` or a
+/// [`Lazy`](crate::Lazy) argument:
+///
+/// ```
+/// use quicklog::{debug, enabled, init, level::Level};
+///
+/// # init!();
+/// # let book = vec![(101.5, 10), (101.0, 4)];
+/// if enabled!(Level::Debug) {
+///     let depth: u32 = book.iter().map(|(_, qty)| qty).sum();
+///     debug!("book depth {}", depth);
+/// }
+/// ```
+#[macro_export]
+macro_rules! enabled {
+    ($level:expr) => {
+        $crate::callsite_enabled!(@dynamic $level)
     };
 }
 
+/// Registers a callsite for the calling line, and checks whether a log record
+/// at `level` from it would be recorded, applying the same checks as the
+/// logging macros, not meant for external use. `@dynamic` registers the
+/// callsite without a level, for levels only known at runtime.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! callsite_enabled {
+    (@dynamic $level:expr) => {
+        $crate::callsite_enabled!(@callsite None, $level)
+    };
+    ($level:expr) => {
+        $crate::callsite_enabled!(@callsite Some($level), $level)
+    };
+    (@callsite $callsite_level:expr, $level:expr) => {{
+        static CALLSITE: $crate::callsite::Callsite = $crate::callsite::Callsite::new(
+            $callsite_level,
+            $crate::module_path!(),
+            $crate::file!(),
+            $crate::line!(),
+            "",
+        );
+        $crate::inventory::submit!($crate::callsite::Registration::new(&CALLSITE));
+
+        const STATIC_MIN_PRIORITY: usize =
+            $crate::level::static_min_priority($crate::module_path!());
+        let level: $crate::level::Level = $level;
+        level.priority() >= STATIC_MIN_PRIORITY
+            && CALLSITE.is_enabled()
+            && $crate::is_level_enabled!(level)
+            && $crate::callsite::filter_enables(&CALLSITE)
+    }};
+}
+
 // in debug, without clone, we have to make a Arc of Store, this ensures
 // we are able to properly keep track of the stores we are using
 //
@@ -75,9 +234,28 @@ macro_rules! is_level_enabled {
 #[macro_export]
 macro_rules! make_store {
     ($serializable:expr) => {{
-        use $crate::serialize::Serialize;
-        let (store, _) = $serializable
-            .encode($crate::logger().get_chunk_as_mut($serializable.buffer_size_required()));
+        use $crate::serialize::EncodeArg as _;
+        let size = $serializable.arg_size_required();
+        let max_size = $crate::logger().max_record_size();
+        let (store, _) = if size <= max_size {
+            $serializable.encode_arg($crate::logger().get_chunk_as_mut(size))
+        } else {
+            $serializable.encode_arg_truncated($crate::logger().get_chunk_as_mut(max_size))
+        };
+
+        store
+    }};
+    (@to $logger:expr, $serializable:expr) => {{
+        use $crate::serialize::EncodeArg as _;
+        let size = $serializable.arg_size_required();
+        let max_size = $logger.max_record_size();
+        // SAFETY: the store is only referenced by the log record passed to
+        // the same logger by the `*_to!` macros
+        let (store, _) = if size <= max_size {
+            $serializable.encode_arg(unsafe { $logger.get_chunk_as_static_mut(size) })
+        } else {
+            $serializable.encode_arg_truncated(unsafe { $logger.get_chunk_as_static_mut(max_size) })
+        };
 
         store
     }};
@@ -110,11 +288,221 @@ macro_rules! flush {
 /// Allows flushing onto an implementor of [`Flush`], which can be modified with
 /// [`with_flush!`] macro and continues trying to flush until no more lines need flushing.
 ///
+/// Returns the [`FlushStats`] of the records flushed.
+///
 /// [`Flush`]: `quicklog_flush::Flush`
+/// [`FlushStats`]: crate::FlushStats
 #[macro_export]
 macro_rules! flush_all {
     () => {
-        while let Ok(()) = $crate::try_flush!() {}
+        $crate::logger().flush_n(usize::MAX)
+    };
+}
+
+/// Records an amount to be added to the counter `name`, see
+/// [`metric`](crate::metric)
+///
+/// The value is converted to `u64` through `as`.
+#[macro_export]
+macro_rules! counter {
+    ($name:expr, $value:expr) => {
+        $crate::metric::record(
+            $crate::metric::Metric::counter($name, $value as u64),
+            $crate::module_path!(),
+            $crate::file!(),
+            $crate::line!(),
+        )
+    };
+}
+
+/// Records the current value of the gauge `name`, see
+/// [`metric`](crate::metric)
+///
+/// The value is converted to `f64` through `as`.
+#[macro_export]
+macro_rules! gauge {
+    ($name:expr, $value:expr) => {
+        $crate::metric::record(
+            $crate::metric::Metric::gauge($name, $value as f64),
+            $crate::module_path!(),
+            $crate::file!(),
+            $crate::line!(),
+        )
+    };
+}
+
+/// Creates a [`RecordBuilder`](crate::builder::RecordBuilder) at
+/// [`Level::Trace`](crate::level::Level::Trace), see [`builder`](crate::builder)
+#[macro_export]
+macro_rules! trace_builder {
+    () => {
+        $crate::builder::RecordBuilder::new(
+            $crate::level::Level::Trace,
+            $crate::module_path!(),
+            $crate::file!(),
+            $crate::line!(),
+            $crate::callsite_enabled!($crate::level::Level::Trace),
+        )
+    };
+}
+
+/// Creates a [`RecordBuilder`](crate::builder::RecordBuilder) at
+/// [`Level::Debug`](crate::level::Level::Debug), see [`builder`](crate::builder)
+#[macro_export]
+macro_rules! debug_builder {
+    () => {
+        $crate::builder::RecordBuilder::new(
+            $crate::level::Level::Debug,
+            $crate::module_path!(),
+            $crate::file!(),
+            $crate::line!(),
+            $crate::callsite_enabled!($crate::level::Level::Debug),
+        )
+    };
+}
+
+/// Creates a [`RecordBuilder`](crate::builder::RecordBuilder) at
+/// [`Level::Info`](crate::level::Level::Info), see [`builder`](crate::builder)
+#[macro_export]
+macro_rules! info_builder {
+    () => {
+        $crate::builder::RecordBuilder::new(
+            $crate::level::Level::Info,
+            $crate::module_path!(),
+            $crate::file!(),
+            $crate::line!(),
+            $crate::callsite_enabled!($crate::level::Level::Info),
+        )
+    };
+}
+
+/// Creates a [`RecordBuilder`](crate::builder::RecordBuilder) at
+/// [`Level::Warn`](crate::level::Level::Warn), see [`builder`](crate::builder)
+#[macro_export]
+macro_rules! warn_builder {
+    () => {
+        $crate::builder::RecordBuilder::new(
+            $crate::level::Level::Warn,
+            $crate::module_path!(),
+            $crate::file!(),
+            $crate::line!(),
+            $crate::callsite_enabled!($crate::level::Level::Warn),
+        )
+    };
+}
+
+/// Creates a [`RecordBuilder`](crate::builder::RecordBuilder) at
+/// [`Level::Error`](crate::level::Level::Error), see [`builder`](crate::builder)
+#[macro_export]
+macro_rules! error_builder {
+    () => {
+        $crate::builder::RecordBuilder::new(
+            $crate::level::Level::Error,
+            $crate::module_path!(),
+            $crate::file!(),
+            $crate::line!(),
+            $crate::callsite_enabled!($crate::level::Level::Error),
+        )
+    };
+}
+
+/// Queues a string literal without copying it, and any other string through
+/// `String::from`, for the [`info_raw!`] family of macros, not meant for
+/// external use
+#[doc(hidden)]
+#[macro_export]
+macro_rules! emit_raw {
+    ($level:expr, $message:literal) => {
+        $crate::emit_raw!(@queue $level, $message)
+    };
+    ($level:expr, $message:expr) => {
+        $crate::emit_raw!(@queue $level, ::std::string::String::from($message))
+    };
+    (@queue $level:expr, $message:expr) => {
+        if $crate::callsite_enabled!($level) {
+            $crate::log_raw(
+                $level,
+                $message,
+                $crate::module_path!(),
+                $crate::file!(),
+                $crate::line!(),
+            )
+        }
+    };
+}
+
+/// Logs a string at [`Level::Trace`](crate::level::Level::Trace) as is, see
+/// [`log_raw`](crate::log_raw)
+#[macro_export]
+macro_rules! trace_raw {
+    ($message:expr) => {
+        $crate::emit_raw!($crate::level::Level::Trace, $message)
+    };
+}
+
+/// Logs a string at [`Level::Debug`](crate::level::Level::Debug) as is, see
+/// [`log_raw`](crate::log_raw)
+#[macro_export]
+macro_rules! debug_raw {
+    ($message:expr) => {
+        $crate::emit_raw!($crate::level::Level::Debug, $message)
+    };
+}
+
+/// Logs a string at [`Level::Info`](crate::level::Level::Info) as is, see
+/// [`log_raw`](crate::log_raw)
+#[macro_export]
+macro_rules! info_raw {
+    ($message:expr) => {
+        $crate::emit_raw!($crate::level::Level::Info, $message)
+    };
+}
+
+/// Logs a string at [`Level::Warn`](crate::level::Level::Warn) as is, see
+/// [`log_raw`](crate::log_raw)
+#[macro_export]
+macro_rules! warn_raw {
+    ($message:expr) => {
+        $crate::emit_raw!($crate::level::Level::Warn, $message)
+    };
+}
+
+/// Logs a string at [`Level::Error`](crate::level::Level::Error) as is, see
+/// [`log_raw`](crate::log_raw)
+#[macro_export]
+macro_rules! error_raw {
+    ($message:expr) => {
+        $crate::emit_raw!($crate::level::Level::Error, $message)
+    };
+}
+
+/// Resets the global logger through [`shutdown()`](crate::shutdown()), and
+/// initializes it again to capture log lines in memory, returning the
+/// [`CaptureFlusher`](crate::test_support::CaptureFlusher), see
+/// [`test_support`](crate::test_support)
+#[macro_export]
+macro_rules! init_test {
+    () => {
+        $crate::test_support::init_capture()
     };
 }
 
+/// Flushes all queued log records, and asserts that a line was logged at
+/// `level` which contains `contains`, since the last [`init_test!`]
+#[macro_export]
+macro_rules! assert_logged {
+    ($level:expr, $contains:expr) => {{
+        let level = $level;
+        let contains = $contains;
+        let lines = $crate::test_support::flush_captured();
+        assert!(
+            lines
+                .iter()
+                .any(|line| $crate::test_support::line_matches(line, level, contains)),
+            "no line logged at {} containing {:?}, captured: {:#?}",
+            level,
+            contains,
+            lines
+        );
+    }};
+}
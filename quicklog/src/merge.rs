@@ -0,0 +1,81 @@
+//! Flushing several loggers into one output, in the order records were
+//! logged.
+//!
+//! When several loggers created through [`Quicklog::new`](crate::Quicklog::new),
+//! e.g. one per thread, write to the same file, flushing each of them in turn
+//! interleaves their log lines out of order.
+//! [`flush_merged`](crate::merge::flush_merged) instead merges the records
+//! queued in all of them by the instant they were logged at, and writes them
+//! through the flusher and formatter of the first logger:
+//!
+//! ```
+//! use quicklog::{config, info_to, merge::flush_merged, Quicklog};
+//! use quicklog_flush::noop_flusher::NoopFlusher;
+//!
+//! let mut orders = Quicklog::new(config().flusher(NoopFlusher));
+//! let mut fills = Quicklog::new(config());
+//! info_to!(&mut orders, "order {} sent", 1);
+//! info_to!(&mut fills, "order {} filled", 1);
+//!
+//! // flushed through the flusher of `orders`
+//! let stats = flush_merged(&mut [&mut orders, &mut fills], usize::MAX);
+//! assert_eq!(stats.flushed, 2);
+//! ```
+//!
+//! Instants are only comparable if the loggers read them from the same
+//! source, such as the default [`QuantaClock`](quicklog_clock::quanta::QuantaClock)
+//! or clones of the same [`MockClock`](quicklog_clock::mock::MockClock).
+
+use std::sync::atomic::Ordering;
+
+use crate::{dequeue, peek_time, FlushStats, Quicklog};
+
+/// Flushes up to `max` records queued in any of `loggers`, oldest first,
+/// through the flusher, formatter and routes of the first logger, stopping
+/// early once all queues are empty. Records logged at the same instant are
+/// flushed in the order of `loggers`.
+///
+/// Each record is picked by comparing the oldest record of every logger,
+/// including records which spilled over its queue, so this is intended for a
+/// handful of loggers. No other thread dequeues from any of them meanwhile,
+/// as with [`Quicklog::flush_n`].
+pub fn flush_merged(loggers: &mut [&mut Quicklog], max: usize) -> FlushStats {
+    let _guards: Vec<_> = loggers
+        .iter()
+        .map(|logger| logger.consumer.lock())
+        .collect();
+    let mut stats = FlushStats::default();
+    for _ in 0..max {
+        let oldest = loggers
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, logger)| {
+                let time_logged = peek_time(logger.receiver.get()?, &logger.spill)?;
+                Some((time_logged, idx))
+            })
+            .min();
+        let Some((_, idx)) = oldest else {
+            break;
+        };
+
+        let source = &mut loggers[idx];
+        let Some((time_logged, record)) = source
+            .receiver
+            .get_mut()
+            .and_then(|receiver| dequeue(receiver, &source.spill))
+        else {
+            break;
+        };
+        source.dequeued.fetch_add(1, Ordering::Relaxed);
+
+        match loggers[0].flush_record(time_logged, record) {
+            Ok(bytes) => {
+                stats.flushed += 1;
+                stats.bytes += bytes;
+            }
+            Err(_) => stats.errors += 1,
+        }
+    }
+
+    stats
+}
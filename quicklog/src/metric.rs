@@ -0,0 +1,143 @@
+//! Metrics recorded through the [`counter!`](crate::counter) and
+//! [`gauge!`](crate::gauge) macros.
+//!
+//! A metric is queued as a log record at [`Level::Info`], whose message is
+//! the metric name and whose fields are the kind of metric and its value, so
+//! that it is flushed along with the other log lines:
+//!
+//! ```
+//! use quicklog::{counter, gauge, init};
+//!
+//! # init!();
+//! counter!("orders_sent", 1);
+//! gauge!("queue_depth", 12.5);
+//! ```
+//!
+//! With the default formatter, these are written as
+//! `orders_sent metric=counter value=1`, and with the JSON formatter as
+//! `"message":"orders_sent","fields":{"metric":"counter","value":1}`, with
+//! the value written as a number.
+
+use std::fmt::{Display, Formatter, Result};
+
+use crate::{
+    is_level_enabled,
+    level::Level,
+    line::{FieldVisitor, LogLine, ValueVisitor},
+    logger,
+    serialize::Value,
+    span, Log, LogRecord,
+};
+
+/// Kind of a [`Metric`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MetricKind {
+    /// Amount to be added to a running total
+    Counter,
+    /// Current value of a measurement, replacing the previous one
+    Gauge,
+}
+
+impl MetricKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MetricKind::Counter => "counter",
+            MetricKind::Gauge => "gauge",
+        }
+    }
+}
+
+impl Display for MetricKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.pad(self.as_str())
+    }
+}
+
+/// Log line recorded by the [`counter!`](crate::counter) and
+/// [`gauge!`](crate::gauge) macros
+#[derive(Clone, Debug, PartialEq)]
+pub struct Metric {
+    kind: MetricKind,
+    name: &'static str,
+    value: Value,
+}
+
+impl Metric {
+    pub fn counter(name: &'static str, value: u64) -> Self {
+        Self {
+            kind: MetricKind::Counter,
+            name,
+            value: Value::UInt(value),
+        }
+    }
+
+    pub fn gauge(name: &'static str, value: f64) -> Self {
+        Self {
+            kind: MetricKind::Gauge,
+            name,
+            value: Value::Float(value),
+        }
+    }
+
+    pub fn kind(&self) -> MetricKind {
+        self.kind
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Value of the metric, a [`Value::UInt`] for counters and a
+    /// [`Value::Float`] for gauges
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+}
+
+/// Writes `name metric=kind value=value`
+impl Display for Metric {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{} metric={} value={}", self.name, self.kind, self.value)
+    }
+}
+
+impl LogLine for Metric {
+    fn fmt_message(&self, f: &mut Formatter<'_>) -> Result {
+        f.write_str(self.name)
+    }
+
+    fn visit_fields(&self, visit: &mut FieldVisitor<'_>) -> Result {
+        visit("metric", &self.kind)?;
+        visit("value", &self.value)
+    }
+
+    fn visit_values(&self, visit: &mut ValueVisitor<'_>) -> Result {
+        visit("metric", Value::Str(self.kind.as_str().to_string()))?;
+        visit("value", self.value.clone())
+    }
+}
+
+/// Internal API
+///
+/// Queues `metric` if [`Level::Info`] is enabled, used by the
+/// [`counter!`](crate::counter) and [`gauge!`](crate::gauge) macros
+#[doc(hidden)]
+pub fn record(metric: Metric, module_path: &'static str, file: &'static str, line: u32) {
+    if !is_level_enabled!(Level::Info) {
+        return;
+    }
+
+    let log_record = LogRecord {
+        level: Level::Info,
+        module_path,
+        file,
+        line,
+        span: span::current(),
+        sequence: 0,
+        thread: None,
+        context: None,
+        log_line: Box::new(metric),
+    };
+
+    _ = logger().log(log_record);
+}
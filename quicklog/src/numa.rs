@@ -0,0 +1,187 @@
+//! Placement of the logging queue and the flush thread on NUMA systems.
+//!
+//! Log records are handed from the logging threads to the flush thread
+//! through the memory of the logging queue, so the queue, the logging
+//! threads and the flush thread should sit on the same NUMA node, as
+//! traffic across nodes adds to the tail latency of logging.
+//!
+//! [`bind_queue_to_node`](crate::numa::bind_queue_to_node) binds the memory
+//! of the logging queue to a node, ideally before anything is logged, and
+//! [`spawn_flush_thread`](crate::numa::spawn_flush_thread) flushes on a
+//! background thread pinned to a core of that node:
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use quicklog::{init, numa};
+//!
+//! init!();
+//! numa::bind_queue_to_node(0).expect("failed to bind the logging queue");
+//! let _flush_handle = numa::spawn_flush_thread(3, Duration::from_millis(10))
+//!     .expect("failed to pin the flush thread");
+//! ```
+//!
+//! [`pin_current_thread`](crate::numa::pin_current_thread) pins any other
+//! thread, e.g. one flushing through a [`FlushHandle`](crate::FlushHandle).
+//! Records which spilled over through
+//! [`Config::grow_to`](crate::Config::grow_to) are not covered by the
+//! binding, and are allocated by the logging thread instead.
+//!
+//! These are only supported on Linux, and return an error of kind
+//! [`Unsupported`](std::io::ErrorKind::Unsupported) elsewhere.
+
+use std::{io, time::Duration};
+
+use crate::{background::BackgroundFlush, logger};
+
+/// Binds the memory of the queue of the global logger to NUMA node `node`,
+/// see [`Quicklog::bind_queue_to_node`](crate::Quicklog::bind_queue_to_node)
+pub fn bind_queue_to_node(node: usize) -> io::Result<()> {
+    logger().bind_queue_to_node(node)
+}
+
+/// Pins the calling thread to the CPU core `core_id`
+pub fn pin_current_thread(core_id: usize) -> io::Result<()> {
+    sys::pin_current_thread(core_id)
+}
+
+/// Spawns a thread pinned to the CPU core `core_id`, which flushes all queued
+/// log records every `interval`, in the same way as
+/// [`Config::background_flush`](crate::Config::background_flush).
+///
+/// Returns an error if the thread could not be pinned, in which case it is
+/// stopped without flushing anything.
+pub fn spawn_flush_thread(core_id: usize, interval: Duration) -> io::Result<BackgroundFlush> {
+    BackgroundFlush::spawn_pinned(interval, Some(core_id))
+}
+
+/// Binds `len` bytes of memory starting at `ptr` to NUMA node `node`, moving
+/// pages which were already allocated elsewhere.
+///
+/// The whole pages spanned by the memory are bound, including the parts of
+/// them which lie outside of it.
+pub(crate) fn bind_memory(ptr: *const u8, len: usize, node: usize) -> io::Result<()> {
+    sys::bind_memory(ptr, len, node)
+}
+
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+mod sys {
+    use std::{
+        io,
+        os::raw::{c_int, c_long, c_ulong},
+    };
+
+    #[cfg(target_arch = "x86_64")]
+    const SYS_MBIND: c_long = 237;
+    #[cfg(target_arch = "aarch64")]
+    const SYS_MBIND: c_long = 235;
+    const MPOL_BIND: c_ulong = 2;
+    const MPOL_MF_MOVE: c_ulong = 1 << 1;
+    const SC_PAGESIZE: c_int = 30;
+
+    extern "C" {
+        fn syscall(number: c_long, ...) -> c_long;
+        fn sysconf(name: c_int) -> c_long;
+        fn sched_setaffinity(pid: c_int, cpusetsize: usize, mask: *const c_ulong) -> c_int;
+    }
+
+    /// Bit mask with only bit `bit` set, as used for sets of CPUs and nodes
+    fn mask(bit: usize) -> Vec<c_ulong> {
+        let bits = c_ulong::BITS as usize;
+        let mut mask = vec![0; bit / bits + 1];
+        mask[bit / bits] = 1 << (bit % bits);
+        mask
+    }
+
+    pub(super) fn pin_current_thread(core_id: usize) -> io::Result<()> {
+        let mask = mask(core_id);
+        // SAFETY: `mask` is valid for `cpusetsize` bytes, and pid 0 is the
+        // calling thread
+        let res = unsafe { sched_setaffinity(0, std::mem::size_of_val(&mask[..]), mask.as_ptr()) };
+        match res {
+            0 => Ok(()),
+            _ => Err(io::Error::last_os_error()),
+        }
+    }
+
+    pub(super) fn bind_memory(ptr: *const u8, len: usize, node: usize) -> io::Result<()> {
+        // SAFETY: `sysconf` has no preconditions
+        let page_size = match unsafe { sysconf(SC_PAGESIZE) } {
+            size if size > 0 => size as usize,
+            _ => return Err(io::Error::last_os_error()),
+        };
+        let start = ptr as usize & !(page_size - 1);
+        let len = ptr as usize + len - start;
+
+        let mask = mask(node);
+        // The kernel ignores the last bit of `maxnode`
+        let max_node = (mask.len() * c_ulong::BITS as usize + 1) as c_ulong;
+        // SAFETY: only the memory policy of the pages spanned by the memory is
+        // changed, which does not affect their contents
+        let res = unsafe {
+            syscall(
+                SYS_MBIND,
+                start,
+                len,
+                MPOL_BIND,
+                mask.as_ptr(),
+                max_node,
+                MPOL_MF_MOVE,
+            )
+        };
+        match res {
+            0 => Ok(()),
+            _ => Err(io::Error::last_os_error()),
+        }
+    }
+}
+
+#[cfg(not(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+)))]
+mod sys {
+    use std::io;
+
+    fn unsupported() -> io::Error {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            "NUMA placement is only supported on Linux",
+        )
+    }
+
+    pub(super) fn pin_current_thread(_: usize) -> io::Result<()> {
+        Err(unsupported())
+    }
+
+    pub(super) fn bind_memory(_: *const u8, _: usize, _: usize) -> io::Result<()> {
+        Err(unsupported())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn pin_and_bind() {
+        // Fails with `EINVAL` only if core 0 is not available to the process
+        let pinned = std::thread::spawn(|| super::pin_current_thread(0))
+            .join()
+            .unwrap();
+        if let Err(err) = pinned {
+            assert_eq!(err.raw_os_error(), Some(22), "{}", err);
+        }
+        assert!(super::pin_current_thread(1 << 20).is_err());
+
+        let memory = vec![0u8; 1 << 16];
+        // Fails with `ENOSYS` or `EPERM` where memory policies are
+        // unavailable, e.g. in some containers, but never for node 0 otherwise
+        if let Err(err) = super::bind_memory(memory.as_ptr(), memory.len(), 0) {
+            assert!(matches!(err.raw_os_error(), Some(1 | 38)), "{}", err);
+        }
+        assert!(super::bind_memory(memory.as_ptr(), memory.len(), 1 << 20).is_err());
+    }
+}
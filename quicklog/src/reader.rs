@@ -0,0 +1,133 @@
+//! Reading queued log records without flushing them, to hand them to a
+//! custom flush loop or transport.
+//!
+//! [`records`](crate::reader::records) dequeues the records of the global
+//! logger along with the time they were logged at, leaving them unformatted,
+//! so that they can e.g. be shipped to another process over IPC:
+//!
+//! ```
+//! use quicklog::{info, init, reader};
+//!
+//! # init!();
+//! info!(id = 7, "order filled");
+//!
+//! for record in reader::records() {
+//!     // e.g. sent over a socket instead
+//!     assert_eq!(record.message, "order filled");
+//!     assert_eq!(record.args[0].0, "id");
+//! }
+//! ```
+//!
+//! [`Quicklog::records`](crate::Quicklog::records) and
+//! [`FlushHandle::records`](crate::FlushHandle::records) read the records of
+//! an owned logger or of the handle returned by
+//! [`init_split!`](crate::init_split) in the same way.
+//!
+//! Records are only dequeued as they are read, and are skipped in the same
+//! way as when flushing if their time cannot be computed or their `^`
+//! arguments were corrupted, see [`FlushErrorHook`](crate::FlushErrorHook).
+//! The message is formatted and the arguments logged with `^` are decoded
+//! from the serialization buffer of the logger into
+//! [`message`](crate::reader::Record::message) and
+//! [`args`](crate::reader::Record::args) as each record is read, so they
+//! remain valid however much is logged afterwards.
+
+use std::fmt::Write;
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    context, level::Level, line::LogLine, serialize::Value, span::SpanContext, thread::ThreadInfo,
+    FlushError, LogRecord, Quicklog,
+};
+
+/// Log record read off the queue, see [`reader`](crate::reader)
+pub struct Record {
+    /// Time the record was logged at, according to the clock of the logger
+    pub timestamp: DateTime<Utc>,
+    /// Level
+    pub level: Level,
+    /// Module path
+    pub module_path: &'static str,
+    /// File
+    pub file: &'static str,
+    /// Line
+    pub line: u32,
+    /// Spans entered when the record was logged
+    pub span: Option<SpanContext>,
+    /// Sequence number of the record, see [`LogRecord::sequence`]
+    pub sequence: u64,
+    /// Thread the record was logged from
+    pub thread: Option<ThreadInfo>,
+    /// Formatted message of the log line
+    pub message: String,
+    /// Names and typed values of the structured fields of the record,
+    /// followed by its context fields, in the same order as formatters
+    /// write them
+    pub args: Vec<(&'static str, Value)>,
+}
+
+impl Record {
+    /// Formats the message and decodes the arguments of `record` while they
+    /// are still valid in the serialization buffer, i.e. as soon as it is
+    /// dequeued
+    pub(crate) fn new(timestamp: DateTime<Utc>, record: LogRecord) -> Self {
+        let mut message = String::new();
+        // Writing into a `String` cannot fail
+        _ = write!(message, "{}", record.log_line.message());
+
+        let mut args = Vec::new();
+        let line = context::with_fields(&*record.log_line, record.context.as_ref());
+        // Collecting into a `Vec` cannot fail
+        _ = line.visit_values(&mut |name, value| {
+            args.push((name, value));
+            Ok(())
+        });
+
+        Self {
+            timestamp,
+            level: record.level,
+            module_path: record.module_path,
+            file: record.file,
+            line: record.line,
+            span: record.span,
+            sequence: record.sequence,
+            thread: record.thread,
+            message,
+            args,
+        }
+    }
+}
+
+/// Iterator over the records queued in a logger, oldest first, ending once
+/// the queue is empty
+pub struct Records<'a> {
+    logger: &'a mut Quicklog,
+}
+
+impl<'a> Records<'a> {
+    pub(crate) fn new(logger: &'a mut Quicklog) -> Self {
+        Self { logger }
+    }
+}
+
+impl Iterator for Records<'_> {
+    type Item = Record;
+
+    fn next(&mut self) -> Option<Record> {
+        loop {
+            match self.logger.read_one() {
+                Ok(record) => return Some(record),
+                Err(FlushError::Empty | FlushError::NotInitialized) => return None,
+                // Skipped in the same way as when flushing
+                Err(_) => {}
+            }
+        }
+    }
+}
+
+/// Reads the records queued in the global logger, see
+/// [`Quicklog::records`]
+pub fn records() -> Records<'static> {
+    crate::logger().records()
+}
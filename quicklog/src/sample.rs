@@ -0,0 +1,43 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Per-callsite counter, declared as a `static` by the `*_sampled!` macros
+pub struct Sampler {
+    count: AtomicU64,
+}
+
+impl Sampler {
+    pub const fn new() -> Self {
+        Self {
+            count: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns `true` for the first of every `rate` calls, starting with the
+    /// very first call. A `rate` of 0 or 1 samples every call.
+    pub fn sample(&self, rate: u64) -> bool {
+        let count = self.count.fetch_add(1, Ordering::Relaxed);
+        rate <= 1 || count % rate == 0
+    }
+}
+
+impl Default for Sampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Sampler;
+
+    #[test]
+    fn sample_one_in_rate() {
+        let sampler = Sampler::new();
+        let sampled: Vec<_> = (0..10).filter(|_| sampler.sample(4)).collect();
+        assert_eq!(sampled.len(), 3);
+
+        let sampler = Sampler::new();
+        assert!((0..5).all(|_| sampler.sample(1)));
+        assert!((0..5).all(|_| sampler.sample(0)));
+    }
+}
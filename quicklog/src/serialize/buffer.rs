@@ -8,14 +8,24 @@ pub struct ByteBuffer {
 
 impl ByteBuffer {
     pub fn new() -> Self {
-        let mut data = Vec::new();
-        data.resize(MAX_SERIALIZE_BUFFER_CAPACITY, 0);
-        Self { data, write_idx: 0 }
+        Self::with_capacity(MAX_SERIALIZE_BUFFER_CAPACITY)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            data: vec![0; capacity],
+            write_idx: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.data.len()
     }
 
     pub fn get_chunk_as_mut(&mut self, chunk_size: usize) -> &mut [u8] {
         let curr_idx = self.write_idx;
-        if chunk_size > MAX_SERIALIZE_BUFFER_CAPACITY {
+        let capacity = self.capacity();
+        if chunk_size > capacity {
             panic!(
                 "BUFFER size insufficient to support chunk_size: {}, please increase MAX_CAPACITY",
                 chunk_size
@@ -23,11 +33,11 @@ impl ByteBuffer {
         }
 
         // This condition guards against the case where the amount of data we want to write
-        // is greater than the MAX_SERIALIZE_BUFFER_CAPACITY. When this happens,
+        // is greater than the capacity of the buffer. When this happens,
         // it is possible that the initial log lines before the one that caused this overflow
         // will be wrong. This is EXPECTED.
         // When this happens, the user should modify the BUFFER_SIZE
-        if curr_idx + chunk_size > MAX_SERIALIZE_BUFFER_CAPACITY {
+        if curr_idx + chunk_size > capacity {
             self.write_idx = chunk_size;
             // in release, overwrite existing items without panic
             &mut self.data[0..chunk_size]
@@ -1,6 +1,25 @@
-use std::{fmt::Display, str::from_utf8};
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fmt::{Display, Write},
+    marker::PhantomData,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+    num::{NonZeroI32, NonZeroI64, NonZeroIsize, NonZeroU32, NonZeroU64, NonZeroUsize, Wrapping},
+    rc::Rc,
+    str::from_utf8,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use chrono::{DateTime, Local, NaiveDateTime, SecondsFormat, TimeZone, Utc};
 
 pub mod buffer;
+mod value;
+
+pub use value::Value;
 
 /// Allows specification of a custom way to serialize the Struct.
 ///
@@ -46,6 +65,28 @@ pub mod buffer;
 ///     c: NoSerializeStruct,
 /// }
 /// ```
+///
+/// Types which cannot be encoded field by field, such as enums with fields,
+/// can instead derive `Serialize` through their `Display` or `Debug`
+/// implementation with `#[quicklog(display)]` or `#[quicklog(debug)]`, in
+/// which case they are formatted eagerly when logged:
+/// ```
+/// use quicklog::Serialize;
+///
+/// #[derive(Debug, Serialize)]
+/// #[quicklog(debug)]
+/// enum Order {
+///     Limit { price: u64, qty: u64 },
+///     Market { qty: u64 },
+/// }
+/// ```
+///
+/// `Serialize` covers both sides of logging a value, while [`Encode`], which
+/// is all that logging a value with the `^` prefix requires, and [`Decode`],
+/// which is only called when flushing, are implemented for every type
+/// implementing `Serialize`. Either can also be implemented on its own
+/// instead, e.g. to decode values in a separate tool which does not depend on
+/// the types which encoded them.
 pub trait Serialize {
     /// Describes how to encode the implementing type into a byte buffer.
     ///
@@ -57,74 +98,423 @@ pub trait Serialize {
     /// Returns a formatted String after parsing the byte buffer, as well as
     /// the remainder of `read_buf` pass in that was not read.
     fn decode(read_buf: &[u8]) -> (String, &[u8]);
+    /// Describes how to decode the implementing type from a byte buffer into
+    /// a typed [Value](crate::serialize::Value).
+    ///
+    /// Defaults to wrapping the output of `decode` in
+    /// [`Value::Str`](crate::serialize::Value::Str).
+    fn decode_value(read_buf: &[u8]) -> (Value, &[u8]) {
+        let (s, rest) = <Self as Serialize>::decode(read_buf);
+        (Value::Str(s), rest)
+    }
+    /// Describes how to encode the implementing type into `write_buf` when
+    /// [`buffer_size_required`](Serialize::buffer_size_required) exceeds the
+    /// limit set through
+    /// [`Config::max_record_size`](crate::config::Config::max_record_size),
+    /// with `write_buf` being exactly as long as that limit, which is at
+    /// least [`MIN_TRUNCATED_SIZE`] bytes.
+    ///
+    /// Defaults to encoding `"..."` in place of the value. Strings and
+    /// collections override this to keep the leading characters or elements
+    /// which fit, followed by `...`.
+    fn encode_truncated<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+        Serialize::encode(&TRUNCATED_MARKER, write_buf)
+    }
     /// The number of bytes required to `encode` the type into a byte buffer.
     fn buffer_size_required(&self) -> usize;
 }
 
+/// Encodes a value on the hot path, see [`Serialize`].
+///
+/// The [`Store`] returned by [`encode`](Encode::encode) holds the function
+/// decoding it, usually the [`Decode`] implementation of the same type.
+pub trait Encode {
+    /// See [`Serialize::encode`]
+    fn encode<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]);
+    /// See [`Serialize::encode_truncated`]
+    fn encode_truncated<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+        Serialize::encode(&TRUNCATED_MARKER, write_buf)
+    }
+    /// See [`Serialize::buffer_size_required`]
+    fn buffer_size_required(&self) -> usize;
+}
+
+impl<T: Serialize + ?Sized> Encode for T {
+    fn encode<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+        Serialize::encode(self, write_buf)
+    }
+
+    fn encode_truncated<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+        Serialize::encode_truncated(self, write_buf)
+    }
+
+    fn buffer_size_required(&self) -> usize {
+        Serialize::buffer_size_required(self)
+    }
+}
+
+/// Encodes the arguments of the logging macros through [`Encode`], with
+/// method names which are not ambiguous when [`Serialize`] is also in scope,
+/// while keeping the auto-dereferencing of method calls.
+#[doc(hidden)]
+pub trait EncodeArg: Encode {
+    fn encode_arg<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+        Encode::encode(self, write_buf)
+    }
+
+    fn encode_arg_truncated<'buf>(
+        &self,
+        write_buf: &'buf mut [u8],
+    ) -> (Store<'buf>, &'buf mut [u8]) {
+        Encode::encode_truncated(self, write_buf)
+    }
+
+    fn arg_size_required(&self) -> usize {
+        Encode::buffer_size_required(self)
+    }
+}
+
+impl<T: Encode + ?Sized> EncodeArg for T {}
+
+/// Decodes a value encoded through [`Encode`] when flushing, see
+/// [`Serialize`].
+pub trait Decode {
+    /// See [`Serialize::decode`]
+    fn decode(read_buf: &[u8]) -> (String, &[u8]);
+    /// See [`Serialize::decode_value`]
+    fn decode_value(read_buf: &[u8]) -> (Value, &[u8]) {
+        let (s, rest) = Self::decode(read_buf);
+        (Value::Str(s), rest)
+    }
+}
+
+impl<T: Serialize + ?Sized> Decode for T {
+    fn decode(read_buf: &[u8]) -> (String, &[u8]) {
+        <T as Serialize>::decode(read_buf)
+    }
+
+    fn decode_value(read_buf: &[u8]) -> (Value, &[u8]) {
+        <T as Serialize>::decode_value(read_buf)
+    }
+}
+
 /// Function pointer which decodes a byte buffer back into `String` representation
 pub type DecodeFn = fn(&[u8]) -> (String, &[u8]);
 
+/// Function pointer which decodes a byte buffer back into a typed [Value]
+pub type DecodeValueFn = fn(&[u8]) -> (Value, &[u8]);
+
 /// Number of bytes it takes to store the size of a type.
 pub const SIZE_LENGTH: usize = std::mem::size_of::<usize>();
 
+/// Minimum number of bytes passed to
+/// [`encode_truncated`](Encode::encode_truncated), enough to encode the
+/// marker of a truncated string along with a few leading characters.
+pub const MIN_TRUNCATED_SIZE: usize = 2 * SIZE_LENGTH;
+
+/// Marker written in place of the part of a value which was truncated
+const TRUNCATED_MARKER: &str = "...";
+
+/// Set in the encoded length of collections which were truncated
+const TRUNCATED_LEN_FLAG: usize = 1 << (usize::BITS - 1);
+
+/// Splits the length prefix of a collection from `read_buf`, returning the
+/// number of encoded elements and whether the collection was truncated
+fn split_collection_len(read_buf: &[u8]) -> (usize, bool, &[u8]) {
+    let (len_chunk, rest) = read_buf.split_at(SIZE_LENGTH);
+    let len = usize::from_le_bytes(len_chunk.try_into().unwrap());
+
+    (
+        len & !TRUNCATED_LEN_FLAG,
+        len & TRUNCATED_LEN_FLAG != 0,
+        rest,
+    )
+}
+
 /// Contains the decode function required to decode `buffer` back into a `String`
 /// representation.
+///
+/// If enabled through [`Config::checksum`](crate::Config::checksum), a CRC-32
+/// of `buffer` is computed on creation and checked again before decoding, so
+/// that a buffer overwritten in the meantime is displayed as `<corrupted>`
+/// instead of being decoded.
 #[derive(Clone)]
 pub struct Store<'buf> {
     decode_fn: DecodeFn,
+    decode_value_fn: Option<DecodeValueFn>,
     buffer: &'buf [u8],
+    checksum: Option<u32>,
 }
 
-impl Store<'_> {
+impl<'buf> Store<'buf> {
     pub fn new(decode_fn: DecodeFn, buffer: &[u8]) -> Store {
-        Store { decode_fn, buffer }
+        Store {
+            decode_fn,
+            decode_value_fn: None,
+            buffer,
+            checksum: CHECKSUM.load(Ordering::Relaxed).then(|| crc32(buffer)),
+        }
+    }
+
+    /// Sets the function used by [`as_value`](Store::as_value), usually
+    /// `Self::decode_value` of the encoded type
+    pub fn with_decode_value(self, decode_value_fn: DecodeValueFn) -> Store<'buf> {
+        Store {
+            decode_value_fn: Some(decode_value_fn),
+            ..self
+        }
     }
 
     pub fn as_string(&self) -> String {
         let (s, _) = (self.decode_fn)(self.buffer);
         s
     }
+
+    /// Decodes the buffer into a typed [Value], falling back to
+    /// [`Value::Str`] if no typed decode function was provided
+    pub fn as_value(&self) -> Value {
+        if !self.is_intact() {
+            return Value::Str(CORRUPTED_STR.to_string());
+        }
+
+        match self.decode_value_fn {
+            Some(decode_value_fn) => decode_value_fn(self.buffer).0,
+            None => Value::Str(self.as_string()),
+        }
+    }
+
+    /// Whether the buffer still matches its checksum, if any, recording a
+    /// mismatch for [`detect_corruption`]
+    fn is_intact(&self) -> bool {
+        match self.checksum {
+            Some(checksum) if crc32(self.buffer) != checksum => {
+                CORRUPTED.with(|corrupted| corrupted.set(true));
+                false
+            }
+            _ => true,
+        }
+    }
 }
 
+/// Honors width, precision and alignment options, which are applied to the
+/// decoded `String` in the same way as for `str`, e.g. `{:>10.3}`
+///
+/// With the alternate flag, i.e. `{:#}`, or within [`pretty`], types with a
+/// typed decoding such as derived structs are written as their pretty-printed
+/// [`Value`] instead, e.g. with each field on its own line.
 impl Display for Store<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.as_string())
+        if !self.is_intact() {
+            return f.write_str(CORRUPTED_STR);
+        }
+        if self.decode_value_fn.is_some() && (f.alternate() || PRETTY.with(Cell::get)) {
+            return write!(f, "{:#}", self.as_value());
+        }
+
+        f.pad(&self.as_string())
+    }
+}
+
+/// Formats integers logged with `^` through the corresponding trait when
+/// flushing, e.g. `{:x}` or `{:#010b}`, based on their typed [`Value`].
+/// Signed integers are formatted as `i64`, so negative values are written in
+/// 64-bit two's complement.
+///
+/// Any other value is written through `Display` instead.
+macro_rules! impl_radix_fmt {
+    ($($trait:ident),*) => {
+        $(
+            impl std::fmt::$trait for Store<'_> {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    match self.as_value() {
+                        Value::Int(x) => std::fmt::$trait::fmt(&x, f),
+                        Value::UInt(x) => std::fmt::$trait::fmt(&x, f),
+                        _ => Display::fmt(self, f),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_radix_fmt!(LowerHex, UpperHex, Octal, Binary);
+
+thread_local! {
+    static PRETTY: Cell<bool> = Cell::new(false);
+    static FLOAT_PRECISION: Cell<Option<usize>> = Cell::new(None);
+    /// Set when a [`Store`] fails its checksum, see [`detect_corruption`]
+    static CORRUPTED: Cell<bool> = Cell::new(false);
+}
+
+/// Whether new [`Store`]s record a checksum, see
+/// [`Config::checksum`](crate::Config::checksum)
+static CHECKSUM: AtomicBool = AtomicBool::new(false);
+
+/// Written in place of a [`Store`] whose buffer no longer matches its checksum
+const CORRUPTED_STR: &str = "<corrupted>";
+
+pub(crate) fn set_checksum(enabled: bool) {
+    CHECKSUM.store(enabled, Ordering::Relaxed)
+}
+
+pub(crate) fn checksum_enabled() -> bool {
+    CHECKSUM.load(Ordering::Relaxed)
+}
+
+/// Runs `f`, returning whether any [`Store`] it decoded failed its checksum
+pub(crate) fn detect_corruption(f: impl FnOnce()) -> bool {
+    CORRUPTED.with(|corrupted| corrupted.set(false));
+    f();
+    CORRUPTED.with(|corrupted| corrupted.replace(false))
+}
+
+/// CRC-32 (IEEE 802.3) of `bytes`
+fn crc32(bytes: &[u8]) -> u32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0; 256];
+        let mut i = 0;
+        while i < 256 {
+            let mut crc = i as u32;
+            let mut bit = 0;
+            while bit < 8 {
+                crc = if crc & 1 == 1 {
+                    (crc >> 1) ^ 0xEDB8_8320
+                } else {
+                    crc >> 1
+                };
+                bit += 1;
+            }
+            table[i] = crc;
+            i += 1;
+        }
+        table
+    };
+
+    !bytes.iter().fold(!0, |crc, &byte| {
+        TABLE[((crc ^ u32::from(byte)) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+/// Runs `f` with every [`Store`] displayed as if formatted with `{:#}`,
+/// pretty-printing the logged values of derived structs and collections.
+///
+/// This allows formatters to choose between compact and pretty output while
+/// formatting a log line, e.g. through
+/// [`QuickLogFormatter::with_pretty`](crate::QuickLogFormatter::with_pretty).
+pub fn pretty<R>(f: impl FnOnce() -> R) -> R {
+    let previous = PRETTY.with(|pretty| pretty.replace(true));
+    let result = f();
+    PRETTY.with(|pretty| pretty.set(previous));
+
+    result
+}
+
+/// Runs `f` with every float logged with `^` written with `precision`
+/// decimal places, e.g. `1.500000` rather than `1.5` with a precision of 6,
+/// instead of the shortest representation which can be up to 17 digits long.
+///
+/// This allows formatters to set the precision of floats while formatting a
+/// log line, e.g. through
+/// [`QuickLogFormatter::with_float_precision`](crate::QuickLogFormatter::with_float_precision).
+pub fn float_precision<R>(precision: usize, f: impl FnOnce() -> R) -> R {
+    let previous = FLOAT_PRECISION.with(|p| p.replace(Some(precision)));
+    let result = f();
+    FLOAT_PRECISION.with(|p| p.set(previous));
+
+    result
+}
+
+/// Writes `x` with the precision set through [`float_precision`], if any
+pub(crate) fn write_float(f: &mut impl Write, x: impl Display) -> std::fmt::Result {
+    match FLOAT_PRECISION.with(Cell::get) {
+        Some(precision) => write!(f, "{:.*}", precision, x),
+        None => write!(f, "{}", x),
     }
 }
 
+fn format_float(x: &impl Display) -> String {
+    let mut s = String::new();
+    // Writing into a `String` cannot fail
+    _ = write_float(&mut s, x);
+    s
+}
+
 macro_rules! gen_serialize {
-    ($primitive:ty) => {
+    ($primitive:ty, $variant:ident) => {
+        gen_serialize!($primitive, $variant, ToString::to_string);
+    };
+    ($primitive:ty, $variant:ident, $to_string:path) => {
         impl Serialize for $primitive {
             fn encode<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
-                let size = self.buffer_size_required();
+                let size = Serialize::buffer_size_required(self);
                 let (x, rest) = write_buf.split_at_mut(size);
                 x.copy_from_slice(&self.to_le_bytes());
 
-                (Store::new(Self::decode, x), rest)
+                (
+                    Store::new(<Self as Serialize>::decode, x)
+                        .with_decode_value(<Self as Serialize>::decode_value),
+                    rest,
+                )
+            }
+
+            fn buffer_size_required(&self) -> usize {
+                std::mem::size_of::<$primitive>()
             }
 
             fn decode(read_buf: &[u8]) -> (String, &[u8]) {
                 let (chunk, rest) = read_buf.split_at(std::mem::size_of::<$primitive>());
                 let x = <$primitive>::from_le_bytes(chunk.try_into().unwrap());
 
-                (format!("{}", x), rest)
+                ($to_string(&x), rest)
             }
 
-            fn buffer_size_required(&self) -> usize {
-                std::mem::size_of::<$primitive>()
+            fn decode_value(read_buf: &[u8]) -> (Value, &[u8]) {
+                let (chunk, rest) = read_buf.split_at(std::mem::size_of::<$primitive>());
+                let x = <$primitive>::from_le_bytes(chunk.try_into().unwrap());
+
+                (Value::$variant(x as _), rest)
             }
         }
     };
 }
 
-gen_serialize!(i32);
-gen_serialize!(i64);
-gen_serialize!(isize);
-gen_serialize!(f32);
-gen_serialize!(f64);
-gen_serialize!(u32);
-gen_serialize!(u64);
-gen_serialize!(usize);
+gen_serialize!(i32, Int);
+gen_serialize!(i64, Int);
+gen_serialize!(isize, Int);
+gen_serialize!(f32, Float, format_float);
+gen_serialize!(f64, Float, format_float);
+gen_serialize!(u32, UInt);
+gen_serialize!(u64, UInt);
+gen_serialize!(usize, UInt);
+
+impl Serialize for bool {
+    fn encode<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+        let (chunk, rest) = write_buf.split_at_mut(Serialize::buffer_size_required(self));
+        chunk[0] = *self as u8;
+
+        (
+            Store::new(<Self as Serialize>::decode, chunk)
+                .with_decode_value(<Self as Serialize>::decode_value),
+            rest,
+        )
+    }
+
+    fn buffer_size_required(&self) -> usize {
+        1
+    }
+
+    fn decode(read_buf: &[u8]) -> (String, &[u8]) {
+        let (value, rest) = <Self as Serialize>::decode_value(read_buf);
+
+        (value.to_string(), rest)
+    }
+
+    fn decode_value(read_buf: &[u8]) -> (Value, &[u8]) {
+        let (chunk, rest) = read_buf.split_at(1);
+
+        (Value::Bool(chunk[0] != 0), rest)
+    }
+}
 
 impl Serialize for &str {
     fn encode<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
@@ -135,7 +525,38 @@ impl Serialize for &str {
         len_chunk.copy_from_slice(&str_len.to_le_bytes());
         str_chunk.copy_from_slice(self.as_bytes());
 
-        (Store::new(Self::decode, chunk), rest)
+        (
+            Store::new(<Self as Serialize>::decode, chunk)
+                .with_decode_value(<Self as Serialize>::decode_value),
+            rest,
+        )
+    }
+
+    /// Keeps as many leading characters as fit, followed by `...`
+    fn encode_truncated<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+        let mut end = write_buf.len() - SIZE_LENGTH - TRUNCATED_MARKER.len();
+        while !self.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        let str_len = end + TRUNCATED_MARKER.len();
+        let (chunk, rest) = write_buf.split_at_mut(str_len + SIZE_LENGTH);
+        let (len_chunk, str_chunk) = chunk.split_at_mut(SIZE_LENGTH);
+        let (prefix_chunk, marker_chunk) = str_chunk.split_at_mut(end);
+
+        len_chunk.copy_from_slice(&str_len.to_le_bytes());
+        prefix_chunk.copy_from_slice(&self.as_bytes()[..end]);
+        marker_chunk.copy_from_slice(TRUNCATED_MARKER.as_bytes());
+
+        (
+            Store::new(<Self as Serialize>::decode, chunk)
+                .with_decode_value(<Self as Serialize>::decode_value),
+            rest,
+        )
+    }
+
+    fn buffer_size_required(&self) -> usize {
+        SIZE_LENGTH + self.len()
     }
 
     fn decode(read_buf: &[u8]) -> (String, &[u8]) {
@@ -148,91 +569,1331 @@ impl Serialize for &str {
         (s.to_string(), rest)
     }
 
-    fn buffer_size_required(&self) -> usize {
-        SIZE_LENGTH + self.len()
+    fn decode_value(read_buf: &[u8]) -> (Value, &[u8]) {
+        let (s, rest) = <Self as Serialize>::decode(read_buf);
+
+        (Value::Str(s), rest)
     }
 }
 
-/// Eager evaluation into a String for debug structs
-pub fn encode_debug<T: std::fmt::Debug>(val: T, write_buf: &mut [u8]) -> (Store, &mut [u8]) {
-    let val_string = format!("{:?}", val);
-    let str_len = val_string.len();
+fn split_len_prefixed(read_buf: &[u8]) -> (&[u8], &[u8]) {
+    let (len_chunk, chunk) = read_buf.split_at(SIZE_LENGTH);
+    let len = usize::from_le_bytes(len_chunk.try_into().unwrap());
 
-    let (chunk, rest) = write_buf.split_at_mut(str_len + SIZE_LENGTH);
-    let (len_chunk, str_chunk) = chunk.split_at_mut(SIZE_LENGTH);
-    len_chunk.copy_from_slice(&str_len.to_le_bytes());
-    str_chunk.copy_from_slice(val_string.as_bytes());
+    chunk.split_at(len)
+}
 
-    (Store::new(<&str as Serialize>::decode, chunk), rest)
+fn escape_bytes(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .flat_map(|b| std::ascii::escape_default(*b))
+        .map(char::from)
+        .collect()
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::serialize::encode_debug;
+/// Formatted with non-printable bytes escaped, e.g. `GET\r\n\x00`
+impl Serialize for &[u8] {
+    fn encode<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+        let (chunk, rest) = write_buf.split_at_mut(Serialize::buffer_size_required(self));
+        let (len_chunk, bytes_chunk) = chunk.split_at_mut(SIZE_LENGTH);
 
-    use super::Serialize;
+        len_chunk.copy_from_slice(&self.len().to_le_bytes());
+        bytes_chunk.copy_from_slice(self);
 
-    macro_rules! assert_primitive_encode_decode {
-        ($primitive:ty, $val:expr) => {{
-            const BUF_SIZE: usize = std::mem::size_of::<$primitive>();
-            let mut buf = [0u8; BUF_SIZE];
+        (
+            Store::new(<Self as Serialize>::decode, chunk)
+                .with_decode_value(<Self as Serialize>::decode_value),
+            rest,
+        )
+    }
 
-            let x: $primitive = $val;
-            let (x_store, _) = x.encode(&mut buf);
-            assert_eq!(format!("{}", x), format!("{}", x_store));
-        }};
+    fn buffer_size_required(&self) -> usize {
+        SIZE_LENGTH + self.len()
     }
 
-    #[test]
-    fn serialize_primitives() {
-        assert_primitive_encode_decode!(i32, -1);
-        assert_primitive_encode_decode!(i64, -123);
-        assert_primitive_encode_decode!(isize, -1234);
-        assert_primitive_encode_decode!(f32, 1.23);
-        assert_primitive_encode_decode!(f64, 1.23456);
-        assert_primitive_encode_decode!(u32, 999);
-        assert_primitive_encode_decode!(u64, 9999);
-        assert_primitive_encode_decode!(usize, 99999);
+    fn decode(read_buf: &[u8]) -> (String, &[u8]) {
+        let (bytes, rest) = split_len_prefixed(read_buf);
+
+        (escape_bytes(bytes), rest)
     }
 
-    #[test]
-    fn serialize_multiple_primitives() {
-        let mut buf = [0; 128];
-        let a: i32 = -1;
-        let b: u32 = 999;
-        let c: usize = 100000;
+    fn decode_value(read_buf: &[u8]) -> (Value, &[u8]) {
+        let (bytes, rest) = split_len_prefixed(read_buf);
 
-        let (a_store, chunk) = a.encode(&mut buf);
-        let (b_store, chunk) = b.encode(chunk);
-        let (c_store, _) = c.encode(chunk);
+        (Value::Bytes(bytes.to_vec()), rest)
+    }
+}
 
-        assert_eq!(
-            format!("{} {} {}", a, b, c),
-            format!("{} {} {}", a_store, b_store, c_store)
+/// Formatted in the same way as `&[u8]`, without encoding the length
+impl<const N: usize> Serialize for [u8; N] {
+    fn encode<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+        let (chunk, rest) = write_buf.split_at_mut(N);
+        chunk.copy_from_slice(self);
+
+        (
+            Store::new(<Self as Serialize>::decode, chunk)
+                .with_decode_value(<Self as Serialize>::decode_value),
+            rest,
         )
     }
 
-    #[test]
-    fn serialize_str() {
-        let mut buf = [0; 128];
-        let s = "hello world";
-        let (store, _) = s.encode(&mut buf);
+    fn buffer_size_required(&self) -> usize {
+        N
+    }
 
-        assert_eq!(s, format!("{}", store).as_str())
+    fn decode(read_buf: &[u8]) -> (String, &[u8]) {
+        let (bytes, rest) = read_buf.split_at(N);
+
+        (escape_bytes(bytes), rest)
     }
 
-    #[test]
-    fn serialize_debug() {
-        #[derive(Debug)]
-        #[allow(unused)]
-        struct DebugStruct {
-            s: &'static str,
+    fn decode_value(read_buf: &[u8]) -> (Value, &[u8]) {
+        let (bytes, rest) = read_buf.split_at(N);
+
+        (Value::Bytes(bytes.to_vec()), rest)
+    }
+}
+
+/// Byte slice formatted as lowercase hex, e.g. `Bytes(&[0xde, 0xad])` as
+/// `dead`, for binary payloads which are unreadable when escaped
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bytes<'a>(pub &'a [u8]);
+
+impl Serialize for Bytes<'_> {
+    fn encode<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+        let (store, rest) = Serialize::encode(&self.0, write_buf);
+
+        (
+            Store::new(<Self as Serialize>::decode, store.buffer)
+                .with_decode_value(<Self as Serialize>::decode_value),
+            rest,
+        )
+    }
+
+    fn buffer_size_required(&self) -> usize {
+        Serialize::buffer_size_required(&self.0)
+    }
+
+    fn decode(read_buf: &[u8]) -> (String, &[u8]) {
+        let (bytes, rest) = split_len_prefixed(read_buf);
+        let mut hex = String::with_capacity(bytes.len() * 2);
+        for b in bytes {
+            _ = write!(hex, "{:02x}", b);
         }
 
-        let mut buf = [0; 128];
-        let s = DebugStruct { s: "Hello World" };
-        let (store, _) = encode_debug(&s, &mut buf);
+        (hex, rest)
+    }
+
+    fn decode_value(read_buf: &[u8]) -> (Value, &[u8]) {
+        <&[u8] as Decode>::decode_value(read_buf)
+    }
+}
+
+/// Formatted in the same way as `&str`
+impl<const N: usize> Serialize for heapless::String<N> {
+    fn encode<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+        Serialize::encode(&self.as_str(), write_buf)
+    }
+
+    fn encode_truncated<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+        Serialize::encode_truncated(&self.as_str(), write_buf)
+    }
+
+    fn buffer_size_required(&self) -> usize {
+        Serialize::buffer_size_required(&self.as_str())
+    }
+
+    fn decode(read_buf: &[u8]) -> (String, &[u8]) {
+        <&str as Decode>::decode(read_buf)
+    }
+
+    fn decode_value(read_buf: &[u8]) -> (Value, &[u8]) {
+        <&str as Decode>::decode_value(read_buf)
+    }
+}
+
+macro_rules! gen_serialize_map {
+    ($map:ident) => {
+        impl<K: Serialize, V: Serialize> Serialize for $map<K, V> {
+            fn encode<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+                let size = Serialize::buffer_size_required(self);
+                let (chunk, rest) = write_buf.split_at_mut(size);
+                let (len_chunk, mut entries_chunk) = chunk.split_at_mut(SIZE_LENGTH);
+                len_chunk.copy_from_slice(&self.len().to_le_bytes());
+
+                for (k, v) in self {
+                    let (_, remaining) = k.encode(entries_chunk);
+                    let (_, remaining) = v.encode(remaining);
+                    entries_chunk = remaining;
+                }
+
+                (
+                    Store::new(<Self as Serialize>::decode, chunk)
+                        .with_decode_value(<Self as Serialize>::decode_value),
+                    rest,
+                )
+            }
 
-        assert_eq!(format!("{:?}", s), format!("{}", store))
+            fn encode_truncated<'buf>(
+                &self,
+                write_buf: &'buf mut [u8],
+            ) -> (Store<'buf>, &'buf mut [u8]) {
+                let mut len = 0;
+                let mut size = SIZE_LENGTH;
+                for (k, v) in self {
+                    let entry_size = k.buffer_size_required() + v.buffer_size_required();
+                    if size + entry_size > write_buf.len() {
+                        break;
+                    }
+                    len += 1;
+                    size += entry_size;
+                }
+
+                let (chunk, rest) = write_buf.split_at_mut(size);
+                let (len_chunk, mut entries_chunk) = chunk.split_at_mut(SIZE_LENGTH);
+                len_chunk.copy_from_slice(&(len | TRUNCATED_LEN_FLAG).to_le_bytes());
+
+                for (k, v) in self.iter().take(len) {
+                    let (_, remaining) = k.encode(entries_chunk);
+                    let (_, remaining) = v.encode(remaining);
+                    entries_chunk = remaining;
+                }
+
+                (
+                    Store::new(<Self as Serialize>::decode, chunk)
+                        .with_decode_value(<Self as Serialize>::decode_value),
+                    rest,
+                )
+            }
+
+            fn buffer_size_required(&self) -> usize {
+                SIZE_LENGTH
+                    + self
+                        .iter()
+                        .map(|(k, v)| k.buffer_size_required() + v.buffer_size_required())
+                        .sum::<usize>()
+            }
+
+            fn decode(read_buf: &[u8]) -> (String, &[u8]) {
+                let (len, truncated, mut rest) = split_collection_len(read_buf);
+
+                let mut s = String::from("{");
+                for i in 0..len {
+                    if i > 0 {
+                        s.push_str(", ");
+                    }
+
+                    let (k, remaining) = K::decode(rest);
+                    let (v, remaining) = V::decode(remaining);
+                    s.push_str(&k);
+                    s.push_str(": ");
+                    s.push_str(&v);
+                    rest = remaining;
+                }
+                if truncated {
+                    s.push_str(if len > 0 { ", ..." } else { "..." });
+                }
+                s.push('}');
+
+                (s, rest)
+            }
+
+            fn decode_value(read_buf: &[u8]) -> (Value, &[u8]) {
+                // Maps have no entry to mark a truncation with, unlike lists
+                let (len, _, mut rest) = split_collection_len(read_buf);
+
+                let mut entries = Vec::with_capacity(len);
+                for _ in 0..len {
+                    let (k, remaining) = K::decode_value(rest);
+                    let (v, remaining) = V::decode_value(remaining);
+                    entries.push((k, v));
+                    rest = remaining;
+                }
+
+                (Value::Map(entries), rest)
+            }
+        }
+    };
+}
+
+macro_rules! gen_serialize_set {
+    ($set:ident) => {
+        impl<T: Serialize> Serialize for $set<T> {
+            fn encode<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+                let size = Serialize::buffer_size_required(self);
+                let (chunk, rest) = write_buf.split_at_mut(size);
+                let (len_chunk, mut elems_chunk) = chunk.split_at_mut(SIZE_LENGTH);
+                len_chunk.copy_from_slice(&self.len().to_le_bytes());
+
+                for elem in self {
+                    let (_, remaining) = elem.encode(elems_chunk);
+                    elems_chunk = remaining;
+                }
+
+                (
+                    Store::new(<Self as Serialize>::decode, chunk)
+                        .with_decode_value(<Self as Serialize>::decode_value),
+                    rest,
+                )
+            }
+
+            fn encode_truncated<'buf>(
+                &self,
+                write_buf: &'buf mut [u8],
+            ) -> (Store<'buf>, &'buf mut [u8]) {
+                let mut len = 0;
+                let mut size = SIZE_LENGTH;
+                for elem in self {
+                    let elem_size = elem.buffer_size_required();
+                    if size + elem_size > write_buf.len() {
+                        break;
+                    }
+                    len += 1;
+                    size += elem_size;
+                }
+
+                let (chunk, rest) = write_buf.split_at_mut(size);
+                let (len_chunk, mut elems_chunk) = chunk.split_at_mut(SIZE_LENGTH);
+                len_chunk.copy_from_slice(&(len | TRUNCATED_LEN_FLAG).to_le_bytes());
+
+                for elem in self.iter().take(len) {
+                    let (_, remaining) = elem.encode(elems_chunk);
+                    elems_chunk = remaining;
+                }
+
+                (
+                    Store::new(<Self as Serialize>::decode, chunk)
+                        .with_decode_value(<Self as Serialize>::decode_value),
+                    rest,
+                )
+            }
+
+            fn buffer_size_required(&self) -> usize {
+                SIZE_LENGTH + self.iter().map(Encode::buffer_size_required).sum::<usize>()
+            }
+
+            fn decode(read_buf: &[u8]) -> (String, &[u8]) {
+                let (len, truncated, mut rest) = split_collection_len(read_buf);
+
+                let mut s = String::from("{");
+                for i in 0..len {
+                    if i > 0 {
+                        s.push_str(", ");
+                    }
+
+                    let (elem, remaining) = T::decode(rest);
+                    s.push_str(&elem);
+                    rest = remaining;
+                }
+                if truncated {
+                    s.push_str(if len > 0 { ", ..." } else { "..." });
+                }
+                s.push('}');
+
+                (s, rest)
+            }
+
+            fn decode_value(read_buf: &[u8]) -> (Value, &[u8]) {
+                let (len, truncated, mut rest) = split_collection_len(read_buf);
+
+                let mut elems = Vec::with_capacity(len);
+                for _ in 0..len {
+                    let (elem, remaining) = T::decode_value(rest);
+                    elems.push(elem);
+                    rest = remaining;
+                }
+                if truncated {
+                    elems.push(Value::Str(TRUNCATED_MARKER.to_string()));
+                }
+
+                (Value::List(elems), rest)
+            }
+        }
+    };
+}
+
+gen_serialize_map!(HashMap);
+gen_serialize_map!(BTreeMap);
+gen_serialize_set!(HashSet);
+gen_serialize_set!(BTreeSet);
+
+fn split_duration(read_buf: &[u8]) -> (Duration, &[u8]) {
+    let (secs_chunk, rest) = read_buf.split_at(8);
+    let (nanos_chunk, rest) = rest.split_at(4);
+    let secs = u64::from_le_bytes(secs_chunk.try_into().unwrap());
+    let nanos = u32::from_le_bytes(nanos_chunk.try_into().unwrap());
+
+    (Duration::new(secs, nanos), rest)
+}
+
+fn write_duration(duration: &Duration, chunk: &mut [u8]) {
+    let (secs_chunk, nanos_chunk) = chunk.split_at_mut(8);
+    secs_chunk.copy_from_slice(&duration.as_secs().to_le_bytes());
+    nanos_chunk.copy_from_slice(&duration.subsec_nanos().to_le_bytes());
+}
+
+/// Formatted in the same way as its `Debug` representation, e.g. `1.5ms`
+impl Serialize for Duration {
+    fn encode<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+        let (chunk, rest) = write_buf.split_at_mut(Serialize::buffer_size_required(self));
+        write_duration(self, chunk);
+
+        (Store::new(<Self as Serialize>::decode, chunk), rest)
+    }
+
+    fn buffer_size_required(&self) -> usize {
+        12
+    }
+
+    fn decode(read_buf: &[u8]) -> (String, &[u8]) {
+        let (duration, rest) = split_duration(read_buf);
+
+        (format!("{:?}", duration), rest)
+    }
+}
+
+/// Formatted as an RFC3339 timestamp in UTC
+impl Serialize for SystemTime {
+    fn encode<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+        let (chunk, rest) = write_buf.split_at_mut(Serialize::buffer_size_required(self));
+        let (sign_chunk, duration_chunk) = chunk.split_at_mut(1);
+        match self.duration_since(UNIX_EPOCH) {
+            Ok(since_epoch) => {
+                sign_chunk[0] = 0;
+                write_duration(&since_epoch, duration_chunk);
+            }
+            Err(e) => {
+                sign_chunk[0] = 1;
+                write_duration(&e.duration(), duration_chunk);
+            }
+        }
+
+        (Store::new(<Self as Serialize>::decode, chunk), rest)
+    }
+
+    fn buffer_size_required(&self) -> usize {
+        13
+    }
+
+    fn decode(read_buf: &[u8]) -> (String, &[u8]) {
+        let (sign_chunk, rest) = read_buf.split_at(1);
+        let (duration, rest) = split_duration(rest);
+        let time = if sign_chunk[0] == 0 {
+            UNIX_EPOCH + duration
+        } else {
+            UNIX_EPOCH - duration
+        };
+        let time: DateTime<Utc> = time.into();
+
+        (time.to_rfc3339_opts(SecondsFormat::AutoSi, true), rest)
+    }
+}
+
+/// Nanoseconds since the Unix epoch, saturating outside of the years 1677 to
+/// 2262 which fit in an `i64`
+fn epoch_nanos(time: DateTime<Utc>) -> i64 {
+    time.timestamp()
+        .checked_mul(1_000_000_000)
+        .and_then(|nanos| nanos.checked_add(time.timestamp_subsec_nanos() as i64))
+        .unwrap_or(if time.timestamp() < 0 {
+            i64::MIN
+        } else {
+            i64::MAX
+        })
+}
+
+fn encode_epoch_nanos(nanos: i64, write_buf: &mut [u8]) -> (Store<'_>, &mut [u8]) {
+    let (chunk, rest) = write_buf.split_at_mut(std::mem::size_of::<i64>());
+    chunk.copy_from_slice(&nanos.to_le_bytes());
+
+    (Store::new(decode_epoch_nanos, chunk), rest)
+}
+
+/// Decodes nanoseconds since the Unix epoch as an RFC3339 timestamp in UTC
+fn decode_epoch_nanos(read_buf: &[u8]) -> (String, &[u8]) {
+    let (chunk, rest) = read_buf.split_at(std::mem::size_of::<i64>());
+    let nanos = i64::from_le_bytes(chunk.try_into().unwrap());
+
+    (
+        Utc.timestamp_nanos(nanos)
+            .to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        rest,
+    )
+}
+
+macro_rules! gen_serialize_datetime {
+    ($datetime:ty, $to_utc:expr) => {
+        /// Encoded as nanoseconds since the Unix epoch, and formatted as an
+        /// RFC3339 timestamp in UTC
+        impl Serialize for $datetime {
+            fn encode<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+                let to_utc: fn(&$datetime) -> DateTime<Utc> = $to_utc;
+                encode_epoch_nanos(epoch_nanos(to_utc(self)), write_buf)
+            }
+
+            fn buffer_size_required(&self) -> usize {
+                std::mem::size_of::<i64>()
+            }
+
+            fn decode(read_buf: &[u8]) -> (String, &[u8]) {
+                decode_epoch_nanos(read_buf)
+            }
+        }
+    };
+}
+
+gen_serialize_datetime!(DateTime<Utc>, |time| *time);
+gen_serialize_datetime!(DateTime<Local>, |time| time.with_timezone(&Utc));
+// Interpreted as a time in UTC
+gen_serialize_datetime!(NaiveDateTime, |time| Utc.from_utc_datetime(time));
+
+/// Encoded as nanoseconds since the Unix epoch, and formatted as an RFC3339
+/// timestamp in UTC, requires the `time` feature
+#[cfg(feature = "time")]
+impl Serialize for time::OffsetDateTime {
+    fn encode<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+        let nanos = self
+            .unix_timestamp_nanos()
+            .clamp(i64::MIN as i128, i64::MAX as i128) as i64;
+        encode_epoch_nanos(nanos, write_buf)
+    }
+
+    fn buffer_size_required(&self) -> usize {
+        std::mem::size_of::<i64>()
+    }
+
+    fn decode(read_buf: &[u8]) -> (String, &[u8]) {
+        decode_epoch_nanos(read_buf)
+    }
+}
+
+fn split_ip(read_buf: &[u8]) -> (IpAddr, &[u8]) {
+    let (tag_chunk, rest) = read_buf.split_at(1);
+    if tag_chunk[0] == 4 {
+        let (octets, rest) = rest.split_at(4);
+        let octets: [u8; 4] = octets.try_into().unwrap();
+        (IpAddr::V4(Ipv4Addr::from(octets)), rest)
+    } else {
+        let (octets, rest) = rest.split_at(16);
+        let octets: [u8; 16] = octets.try_into().unwrap();
+        (IpAddr::V6(Ipv6Addr::from(octets)), rest)
+    }
+}
+
+fn write_ip(ip: &IpAddr, chunk: &mut [u8]) {
+    let (tag_chunk, octets_chunk) = chunk.split_at_mut(1);
+    match ip {
+        IpAddr::V4(ip) => {
+            tag_chunk[0] = 4;
+            octets_chunk.copy_from_slice(&ip.octets());
+        }
+        IpAddr::V6(ip) => {
+            tag_chunk[0] = 6;
+            octets_chunk.copy_from_slice(&ip.octets());
+        }
+    }
+}
+
+impl Serialize for IpAddr {
+    fn encode<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+        let (chunk, rest) = write_buf.split_at_mut(Serialize::buffer_size_required(self));
+        write_ip(self, chunk);
+
+        (Store::new(<Self as Serialize>::decode, chunk), rest)
+    }
+
+    fn buffer_size_required(&self) -> usize {
+        match self {
+            IpAddr::V4(_) => 1 + 4,
+            IpAddr::V6(_) => 1 + 16,
+        }
+    }
+
+    fn decode(read_buf: &[u8]) -> (String, &[u8]) {
+        let (ip, rest) = split_ip(read_buf);
+
+        (ip.to_string(), rest)
+    }
+}
+
+impl Serialize for SocketAddr {
+    fn encode<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+        let (chunk, rest) = write_buf.split_at_mut(Serialize::buffer_size_required(self));
+        let ip = self.ip();
+        let (ip_chunk, port_chunk) = chunk.split_at_mut(Serialize::buffer_size_required(&ip));
+        write_ip(&ip, ip_chunk);
+
+        let (port_chunk, scope_chunk) = port_chunk.split_at_mut(2);
+        port_chunk.copy_from_slice(&self.port().to_le_bytes());
+        if let SocketAddr::V6(addr) = self {
+            scope_chunk.copy_from_slice(&addr.scope_id().to_le_bytes());
+        }
+
+        (Store::new(<Self as Serialize>::decode, chunk), rest)
+    }
+
+    fn buffer_size_required(&self) -> usize {
+        match self {
+            SocketAddr::V4(_) => 1 + 4 + 2,
+            SocketAddr::V6(_) => 1 + 16 + 2 + 4,
+        }
+    }
+
+    fn decode(read_buf: &[u8]) -> (String, &[u8]) {
+        let (ip, rest) = split_ip(read_buf);
+        let (port_chunk, rest) = rest.split_at(2);
+        let port = u16::from_le_bytes(port_chunk.try_into().unwrap());
+
+        match ip {
+            IpAddr::V4(ip) => (SocketAddrV4::new(ip, port).to_string(), rest),
+            IpAddr::V6(ip) => {
+                let (scope_chunk, rest) = rest.split_at(4);
+                let scope_id = u32::from_le_bytes(scope_chunk.try_into().unwrap());
+                (SocketAddrV6::new(ip, port, 0, scope_id).to_string(), rest)
+            }
+        }
+    }
+}
+
+macro_rules! gen_serialize_wrapper {
+    ($wrapper:ident) => {
+        /// Encoded as the value it points to
+        impl<T: Serialize> Serialize for $wrapper<T> {
+            fn encode<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+                (**self).encode(write_buf)
+            }
+
+            fn encode_truncated<'buf>(
+                &self,
+                write_buf: &'buf mut [u8],
+            ) -> (Store<'buf>, &'buf mut [u8]) {
+                (**self).encode_truncated(write_buf)
+            }
+
+            fn buffer_size_required(&self) -> usize {
+                (**self).buffer_size_required()
+            }
+
+            fn decode(read_buf: &[u8]) -> (String, &[u8]) {
+                T::decode(read_buf)
+            }
+
+            fn decode_value(read_buf: &[u8]) -> (Value, &[u8]) {
+                T::decode_value(read_buf)
+            }
+        }
+    };
+}
+
+gen_serialize_wrapper!(Arc);
+gen_serialize_wrapper!(Rc);
+
+/// Encoded as the wrapped value
+impl<T: Serialize> Serialize for Wrapping<T> {
+    fn encode<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+        self.0.encode(write_buf)
+    }
+
+    fn encode_truncated<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+        self.0.encode_truncated(write_buf)
+    }
+
+    fn buffer_size_required(&self) -> usize {
+        self.0.buffer_size_required()
+    }
+
+    fn decode(read_buf: &[u8]) -> (String, &[u8]) {
+        T::decode(read_buf)
+    }
+
+    fn decode_value(read_buf: &[u8]) -> (Value, &[u8]) {
+        T::decode_value(read_buf)
+    }
+}
+
+/// Encoded as a copy of the current value
+impl<T: Serialize + Copy> Serialize for Cell<T> {
+    fn encode<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+        self.get().encode(write_buf)
+    }
+
+    fn encode_truncated<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+        self.get().encode_truncated(write_buf)
+    }
+
+    fn buffer_size_required(&self) -> usize {
+        self.get().buffer_size_required()
+    }
+
+    fn decode(read_buf: &[u8]) -> (String, &[u8]) {
+        T::decode(read_buf)
+    }
+
+    fn decode_value(read_buf: &[u8]) -> (Value, &[u8]) {
+        T::decode_value(read_buf)
+    }
+}
+
+/// Marker written in place of a `RefCell` which is mutably borrowed while
+/// logging, as in its `Debug` representation
+const BORROWED_MARKER: &str = "<borrowed>";
+
+/// Encoded as the current value, or as `<borrowed>` if the cell is mutably
+/// borrowed while logging
+impl<T: Serialize> Serialize for RefCell<T> {
+    fn encode<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+        match self.try_borrow() {
+            Ok(value) => value.encode(write_buf),
+            Err(_) => Serialize::encode(&BORROWED_MARKER, write_buf),
+        }
+    }
+
+    fn encode_truncated<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+        match self.try_borrow() {
+            Ok(value) => value.encode_truncated(write_buf),
+            Err(_) => Serialize::encode_truncated(&BORROWED_MARKER, write_buf),
+        }
+    }
+
+    fn buffer_size_required(&self) -> usize {
+        match self.try_borrow() {
+            Ok(value) => value.buffer_size_required(),
+            Err(_) => Serialize::buffer_size_required(&BORROWED_MARKER),
+        }
+    }
+
+    fn decode(read_buf: &[u8]) -> (String, &[u8]) {
+        T::decode(read_buf)
+    }
+
+    fn decode_value(read_buf: &[u8]) -> (Value, &[u8]) {
+        T::decode_value(read_buf)
+    }
+}
+
+macro_rules! gen_serialize_nonzero {
+    ($nonzero:ty, $primitive:ty) => {
+        impl Serialize for $nonzero {
+            fn encode<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+                Serialize::encode(&self.get(), write_buf)
+            }
+
+            fn buffer_size_required(&self) -> usize {
+                std::mem::size_of::<$primitive>()
+            }
+
+            fn decode(read_buf: &[u8]) -> (String, &[u8]) {
+                <$primitive as Serialize>::decode(read_buf)
+            }
+
+            fn decode_value(read_buf: &[u8]) -> (Value, &[u8]) {
+                <$primitive as Serialize>::decode_value(read_buf)
+            }
+        }
+    };
+}
+
+gen_serialize_nonzero!(NonZeroI32, i32);
+gen_serialize_nonzero!(NonZeroI64, i64);
+gen_serialize_nonzero!(NonZeroIsize, isize);
+gen_serialize_nonzero!(NonZeroU32, u32);
+gen_serialize_nonzero!(NonZeroU64, u64);
+gen_serialize_nonzero!(NonZeroUsize, usize);
+
+/// Encoded as nothing, and formatted as `PhantomData`
+impl<T: ?Sized> Serialize for PhantomData<T> {
+    fn encode<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+        let (chunk, rest) = write_buf.split_at_mut(0);
+
+        (Store::new(<Self as Serialize>::decode, chunk), rest)
+    }
+
+    fn buffer_size_required(&self) -> usize {
+        0
+    }
+
+    fn decode(read_buf: &[u8]) -> (String, &[u8]) {
+        ("PhantomData".to_string(), read_buf)
+    }
+}
+
+/// Eager evaluation of debug structs, formatted straight into `write_buf`
+pub fn encode_debug<T: std::fmt::Debug>(val: T, write_buf: &mut [u8]) -> (Store, &mut [u8]) {
+    encode_formatted(format_args!("{:?}", val), write_buf, false)
+}
+
+/// Eager evaluation of display structs, formatted straight into `write_buf`
+pub fn encode_display<T: Display>(val: T, write_buf: &mut [u8]) -> (Store, &mut [u8]) {
+    encode_formatted(format_args!("{}", val), write_buf, false)
+}
+
+/// Serializes any value implementing `Display` by formatting it when logged,
+/// so that it can be passed with the `^` prefix and written straight into the
+/// serialization buffer, rather than being copied into the log record as a
+/// `%` argument would be.
+///
+/// ```
+/// use quicklog::{info, init, serialize::Disp};
+///
+/// # init!();
+/// let addr: std::net::SocketAddr = "127.0.0.1:8080".parse().unwrap();
+/// info!("connected to {}", ^Disp(&addr));
+/// ```
+///
+/// The value is decoded in the same way as a `&str`, and truncated in the
+/// same way when it exceeds
+/// [`Config::max_record_size`](crate::config::Config::max_record_size).
+#[derive(Clone, Copy)]
+pub struct Disp<'a>(pub &'a dyn Display);
+
+impl Serialize for Disp<'_> {
+    fn encode<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+        encode_display(self.0, write_buf)
+    }
+
+    fn encode_truncated<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+        encode_formatted(format_args!("{}", self.0), write_buf, true)
+    }
+
+    fn buffer_size_required(&self) -> usize {
+        formatted_size(format_args!("{}", self.0))
+    }
+
+    fn decode(read_buf: &[u8]) -> (String, &[u8]) {
+        <&str as Decode>::decode(read_buf)
+    }
+}
+
+/// The number of bytes required by [`encode_debug`] or [`encode_display`],
+/// given the arguments they format, e.g. `format_args!("{:?}", val)`.
+///
+/// The value is formatted without allocating, only counting the bytes written.
+pub fn formatted_size(args: std::fmt::Arguments) -> usize {
+    struct Counter(usize);
+
+    impl Write for Counter {
+        fn write_str(&mut self, s: &str) -> std::fmt::Result {
+            self.0 += s.len();
+            Ok(())
+        }
+    }
+
+    let mut counter = Counter(0);
+    _ = counter.write_fmt(args);
+
+    counter.0 + SIZE_LENGTH
+}
+
+/// Writes formatted output into a byte slice, keeping as many whole
+/// characters as fit and failing once the slice is full.
+struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+    overflowed: bool,
+}
+
+impl Write for SliceWriter<'_> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        let available = self.buf.len() - self.len;
+        let mut end = s.len().min(available);
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        self.buf[self.len..self.len + end].copy_from_slice(&s.as_bytes()[..end]);
+        self.len += end;
+        if end < s.len() {
+            self.overflowed = true;
+            return Err(std::fmt::Error);
+        }
+
+        Ok(())
+    }
+}
+
+/// Formats a value into `write_buf` in the same way as a `&str` is encoded.
+///
+/// The value is formatted a single time and its actual length is written, so
+/// a value formatting differently than when its size was reserved is clamped
+/// to `write_buf`, rather than panicking. With `truncate`, a clamped value is
+/// cut short to end with `...`, as in [`Encode::encode_truncated`] for `&str`.
+fn encode_formatted<'buf>(
+    args: std::fmt::Arguments,
+    write_buf: &'buf mut [u8],
+    truncate: bool,
+) -> (Store<'buf>, &'buf mut [u8]) {
+    let (len_chunk, str_chunk) = write_buf.split_at_mut(SIZE_LENGTH);
+    let mut writer = SliceWriter {
+        buf: str_chunk,
+        len: 0,
+        overflowed: false,
+    };
+    _ = writer.write_fmt(args);
+
+    let mut str_len = writer.len;
+    if truncate && writer.overflowed && str_chunk.len() >= TRUNCATED_MARKER.len() {
+        // step back to a character boundary, i.e. not a UTF-8 continuation byte
+        let mut end = str_len.min(str_chunk.len() - TRUNCATED_MARKER.len());
+        while end < str_len && str_chunk[end] & 0xC0 == 0x80 {
+            end -= 1;
+        }
+        str_len = end + TRUNCATED_MARKER.len();
+        str_chunk[end..str_len].copy_from_slice(TRUNCATED_MARKER.as_bytes());
+    }
+    len_chunk.copy_from_slice(&str_len.to_le_bytes());
+
+    let (chunk, rest) = write_buf.split_at_mut(SIZE_LENGTH + str_len);
+    (Store::new(<&str as Decode>::decode, chunk), rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::serialize::encode_debug;
+
+    use super::{Disp, Encode};
+
+    macro_rules! assert_primitive_encode_decode {
+        ($primitive:ty, $val:expr) => {{
+            const BUF_SIZE: usize = std::mem::size_of::<$primitive>();
+            let mut buf = [0u8; BUF_SIZE];
+
+            let x: $primitive = $val;
+            let (x_store, _) = x.encode(&mut buf);
+            assert_eq!(format!("{}", x), format!("{}", x_store));
+        }};
+    }
+
+    #[test]
+    fn serialize_primitives() {
+        assert_primitive_encode_decode!(i32, -1);
+        assert_primitive_encode_decode!(i64, -123);
+        assert_primitive_encode_decode!(isize, -1234);
+        assert_primitive_encode_decode!(f32, 1.23);
+        assert_primitive_encode_decode!(f64, 1.23456);
+        assert_primitive_encode_decode!(u32, 999);
+        assert_primitive_encode_decode!(u64, 9999);
+        assert_primitive_encode_decode!(usize, 99999);
+    }
+
+    #[test]
+    fn serialize_multiple_primitives() {
+        let mut buf = [0; 128];
+        let a: i32 = -1;
+        let b: u32 = 999;
+        let c: usize = 100000;
+
+        let (a_store, chunk) = a.encode(&mut buf);
+        let (b_store, chunk) = b.encode(chunk);
+        let (c_store, _) = c.encode(chunk);
+
+        assert_eq!(
+            format!("{} {} {}", a, b, c),
+            format!("{} {} {}", a_store, b_store, c_store)
+        )
+    }
+
+    #[test]
+    fn serialize_str() {
+        let mut buf = [0; 128];
+        let s = "hello world";
+        let (store, _) = s.encode(&mut buf);
+
+        assert_eq!(s, format!("{}", store).as_str())
+    }
+
+    #[test]
+    fn serialize_maps() {
+        use std::collections::{BTreeMap, HashMap};
+
+        let mut buf = [0; 128];
+        let map: BTreeMap<&str, u64> = [("b", 2), ("a", 1)].into_iter().collect();
+        let (store, rest) = map.encode(&mut buf);
+        assert_eq!(format!("{}", store), "{a: 1, b: 2}");
+
+        let map: HashMap<i32, &str> = [(1, "one")].into_iter().collect();
+        let (store, _) = map.encode(rest);
+        assert_eq!(format!("{}", store), "{1: one}");
+
+        let empty: BTreeMap<i32, i32> = BTreeMap::new();
+        let (store, _) = empty.encode(&mut buf);
+        assert_eq!(format!("{}", store), "{}");
+    }
+
+    #[test]
+    fn serialize_sets() {
+        use std::collections::{BTreeSet, HashSet};
+
+        let mut buf = [0; 128];
+        let set: BTreeSet<i64> = [3, 1, 2].into_iter().collect();
+        let (store, rest) = set.encode(&mut buf);
+        assert_eq!(format!("{}", store), "{1, 2, 3}");
+
+        let set: HashSet<&str> = ["hello"].into_iter().collect();
+        let (store, _) = set.encode(rest);
+        assert_eq!(format!("{}", store), "{hello}");
+    }
+
+    #[test]
+    fn serialize_time() {
+        use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+        let mut buf = [0; 128];
+        let d = Duration::from_micros(1500);
+        let (store, rest) = d.encode(&mut buf);
+        assert_eq!(format!("{:?}", d), format!("{}", store));
+
+        let t = UNIX_EPOCH + Duration::from_millis(1_700_000_000_123);
+        let (store, rest) = t.encode(rest);
+        assert_eq!(format!("{}", store), "2023-11-14T22:13:20.123Z");
+
+        let t = UNIX_EPOCH - Duration::from_secs(1);
+        let (store, _) = t.encode(rest);
+        assert_eq!(format!("{}", store), "1969-12-31T23:59:59Z");
+
+        let now = SystemTime::now();
+        let (store, _) = now.encode(&mut buf);
+        assert!(format!("{}", store).ends_with('Z'));
+    }
+
+    #[test]
+    fn serialize_datetimes() {
+        use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
+
+        let mut buf = [0; 128];
+        let time = Utc.with_ymd_and_hms(2023, 11, 14, 22, 13, 20).unwrap()
+            + chrono::Duration::milliseconds(123);
+        let (store, rest) = time.encode(&mut buf);
+        assert_eq!(format!("{}", store), "2023-11-14T22:13:20.123Z");
+
+        let local: DateTime<Local> = time.with_timezone(&Local);
+        let (store, rest) = local.encode(rest);
+        assert_eq!(format!("{}", store), "2023-11-14T22:13:20.123Z");
+
+        let naive = NaiveDate::from_ymd_opt(1969, 12, 31)
+            .unwrap()
+            .and_hms_opt(23, 59, 59)
+            .unwrap();
+        let (store, rest) = naive.encode(rest);
+        assert_eq!(format!("{}", store), "1969-12-31T23:59:59Z");
+
+        // Saturates outside of the range of `i64` nanoseconds
+        let far = Utc.with_ymd_and_hms(3000, 1, 1, 0, 0, 0).unwrap();
+        let (store, _) = far.encode(rest);
+        assert_eq!(format!("{}", store), "2262-04-11T23:47:16.854775807Z");
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn serialize_offset_datetime() {
+        let mut buf = [0; 128];
+        let time = time::OffsetDateTime::from_unix_timestamp_nanos(1_700_000_000_123_000_000)
+            .unwrap()
+            .to_offset(time::UtcOffset::from_hms(8, 0, 0).unwrap());
+        let (store, _) = time.encode(&mut buf);
+        assert_eq!(format!("{}", store), "2023-11-14T22:13:20.123Z");
+    }
+
+    #[test]
+    fn serialize_net() {
+        use std::net::{IpAddr, SocketAddr};
+
+        let mut buf = [0; 128];
+        let mut rest = &mut buf[..];
+        for addr in ["127.0.0.1", "::1", "fe80::1"] {
+            let ip: IpAddr = addr.parse().unwrap();
+            let (store, remaining) = ip.encode(rest);
+            assert_eq!(format!("{}", store), addr);
+            rest = remaining;
+        }
+
+        for addr in ["10.0.0.1:8080", "[::1]:443", "[fe80::1%2]:80"] {
+            let socket: SocketAddr = addr.parse().unwrap();
+            let (store, remaining) = socket.encode(rest);
+            assert_eq!(format!("{}", store), addr);
+            rest = remaining;
+        }
+    }
+
+    #[test]
+    fn serialize_values() {
+        use std::collections::{BTreeMap, BTreeSet};
+
+        use super::Value;
+
+        let mut buf = [0; 128];
+        let (store, rest) = (-1i32).encode(&mut buf);
+        assert_eq!(store.as_value(), Value::Int(-1));
+        let (store, rest) = 7usize.encode(rest);
+        assert_eq!(store.as_value(), Value::UInt(7));
+        let (store, rest) = 1.5f64.encode(rest);
+        assert_eq!(store.as_value(), Value::Float(1.5));
+        let (store, rest) = true.encode(rest);
+        assert_eq!(store.as_value(), Value::Bool(true));
+        assert_eq!(format!("{}", store), "true");
+        let (store, rest) = "hello".encode(rest);
+        assert_eq!(store.as_value(), Value::Str("hello".to_string()));
+
+        let map: BTreeMap<&str, u64> = [("a", 1)].into_iter().collect();
+        let (store, rest) = map.encode(rest);
+        assert_eq!(
+            store.as_value(),
+            Value::Map(vec![(Value::Str("a".to_string()), Value::UInt(1))])
+        );
+
+        let set: BTreeSet<i64> = [2, 1].into_iter().collect();
+        let (store, rest) = set.encode(rest);
+        assert_eq!(
+            store.as_value(),
+            Value::List(vec![Value::Int(1), Value::Int(2)])
+        );
+        assert_eq!(store.as_value().to_string(), "[1, 2]");
+
+        // Types without a typed decoding fall back to their formatted `String`
+        let (store, _) = std::time::Duration::from_millis(5).encode(rest);
+        assert_eq!(store.as_value(), Value::Str("5ms".to_string()));
+    }
+
+    #[test]
+    fn serialize_bytes() {
+        use super::{Bytes, Value};
+
+        let mut buf = [0; 128];
+        let payload: &[u8] = b"GET\r\n\x00";
+        let (store, rest) = payload.encode(&mut buf);
+        assert_eq!(format!("{}", store), "GET\\r\\n\\x00");
+        assert_eq!(store.as_value(), Value::Bytes(payload.to_vec()));
+
+        let (store, rest) = [0x41u8, 0xff].encode(rest);
+        assert_eq!(format!("{}", store), "A\\xff");
+        assert_eq!([0u8; 4].buffer_size_required(), 4);
+
+        let (store, rest) = Bytes(&[0xde, 0xad, 0x00, 0x0f]).encode(rest);
+        assert_eq!(format!("{}", store), "dead000f");
+        assert_eq!(store.as_value(), Value::Bytes(vec![0xde, 0xad, 0x00, 0x0f]));
+
+        let s: heapless::String<16> = "symbol".into();
+        let (store, _) = s.encode(rest);
+        assert_eq!(format!("{}", store), "symbol");
+    }
+
+    #[test]
+    fn serialize_truncated() {
+        use std::{
+            collections::{BTreeMap, BTreeSet},
+            time::Duration,
+        };
+
+        use super::{Value, MIN_TRUNCATED_SIZE, SIZE_LENGTH};
+
+        let mut buf = [0; 128];
+        // Cut before the 2-byte `é`
+        let (store, rest) = "héllo world".encode_truncated(&mut buf[..SIZE_LENGTH + 5]);
+        assert_eq!(store.to_string(), "h...");
+        assert_eq!(rest.len(), 1);
+
+        let set: BTreeSet<u64> = (0..10).collect();
+        let (store, _) = set.encode_truncated(&mut buf[..4 * SIZE_LENGTH]);
+        assert_eq!(store.to_string(), "{0, 1, 2, ...}");
+        assert_eq!(
+            store.as_value(),
+            Value::List(vec![
+                Value::UInt(0),
+                Value::UInt(1),
+                Value::UInt(2),
+                Value::Str("...".to_string())
+            ])
+        );
+
+        let map: BTreeMap<&str, u64> = [("a", 1), ("b", 2)].into_iter().collect();
+        let (store, _) = map.encode_truncated(&mut buf[..MIN_TRUNCATED_SIZE]);
+        assert_eq!(store.to_string(), "{...}");
+
+        let (store, _) = Duration::from_secs(1).encode_truncated(&mut buf[..MIN_TRUNCATED_SIZE]);
+        assert_eq!(store.to_string(), "...");
+    }
+
+    #[test]
+    fn serialize_pretty() {
+        use std::collections::BTreeMap;
+
+        let mut buf = [0; 128];
+        let map: BTreeMap<&str, u64> = [("a", 1), ("b", 2)].into_iter().collect();
+        let (store, rest) = map.encode(&mut buf);
+        let pretty = "{\n    a: 1,\n    b: 2,\n}";
+        assert_eq!(format!("{:#}", store), pretty);
+        assert_eq!(super::pretty(|| store.to_string()), pretty);
+        assert_eq!(store.to_string(), "{a: 1, b: 2}");
+
+        // Types without a typed decoding are unaffected
+        let (store, _) = std::time::Duration::from_millis(5).encode(rest);
+        assert_eq!(format!("{:#}", store), "5ms");
+    }
+
+    #[test]
+    fn serialize_wrappers() {
+        use std::{
+            cell::{Cell, RefCell},
+            collections::BTreeSet,
+            marker::PhantomData,
+            num::{NonZeroI64, NonZeroU32, Wrapping},
+            rc::Rc,
+            sync::Arc,
+        };
+
+        use super::Value;
+
+        let mut buf = [0; 128];
+        let (store, rest) = Arc::new("shared").encode(&mut buf);
+        assert_eq!(format!("{}", store), "shared");
+        let set: BTreeSet<u64> = [2, 1].into_iter().collect();
+        let (store, rest) = Rc::new(set).encode(rest);
+        assert_eq!(format!("{}", store), "{1, 2}");
+
+        let (store, rest) = NonZeroU32::new(7).unwrap().encode(rest);
+        assert_eq!(format!("{}", store), "7");
+        assert_eq!(store.as_value(), Value::UInt(7));
+        let (store, rest) = NonZeroI64::new(-3).unwrap().encode(rest);
+        assert_eq!(store.as_value(), Value::Int(-3));
+
+        let (store, rest) = Wrapping(u64::MAX).encode(rest);
+        assert_eq!(format!("{}", store), u64::MAX.to_string());
+        let (store, rest) = Cell::new(1.5f64).encode(rest);
+        assert_eq!(store.as_value(), Value::Float(1.5));
+
+        let cell = RefCell::new("value");
+        let (store, rest) = cell.encode(rest);
+        assert_eq!(format!("{}", store), "value");
+        let _guard = cell.borrow_mut();
+        let (store, rest) = cell.encode(rest);
+        assert_eq!(format!("{}", store), "<borrowed>");
+
+        let len = rest.len();
+        let (store, rest) = PhantomData::<String>.encode(rest);
+        assert_eq!(format!("{}", store), "PhantomData");
+        assert_eq!(rest.len(), len);
+    }
+
+    #[test]
+    fn serialize_float_precision() {
+        use super::Value;
+
+        let mut buf = [0; 128];
+        let (price, rest) = 0.1f64.encode(&mut buf);
+        let (qty, rest) = 2.5f32.encode(rest);
+        let (count, _) = 3u64.encode(rest);
+        assert_eq!(format!("{} {} {}", price, qty, count), "0.1 2.5 3");
+        assert_eq!(
+            super::float_precision(3, || format!("{} {} {}", price, qty, count)),
+            "0.100 2.500 3"
+        );
+        assert_eq!(
+            super::float_precision(2, || Value::Float(1.0 / 3.0).to_string()),
+            "0.33"
+        );
+        assert_eq!(price.to_string(), "0.1");
+    }
+
+    #[test]
+    fn serialize_debug() {
+        #[derive(Debug)]
+        #[allow(unused)]
+        struct DebugStruct {
+            s: &'static str,
+        }
+
+        let mut buf = [0; 128];
+        let s = DebugStruct { s: "Hello World" };
+        let (store, rest) = encode_debug(&s, &mut buf);
+        assert_eq!(format!("{:?}", s), format!("{}", store));
+        assert_eq!(
+            super::formatted_size(format_args!("{:?}", s)),
+            128 - rest.len()
+        );
+
+        let (store, rest) = super::encode_display(1.5, rest);
+        assert_eq!(format!("{}", store), "1.5");
+        let written = 2 * super::SIZE_LENGTH + format!("{:?}", s).len() + "1.5".len();
+        assert_eq!(rest.len(), 128 - written);
+    }
+
+    #[test]
+    fn serialize_disp() {
+        let mut buf = [0; 64];
+        let addr = std::net::Ipv4Addr::new(127, 0, 0, 1);
+        let disp = Disp(&addr);
+        assert_eq!(
+            disp.buffer_size_required(),
+            super::SIZE_LENGTH + "127.0.0.1".len()
+        );
+
+        let (store, rest) = disp.encode(&mut buf);
+        assert_eq!(format!("{}", store), "127.0.0.1");
+        assert_eq!(rest.len(), 64 - disp.buffer_size_required());
+
+        let mut buf = [0; super::MIN_TRUNCATED_SIZE];
+        let (store, _) = disp.encode_truncated(&mut buf);
+        assert_eq!(format!("{}", store), "127.0...");
+    }
+
+    #[test]
+    fn serialize_disp_resized() {
+        // Formats one more character every time it is formatted
+        struct Growing(std::cell::Cell<usize>);
+
+        impl std::fmt::Display for Growing {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let n = self.0.get();
+                self.0.set(n + 1);
+                f.write_str(&"é".repeat(n))
+            }
+        }
+
+        let growing = Growing(std::cell::Cell::new(2));
+        let disp = Disp(&growing);
+        let size = disp.buffer_size_required();
+        let mut buf = [0; 64];
+        // clamped to the size reserved, keeping whole characters only
+        let (chunk, _) = buf.split_at_mut(size + 1);
+        let (store, rest) = disp.encode(chunk);
+        assert_eq!(format!("{}", store), "éé");
+        assert_eq!(rest.len(), 1);
+
+        // only the formatted length is used, not the whole chunk
+        let empty = Growing(std::cell::Cell::new(0));
+        let (store, rest) = Disp(&empty).encode(&mut buf);
+        assert_eq!(format!("{}", store), "");
+        assert_eq!(rest.len(), 64 - super::SIZE_LENGTH);
+    }
+
+    #[test]
+    fn serialize_decode_only() {
+        use super::Decode;
+
+        // Decodes prices encoded as `i64` cents, without depending on the
+        // type which encoded them
+        struct Cents;
+
+        impl Decode for Cents {
+            fn decode(read_buf: &[u8]) -> (String, &[u8]) {
+                let (chunk, rest) = read_buf.split_at(std::mem::size_of::<i64>());
+                let cents = i64::from_le_bytes(chunk.try_into().unwrap());
+                (format!("{:.2}", cents as f64 / 100.0), rest)
+            }
+        }
+
+        let mut buf = [0; 16];
+        let (_, rest) = 12345i64.encode(&mut buf);
+        _ = (-50i64).encode(rest);
+
+        let (price, rest) = Cents::decode(&buf);
+        let (discount, rest) = Cents::decode(rest);
+        assert_eq!((price.as_str(), discount.as_str()), ("123.45", "-0.50"));
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(super::crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(super::crc32(b""), 0);
     }
 }
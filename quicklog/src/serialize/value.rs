@@ -0,0 +1,93 @@
+use std::fmt::Display;
+
+/// Typed representation of a decoded [`Serialize`](crate::serialize::Serialize)
+/// value.
+///
+/// While [`decode`](crate::serialize::Serialize::decode) always produces a
+/// formatted `String`, [`decode_value`](crate::serialize::Serialize::decode_value)
+/// keeps the type of the logged value, e.g. so that structured output can
+/// emit numbers and booleans as such rather than as quoted strings.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    Bool(bool),
+    Str(String),
+    Bytes(Vec<u8>),
+    /// Sequence of values, e.g. the elements of a set
+    List(Vec<Value>),
+    /// Key-value pairs, e.g. the entries of a map or the fields of a struct
+    Map(Vec<(Value, Value)>),
+}
+
+/// Formatted in the same way as the corresponding `decode` output where
+/// possible, with lists as `[a, b]` and maps as `{k: v}`.
+///
+/// The alternate flag, i.e. `{:#}`, writes nested lists and maps across
+/// indented lines instead, in the same way as `{:#?}`.
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            return self.fmt_pretty(f, 0);
+        }
+
+        match self {
+            Value::Int(x) => write!(f, "{}", x),
+            Value::UInt(x) => write!(f, "{}", x),
+            Value::Float(x) => super::write_float(f, x),
+            Value::Bool(x) => write!(f, "{}", x),
+            Value::Str(s) => f.write_str(s),
+            Value::Bytes(bytes) => write!(f, "{:?}", bytes),
+            Value::List(values) => {
+                f.write_str("[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                f.write_str("]")
+            }
+            Value::Map(entries) => {
+                f.write_str("{")?;
+                for (i, (k, v)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}: {}", k, v)?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+impl Value {
+    fn fmt_pretty(&self, f: &mut std::fmt::Formatter<'_>, indent: usize) -> std::fmt::Result {
+        const INDENT: &str = "    ";
+
+        let inner = INDENT.repeat(indent + 1);
+        match self {
+            Value::List(values) if !values.is_empty() => {
+                f.write_str("[\n")?;
+                for value in values {
+                    f.write_str(&inner)?;
+                    value.fmt_pretty(f, indent + 1)?;
+                    f.write_str(",\n")?;
+                }
+                write!(f, "{}]", INDENT.repeat(indent))
+            }
+            Value::Map(entries) if !entries.is_empty() => {
+                f.write_str("{\n")?;
+                for (k, v) in entries {
+                    write!(f, "{}{}: ", inner, k)?;
+                    v.fmt_pretty(f, indent + 1)?;
+                    f.write_str(",\n")?;
+                }
+                write!(f, "{}}}", INDENT.repeat(indent))
+            }
+            value => write!(f, "{}", value),
+        }
+    }
+}
@@ -0,0 +1,167 @@
+//! Spans attach structured context to every log line recorded while they
+//! are entered.
+//!
+//! A [`Span`](crate::span::Span) is created through the [`span!`](crate::span!) macro, which takes
+//! a name followed by fields using the same syntax as the logging macros.
+//! Fields are eagerly formatted once when the span is created, so they cost
+//! nothing on the hot path afterwards. As such, fields prefixed with `^` are
+//! formatted through `Display` like fields without a prefix.
+//!
+//! ```
+//! # use quicklog::{info, init, span};
+//! # init!();
+//! # let order_id = 10;
+//! let span = span!("order", id = order_id, side = ?"Bid");
+//! let _entered = span.enter();
+//!
+//! // recorded with span context "order{id=10 side="Bid"}"
+//! info!("order accepted");
+//! ```
+//!
+//! Entered spans are tracked per thread, and nest: entering a span while
+//! another is active renders both, outermost first, e.g.
+//! `outer{a=1}:inner{b=2}`.
+
+use std::{cell::RefCell, fmt::Display, marker::PhantomData, sync::Arc};
+
+thread_local! {
+    static CURRENT: RefCell<Option<Arc<SpanNode>>> = RefCell::new(None);
+}
+
+struct SpanData {
+    name: &'static str,
+    fields: String,
+}
+
+/// A named set of fields which can be entered to provide context to log lines
+#[derive(Clone)]
+pub struct Span {
+    data: Arc<SpanData>,
+}
+
+impl Span {
+    /// Creates a new span. Prefer using [`span!`](crate::span!), which also
+    /// formats the fields.
+    pub fn new(name: &'static str, fields: String) -> Self {
+        Self {
+            data: Arc::new(SpanData { name, fields }),
+        }
+    }
+
+    /// Name of the span
+    pub fn name(&self) -> &'static str {
+        self.data.name
+    }
+
+    /// Fields of the span, formatted as `key=value` pairs
+    pub fn fields(&self) -> &str {
+        &self.data.fields
+    }
+
+    /// Enters the span on the current thread, until the returned guard is dropped
+    pub fn enter(&self) -> Entered<'_> {
+        let prev = CURRENT.with(|current| {
+            let mut current = current.borrow_mut();
+            let prev = current.take();
+            *current = Some(Arc::new(SpanNode {
+                span: self.data.clone(),
+                parent: prev.clone(),
+            }));
+            prev
+        });
+
+        Entered {
+            prev,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Runs `f` with the span entered
+    pub fn in_scope<T>(&self, f: impl FnOnce() -> T) -> T {
+        let _entered = self.enter();
+        f()
+    }
+}
+
+/// Guard returned by [`Span::enter`], exits the span when dropped.
+///
+/// As spans are tracked per thread, this guard cannot be sent to other threads.
+pub struct Entered<'a> {
+    prev: Option<Arc<SpanNode>>,
+    _marker: PhantomData<(&'a Span, *const ())>,
+}
+
+impl Drop for Entered<'_> {
+    fn drop(&mut self) {
+        let prev = self.prev.take();
+        _ = CURRENT.try_with(|current| *current.borrow_mut() = prev);
+    }
+}
+
+struct SpanNode {
+    span: Arc<SpanData>,
+    parent: Option<Arc<SpanNode>>,
+}
+
+/// Snapshot of the spans entered when a log line was recorded
+#[derive(Clone)]
+pub struct SpanContext {
+    node: Arc<SpanNode>,
+}
+
+impl Display for SpanContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn fmt_node(node: &SpanNode, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            if let Some(parent) = &node.parent {
+                fmt_node(parent, f)?;
+                f.write_str(":")?;
+            }
+
+            f.write_str(node.span.name)?;
+            if !node.span.fields.is_empty() {
+                write!(f, "{{{}}}", node.span.fields)?;
+            }
+
+            Ok(())
+        }
+
+        fmt_node(&self.node, f)
+    }
+}
+
+/// Returns the spans currently entered on this thread, if any
+pub fn current() -> Option<SpanContext> {
+    CURRENT
+        .try_with(|current| {
+            current
+                .borrow()
+                .as_ref()
+                .map(|node| SpanContext { node: node.clone() })
+        })
+        .ok()
+        .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{current, Span};
+
+    #[test]
+    fn nested_spans() {
+        let outer = Span::new("outer", "a=1".to_string());
+        let inner = Span::new("inner", String::new());
+        assert!(current().is_none());
+
+        {
+            let _outer = outer.enter();
+            assert_eq!(current().unwrap().to_string(), "outer{a=1}");
+
+            inner.in_scope(|| {
+                assert_eq!(current().unwrap().to_string(), "outer{a=1}:inner");
+            });
+            assert_eq!(current().unwrap().to_string(), "outer{a=1}");
+        }
+
+        assert!(current().is_none());
+    }
+}
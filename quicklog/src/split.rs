@@ -0,0 +1,95 @@
+//! Flushing from a thread chosen by the application.
+//!
+//! [`init_split!`](crate::init_split) initializes the global logger like
+//! [`init!`](crate::init), but hands the consumer side of the logging queue
+//! over to a [`FlushHandle`](crate::FlushHandle), along with the flusher,
+//! formatter and routes. The logging threads keep only the producer side,
+//! while the handle can be moved to any other thread to flush from there:
+//!
+//! ```
+//! use quicklog::{config, info, init_split};
+//! use quicklog_flush::noop_flusher::NoopFlusher;
+//!
+//! let mut flush_handle = init_split!(config().flusher(NoopFlusher)).unwrap();
+//! let flush_thread = std::thread::spawn(move || {
+//!     // e.g. until the application shuts down
+//!     flush_handle.flush_n(usize::MAX);
+//! });
+//!
+//! info!("flushed on the thread owning the handle");
+//! # flush_thread.join().unwrap();
+//! ```
+//!
+//! As the handle is moved to another thread, flushers, formatters and flush
+//! error hooks are required to be `Send`, and clocks to be `Send + Sync`.
+//! The clock is shared with the logger, while flushers, formatters and
+//! routes set afterwards through [`with_flush!`](crate::with_flush) or
+//! [`with_formatter!`](crate::with_formatter) are not used by the handle,
+//! unlike those set through [`update_config`](crate::update_config). Records
+//! dropped because the queue was full are counted by the logger, but not
+//! reported by the handle, regardless of
+//! [`Config::report_dropped`](crate::Config::report_dropped).
+//!
+//! The handle should be dropped before calling
+//! [`shutdown()`](crate::shutdown()). Until it is dropped, initializing the
+//! logger again leaves it uninitialized, so that records are never dequeued
+//! by two consumers.
+
+use std::sync::atomic::Ordering;
+
+use crate::{
+    reader::{Record, Records},
+    FlushError, FlushStats, Log, Quicklog, RecvResult, QUEUE_HANDED_OVER,
+};
+
+/// Owner of the consumer side of the logging queue, returned by
+/// [`init_split!`](crate::init_split), see [`split`](crate::split).
+///
+/// Remaining log records are flushed when the handle is dropped.
+pub struct FlushHandle {
+    /// Logger without a producer side, which only flushes
+    consumer: Quicklog,
+}
+
+impl FlushHandle {
+    pub(crate) fn new(consumer: Quicklog) -> Self {
+        Self { consumer }
+    }
+
+    /// Flushes a single log record, see [`Log::flush_one`]
+    pub fn flush_one(&mut self) -> RecvResult {
+        self.consumer.flush_one()
+    }
+
+    /// Flushes up to `max` log records, see [`Quicklog::flush_n`]
+    pub fn flush_n(&mut self, max: usize) -> FlushStats {
+        self.consumer.flush_n(max)
+    }
+
+    /// Dequeues and formats a single log record, without passing it to the
+    /// flusher, see [`Quicklog::format_one`]
+    pub fn format_one(&mut self) -> Result<String, FlushError> {
+        self.consumer.format_one()
+    }
+
+    /// Dequeues a single log record without formatting or flushing it, see
+    /// [`Quicklog::read_one`]
+    pub fn read_one(&mut self) -> Result<Record, FlushError> {
+        self.consumer.read_one()
+    }
+
+    /// Reads the queued log records, oldest first, until the queue is empty,
+    /// see [`reader`](crate::reader)
+    pub fn records(&mut self) -> Records<'_> {
+        self.consumer.records()
+    }
+}
+
+impl Drop for FlushHandle {
+    fn drop(&mut self) {
+        self.consumer.flush_n(usize::MAX);
+        self.consumer.close_flushers();
+        _ = self.consumer.receiver.take();
+        QUEUE_HANDED_OVER.store(false, Ordering::Release);
+    }
+}
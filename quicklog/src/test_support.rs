@@ -0,0 +1,142 @@
+//! Capturing of log lines in memory, for asserting on what was logged in
+//! tests.
+//!
+//! [`init_test!`](crate::init_test) (re)initializes the global logger with a
+//! [`CaptureFlusher`](crate::test_support::CaptureFlusher) and a
+//! [`CaptureFormatter`](crate::test_support::CaptureFormatter), which writes
+//! each record as `[LEVEL]\tmessage` without a timestamp so that the output
+//! is deterministic. [`assert_logged!`](crate::assert_logged) then flushes and
+//! checks the captured lines:
+//!
+//! ```
+//! use quicklog::{assert_logged, info, init_test, level::Level};
+//!
+//! let capture = init_test!();
+//! info!("order {} filled", 42);
+//! assert_logged!(Level::Info, "order 42 filled");
+//! assert_eq!(capture.lines(), vec!["[INFO]\torder 42 filled\n"]);
+//! ```
+//!
+//! As the logger is global, tests logging through it should not run
+//! concurrently, e.g. by running them with `--test-threads=1`.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use chrono::{DateTime, Utc};
+use quicklog_flush::Flush;
+
+use crate::{
+    config,
+    level::{Level, LevelFilter},
+    logger, LogRecord, PatternFormatter,
+};
+
+/// Capture installed by the last call to [`init_test!`](crate::init_test)
+static CURRENT: Mutex<Option<CaptureFlusher>> = Mutex::new(None);
+
+/// Flusher which keeps every flushed line in memory.
+///
+/// Clones share the same lines, so one clone can be passed to the logger
+/// while another is kept to read them back.
+#[derive(Clone, Default)]
+pub struct CaptureFlusher {
+    lines: Arc<Mutex<Vec<String>>>,
+}
+
+impl CaptureFlusher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lines flushed so far, in the order they were flushed
+    pub fn lines(&self) -> Vec<String> {
+        self.lock().clone()
+    }
+
+    /// Returns the lines flushed so far, removing them from the capture
+    pub fn take(&self) -> Vec<String> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<String>> {
+        self.lines.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Flush for CaptureFlusher {
+    fn flush_one(&mut self, display: String) {
+        self.lock().push(display)
+    }
+
+    fn flush_str(&mut self, display: &str) {
+        self.lock().push(display.to_string())
+    }
+}
+
+/// Formats records as `[LEVEL]\tmessage`, prefixing the message with the
+/// current spans if any, without a timestamp
+#[derive(Clone, Copy, Default)]
+pub struct CaptureFormatter;
+
+impl PatternFormatter for CaptureFormatter {
+    fn custom_format(&mut self, _: DateTime<Utc>, log_record: LogRecord) -> String {
+        match log_record.span {
+            Some(span) => format!(
+                "[{}]\t{}: {}\n",
+                log_record.level, span, log_record.log_line
+            ),
+            None => format!("[{}]\t{}\n", log_record.level, log_record.log_line),
+        }
+    }
+}
+
+/// Internal API
+///
+/// Resets the global logger and initializes it again with a new
+/// [`CaptureFlusher`], used by [`init_test!`](crate::init_test)
+#[doc(hidden)]
+pub fn init_capture() -> CaptureFlusher {
+    let capture = CaptureFlusher::new();
+    crate::shutdown();
+    _ = logger().init_with_config(
+        config()
+            .flusher(capture.clone())
+            .formatter(CaptureFormatter)
+            .max_level(LevelFilter::Trace),
+    );
+    *CURRENT.lock().unwrap_or_else(|e| e.into_inner()) = Some(capture.clone());
+
+    capture
+}
+
+/// Internal API
+///
+/// Flushes all queued records and returns the lines captured since the last
+/// [`init_test!`](crate::init_test), used by
+/// [`assert_logged!`](crate::assert_logged)
+#[doc(hidden)]
+pub fn flush_captured() -> Vec<String> {
+    logger().flush_n(usize::MAX);
+    CURRENT
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .expect("assert_logged! requires the logger to be initialized through init_test!")
+        .lines()
+}
+
+/// Internal API
+///
+/// Whether `line` was formatted by [`CaptureFormatter`] from a record at
+/// `level` and contains `contains`
+#[doc(hidden)]
+pub fn line_matches(line: &str, level: Level, contains: &str) -> bool {
+    line.strip_prefix('[')
+        .and_then(|line| line.strip_prefix(&level.to_string()))
+        .map_or(false, |line| line.starts_with("]\t"))
+        && line.contains(contains)
+}
@@ -0,0 +1,100 @@
+//! Identification of the thread a log record was logged from.
+//!
+//! [`Log::log`](crate::Log::log) attaches the
+//! [`ThreadInfo`](crate::thread::ThreadInfo) of the calling thread to every
+//! record, so that the output of logger instances running on different
+//! threads can be merged and still be told apart.
+
+use std::{
+    fmt::Display,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    static CURRENT: ThreadInfo = ThreadInfo::capture();
+}
+
+/// Id and name of a thread, captured once per thread on its first log record.
+///
+/// Cloning only increments a reference count, as the name is shared.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ThreadInfo(Arc<Inner>);
+
+#[derive(Debug, PartialEq, Eq)]
+struct Inner {
+    id: u64,
+    name: Option<String>,
+}
+
+impl ThreadInfo {
+    /// Returns the [`ThreadInfo`] of the current thread
+    pub fn current() -> Self {
+        CURRENT.with(Clone::clone)
+    }
+
+    /// Id of the current thread, see [`ThreadInfo::id`], or `u64::MAX` once
+    /// its thread local storage was destroyed, e.g. in `atexit` handlers
+    pub(crate) fn current_id() -> u64 {
+        CURRENT.try_with(ThreadInfo::id).unwrap_or(u64::MAX)
+    }
+
+    fn capture() -> Self {
+        Self(Arc::new(Inner {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            name: std::thread::current().name().map(str::to_string),
+        }))
+    }
+
+    /// Id of the thread, unique within the process and starting at 1 in the
+    /// order threads first log.
+    ///
+    /// Unlike [`std::thread::ThreadId`], this can be written as a number.
+    pub fn id(&self) -> u64 {
+        self.0.id
+    }
+
+    /// Name of the thread, if it was named through [`std::thread::Builder`]
+    pub fn name(&self) -> Option<&str> {
+        self.0.name.as_deref()
+    }
+}
+
+/// Writes the name of the thread, or its id if unnamed
+impl Display for ThreadInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "{}", self.id()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ThreadInfo;
+
+    #[test]
+    fn thread_info_per_thread() {
+        let current = ThreadInfo::current();
+        assert_eq!(current, ThreadInfo::current());
+
+        let named = std::thread::Builder::new()
+            .name("worker".to_string())
+            .spawn(ThreadInfo::current)
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_ne!(named.id(), current.id());
+        assert_eq!(named.name(), Some("worker"));
+        assert_eq!(named.to_string(), "worker");
+
+        let unnamed = std::thread::spawn(ThreadInfo::current).join().unwrap();
+        assert_eq!(unnamed.name(), None);
+        assert_eq!(unnamed.to_string(), unnamed.id().to_string());
+    }
+}
@@ -0,0 +1,66 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        OnceLock,
+    },
+    time::{Duration, Instant},
+};
+
+/// Nanoseconds are measured relative to the first use of any [`Throttle`]
+static EPOCH: OnceLock<Instant> = OnceLock::new();
+
+const NEVER: u64 = u64::MAX;
+
+/// Per-callsite rate limiter, declared as a `static` by the `*_throttle!`
+/// macros
+pub struct Throttle {
+    last: AtomicU64,
+}
+
+impl Throttle {
+    pub const fn new() -> Self {
+        Self {
+            last: AtomicU64::new(NEVER),
+        }
+    }
+
+    /// Returns `true` if at least `interval` has passed since the last time
+    /// this returned `true`, or if it never has
+    pub fn try_acquire(&self, interval: Duration) -> bool {
+        let now = EPOCH.get_or_init(Instant::now).elapsed().as_nanos() as u64;
+        let last = self.last.load(Ordering::Relaxed);
+        if last != NEVER && now.saturating_sub(last) < interval.as_nanos() as u64 {
+            return false;
+        }
+
+        self.last
+            .compare_exchange(last, now, Ordering::Relaxed, Ordering::Relaxed)
+            .is_ok()
+    }
+}
+
+impl Default for Throttle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Throttle;
+
+    #[test]
+    fn acquire_once_per_interval() {
+        let throttle = Throttle::new();
+        assert!(throttle.try_acquire(Duration::from_secs(60)));
+        assert!(!throttle.try_acquire(Duration::from_secs(60)));
+        assert!(throttle.try_acquire(Duration::ZERO));
+
+        let throttle = Throttle::new();
+        assert!(throttle.try_acquire(Duration::from_millis(1)));
+        std::thread::sleep(Duration::from_millis(2));
+        assert!(throttle.try_acquire(Duration::from_millis(1)));
+    }
+}
@@ -0,0 +1,18 @@
+use quicklog::{app_info, init};
+
+fn main() {
+    assert!(app_info().is_none());
+
+    init!(version = "1.2.3");
+
+    let info = app_info().unwrap();
+    assert_eq!(info.version(), Some("1.2.3"));
+    assert_eq!(info.pid(), std::process::id());
+    assert!(!info.executable().is_empty());
+
+    // initializing again after shutdown captures the new version
+    quicklog::shutdown();
+    assert!(app_info().is_none());
+    init!(version = "2.0");
+    assert_eq!(app_info().unwrap().version(), Some("2.0"));
+}
@@ -0,0 +1,43 @@
+use std::{sync::Mutex, thread, time::Duration};
+
+use quicklog::{config, info, init, with_flush, with_formatter};
+use quicklog_flush::Flush;
+
+mod common;
+
+static LINES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+struct MutexFlusher;
+
+impl Flush for MutexFlusher {
+    fn flush_one(&mut self, display: String) {
+        LINES.lock().unwrap().push(display);
+    }
+}
+
+fn flushed_messages() -> Vec<String> {
+    common::from_log_lines(&LINES.lock().unwrap(), common::message_from_log_line)
+}
+
+fn main() {
+    with_flush!(MutexFlusher);
+    with_formatter!(common::TestFormatter::new());
+    let handle = init!(config().background_flush(Duration::from_millis(1))).unwrap();
+
+    info!("flushed in background");
+    for _ in 0..1000 {
+        if !flushed_messages().is_empty() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+    assert_eq!(flushed_messages(), vec!["flushed in background"]);
+
+    // remaining records are drained when the handle is dropped
+    for i in 0..100 {
+        info!("drained {}", i);
+    }
+    drop(handle);
+    assert_eq!(flushed_messages().len(), 101);
+    assert_eq!(flushed_messages().last().unwrap(), "drained 99");
+}
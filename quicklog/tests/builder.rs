@@ -0,0 +1,76 @@
+use quicklog::{
+    callsites, config, debug_builder, flush_all, formatter, info_builder, init,
+    level::LevelFilter, serialize::Value, test_support::CaptureFlusher, with_formatter,
+};
+
+fn main() {
+    let capture = CaptureFlusher::new();
+    init!(config()
+        .max_level(LevelFilter::Info)
+        .flusher(capture.clone()));
+
+    for filled in [true, false] {
+        let mut rec = info_builder!();
+        rec.field("qty", 5);
+        if filled {
+            rec.msg("order filled");
+        } else {
+            rec.field("reason", "no liquidity").msg("order rejected");
+        }
+        rec.finish();
+    }
+    flush_all!();
+    let lines = capture.lines();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with("order filled qty=5\n"));
+    assert!(lines[1].ends_with("order rejected qty=5 reason=no liquidity\n"));
+
+    // disabled level, nothing is queued
+    let mut rec = debug_builder!();
+    assert!(!rec.is_enabled());
+    rec.msg("hidden");
+    rec.finish();
+    flush_all!();
+    assert_eq!(capture.lines().len(), 2);
+
+    // dropped without finishing, nothing is queued
+    let mut rec = info_builder!();
+    rec.msg("dropped");
+    drop(rec);
+    flush_all!();
+    assert_eq!(capture.lines().len(), 2);
+
+    with_formatter!(formatter().json().build());
+    let mut rec = info_builder!();
+    rec.value("qty", Value::UInt(5))
+        .field("side", "buy")
+        .msg("order sent");
+    rec.finish();
+    flush_all!();
+
+    let line = capture.lines()[2].clone();
+    assert!(
+        line.ends_with(concat!(
+            r#""message":"order sent","fields":{"qty":5,"side":"buy"}}"#,
+            "\n"
+        )),
+        "{}",
+        line
+    );
+
+    // disabled callsite, nothing is queued
+    fn report() -> bool {
+        let mut rec = info_builder!();
+        let enabled = rec.is_enabled();
+        rec.msg("report");
+        rec.finish();
+        enabled
+    }
+    assert!(report());
+    callsites()
+        .filter(|callsite| callsite.file() == file!())
+        .for_each(|callsite| callsite.set_enabled(false));
+    assert!(!report());
+    flush_all!();
+    assert_eq!(capture.lines().len(), 4);
+}
@@ -0,0 +1,53 @@
+use quicklog::{callsites, info, level::Level, log, warn};
+
+mod common;
+
+fn main() {
+    setup!();
+
+    let s = "hello";
+    let first_line = line!() + 1;
+    info!("hello {}", s);
+    warn!(?s, "with fields");
+    log!(Level::Error, "dynamic");
+
+    let mut registered: Vec<_> = callsites()
+        .filter(|callsite| callsite.file() == file!() && callsite.line() < first_line + 3)
+        .map(|callsite| {
+            (
+                callsite.line() - first_line,
+                callsite.level(),
+                callsite.format_string(),
+            )
+        })
+        .collect();
+    registered.sort_by_key(|(line, _, _)| *line);
+
+    assert_eq!(
+        registered,
+        vec![
+            (0, Some(Level::Info), "hello {}"),
+            (1, Some(Level::Warn), "with fields s={:?}"),
+            (2, None, "dynamic"),
+        ]
+    );
+
+    quicklog::flush_all!();
+    unsafe { VEC.clear() };
+
+    // disabling a callsite only silences that one line
+    fn noisy(i: usize) {
+        info!("noisy {}", i);
+        info!("quiet {}", i);
+    }
+    callsites()
+        .filter(|callsite| callsite.format_string() == "noisy {}")
+        .for_each(|callsite| callsite.set_enabled(false));
+
+    noisy(1);
+    quicklog::flush_all!();
+    assert_eq!(
+        common::from_log_lines(unsafe { &VEC }, common::message_from_log_line),
+        vec!["quiet 1"]
+    );
+}
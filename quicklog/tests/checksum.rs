@@ -0,0 +1,32 @@
+use std::sync::{Arc, Mutex};
+
+use quicklog::{
+    config, flush_all, info, init,
+    test_support::{CaptureFlusher, CaptureFormatter},
+    FlushError,
+};
+
+fn main() {
+    let capture = CaptureFlusher::new();
+    let errors = Arc::new(Mutex::new(Vec::new()));
+    let hook_errors = errors.clone();
+    init!(config()
+        .checksum(true)
+        .serialize_buffer_capacity(64)
+        .flusher(capture.clone())
+        .formatter(CaptureFormatter)
+        .on_flush_error(move |err, record| hook_errors.lock().unwrap().push((err, record.line))));
+
+    let first = "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+    let second = "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+    info!("first {}", ^first);
+    let line = line!() - 1;
+    // wraps around to the start of the buffer, overwriting the first argument
+    info!("second {}", ^second);
+
+    let stats = flush_all!();
+    assert_eq!(stats.flushed, 1);
+    assert_eq!(stats.errors, 1);
+    assert_eq!(*errors.lock().unwrap(), vec![(FlushError::Corrupted, line)]);
+    assert_eq!(capture.lines(), vec![format!("[INFO]\tsecond {}\n", second)]);
+}
@@ -0,0 +1,31 @@
+use std::time::Duration;
+
+use chrono::{TimeZone, Utc};
+use quicklog::{config, flush_all, info, init, with_formatter};
+use quicklog_clock::mock::MockClock;
+
+mod common;
+
+static mut VEC: Vec<String> = Vec::new();
+
+fn main() {
+    let start = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+    let clock = MockClock::new(start);
+    init!(config()
+        .clock(clock.clone())
+        .flusher(unsafe { common::VecFlusher::new(&mut VEC) }));
+    with_formatter!(common::TestFormatter::new());
+
+    info!("first");
+    clock.advance(Duration::from_nanos(1_500));
+    info!("second");
+    flush_all!();
+
+    assert_eq!(
+        unsafe { VEC.clone() },
+        vec![
+            "[2023-11-14T22:13:20Z][INFO]\tfirst\n",
+            "[2023-11-14T22:13:20.000001500Z][INFO]\tsecond\n",
+        ]
+    );
+}
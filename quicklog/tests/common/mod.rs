@@ -34,10 +34,16 @@ impl TestFormatter {
 
 impl PatternFormatter for TestFormatter {
     fn custom_format(&mut self, time: DateTime<Utc>, log_record: LogRecord) -> String {
-        format!(
-            "[{:?}][{}]\t{}\n",
-            time, log_record.level, log_record.log_line
-        )
+        match log_record.span {
+            Some(span) => format!(
+                "[{:?}][{}]\t{}: {}\n",
+                time, log_record.level, span, log_record.log_line
+            ),
+            None => format!(
+                "[{:?}][{}]\t{}\n",
+                time, log_record.level, log_record.log_line
+            ),
+        }
     }
 }
 
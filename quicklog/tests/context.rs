@@ -0,0 +1,79 @@
+use chrono::{TimeZone, Utc};
+use quicklog::{
+    config, flush_all, formatter, info, init, scoped_context, test_support::CaptureFlusher,
+    with_context, with_formatter, with_thread_context,
+};
+use quicklog_clock::mock::MockClock;
+
+fn logged(capture: &CaptureFlusher) -> String {
+    flush_all!();
+    capture.take().pop().unwrap()
+}
+
+fn main() {
+    let clock = MockClock::new(Utc.timestamp_opt(1_700_000_000, 0).unwrap());
+    let capture = CaptureFlusher::new();
+    init!(config().clock(clock).flusher(capture.clone()));
+
+    let strategy = "mm1";
+    with_context!(region = "us-east", strategy);
+    info!(id = 7, "order filled");
+    assert_eq!(
+        logged(&capture),
+        "[2023-11-14T22:13:20Z]order filled id=7 region=us-east strategy=mm1\n"
+    );
+
+    // other threads only see the global fields
+    let worker = std::thread::spawn(|| {
+        with_thread_context!(worker = 1);
+        info!("from worker");
+    });
+    worker.join().unwrap();
+    assert_eq!(
+        logged(&capture),
+        "[2023-11-14T22:13:20Z]from worker region=us-east strategy=mm1 worker=1\n"
+    );
+
+    {
+        let _guard = scoped_context!(venue = ?"XNAS");
+        with_context!(strategy = "mm2");
+        info!("in scope");
+        assert_eq!(
+            logged(&capture),
+            "[2023-11-14T22:13:20Z]in scope region=us-east strategy=mm2 venue=\"XNAS\"\n"
+        );
+
+        with_formatter!(formatter().json().build());
+        info!(id = 7, "json");
+        let line = logged(&capture);
+        assert!(
+            line.ends_with(concat!(
+                r#""message":"json","fields":{"id":7,"region":"us-east","strategy":"mm2","#,
+                r#""venue":"\"XNAS\""}}"#,
+                "\n"
+            )),
+            "{}",
+            line
+        );
+        with_formatter!(formatter().logfmt().build());
+        info!("logfmt");
+        let line = logged(&capture);
+        assert!(
+            line.ends_with(r#"msg=logfmt region=us-east strategy=mm2 venue="\"XNAS\""
+"#),
+            "{}",
+            line
+        );
+    }
+
+    with_formatter!(formatter());
+    info!("out of scope");
+    assert_eq!(
+        logged(&capture),
+        "[2023-11-14T22:13:20Z]out of scope region=us-east strategy=mm2\n"
+    );
+
+    quicklog::context::clear_global();
+    info!("cleared");
+    assert_eq!(logged(&capture), "[2023-11-14T22:13:20Z]cleared\n");
+}
@@ -0,0 +1,25 @@
+use quicklog::{define_level, info, level::LevelFilter};
+
+mod common;
+
+define_level!(AUDIT, audit, "AUDIT", 5);
+define_level!(pub NOTICE, notice, "NOTICE", 2, color = "\x1b[36m");
+
+fn main() {
+    setup!();
+
+    assert_message_with_level_equal!(audit!("password changed"), "[AUDIT]\tpassword changed");
+    assert_message_with_level_equal!(notice!(?AUDIT, "notice"), "[NOTICE]\tnotice AUDIT=AUDIT");
+    assert!(AUDIT > NOTICE);
+
+    quicklog::level::set_max_level(LevelFilter::Error);
+    info!("skipped");
+    notice!("skipped");
+    audit!(user = "admin", "still recorded");
+    quicklog::flush_all!();
+
+    assert_eq!(
+        common::from_log_lines(unsafe { &VEC }, common::message_and_level_from_log_line),
+        vec!["[AUDIT]\tstill recorded user=admin"]
+    );
+}
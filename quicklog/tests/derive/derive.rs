@@ -6,4 +6,11 @@ fn derive() {
     t.pass("tests/derive/derive_02.rs");
     t.pass("tests/derive/derive_03.rs");
     t.pass("tests/derive/derive_04.rs");
+    t.pass("tests/derive/derive_05.rs");
+    t.pass("tests/derive/derive_06.rs");
+    t.pass("tests/derive/derive_07.rs");
+    t.pass("tests/derive/derive_08.rs");
+    t.pass("tests/derive/derive_09.rs");
+    t.pass("tests/derive/derive_10.rs");
+    t.pass("tests/derive/derive_11.rs");
 }
@@ -0,0 +1,57 @@
+// Testing skip, rename and with field attributes.
+use quicklog::serialize::Encode as _;
+use quicklog::Serialize;
+
+mod masked {
+    use quicklog::serialize::{Decode, Encode, Store};
+
+    pub fn encode<'buf>(_: &&str, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+        "****".encode(write_buf)
+    }
+
+    pub fn decode(read_buf: &[u8]) -> (String, &[u8]) {
+        <&str as Decode>::decode(read_buf)
+    }
+
+    pub fn buffer_size_required(_: &&str) -> usize {
+        "****".buffer_size_required()
+    }
+}
+
+#[derive(Serialize)]
+struct TestStruct {
+    a: usize,
+    #[quicklog(skip)]
+    #[allow(dead_code)]
+    payload: Vec<u8>,
+    #[quicklog(rename = "user")]
+    user_name: &'static str,
+    #[quicklog(with = "masked")]
+    password: &'static str,
+}
+
+#[derive(Serialize)]
+struct SingleField {
+    #[quicklog(skip)]
+    #[allow(dead_code)]
+    a: usize,
+    #[quicklog(rename = "b")]
+    b: i32,
+}
+
+fn main() {
+    let s = TestStruct {
+        a: 999,
+        payload: vec![0; 1024],
+        user_name: "admin",
+        password: "hunter2",
+    };
+    let mut buf = [0; 128];
+
+    let (store, rest) = s.encode(&mut buf);
+    assert_eq!(format!("{}", store), "999 user=admin ****");
+
+    let s = SingleField { a: 1, b: -5 };
+    let (store, _) = s.encode(rest);
+    assert_eq!(format!("{}", store), "b=-5");
+}
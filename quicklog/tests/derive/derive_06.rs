@@ -0,0 +1,55 @@
+// Testing typed decoding of derived structs.
+use quicklog::serialize::{Encode as _, Value};
+use quicklog::Serialize;
+
+#[derive(Serialize)]
+struct Inner {
+    flag: bool,
+}
+
+#[derive(Serialize)]
+struct TestStruct {
+    a: usize,
+    #[quicklog(rename = "price")]
+    b: f64,
+    c: &'static str,
+    inner: Inner,
+}
+
+#[derive(Serialize)]
+struct SingleField {
+    a: i32,
+}
+
+fn main() {
+    let s = TestStruct {
+        a: 999,
+        b: 1.5,
+        c: "hello",
+        inner: Inner { flag: true },
+    };
+    let mut buf = [0; 128];
+
+    let (store, rest) = s.encode(&mut buf);
+    assert_eq!(format!("{}", store), "999 price=1.5 hello true");
+    assert_eq!(
+        store.as_value(),
+        Value::Map(vec![
+            (Value::Str("a".to_string()), Value::UInt(999)),
+            (Value::Str("price".to_string()), Value::Float(1.5)),
+            (Value::Str("c".to_string()), Value::Str("hello".to_string())),
+            (
+                Value::Str("inner".to_string()),
+                Value::Map(vec![(Value::Str("flag".to_string()), Value::Bool(true))])
+            ),
+        ])
+    );
+
+    let s = SingleField { a: -5 };
+    let (store, _) = s.encode(rest);
+    assert_eq!(format!("{}", store), "-5");
+    assert_eq!(
+        store.as_value(),
+        Value::Map(vec![(Value::Str("a".to_string()), Value::Int(-5))])
+    );
+}
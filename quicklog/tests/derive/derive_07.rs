@@ -0,0 +1,49 @@
+// Testing generic structs and the bound attribute.
+use std::marker::PhantomData;
+
+use quicklog::serialize::Encode as _;
+use quicklog::Serialize;
+
+#[derive(Serialize)]
+struct Wrapper<T> {
+    inner: T,
+}
+
+#[derive(Serialize)]
+struct Pair<A, B>
+where
+    A: Copy,
+{
+    a: A,
+    #[quicklog(rename = "b")]
+    b: B,
+}
+
+struct NotSerialize;
+
+#[derive(Serialize)]
+#[quicklog(bound = "")]
+struct Tagged<T> {
+    id: u32,
+    #[quicklog(skip)]
+    _tag: PhantomData<T>,
+}
+
+fn main() {
+    let mut buf = [0; 128];
+
+    let w = Wrapper { inner: 5u64 };
+    let (store, rest) = w.encode(&mut buf);
+    assert_eq!(format!("{}", store), "5");
+
+    let p = Pair { a: 1i32, b: "two" };
+    let (store, rest) = p.encode(rest);
+    assert_eq!(format!("{}", store), "1 b=two");
+
+    let t = Tagged::<NotSerialize> {
+        id: 7,
+        _tag: PhantomData,
+    };
+    let (store, _) = t.encode(rest);
+    assert_eq!(format!("{}", store), "7");
+}
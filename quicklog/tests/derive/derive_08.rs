@@ -0,0 +1,56 @@
+// Testing enums without fields, with and without explicit discriminants.
+use quicklog::serialize::{Encode as _, Value};
+use quicklog::Serialize;
+
+#[derive(Serialize)]
+enum Side {
+    Buy,
+    Sell,
+}
+
+#[derive(Serialize)]
+#[repr(u16)]
+enum Status {
+    Active = 100,
+    Halted = 300,
+    Closed,
+}
+
+#[derive(Serialize)]
+#[allow(dead_code)]
+enum Sparse {
+    Low = -5,
+    High = 1000,
+}
+
+#[derive(Serialize)]
+struct Order {
+    side: Side,
+    status: Status,
+}
+
+fn main() {
+    let mut buf = [0; 128];
+
+    assert_eq!(Side::Sell.buffer_size_required(), 1);
+    let (store, rest) = Side::Sell.encode(&mut buf);
+    assert_eq!(format!("{}", store), "Sell");
+    assert_eq!(store.as_value(), Value::Str("Sell".to_string()));
+
+    assert_eq!(Status::Closed.buffer_size_required(), 2);
+    let (store, rest) = Status::Halted.encode(rest);
+    assert_eq!(format!("{}", store), "Halted");
+    let (store, rest) = Status::Closed.encode(rest);
+    assert_eq!(format!("{}", store), "Closed");
+
+    assert_eq!(Sparse::High.buffer_size_required(), 1);
+    let (store, rest) = Sparse::High.encode(rest);
+    assert_eq!(format!("{}", store), "High");
+
+    let order = Order {
+        side: Side::Buy,
+        status: Status::Active,
+    };
+    let (store, _) = order.encode(rest);
+    assert_eq!(format!("{}", store), "Buy Active");
+}
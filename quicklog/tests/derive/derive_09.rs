@@ -0,0 +1,45 @@
+// Testing the redact field attribute.
+use quicklog::serialize::{Encode as _, Value};
+use quicklog::Serialize;
+
+#[derive(Serialize)]
+struct Account {
+    #[quicklog(rename = "id")]
+    id: u32,
+    #[quicklog(rename = "number", redact)]
+    number: &'static str,
+}
+
+#[derive(Serialize)]
+struct Secret {
+    #[quicklog(redact)]
+    key: u64,
+}
+
+fn main() {
+    let mut buf = [0; 128];
+
+    let account = Account {
+        id: 7,
+        number: "1234-5678",
+    };
+    // the redacted field is not encoded at all
+    assert_eq!(account.buffer_size_required(), 4);
+    let (store, rest) = account.encode(&mut buf);
+    assert_eq!(format!("{}", store), "id=7 number=***");
+    assert_eq!(
+        store.as_value(),
+        Value::Map(vec![
+            (Value::Str("id".to_string()), Value::UInt(7)),
+            (
+                Value::Str("number".to_string()),
+                Value::Str("***".to_string())
+            ),
+        ])
+    );
+
+    let secret = Secret { key: 42 };
+    assert_eq!(secret.buffer_size_required(), 0);
+    let (store, _) = secret.encode(rest);
+    assert_eq!(format!("{}", store), "***");
+}
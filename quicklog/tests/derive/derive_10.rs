@@ -0,0 +1,60 @@
+// Testing the display and debug struct attributes.
+use std::fmt::{self, Display};
+
+use quicklog::serialize::{Encode as _, Value};
+use quicklog::Serialize;
+
+#[derive(Serialize)]
+#[quicklog(display)]
+enum Side {
+    Bid(u64),
+    Ask(u64),
+}
+
+impl Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Side::Bid(price) => write!(f, "bid@{}", price),
+            Side::Ask(price) => write!(f, "ask@{}", price),
+        }
+    }
+}
+
+// does not implement `Serialize`
+#[derive(Debug)]
+struct Venue {
+    name: String,
+}
+
+#[derive(Debug, Serialize)]
+#[quicklog(debug)]
+struct Order<T> {
+    venue: Venue,
+    id: T,
+}
+
+fn main() {
+    let mut buf = [0; 256];
+
+    let bid = Side::Bid(100);
+    assert_eq!(bid.buffer_size_required(), 8 + "bid@100".len());
+    let (store, rest) = bid.encode(&mut buf);
+    assert_eq!(format!("{}", store), "bid@100");
+    assert_eq!(store.as_value(), Value::Str("bid@100".to_string()));
+
+    let (store, rest) = Side::Ask(7).encode(rest);
+    assert_eq!(format!("{:>6}", store), " ask@7");
+
+    let order = Order {
+        venue: Venue {
+            name: "XNAS".to_string(),
+        },
+        id: 3u8,
+    };
+    let (store, _) = order.encode(rest);
+    assert_eq!(format!("{}", store), format!("{:?}", order));
+    assert_eq!(
+        format!("{}", store),
+        r#"Order { venue: Venue { name: "XNAS" }, id: 3 }"#
+    );
+}
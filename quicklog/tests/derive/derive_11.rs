@@ -0,0 +1,86 @@
+// Testing tuple structs and renamed enum variants.
+use quicklog::serialize::{Encode as _, Value};
+use quicklog::Serialize;
+
+#[derive(Serialize)]
+struct Wide(
+    u32,
+    u64,
+    usize,
+    i32,
+    i64,
+    isize,
+    f32,
+    f64,
+    bool,
+    u32,
+    u64,
+    i32,
+    i64,
+    #[quicklog(skip)]
+    #[allow(dead_code)]
+    Vec<u8>,
+    #[quicklog(rename = "venue")] &'static str,
+);
+
+#[derive(Serialize)]
+struct Price(u64);
+
+#[derive(Serialize)]
+enum Side {
+    #[quicklog(rename = "B")]
+    Bid,
+    #[quicklog(rename = "S")]
+    Ask,
+    Cross,
+}
+
+fn main() {
+    let mut buf = [0; 256];
+
+    let wide = Wide(
+        1,
+        2,
+        3,
+        -4,
+        -5,
+        -6,
+        1.5,
+        2.5,
+        true,
+        10,
+        11,
+        -12,
+        -13,
+        vec![0; 64],
+        "XNAS",
+    );
+    let (store, rest) = wide.encode(&mut buf);
+    assert_eq!(
+        format!("{}", store),
+        "1 2 3 -4 -5 -6 1.5 2.5 true 10 11 -12 -13 venue=XNAS"
+    );
+    let Value::Map(entries) = store.as_value() else {
+        panic!("expected a map");
+    };
+    assert_eq!(entries.len(), 14);
+    assert_eq!(entries[0], (Value::Str("0".to_string()), Value::UInt(1)));
+    assert_eq!(
+        entries[13],
+        (
+            Value::Str("venue".to_string()),
+            Value::Str("XNAS".to_string())
+        )
+    );
+
+    let (store, rest) = Price(100).encode(rest);
+    assert_eq!(format!("{}", store), "100");
+
+    let (store, rest) = Side::Bid.encode(rest);
+    assert_eq!(format!("{}", store), "B");
+    assert_eq!(store.as_value(), Value::Str("B".to_string()));
+    let (store, rest) = Side::Ask.encode(rest);
+    assert_eq!(format!("{}", store), "S");
+    let (store, _) = Side::Cross.encode(rest);
+    assert_eq!(format!("{}", store), "Cross");
+}
@@ -0,0 +1,37 @@
+use quicklog::{config, dropped_count, info, init, with_flush, MAX_LOGGER_CAPACITY};
+use quicklog_flush::Flush;
+
+static mut FLUSHED: usize = 0;
+static mut LAST_LINE: String = String::new();
+
+struct LastLineFlusher;
+
+impl Flush for LastLineFlusher {
+    fn flush_one(&mut self, display: String) {
+        unsafe {
+            FLUSHED += 1;
+            LAST_LINE = display;
+        }
+    }
+}
+
+fn main() {
+    init!(config().report_dropped(true));
+    with_flush!(LastLineFlusher);
+    assert_eq!(dropped_count(), 0);
+
+    for i in 0..MAX_LOGGER_CAPACITY + 10 {
+        info!("log {}", i);
+    }
+    let dropped = dropped_count();
+    assert!(dropped >= 10);
+
+    // one flushed record, followed by the synthetic warning
+    quicklog::flush!();
+    assert_eq!(unsafe { FLUSHED }, 2);
+    assert!(unsafe { LAST_LINE.contains(&format!("{} messages dropped", dropped)) });
+
+    // only reported once
+    quicklog::flush_all!();
+    assert_eq!(unsafe { FLUSHED }, MAX_LOGGER_CAPACITY + 11 - dropped);
+}
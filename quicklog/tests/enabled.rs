@@ -0,0 +1,58 @@
+use quicklog::{
+    callsites, config, enabled, init,
+    level::{static_min_priority, Level, LevelFilter},
+};
+
+mod common;
+
+fn main() {
+    init!();
+
+    quicklog::level::set_max_level(LevelFilter::Info);
+    assert!(!enabled!(Level::Debug));
+    assert!(enabled!(Level::Info));
+    assert!(enabled!(Level::Error));
+
+    let level = Level::Trace;
+    quicklog::level::set_max_level(LevelFilter::Trace);
+    assert!(enabled!(level));
+
+    // levels compiled out through `QUICKLOG_MIN_LEVEL` are never enabled,
+    // whichever directives this was built with
+    for level in [
+        Level::Trace,
+        Level::Debug,
+        Level::Info,
+        Level::Warn,
+        Level::Error,
+    ] {
+        let compiled_in = level.priority() >= static_min_priority(module_path!());
+        assert_eq!(enabled!(level), compiled_in);
+    }
+
+    // a disabled callsite only affects the calling line
+    assert!(checked::checked());
+    callsites()
+        .filter(|callsite| callsite.module_path().ends_with("::checked"))
+        .for_each(|callsite| callsite.set_enabled(false));
+    assert!(!checked::checked());
+    assert!(enabled!(Level::Error));
+
+    // filters apply as they do to the logging macros
+    quicklog::shutdown();
+    init!(config().filter(|callsite| !callsite.module_path().ends_with("::filtered")));
+    assert!(!filtered::filtered());
+    assert!(enabled!(Level::Error));
+}
+
+mod checked {
+    pub fn checked() -> bool {
+        quicklog::enabled!(quicklog::level::Level::Error)
+    }
+}
+
+mod filtered {
+    pub fn filtered() -> bool {
+        quicklog::enabled!(quicklog::level::Level::Error)
+    }
+}
@@ -0,0 +1,55 @@
+use std::{error::Error, fmt::Display};
+
+use quicklog::{flush_all, formatter, info, with_formatter};
+
+mod common;
+
+#[derive(Debug)]
+struct Wrapped(&'static str, Option<Box<Wrapped>>);
+
+impl Display for Wrapped {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl Error for Wrapped {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.1.as_deref().map(|e| e as _)
+    }
+}
+
+fn main() {
+    setup!();
+
+    let err = Wrapped(
+        "request failed",
+        Some(Box::new(Wrapped(
+            "connection reset",
+            Some(Box::new(Wrapped("timed out", None))),
+        ))),
+    );
+    assert_message_equal!(
+        info!(!err, "order rejected"),
+        "order rejected error=request failed error.cause_chain=[connection reset, timed out]"
+    );
+
+    // Errors without a source only have the `error` field
+    let msg = String::from("no liquidity");
+    assert_message_equal!(info!(reason = !msg), "reason=no liquidity");
+
+    with_formatter!(formatter().json().build());
+    let err = Wrapped("outer", Some(Box::new(Wrapped("inner", None))));
+    info!(!err, "json");
+    flush_all!();
+
+    let line = unsafe { VEC[0].clone() };
+    assert!(
+        line.ends_with(concat!(
+            r#""fields":{"error":{"message":"outer","cause_chain":["inner"]}}}"#,
+            "\n"
+        )),
+        "{}",
+        line
+    );
+}
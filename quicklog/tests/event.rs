@@ -0,0 +1,49 @@
+use std::collections::BTreeSet;
+
+use quicklog::{
+    config, event, flush_all, formatter, init, test_support::CaptureFlusher, with_formatter,
+    Serialize,
+};
+
+#[derive(Serialize)]
+struct Venue {
+    name: &'static str,
+    id: u32,
+}
+
+#[derive(Serialize)]
+struct Fill {
+    price: f64,
+    qty: u64,
+    venue: Venue,
+}
+
+fn main() {
+    let capture = CaptureFlusher::new();
+    init!(config().flusher(capture.clone()));
+    with_formatter!(formatter().json().build());
+
+    let fill = Fill {
+        price: 1.5,
+        qty: 10,
+        venue: Venue {
+            name: "XNAS",
+            id: 3,
+        },
+    };
+    let legs: BTreeSet<u64> = [1, 2].into();
+    event!("order_filled", fill, legs, side = %"buy", order.id = 7);
+    flush_all!();
+
+    let line = capture.take().pop().unwrap();
+    assert!(line.contains(r#""level":"EVENT""#), "{}", line);
+    assert!(
+        line.ends_with(concat!(
+            r#""message":"order_filled","fields":{"fill":{"price":1.5,"qty":10,"#,
+            r#""venue":{"name":"XNAS","id":3}},"legs":[1,2],"side":"buy","order":{"id":7}}}"#,
+            "\n"
+        )),
+        "{}",
+        line
+    );
+}
@@ -0,0 +1,9 @@
+use quicklog::Serialize;
+
+#[derive(Serialize)]
+union Price {
+    ticks: u64,
+    raw: f64,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+use quicklog::info;
+
+fn main() {
+    let a = 1;
+    info!("debug {}", a:?);
+    info!(@a, "unknown prefix");
+}
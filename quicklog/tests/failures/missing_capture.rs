@@ -0,0 +1,6 @@
+use quicklog::info;
+
+fn main() {
+    info!("price {some_var}");
+    info!("{} {}", 1);
+}
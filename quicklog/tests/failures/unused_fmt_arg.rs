@@ -0,0 +1,7 @@
+use quicklog::info;
+
+fn main() {
+    let a = 1;
+    info!("only {}", a, 2);
+    info!("named {a}", a = a, b = 2);
+}
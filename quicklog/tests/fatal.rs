@@ -0,0 +1,42 @@
+use chrono::{TimeZone, Utc};
+use quicklog::{config, fatal, info, init, level::LevelFilter, test_support::CaptureFlusher};
+use quicklog_clock::mock::MockClock;
+
+mod common;
+
+fn main() {
+    let capture = CaptureFlusher::new();
+    let clock = MockClock::new(Utc.timestamp_opt(1_700_000_000, 0).unwrap());
+    init!(config()
+        .clock(clock)
+        .max_level(LevelFilter::Error)
+        .formatter(common::TestFormatter::new())
+        .flusher(capture.clone()));
+
+    info!("filtered out");
+    // logged above every built-in level, and flushed without `flush!`
+    let err = "disk full";
+    fatal!(%err, "journal unwritable");
+    let lines = capture.take();
+    assert_eq!(
+        common::from_log_lines(&lines, common::message_and_level_from_log_line),
+        vec!["[FATAL]\tjournal unwritable err=disk full"]
+    );
+
+    // flushing concurrently with another thread never dequeues a record twice
+    let flushing = std::thread::spawn(|| {
+        for _ in 0..1000 {
+            quicklog::flush_all!();
+        }
+    });
+    for i in 0..1000 {
+        quicklog::error!("line {}", i);
+        if i % 100 == 0 {
+            fatal!("checkpoint {}", i);
+        }
+    }
+    flushing.join().unwrap();
+    quicklog::flush_all!();
+    let lines = capture.take();
+    assert_eq!(lines.len(), 1010);
+}
@@ -0,0 +1,74 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use quicklog::{config, fatal, flush_all, init, test_support::CaptureFlusher, try_info};
+use quicklog_flush::Flush;
+
+mod common;
+
+/// Blocks on its first log line until `release` is set, e.g. as if writing to
+/// a dead disk
+struct StuckFlusher {
+    capture: CaptureFlusher,
+    stuck: Arc<AtomicBool>,
+    release: Arc<AtomicBool>,
+}
+
+impl Flush for StuckFlusher {
+    fn flush_one(&mut self, display: String) {
+        self.flush_str(&display)
+    }
+
+    fn flush_str(&mut self, display: &str) {
+        if !self.stuck.swap(true, Ordering::AcqRel) {
+            while !self.release.load(Ordering::Acquire) {
+                std::thread::yield_now();
+            }
+        }
+        self.capture.flush_str(display);
+    }
+}
+
+fn main() {
+    let capture = CaptureFlusher::new();
+    let stuck = Arc::new(AtomicBool::new(false));
+    let release = Arc::new(AtomicBool::new(false));
+    init!(config()
+        .formatter(common::TestFormatter::new())
+        .flusher(StuckFlusher {
+            capture: capture.clone(),
+            stuck: stuck.clone(),
+            release: release.clone(),
+        }));
+
+    _ = try_info!("first");
+    // holds the consumer lock while stuck flushing the first line
+    let flushing = std::thread::spawn(|| {
+        flush_all!();
+    });
+    while !stuck.load(Ordering::Acquire) {
+        std::thread::yield_now();
+    }
+    while try_info!("queued").is_ok() {}
+
+    // written anyway once the other thread has held the lock for too long,
+    // even though the queue is full
+    let start = Instant::now();
+    fatal!("journal unwritable");
+    assert!(start.elapsed() >= Duration::from_millis(500));
+    assert_eq!(
+        common::from_log_lines(&capture.lines(), common::message_and_level_from_log_line),
+        vec!["[FATAL]\tjournal unwritable"]
+    );
+
+    // the queue is left to the other thread
+    release.store(true, Ordering::Release);
+    flushing.join().unwrap();
+    let lines = common::from_log_lines(&capture.lines(), common::message_and_level_from_log_line);
+    assert_eq!(lines[..3], ["[FATAL]\tjournal unwritable", "[INFO]\tfirst", "[INFO]\tqueued"]);
+}
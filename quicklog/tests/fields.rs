@@ -58,4 +58,11 @@ fn main() {
         ),
         format!("reuse debug, nested field, able to reuse after pass by ref: reuse.debug={:?} some_inner_field.some.field.included=hello world able.to.reuse.s2.borrow={}", s1, &s2)
     );
+
+    // Unnamed fields are named after their expression, without references
+    let sizes = vec![5, 10];
+    assert_message_equal!(
+        info!(%nested.thing.some_str, %&sizes[1], ?sizes.len(), "auto names"),
+        "auto names nested.thing.some_str=hello nested sizes[1]=10 sizes.len()=2"
+    );
 }
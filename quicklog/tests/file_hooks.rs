@@ -0,0 +1,36 @@
+use chrono::{TimeZone, Utc};
+use quicklog::{config, dropped_count, info, init, logger};
+use quicklog_clock::mock::MockClock;
+use quicklog_flush::buffered_file_flusher::BufferedFileFlusher;
+
+fn main() {
+    let path = std::env::temp_dir().join(format!("quicklog-hooks-{}.log", std::process::id()));
+    _ = std::fs::remove_file(&path);
+
+    let flusher = BufferedFileFlusher::new(&path)
+        .header(|_| "# format: text v1\n".to_string())
+        .footer(|summary| {
+            format!(
+                "# lines={} dropped={}\n",
+                summary.lines,
+                dropped_count()
+            )
+        });
+    let clock = MockClock::new(Utc.timestamp_opt(1_700_000_000, 0).unwrap());
+    init!(config().clock(clock).flusher(flusher));
+
+    info!("hello");
+    info!("world");
+    // flushes the remaining lines, then writes the footer
+    logger().shutdown();
+
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "# format: text v1\n\
+         [2023-11-14T22:13:20Z]hello\n\
+         [2023-11-14T22:13:20Z]world\n\
+         # lines=2 dropped=0\n"
+    );
+
+    _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,20 @@
+use quicklog::{callsite, config, flush_all, info, init, test_support::CaptureFlusher, warn};
+
+fn main() {
+    let capture = CaptureFlusher::new();
+    init!(config()
+        .flusher(capture.clone())
+        .filter(|callsite| !callsite.format_string().starts_with("noisy")));
+
+    info!("noisy {}", 1);
+    warn!("kept {}", 2);
+    flush_all!();
+    let lines = capture.take();
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].ends_with("kept 2\n"));
+
+    callsite::clear_filter();
+    info!("noisy {}", 3);
+    flush_all!();
+    assert!(capture.take()[0].ends_with("noisy 3\n"));
+}
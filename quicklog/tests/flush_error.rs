@@ -0,0 +1,43 @@
+use chrono::{DateTime, OutOfRangeError, Utc};
+use quanta::Instant;
+use quicklog::{config, flush_all, info, init, with_clock, FlushError};
+use quicklog_clock::Clock;
+
+static mut ERRORS: Vec<(FlushError, u32)> = Vec::new();
+
+struct BrokenClock;
+
+impl Clock for BrokenClock {
+    fn get_instant(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn compute_system_time_from_instant(
+        &self,
+        _: Instant,
+    ) -> Result<DateTime<Utc>, OutOfRangeError> {
+        chrono::Duration::seconds(-1).to_std().map(|_| Utc::now())
+    }
+}
+
+fn main() {
+    init!(config().on_flush_error(|err, record| unsafe { ERRORS.push((err, record.line)) }));
+    with_clock!(BrokenClock);
+
+    info!("first");
+    let line = line!() - 1;
+    info!("second");
+
+    assert_eq!(quicklog::try_flush!(), Err(FlushError::InvalidTime));
+    let stats = flush_all!();
+    assert_eq!(stats.flushed, 0);
+    assert_eq!(stats.errors, 1);
+
+    assert_eq!(
+        unsafe { ERRORS.clone() },
+        vec![
+            (FlushError::InvalidTime, line),
+            (FlushError::InvalidTime, line + 2)
+        ]
+    );
+}
@@ -0,0 +1,77 @@
+use std::{
+    io,
+    sync::{Arc, Mutex},
+};
+
+use quicklog::{config, flush_all, info, init, test_support::CaptureFlusher, FlushError};
+use quicklog_flush::Flush;
+
+mod common;
+
+/// Sink which fails once `healthy` is unset, e.g. as the disk is full
+struct DeadFlusher {
+    healthy: bool,
+    written: CaptureFlusher,
+}
+
+impl Flush for DeadFlusher {
+    fn flush_one(&mut self, display: String) {
+        self.flush_str(&display)
+    }
+
+    fn flush_str(&mut self, display: &str) {
+        _ = self.try_flush_one(display);
+    }
+
+    fn try_flush_one(&mut self, display: &str) -> io::Result<()> {
+        if !self.healthy {
+            return Err(io::ErrorKind::BrokenPipe.into());
+        }
+        self.healthy = false;
+        self.written.flush_str(display);
+
+        Ok(())
+    }
+}
+
+fn main() {
+    let written = CaptureFlusher::new();
+    let failed_over = Arc::new(Mutex::new(Vec::new()));
+    let hook_failed_over = failed_over.clone();
+    init!(config()
+        .flusher(DeadFlusher {
+            healthy: true,
+            written: written.clone(),
+        })
+        .formatter(common::TestFormatter::new())
+        .on_flush_error(move |err, record| {
+            assert!(matches!(err, FlushError::Io(_)));
+            hook_failed_over
+                .lock()
+                .unwrap()
+                .push(record.log_line.to_string())
+        }));
+
+    info!("first");
+    info!("second");
+    info!("third");
+
+    assert_eq!(quicklog::try_flush!(), Ok(()));
+    let err = quicklog::try_flush!().unwrap_err();
+    assert_eq!(err, FlushError::Io(io::ErrorKind::BrokenPipe.into()));
+    assert_eq!(err.to_string(), "failed to flush log line: broken pipe");
+
+    let stats = flush_all!();
+    assert_eq!(stats.flushed, 0);
+    assert_eq!(stats.errors, 1);
+
+    assert_eq!(
+        common::from_log_lines(&written.lines(), common::message_from_log_line),
+        vec!["first"]
+    );
+    // the hook is passed the formatted log lines which could not be flushed
+    assert_eq!(
+        common::from_log_lines(&failed_over.lock().unwrap(), common::message_from_log_line),
+        vec!["second", "third"]
+    );
+}
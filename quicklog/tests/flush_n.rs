@@ -0,0 +1,29 @@
+use quicklog::{flush_all, info, FlushStats};
+
+mod common;
+
+fn main() {
+    setup!();
+
+    for i in 0..5 {
+        info!("log {}", i);
+    }
+
+    let stats = quicklog::logger().flush_n(2);
+    assert_eq!(stats.flushed, 2);
+    assert_eq!(stats.errors, 0);
+    assert_eq!(
+        stats.bytes,
+        unsafe { VEC.iter() }.map(|line| line.len()).sum::<usize>()
+    );
+
+    let stats = flush_all!();
+    assert_eq!(stats.flushed, 3);
+    assert_eq!(unsafe { VEC.len() }, 5);
+    assert_eq!(
+        common::from_log_lines(unsafe { &VEC }, common::message_from_log_line),
+        (0..5).map(|i| format!("log {}", i)).collect::<Vec<_>>()
+    );
+
+    assert_eq!(quicklog::logger().flush_n(10), FlushStats::default());
+}
@@ -0,0 +1,34 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use quicklog::{config, info, init};
+
+mod common;
+
+static mut VEC: Vec<String> = Vec::new();
+static PREVIOUS_HOOK_CALLED: AtomicBool = AtomicBool::new(false);
+
+fn main() {
+    std::panic::set_hook(Box::new(|_| {
+        // log lines are flushed before the previous hook is called
+        assert_eq!(unsafe { VEC.len() }, 2);
+        PREVIOUS_HOOK_CALLED.store(true, Ordering::Relaxed);
+    }));
+
+    init!(config()
+        .flush_on_panic(true)
+        .formatter(common::TestFormatter::new())
+        .flusher(unsafe { common::VecFlusher::new(&mut VEC) }));
+
+    info!("before panic");
+    let result = std::panic::catch_unwind(|| {
+        info!("about to panic");
+        panic!("crash");
+    });
+
+    assert!(result.is_err());
+    assert!(PREVIOUS_HOOK_CALLED.load(Ordering::Relaxed));
+    assert_eq!(
+        common::from_log_lines(unsafe { &VEC }, common::message_from_log_line),
+        vec!["before panic", "about to panic"]
+    );
+}
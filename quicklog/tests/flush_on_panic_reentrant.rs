@@ -0,0 +1,39 @@
+use quicklog::{config, flush_all, info, init};
+use quicklog_flush::Flush;
+
+mod common;
+
+static mut VEC: Vec<String> = Vec::new();
+
+struct PanickingFlusher;
+
+impl Flush for PanickingFlusher {
+    fn flush_one(&mut self, display: String) {
+        if display.ends_with("boom\n") {
+            panic!("flusher failed");
+        }
+        unsafe { VEC.push(display) };
+    }
+}
+
+fn main() {
+    std::panic::set_hook(Box::new(|_| {}));
+    init!(config()
+        .flush_on_panic(true)
+        .formatter(common::TestFormatter::new())
+        .flusher(PanickingFlusher));
+
+    info!("boom");
+    info!("after the panic");
+    // the panic hook does not flush again from within the flusher
+    let result = std::panic::catch_unwind(|| flush_all!());
+    assert!(result.is_err());
+    assert!(unsafe { VEC.is_empty() });
+
+    // flushing is not locked out after the flusher panicked
+    assert_eq!(flush_all!().flushed, 1);
+    assert_eq!(
+        common::from_log_lines(unsafe { &VEC }, common::message_from_log_line),
+        vec!["after the panic"]
+    );
+}
@@ -0,0 +1,54 @@
+use std::time::Duration;
+
+use chrono::{TimeZone, Utc};
+use quicklog::{
+    config, info_to,
+    merge::flush_merged,
+    test_support::{CaptureFlusher, CaptureFormatter},
+    Quicklog, MAX_LOGGER_CAPACITY,
+};
+use quicklog_clock::mock::MockClock;
+
+fn main() {
+    let queue_capacity = MAX_LOGGER_CAPACITY - 1;
+    let clock = MockClock::new(Utc.timestamp_opt(1_700_000_000, 0).unwrap());
+    let capture = CaptureFlusher::new();
+    let mut orders = Quicklog::new(
+        config()
+            .clock(clock.clone())
+            .grow_to(queue_capacity + 10)
+            .flusher(capture.clone())
+            .formatter(CaptureFormatter),
+    );
+    let mut fills = Quicklog::new(config().clock(clock.clone()));
+
+    // the last 5 records spill over the full queue
+    for i in 0..queue_capacity + 5 {
+        info_to!(&mut orders, "order {}", i);
+    }
+    clock.advance(Duration::from_micros(1));
+    info_to!(&mut fills, "fill");
+    clock.advance(Duration::from_micros(1));
+    // spills over too, as earlier records are still spilled
+    info_to!(&mut orders, "order {}", queue_capacity + 5);
+
+    let stats = orders.queue_stats();
+    assert_eq!(stats.len, queue_capacity + 6);
+    assert_eq!(stats.resizes, 1);
+    assert_eq!(stats.dropped, 0);
+
+    // spilled records are flushed after those in the queue, and merged with
+    // other loggers by the instant they were logged at
+    let stats = flush_merged(&mut [&mut orders, &mut fills], usize::MAX);
+    assert_eq!(stats.flushed, queue_capacity + 7);
+    assert_eq!(orders.queue_stats().len, 0);
+
+    let lines = capture.take();
+    let expected = (0..queue_capacity + 5)
+        .map(|i| format!("[INFO]\torder {}\n", i))
+        .chain([
+            "[INFO]\tfill\n".to_string(),
+            format!("[INFO]\torder {}\n", queue_capacity + 5),
+        ]);
+    assert!(lines.into_iter().eq(expected));
+}
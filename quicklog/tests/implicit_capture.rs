@@ -0,0 +1,17 @@
+use quicklog::info;
+
+mod common;
+
+fn main() {
+    setup!();
+
+    let symbol = String::from("AAPL");
+    let width = 6;
+    assert_message_equal!(info!("[{symbol:>width$}]"), "[  AAPL]");
+    assert_message_equal!(
+        info!("{symbol} {0} {qty}", 1.5, qty = 10),
+        "AAPL 1.5 10"
+    );
+    // Captured by clone rather than moved into the log line
+    assert_eq!(symbol, "AAPL");
+}
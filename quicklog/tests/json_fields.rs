@@ -0,0 +1,80 @@
+use std::collections::BTreeSet;
+
+use chrono::{TimeZone, Utc};
+use quicklog::{app_info, config, flush_all, formatter, info, init, with_formatter};
+use quicklog_clock::mock::MockClock;
+
+mod common;
+
+static mut VEC: Vec<String> = Vec::new();
+
+fn main() {
+    let clock = MockClock::new(Utc.timestamp_opt(1_700_000_000, 0).unwrap());
+    init!(config()
+        .clock(clock)
+        .version("1.2.3")
+        .flusher(unsafe { common::VecFlusher::new(&mut VEC) }));
+    with_formatter!(formatter().json().build());
+
+    let ids: BTreeSet<u32> = [1, 2].into_iter().collect();
+    let count = 42;
+    info!(
+        flag = true,
+        count,
+        delta = -1i8,
+        ratio = 0.5,
+        name = "a",
+        debug = ?"a",
+        display = %7,
+        ^ids,
+        "typed"
+    );
+    flush_all!();
+
+    let line = unsafe { VEC[0].clone() };
+    assert!(
+        line.ends_with(concat!(
+            r#""fields":{"flag":true,"count":42,"delta":-1,"ratio":0.5,"name":"a","#,
+            r#""debug":"\"a\"","display":"7","ids":[1,2]}}"#,
+            "\n"
+        )),
+        "{}",
+        line
+    );
+
+    // Variables captured by the format string are also written as fields
+    let symbol = "AAPL";
+    let qty = 10;
+    let price = 1.5;
+    let side = Some("buy");
+    info!(count, "{symbol} {qty:>4} @ {price:.2} {side:?}");
+    flush_all!();
+
+    let line = unsafe { VEC[1].clone() };
+    assert!(
+        line.ends_with(concat!(
+            r#""message":"AAPL   10 @ 1.50 Some(\"buy\")","#,
+            r#""fields":{"count":42,"symbol":"AAPL","qty":10,"price":1.5,"side":"Some(\"buy\")"}}"#,
+            "\n"
+        )),
+        "{}",
+        line
+    );
+
+    // The process captured at init is written as top-level fields
+    let info = app_info().unwrap();
+    let expected = format!(
+        r#","hostname":"{}","pid":{},"executable":"{}","version":"1.2.3","message":"#,
+        info.hostname(),
+        info.pid(),
+        info.executable()
+    );
+    assert!(line.contains(&expected), "{}", line);
+
+    with_formatter!(formatter().json().with_app_info(false).build());
+    info!("without app info");
+    flush_all!();
+    let line = unsafe { VEC[2].clone() };
+    assert!(!line.contains("\"hostname\""), "{}", line);
+    assert!(!line.contains("\"version\""), "{}", line);
+}
@@ -0,0 +1,31 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use quicklog::{debug, info, level, Lazy};
+
+mod common;
+
+static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+fn main() {
+    setup!();
+    level::set_max_level(level::LevelFilter::Info);
+
+    let expensive = vec![1, 2, 3];
+    let lazy = Lazy::new(move |f| {
+        CALLS.fetch_add(1, Ordering::Relaxed);
+        write!(f, "{:?}", expensive)
+    });
+
+    // Only formatted when flushing, and never if filtered out
+    debug!("filtered {}", lazy.clone());
+    assert_message_equal!(
+        {
+            info!("logged {}", lazy.clone());
+            assert_eq!(CALLS.load(Ordering::Relaxed), 0);
+        },
+        "logged [1, 2, 3]"
+    );
+    assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+
+    assert_message_equal!(info!(values = lazy, "with field"), "with field values=[1, 2, 3]");
+}
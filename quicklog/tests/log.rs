@@ -0,0 +1,38 @@
+use quicklog::{
+    level::{set_max_level, Level, LevelFilter},
+    log,
+};
+
+mod common;
+
+fn severity_to_level(severity: u8) -> Level {
+    match severity {
+        0 => Level::Debug,
+        1 => Level::Info,
+        _ => Level::Error,
+    }
+}
+
+fn main() {
+    setup!();
+
+    let level = 1;
+    assert_message_with_level_equal!(
+        log!(severity_to_level(0), "Hello world {}", level),
+        "[DEBUG]\tHello world 1".to_string()
+    );
+    assert_message_with_level_equal!(
+        log!(severity_to_level(level), ?level, "Hello world"),
+        "[INFO]\tHello world level=1".to_string()
+    );
+
+    set_max_level(LevelFilter::Info);
+    log!(severity_to_level(0), "filtered out");
+    quicklog::flush!();
+    assert!(unsafe { VEC.is_empty() });
+
+    assert_message_with_level_equal!(
+        log!(severity_to_level(2), "Hello world"),
+        "[ERROR]\tHello world".to_string()
+    );
+}
@@ -0,0 +1,40 @@
+use quicklog::{
+    config, flush_all, formatter, init, level::Level, level::LevelFilter, log_dynamic,
+    serialize::Value, test_support::CaptureFlusher, with_formatter,
+};
+
+fn main() {
+    let capture = CaptureFlusher::new();
+    init!(config()
+        .max_level(LevelFilter::Info)
+        .flusher(capture.clone()));
+
+    let message = format!("alert {} triggered", "cpu");
+    log_dynamic(Level::Warn, "alerts", message, vec![("threshold", Value::UInt(10))]).unwrap();
+    log_dynamic(Level::Debug, "alerts", "hidden", Vec::new()).unwrap();
+    flush_all!();
+    let lines = capture.take();
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].ends_with("alert cpu triggered threshold=10\n"));
+
+    with_formatter!(formatter().json().build());
+    let line_logged = line!() + 1;
+    log_dynamic(Level::Info, "alerts", "cleared", vec![("threshold", Value::UInt(10))]).unwrap();
+    flush_all!();
+
+    let line = capture.take().remove(0);
+    assert!(line.contains(r#""target":"alerts","file":"#), "{}", line);
+    assert!(
+        line.contains(&format!(r#"log_dynamic.rs","line":{},"#, line_logged)),
+        "{}",
+        line
+    );
+    assert!(
+        line.ends_with(concat!(
+            r#""message":"cleared","fields":{"threshold":10}}"#,
+            "\n"
+        )),
+        "{}",
+        line
+    );
+}
@@ -0,0 +1,41 @@
+use chrono::{TimeZone, Utc};
+use quicklog::{config, flush_all, formatter, info, init, span, warn, with_formatter};
+use quicklog_clock::mock::MockClock;
+
+use common::Something;
+
+mod common;
+
+static mut VEC: Vec<String> = Vec::new();
+
+fn main() {
+    let clock = MockClock::new(Utc.timestamp_opt(1_700_000_000, 0).unwrap());
+    init!(config()
+        .clock(clock)
+        .flusher(unsafe { common::VecFlusher::new(&mut VEC) }));
+    with_formatter!(formatter().logfmt().build());
+
+    let s1 = Something {
+        some_str: "Hello world",
+    };
+    let id = 10;
+
+    info!(id, order.side = "buy", "order {} filled", id);
+    warn!(?s1, empty = "", "quote \"this\"");
+    span!("session", user = "a b").in_scope(|| info!("in span"));
+    flush_all!();
+
+    assert_eq!(
+        unsafe { VEC.clone() },
+        vec![
+            "ts=2023-11-14T22:13:20Z level=INF seq=0 tid=1 thread=main msg=\"order 10 filled\" id=10 order.side=buy\n"
+                .to_string(),
+            format!(
+                "ts=2023-11-14T22:13:20Z level=WRN seq=1 tid=1 thread=main msg=\"quote \\\"this\\\"\" s1=\"{}\" empty=\"\"\n",
+                format!("{:?}", s1).replace('"', "\\\"")
+            ),
+            "ts=2023-11-14T22:13:20Z level=INF seq=2 tid=1 thread=main msg=\"in span\" span=\"session{user=a b}\"\n"
+                .to_string(),
+        ]
+    );
+}
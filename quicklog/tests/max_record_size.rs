@@ -0,0 +1,21 @@
+use std::collections::BTreeSet;
+
+use quicklog::{config, info, init};
+
+mod common;
+
+static mut VEC: Vec<String> = Vec::new();
+
+fn main() {
+    init!(config()
+        .max_record_size(32)
+        .flusher(unsafe { common::VecFlusher::new(&mut VEC) })
+        .formatter(common::TestFormatter::new()));
+
+    let long = "a very long string which does not fit";
+    let set: BTreeSet<u64> = (0..100).collect();
+
+    assert_message_equal!(info!("{}", ^long), "a very long string wh...");
+    assert_message_equal!(info!(^set, "set:"), "set: set={0, 1, 2, ...}");
+    assert_message_equal!(info!("{}", ^"fits"), "fits");
+}
@@ -0,0 +1,47 @@
+use std::time::Duration;
+
+use chrono::{TimeZone, Utc};
+use quicklog::{
+    config, info_to,
+    merge::flush_merged,
+    test_support::{CaptureFlusher, CaptureFormatter},
+    warn_to, Quicklog,
+};
+use quicklog_clock::mock::MockClock;
+
+fn main() {
+    let clock = MockClock::new(Utc.timestamp_opt(1_700_000_000, 0).unwrap());
+    let capture = CaptureFlusher::new();
+    let mut orders = Quicklog::new(
+        config()
+            .clock(clock.clone())
+            .flusher(capture.clone())
+            .formatter(CaptureFormatter),
+    );
+    let mut fills = Quicklog::new(config().clock(clock.clone()));
+
+    info_to!(&mut orders, "order {} sent", 1);
+    clock.advance(Duration::from_micros(1));
+    info_to!(&mut fills, "order {} filled", 1);
+    clock.advance(Duration::from_micros(1));
+    info_to!(&mut orders, "order {} sent", 2);
+    clock.advance(Duration::from_micros(1));
+    warn_to!(&mut fills, "order {} rejected", 2);
+
+    assert_eq!(flush_merged(&mut [&mut orders, &mut fills], 3).flushed, 3);
+    assert_eq!(
+        flush_merged(&mut [&mut orders, &mut fills], usize::MAX).flushed,
+        1
+    );
+    assert_eq!(fills.flush_n(usize::MAX).flushed, 0);
+
+    assert_eq!(
+        capture.lines(),
+        vec![
+            "[INFO]\torder 1 sent\n",
+            "[INFO]\torder 1 filled\n",
+            "[INFO]\torder 2 sent\n",
+            "[WARN]\torder 2 rejected\n",
+        ]
+    );
+}
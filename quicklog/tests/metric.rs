@@ -0,0 +1,32 @@
+use chrono::{TimeZone, Utc};
+use quicklog::{config, counter, flush_all, formatter, gauge, init, with_formatter};
+use quicklog_clock::mock::MockClock;
+
+mod common;
+
+static mut VEC: Vec<String> = Vec::new();
+
+fn main() {
+    let clock = MockClock::new(Utc.timestamp_opt(1_700_000_000, 0).unwrap());
+    init!(config()
+        .clock(clock)
+        .flusher(unsafe { common::VecFlusher::new(&mut VEC) }));
+
+    counter!("orders_sent", 3);
+    flush_all!();
+    assert!(unsafe { VEC[0].ends_with("orders_sent metric=counter value=3\n") });
+
+    with_formatter!(formatter().json().build());
+    gauge!("queue_depth", 12.5);
+    flush_all!();
+
+    let line = unsafe { VEC[1].clone() };
+    assert!(
+        line.ends_with(concat!(
+            r#""message":"queue_depth","fields":{"metric":"gauge","value":12.5}}"#,
+            "\n"
+        )),
+        "{}",
+        line
+    );
+}
@@ -0,0 +1,42 @@
+use std::cell::Cell;
+
+use quicklog::info;
+
+use common::{SerializeStruct, Something};
+
+mod common;
+
+// Neither `Clone` nor `Display`, so only usable through the `?` prefix
+#[derive(Debug)]
+struct Counter(Cell<u32>);
+
+fn main() {
+    setup!();
+
+    let s = Something {
+        some_str: "Hello world",
+    };
+    let ser = SerializeStruct {
+        symbol: String::from("Hello"),
+    };
+    let counter = Counter(Cell::new(1));
+
+    assert_message_equal!(
+        info!("{} {:?} {} {}", %s, ?s.some_str, ^ser, 5),
+        "Something display: Hello world \"Hello world\" Hello 5"
+    );
+    assert_message_equal!(
+        info!(?s, "[{a:>7}|{b:<4}]", a = ^ser, b = %1),
+        format!("[  Hello|1   ] s={:?}", s)
+    );
+
+    // Prefixed format arguments are formatted when logging, rather than
+    // cloned and formatted when flushing
+    info!("counter: {:?}", ?counter);
+    counter.0.set(2);
+    quicklog::flush!();
+    assert_eq!(
+        common::from_log_lines(unsafe { &VEC }, common::message_from_log_line),
+        vec!["counter: Counter(Cell { value: 1 })"]
+    );
+}
@@ -0,0 +1,67 @@
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use quicklog::{config, info, init, with_flush, OverflowPolicy, MAX_LOGGER_CAPACITY};
+use quicklog_flush::Flush;
+
+static mut FLUSHED: usize = 0;
+static FLUSHED_CONCURRENTLY: AtomicUsize = AtomicUsize::new(0);
+
+struct CountingFlusher;
+
+impl Flush for CountingFlusher {
+    fn flush_one(&mut self, _display: String) {
+        unsafe { FLUSHED += 1 };
+    }
+}
+
+struct AtomicCountingFlusher;
+
+impl Flush for AtomicCountingFlusher {
+    fn flush_one(&mut self, _display: String) {
+        FLUSHED_CONCURRENTLY.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+fn log_past_capacity() {
+    for i in 0..MAX_LOGGER_CAPACITY + 10 {
+        info!("log {}", i);
+    }
+}
+
+fn main() {
+    // default policy drops records once the queue is full
+    init!();
+    with_flush!(CountingFlusher);
+    log_past_capacity();
+    quicklog::flush_all!();
+    let queue_capacity = unsafe { FLUSHED };
+    assert!(queue_capacity < MAX_LOGGER_CAPACITY + 10);
+
+    quicklog::logger().shutdown();
+    unsafe { FLUSHED = 0 };
+
+    // flushing in place keeps every record
+    init!(config().overflow_policy(OverflowPolicy::FlushInPlace));
+    log_past_capacity();
+    assert_eq!(unsafe { FLUSHED }, MAX_LOGGER_CAPACITY + 10 - queue_capacity);
+    quicklog::flush_all!();
+    assert_eq!(unsafe { FLUSHED }, MAX_LOGGER_CAPACITY + 10);
+
+    quicklog::logger().shutdown();
+
+    // flushing in place waits for the background flush thread rather than
+    // dequeuing at the same time, and still keeps every record
+    let handle = init!(config()
+        .overflow_policy(OverflowPolicy::FlushInPlace)
+        .background_flush(Duration::from_micros(1))
+        .flusher(AtomicCountingFlusher));
+    log_past_capacity();
+    drop(handle);
+    assert_eq!(
+        FLUSHED_CONCURRENTLY.load(Ordering::Relaxed),
+        MAX_LOGGER_CAPACITY + 10
+    );
+}
@@ -0,0 +1,36 @@
+use quicklog::info;
+
+mod common;
+
+#[derive(Debug)]
+struct DebugOnly(Vec<u32>);
+
+struct DisplayOnly(String);
+
+impl std::fmt::Display for DisplayOnly {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<{}>", self.0)
+    }
+}
+
+fn main() {
+    setup!();
+
+    // Not `Clone`, so formatted when logged
+    let fills = DebugOnly(vec![1, 2]);
+    let venue = DisplayOnly("XNYS".to_string());
+    assert_message_equal!(
+        info!(?fills, %venue, "order filled"),
+        "order filled fills=DebugOnly([1, 2]) venue=<XNYS>"
+    );
+
+    // Borrowed from locals, and owned through `ToOwned`
+    let owner = "alice".to_string();
+    let account = Some(owner.as_str());
+    assert_message_equal!(
+        info!(?account, "account {:?}", account),
+        "account Some(\"alice\") account=Some(\"alice\")"
+    );
+    let nested = &owner.as_str();
+    assert_message_equal!(info!(nested, "owner {}", nested), "owner alice nested=alice");
+}
@@ -0,0 +1,41 @@
+use quicklog::{flush_all, info, queue_stats, QueueStats};
+
+mod common;
+
+fn main() {
+    setup!();
+
+    let capacity = queue_stats().capacity;
+    assert!(capacity > 0);
+
+    for i in 0..3 {
+        info!("hello {}", i);
+    }
+    assert_eq!(
+        queue_stats(),
+        QueueStats {
+            len: 3,
+            high_watermark: 3,
+            capacity,
+            resizes: 0,
+            enqueued: 3,
+            dequeued: 0,
+            dropped: 0,
+        }
+    );
+
+    flush_all!();
+    info!("after flush");
+    assert_eq!(
+        queue_stats(),
+        QueueStats {
+            len: 1,
+            high_watermark: 3,
+            capacity,
+            resizes: 0,
+            enqueued: 4,
+            dequeued: 3,
+            dropped: 0,
+        }
+    );
+}
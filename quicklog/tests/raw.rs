@@ -0,0 +1,28 @@
+use quicklog::{callsites, debug_raw, info_raw, warn_raw};
+
+mod common;
+
+fn main() {
+    setup!();
+
+    assert_message_equal!(info_raw!("market open"), "market open");
+
+    let rendered = format!("{} orders pending", 3);
+    assert_message_equal!(warn_raw!(rendered), "3 orders pending");
+
+    let borrowed = String::from("borrowed");
+    assert_message_equal!(debug_raw!(borrowed.as_str().to_owned()), "borrowed");
+    assert_message_equal!(debug_raw!(&borrowed), "borrowed");
+    assert_message_equal!(debug_raw!(borrowed.as_str()), "borrowed");
+
+    // disabling the callsite silences the raw log line
+    fn report() {
+        info_raw!("report");
+    }
+    callsites()
+        .filter(|callsite| callsite.file() == file!())
+        .for_each(|callsite| callsite.set_enabled(false));
+    report();
+    quicklog::flush_all!();
+    assert!(unsafe { VEC.is_empty() });
+}
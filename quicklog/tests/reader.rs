@@ -0,0 +1,60 @@
+use std::time::Duration;
+
+use chrono::{TimeZone, Utc};
+use quicklog::{
+    config, info_to, level::Level, serialize::Value, test_support::CaptureFlusher, warn_to,
+    FlushError, Quicklog,
+};
+use quicklog_clock::mock::MockClock;
+
+fn main() {
+    let clock = MockClock::new(Utc.timestamp_opt(1_700_000_000, 0).unwrap());
+    let capture = CaptureFlusher::new();
+    let mut logger = Quicklog::new(
+        config()
+            .clock(clock.clone())
+            .serialize_buffer_capacity(256)
+            .flusher(capture.clone()),
+    );
+
+    let qty = 100u64;
+    info_to!(&mut logger, ^qty, venue = "XNAS", "order {} filled", 1);
+    clock.advance(Duration::from_secs(1));
+    warn_to!(&mut logger, "order {} rejected", 2);
+
+    let records: Vec<_> = logger.records().collect();
+    assert_eq!(records.len(), 2);
+
+    let filled = &records[0];
+    assert_eq!(filled.timestamp, Utc.timestamp_opt(1_700_000_000, 0).unwrap());
+    assert_eq!(filled.level, Level::Info);
+    assert_eq!(filled.message, "order 1 filled");
+    assert_eq!(
+        filled.args,
+        vec![
+            ("qty", Value::UInt(100)),
+            ("venue", Value::Str("XNAS".to_string())),
+        ]
+    );
+
+    let rejected = &records[1];
+    assert_eq!(rejected.timestamp, Utc.timestamp_opt(1_700_000_001, 0).unwrap());
+    assert_eq!(rejected.level, Level::Warn);
+    assert_eq!(rejected.message, "order 2 rejected");
+    assert_eq!(rejected.sequence, filled.sequence + 1);
+    assert!(rejected.args.is_empty());
+
+    // Read records are not flushed again
+    assert!(matches!(logger.read_one(), Err(FlushError::Empty)));
+    assert_eq!(logger.flush_n(usize::MAX).flushed, 0);
+    assert!(capture.lines().is_empty());
+
+    // The message and arguments were decoded when read, so reusing the
+    // serialization buffer leaves them intact
+    for i in 0..100u64 {
+        info_to!(&mut logger, ^i, "overwrite");
+    }
+    assert_eq!(logger.flush_n(usize::MAX).flushed, 100);
+    assert_eq!(filled.message, "order 1 filled");
+    assert_eq!(filled.args[0], ("qty", Value::UInt(100)));
+}
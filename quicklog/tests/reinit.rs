@@ -0,0 +1,52 @@
+use quicklog::{config, flush_all, info, init, level::LevelFilter};
+use quicklog_flush::Flush;
+
+mod common;
+
+static mut FIRST: Vec<String> = Vec::new();
+static mut SECOND: Vec<String> = Vec::new();
+static mut DROPPED_FLUSHERS: usize = 0;
+
+struct DropFlusher(common::VecFlusher);
+
+impl Flush for DropFlusher {
+    fn flush_one(&mut self, display: String) {
+        self.0.flush_one(display)
+    }
+}
+
+impl Drop for DropFlusher {
+    fn drop(&mut self) {
+        unsafe { DROPPED_FLUSHERS += 1 };
+    }
+}
+
+fn main() {
+    init!(config()
+        .max_level(LevelFilter::Warn)
+        .flusher(DropFlusher(unsafe { common::VecFlusher::new(&mut FIRST) }))
+        .formatter(common::TestFormatter::new()));
+    quicklog::warn!("first");
+    quicklog::warn!("flushed on shutdown");
+
+    quicklog::shutdown();
+    assert_eq!(unsafe { DROPPED_FLUSHERS }, 1);
+    assert_eq!(
+        unsafe { common::from_log_lines(&FIRST, common::message_from_log_line) },
+        vec!["first", "flushed on shutdown"]
+    );
+    assert_eq!(quicklog::logger().queue_stats().enqueued, 0);
+
+    // Initializing again applies the new configuration
+    init!(config()
+        .max_level(LevelFilter::Trace)
+        .flusher(unsafe { common::VecFlusher::new(&mut SECOND) })
+        .formatter(common::TestFormatter::new()));
+    info!("second");
+    flush_all!();
+    assert_eq!(
+        unsafe { common::from_log_lines(&SECOND, common::message_from_log_line) },
+        vec!["second"]
+    );
+    assert_eq!(unsafe { FIRST.len() }, 2);
+}
@@ -0,0 +1,27 @@
+use quicklog::{config, flush_all, info, init};
+use quicklog_flush::rolling_file_flusher::{RollingFileFlusher, Rotation};
+
+fn main() {
+    let dir = std::env::temp_dir().join(format!("quicklog-rolling-{}", std::process::id()));
+    _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    init!(config().rolling_file_flusher(
+        RollingFileFlusher::new(&dir, "app.log")
+            .rotation(Rotation::Never)
+            .max_file_size(1)
+            .max_files(2)
+    ));
+
+    info!("first");
+    info!("second");
+    info!("third");
+    flush_all!();
+
+    let read = |name: &str| std::fs::read_to_string(dir.join(name)).unwrap();
+    assert!(!dir.join("app.log").exists());
+    assert!(read("app.1.log").ends_with("second\n"));
+    assert!(read("app.2.log").ends_with("third\n"));
+
+    _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,27 @@
+use quicklog::{config, debug, error, info, init, level::LevelFilter};
+
+mod common;
+
+static mut ERRORS: Vec<String> = Vec::new();
+static mut INFOS: Vec<String> = Vec::new();
+static mut REST: Vec<String> = Vec::new();
+
+fn main() {
+    init!(unsafe {
+        config()
+            .flusher(common::VecFlusher::new(&mut REST))
+            .route(LevelFilter::Error, common::VecFlusher::new(&mut ERRORS))
+            .route(LevelFilter::Info, common::VecFlusher::new(&mut INFOS))
+    });
+    quicklog::logger().use_formatter(Box::new(common::TestFormatter::new()));
+
+    debug!("debug");
+    info!("info");
+    error!("error");
+    quicklog::flush_all!();
+
+    let messages = |lines: &Vec<String>| common::from_log_lines(lines, common::message_from_log_line);
+    assert_eq!(messages(unsafe { &ERRORS }), vec!["error"]);
+    assert_eq!(messages(unsafe { &INFOS }), vec!["info", "error"]);
+    assert_eq!(messages(unsafe { &REST }), vec!["debug"]);
+}
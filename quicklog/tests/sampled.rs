@@ -0,0 +1,25 @@
+use quicklog::{info_sampled, warn_sampled};
+
+mod common;
+
+fn main() {
+    setup!();
+
+    for i in 0..7 {
+        info_sampled!(rate = 3, "tick {}", i);
+        warn_sampled!(1, ?i, "every tick");
+    }
+    quicklog::flush_all!();
+
+    // only the first of every 3 info lines is recorded
+    let mut expected: Vec<_> = [0, 3, 6]
+        .iter()
+        .map(|i| format!("[INFO]\ttick {}", i))
+        .collect();
+    expected.extend((0..7).map(|i| format!("[WARN]\tevery tick i={}", i)));
+
+    let mut lines =
+        common::from_log_lines(unsafe { &VEC }, common::message_and_level_from_log_line);
+    lines.sort_by_key(|line| !line.starts_with("[INFO]"));
+    assert_eq!(lines, expected);
+}
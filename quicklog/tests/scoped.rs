@@ -0,0 +1,48 @@
+use quicklog::{config, error_to, flush_all, info, info_to, warn_to, Quicklog};
+
+mod common;
+
+static mut AUDIT: Vec<String> = Vec::new();
+static mut APP: Vec<String> = Vec::new();
+
+fn main() {
+    setup!();
+
+    let mut audit = Quicklog::new(
+        config()
+            .flusher(unsafe { common::VecFlusher::new(&mut AUDIT) })
+            .formatter(common::TestFormatter::new()),
+    );
+    let mut app = Quicklog::new(
+        config()
+            .flusher(unsafe { common::VecFlusher::new(&mut APP) })
+            .formatter(common::TestFormatter::new()),
+    );
+
+    let symbol = "AAPL";
+    info_to!(&mut audit, ?symbol, "order {} accepted", ^42u64);
+    warn_to!(&mut app, "retrying {}", ^"connect");
+    error_to!(&mut app, attempts = 3, "giving up");
+    info!("global");
+
+    assert_eq!(audit.flush_n(usize::MAX).flushed, 1);
+    assert_eq!(app.flush_n(usize::MAX).flushed, 2);
+    flush_all!();
+
+    assert_eq!(
+        unsafe { common::from_log_lines(&AUDIT, common::message_and_level_from_log_line) },
+        vec!["[INFO]\torder 42 accepted symbol=\"AAPL\""]
+    );
+    assert_eq!(
+        unsafe { common::from_log_lines(&APP, common::message_and_level_from_log_line) },
+        vec!["[WARN]\tretrying connect", "[ERROR]\tgiving up attempts=3"]
+    );
+    assert_eq!(
+        unsafe { common::from_log_lines(&VEC, common::message_from_log_line) },
+        vec!["global"]
+    );
+
+    // Records still queued are dropped along with the logger
+    info_to!(&mut audit, "never flushed");
+    drop(audit);
+}
@@ -0,0 +1,37 @@
+use chrono::{DateTime, Utc};
+use quicklog::{dropped_count, flush_all, info, init, with_flush, with_formatter};
+use quicklog::{LogRecord, PatternFormatter, MAX_LOGGER_CAPACITY};
+use quicklog_flush::noop_flusher::NoopFlusher;
+
+static mut SEQUENCES: Vec<u64> = Vec::new();
+
+struct SequenceFormatter;
+
+impl PatternFormatter for SequenceFormatter {
+    fn custom_format(&mut self, _: DateTime<Utc>, log_record: LogRecord) -> String {
+        unsafe { SEQUENCES.push(log_record.sequence) };
+        String::new()
+    }
+}
+
+fn main() {
+    init!();
+    with_flush!(NoopFlusher);
+    with_formatter!(SequenceFormatter);
+
+    for i in 0..MAX_LOGGER_CAPACITY + 10 {
+        info!("log {}", i);
+    }
+    flush_all!();
+    info!("after drops");
+    flush_all!();
+
+    let sequences = unsafe { SEQUENCES.clone() };
+    let flushed = sequences.len() - 1;
+    assert!((0..flushed as u64).eq(sequences[..flushed].iter().copied()));
+
+    // the gap before the last line is exactly the number of dropped records
+    let gap = sequences[flushed] - sequences[flushed - 1] - 1;
+    assert_eq!(gap as usize, dropped_count());
+    assert_eq!(sequences[flushed], (MAX_LOGGER_CAPACITY + 10) as u64);
+}
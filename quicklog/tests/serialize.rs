@@ -1,9 +1,23 @@
 use quicklog::info;
+use quicklog::serialize::{Encode, Store};
 
 use common::{BigStruct, SerializeStruct};
 
 mod common;
 
+/// Only implements `Encode`, as its store is decoded as a `&str`
+struct Ticker(&'static str);
+
+impl Encode for Ticker {
+    fn encode<'buf>(&self, write_buf: &'buf mut [u8]) -> (Store<'buf>, &'buf mut [u8]) {
+        self.0.encode(write_buf)
+    }
+
+    fn buffer_size_required(&self) -> usize {
+        self.0.buffer_size_required()
+    }
+}
+
 fn main() {
     setup!();
 
@@ -25,4 +39,22 @@ fn main() {
             "The quick brown fox jumps over the lazy dog"
         )
     );
+    assert_message_equal!(info!("s: {}", ^s), "s: Hello");
+    assert_message_equal!(
+        info!("[{:>8}] [{:<7.3}] [{sym:^9}]", ^s, ^s, sym = ^s),
+        "[   Hello] [Hel    ] [  Hello  ]"
+    );
+    assert_message_equal!(info!(^s, "{:.3}", ^s), "Hel s=Hello");
+
+    // radix specifiers are applied to serialized integers when flushing
+    let id = 0xbeef_u32;
+    let flags = 5u32;
+    assert_message_equal!(
+        info!("{:x} {:#X} {:o} {:#010b}", ^id, ^id, ^flags, ^flags),
+        "beef 0xBEEF 5 0b00000101"
+    );
+    assert_message_equal!(info!("{:x}", ^s), "Hello");
+
+    let ticker = Ticker("AAPL");
+    assert_message_equal!(info!(^ticker, "quote"), "quote ticker=AAPL");
 }
@@ -0,0 +1,23 @@
+use quicklog::{config, info, init};
+
+mod common;
+
+static mut VEC: Vec<String> = Vec::new();
+
+fn main() {
+    init!(config()
+        .serialize_buffer_capacity(16)
+        .flusher(unsafe { common::VecFlusher::new(&mut VEC) })
+        .formatter(common::TestFormatter::new()));
+
+    assert_eq!(quicklog::logger().max_record_size(), 16);
+
+    // Arguments larger than the buffer are truncated rather than panicking
+    let long = "a string longer than the buffer";
+    assert_message_equal!(info!("{}", ^long), "a str...");
+
+    // The buffer is reused from the start once full
+    for i in 0..4u64 {
+        assert_message_equal!(info!("{} {}", ^i, ^(i * 2)), format!("{} {}", i, i * 2));
+    }
+}
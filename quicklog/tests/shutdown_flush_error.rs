@@ -0,0 +1,52 @@
+use std::io;
+
+use quicklog::{config, info, init};
+use quicklog_flush::Flush;
+
+mod common;
+
+static mut VEC: Vec<String> = Vec::new();
+
+/// Sink which fails to write the second log line only, e.g. as the disk was
+/// briefly full
+struct FlakyFlusher {
+    written: usize,
+}
+
+impl Flush for FlakyFlusher {
+    fn flush_one(&mut self, display: String) {
+        self.flush_str(&display)
+    }
+
+    fn flush_str(&mut self, display: &str) {
+        _ = self.try_flush_one(display);
+    }
+
+    fn try_flush_one(&mut self, display: &str) -> io::Result<()> {
+        self.written += 1;
+        if self.written == 2 {
+            return Err(io::ErrorKind::WriteZero.into());
+        }
+        unsafe { VEC.push(display.to_string()) };
+
+        Ok(())
+    }
+}
+
+fn main() {
+    init!(config()
+        .flusher(FlakyFlusher { written: 0 })
+        .formatter(common::TestFormatter::new()));
+
+    info!("first");
+    info!("second");
+    info!("third");
+    info!("fourth");
+
+    // records after the one which failed are still flushed on shutdown
+    quicklog::shutdown();
+    assert_eq!(
+        common::from_log_lines(unsafe { &VEC }, common::message_from_log_line),
+        vec!["first", "third", "fourth"]
+    );
+}
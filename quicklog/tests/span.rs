@@ -0,0 +1,49 @@
+use quicklog::{info, span};
+
+use common::Something;
+
+mod common;
+
+fn main() {
+    setup!();
+
+    let s1 = Something {
+        some_str: "Hello world 1",
+    };
+    let id = 10;
+
+    let outer = span!("outer", id, debug = ?s1);
+    let inner = span!("inner", a.b = %s1, some.value = 5);
+
+    assert_message_equal!(info!("outside of span"), "outside of span");
+    {
+        let _entered = outer.enter();
+        assert_message_equal!(
+            info!("in outer span"),
+            format!("outer{{id=10 debug={:?}}}: in outer span", s1)
+        );
+
+        inner.in_scope(|| {
+            assert_message_equal!(
+                info!(x = 1, "in inner span"),
+                format!(
+                    "outer{{id=10 debug={:?}}}:inner{{a.b={} some.value=5}}: in inner span x=1",
+                    s1, s1
+                )
+            );
+        });
+    }
+    assert_message_equal!(info!("span exited"), "span exited");
+
+    let empty = span!("empty");
+    empty.in_scope(|| {
+        assert_message_equal!(info!("no fields"), "empty: no fields");
+    });
+
+    // `^` fields are formatted when the span is created, like the others
+    let price = 1.5;
+    let serialized = span!("order", ^price);
+    serialized.in_scope(|| {
+        assert_message_equal!(info!("filled"), "order{price=1.5}: filled");
+    });
+}
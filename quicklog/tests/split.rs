@@ -0,0 +1,44 @@
+use quicklog::{config, flush_all, info, init, init_split, try_info, QueueError};
+use quicklog_flush::noop_flusher::NoopFlusher;
+
+mod common;
+
+static mut VEC: Vec<String> = Vec::new();
+
+fn main() {
+    let mut flush_handle =
+        init_split!(config().flusher(unsafe { common::VecFlusher::new(&mut VEC) })).unwrap();
+    assert!(init_split!().is_none());
+
+    info!("hello {}", 1);
+    info!("hello {}", 2);
+    // the global logger no longer owns the consumer side
+    assert_eq!(flush_all!().flushed, 0);
+
+    let flush_thread = std::thread::spawn(move || {
+        assert_eq!(flush_handle.flush_n(usize::MAX).flushed, 2);
+        flush_handle
+    });
+    let flush_handle = flush_thread.join().unwrap();
+    assert_eq!(unsafe { VEC.len() }, 2);
+    assert!(unsafe { VEC[1].ends_with("hello 2\n") });
+
+    // remaining records are flushed when the handle is dropped
+    info!("hello {}", 3);
+    drop(flush_handle);
+    assert_eq!(unsafe { VEC.len() }, 3);
+
+    // the queue is not split again while a handle still owns its consumer
+    quicklog::shutdown();
+    let mut flush_handle = init_split!().unwrap();
+    quicklog::shutdown();
+    init!();
+    assert_eq!(try_info!("dropped"), Err(QueueError::NotInitialized));
+    assert_eq!(flush_handle.flush_n(usize::MAX).flushed, 0);
+
+    drop(flush_handle);
+    quicklog::shutdown();
+    init!(config().flusher(NoopFlusher));
+    assert_eq!(try_info!("logged"), Ok(()));
+    assert_eq!(flush_all!().flushed, 1);
+}
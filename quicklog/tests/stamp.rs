@@ -0,0 +1,52 @@
+use chrono::{TimeZone, Utc};
+use quicklog::{
+    app::BuildInfo, build_info, config, flush_all, info, init, test_support::CaptureFlusher,
+    Quicklog,
+};
+use quicklog_clock::mock::MockClock;
+
+
+fn main() {
+    let capture = CaptureFlusher::new();
+    let clock = MockClock::new(Utc.timestamp_opt(1_700_000_000, 0).unwrap());
+    init!(config()
+        .clock(clock)
+        .stamp(build_info!())
+        .flusher(capture.clone()));
+    info!("hello");
+    flush_all!();
+
+    let lines = capture.take();
+    assert_eq!(lines.len(), 2);
+    // the stamp is the first record, ahead of anything logged after init
+    let expected = format!(
+        "[2023-11-14T22:13:20Z]build info name={} version={} hostname=",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+    );
+    assert!(lines[0].starts_with(&expected), "{}", lines[0]);
+    let expected = format!(
+        " pid={} start_time=2023-11-14T22:13:20Z\n",
+        std::process::id()
+    );
+    assert!(lines[0].ends_with(&expected), "{}", lines[0]);
+    assert_eq!(lines[1], "[2023-11-14T22:13:20Z]hello\n");
+
+    // owned loggers are stamped in the same way
+    let clock = MockClock::new(Utc.timestamp_opt(1_700_000_000, 0).unwrap());
+    let mut owned = Quicklog::new(
+        config()
+            .clock(clock)
+            .stamp(BuildInfo::new("audit", "1.2.3", Some("4f2c1e0")))
+            .flusher(capture.clone()),
+    );
+    assert_eq!(owned.flush_n(usize::MAX).flushed, 1);
+    let lines = capture.take();
+    assert!(
+        lines[0].starts_with(
+            "[2023-11-14T22:13:20Z]build info name=audit version=1.2.3 git_hash=4f2c1e0 hostname="
+        ),
+        "{}",
+        lines[0]
+    );
+}
@@ -0,0 +1,39 @@
+use quicklog::{info, FlushError};
+
+mod common;
+
+struct LogOnDrop;
+
+impl Drop for LogOnDrop {
+    fn drop(&mut self) {
+        info!("logging from drop");
+    }
+}
+
+fn main() {
+    // logging before initialization is a no-op
+    info!("not initialized yet");
+    assert!(matches!(
+        quicklog::try_flush!(),
+        Err(FlushError::NotInitialized)
+    ));
+
+    setup!();
+
+    info!("hello world");
+    info!("flushed on shutdown");
+    quicklog::logger().shutdown();
+    assert_eq!(
+        unsafe { common::from_log_lines(&VEC, common::message_from_log_line) },
+        vec!["hello world", "flushed on shutdown"]
+    );
+
+    // late logging, e.g. from `Drop` impls, is silently dropped
+    drop(LogOnDrop);
+    info!("after shutdown");
+    assert!(matches!(
+        quicklog::try_flush!(),
+        Err(FlushError::NotInitialized)
+    ));
+    assert_eq!(unsafe { VEC.len() }, 2);
+}
@@ -0,0 +1,30 @@
+use quicklog::{assert_logged, info, init_test, level::Level, span, warn};
+
+fn main() {
+    let capture = init_test!();
+    info!("order {} filled", 42);
+    warn!(qty = 5, "partial fill");
+    assert_logged!(Level::Info, "order 42 filled");
+    assert_logged!(Level::Warn, "partial fill qty=5");
+    assert_eq!(
+        capture.take(),
+        vec!["[INFO]\torder 42 filled\n", "[WARN]\tpartial fill qty=5\n"]
+    );
+
+    // Initializing again flushes queued lines into the previous capture
+    info!("queued before re-init");
+    let previous = capture;
+    let capture = init_test!();
+    assert_eq!(previous.lines(), vec!["[INFO]\tqueued before re-init\n"]);
+
+    let order = span!("order", id = 7);
+    {
+        let _guard = order.enter();
+        info!("in span");
+    }
+    assert_logged!(Level::Info, "in span");
+    assert_eq!(capture.lines().len(), 1);
+
+    let result = std::panic::catch_unwind(|| assert_logged!(Level::Error, "in span"));
+    assert!(result.is_err());
+}
@@ -0,0 +1,34 @@
+use chrono::{TimeZone, Utc};
+use quicklog::{config, flush_all, formatter, info, init, with_formatter};
+use quicklog_clock::mock::MockClock;
+
+mod common;
+
+static mut VEC: Vec<String> = Vec::new();
+
+fn main() {
+    let clock = MockClock::new(Utc.timestamp_opt(1_700_000_000, 0).unwrap());
+    init!(config()
+        .clock(clock)
+        .flusher(unsafe { common::VecFlusher::new(&mut VEC) }));
+    with_formatter!(formatter().with_time_epoch_nanos().with_thread(true));
+
+    info!("from main");
+    std::thread::Builder::new()
+        .name("worker".to_string())
+        .spawn(|| info!("from worker"))
+        .unwrap()
+        .join()
+        .unwrap();
+    std::thread::spawn(|| info!("from unnamed")).join().unwrap();
+    flush_all!();
+
+    assert_eq!(
+        unsafe { VEC.clone() },
+        vec![
+            "[1700000000000000000][main]from main\n",
+            "[1700000000000000000][worker]from worker\n",
+            "[1700000000000000000][3]from unnamed\n",
+        ]
+    );
+}
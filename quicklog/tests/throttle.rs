@@ -0,0 +1,24 @@
+use std::time::Duration;
+
+use quicklog::{info_throttle, warn_throttle};
+
+mod common;
+
+fn main() {
+    setup!();
+
+    for i in 0..5 {
+        info_throttle!(Duration::from_secs(60), "tick {}", i);
+        warn_throttle!(Duration::ZERO, ?i, "every tick");
+    }
+    quicklog::flush_all!();
+
+    // only the first info line is recorded within the interval
+    let mut expected = vec!["[INFO]\ttick 0".to_string()];
+    expected.extend((0..5).map(|i| format!("[WARN]\tevery tick i={}", i)));
+
+    assert_eq!(
+        common::from_log_lines(unsafe { &VEC }, common::message_and_level_from_log_line),
+        expected
+    );
+}
@@ -0,0 +1,27 @@
+use quicklog::{
+    dropped_count, flush_all, init, level, try_debug, try_error, try_info, try_trace, try_warn,
+    with_flush, QueueError, MAX_LOGGER_CAPACITY,
+};
+use quicklog_flush::noop_flusher::NoopFlusher;
+
+fn main() {
+    assert_eq!(try_info!("before init"), Err(QueueError::NotInitialized));
+
+    init!();
+    with_flush!(NoopFlusher);
+    assert_eq!(try_trace!("trace {}", 1), Ok(()));
+    assert_eq!(try_debug!(a = 1, "debug"), Ok(()));
+    assert_eq!(try_warn!("warn"), Ok(()));
+    flush_all!();
+
+    // The queue keeps one slot empty
+    for i in 0..MAX_LOGGER_CAPACITY - 1 {
+        assert_eq!(try_info!("log {}", i), Ok(()));
+    }
+    assert_eq!(try_error!("full"), Err(QueueError::Full));
+    assert_eq!(dropped_count(), 1);
+
+    // Filtered out log lines are not an error
+    level::set_max_level(level::LevelFilter::Off);
+    assert_eq!(try_error!("disabled"), Ok(()));
+}
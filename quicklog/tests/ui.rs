@@ -11,4 +11,58 @@ fn ui() {
     t.pass("tests/eager.rs");
     t.pass("tests/fields.rs");
     t.pass("tests/serialize.rs");
+    t.pass("tests/teardown.rs");
+    t.pass("tests/shutdown_flush_error.rs");
+    t.pass("tests/app_info.rs");
+    t.pass("tests/overflow.rs");
+    t.pass("tests/dropped.rs");
+    t.pass("tests/span.rs");
+    t.pass("tests/background_flush.rs");
+    t.pass("tests/rolling_file.rs");
+    t.pass("tests/flush_n.rs");
+    t.pass("tests/flush_error.rs");
+    t.pass("tests/flush_io_error.rs");
+    t.pass("tests/log.rs");
+    t.pass("tests/throttle.rs");
+    t.pass("tests/callsite.rs");
+    t.pass("tests/route.rs");
+    t.pass("tests/mixed_args.rs");
+    t.pass("tests/clock.rs");
+    t.pass("tests/update_config.rs");
+    t.pass("tests/logfmt.rs");
+    t.pass("tests/sampled.rs");
+    t.pass("tests/queue_stats.rs");
+    t.pass("tests/sequence.rs");
+    t.pass("tests/flush_on_panic.rs");
+    t.pass("tests/flush_on_panic_reentrant.rs");
+    t.pass("tests/custom_level.rs");
+    t.pass("tests/thread.rs");
+    t.pass("tests/max_record_size.rs");
+    t.pass("tests/try_log.rs");
+    t.pass("tests/json_fields.rs");
+    t.pass("tests/lazy.rs");
+    t.pass("tests/metric.rs");
+    t.pass("tests/builder.rs");
+    t.pass("tests/raw.rs");
+    t.pass("tests/filter.rs");
+    t.pass("tests/enabled.rs");
+    t.pass("tests/owned.rs");
+    t.pass("tests/context.rs");
+    t.pass("tests/stamp.rs");
+    t.pass("tests/file_hooks.rs");
+    t.pass("tests/fatal.rs");
+    t.pass("tests/fatal_stuck.rs");
+    t.pass("tests/event.rs");
+    t.pass("tests/log_dynamic.rs");
+    t.pass("tests/serialize_buffer_capacity.rs");
+    t.pass("tests/test_support.rs");
+    t.pass("tests/reinit.rs");
+    t.pass("tests/scoped.rs");
+    t.pass("tests/split.rs");
+    t.pass("tests/merge.rs");
+    t.pass("tests/grow.rs");
+    t.pass("tests/checksum.rs");
+    t.pass("tests/error_field.rs");
+    t.pass("tests/implicit_capture.rs");
+    t.pass("tests/reader.rs");
 }
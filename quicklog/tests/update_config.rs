@@ -0,0 +1,56 @@
+use quicklog::{
+    debug, flush_all, formatter, info, init, level::LevelFilter, update_config, with_flush,
+};
+
+mod common;
+
+static mut BEFORE: Vec<String> = Vec::new();
+static mut AFTER: Vec<String> = Vec::new();
+static mut ERRORS: Vec<String> = Vec::new();
+
+fn main() {
+    init!();
+    with_flush!(unsafe { common::VecFlusher::new(&mut BEFORE) });
+
+    info!("before");
+    flush_all!();
+
+    info!("queued");
+    update_config(|cfg| {
+        cfg.formatter(formatter().with_time_epoch_nanos())
+            .flusher(unsafe { common::VecFlusher::new(&mut AFTER) })
+            .route(LevelFilter::Error, unsafe {
+                common::VecFlusher::new(&mut ERRORS)
+            })
+            .max_level(LevelFilter::Info);
+    });
+    // Not applied until flushing
+    debug!("debug");
+    flush_all!();
+
+    debug!("filtered");
+    quicklog::error!("error");
+    flush_all!();
+
+    unsafe {
+        assert_eq!(BEFORE.len(), 1);
+        assert!(BEFORE[0].starts_with("[20"));
+
+        assert_eq!(AFTER.len(), 2);
+        for (line, message) in AFTER.iter().zip(["queued", "debug"]) {
+            let (time, rest) = line.split_once(']').unwrap();
+            assert!(time[1..].parse::<u128>().is_ok());
+            assert_eq!(rest, format!("{}\n", message));
+        }
+
+        assert_eq!(ERRORS.len(), 1);
+        assert!(ERRORS[0].ends_with("]error\n"));
+    }
+
+    update_config(|cfg| {
+        cfg.clear_routes();
+    });
+    quicklog::error!("error");
+    flush_all!();
+    assert_eq!(unsafe { (ERRORS.len(), AFTER.len()) }, (1, 3));
+}